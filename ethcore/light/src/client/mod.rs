@@ -32,7 +32,7 @@ use ethcore::encoded;
 use io::IoChannel;
 
 use util::{H256, U256, Mutex, RwLock};
-use util::kvdb::{KeyValueDB, CompactionProfile};
+use util::kvdb::{KeyValueDB, CompactionProfile, DatabaseBackend};
 
 use self::header_chain::{AncestryIter, HeaderChain};
 
@@ -56,6 +56,8 @@ pub struct Config {
 	pub db_compaction: CompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool,
+	/// Database storage backend.
+	pub db_backend: DatabaseBackend,
 }
 
 /// Trait for interacting with the header chain abstractly.
@@ -113,8 +115,9 @@ pub trait LightChainClient: Send + Sync {
 
 /// An actor listening to light chain events.
 pub trait LightChainNotify: Send + Sync {
-	/// Notifies about imported headers.
-	fn new_headers(&self, good: &[H256]);
+	/// Notifies about imported headers which became canonical (`enacted`, in ascending order)
+	/// and previously canonical headers which were retracted (`retracted`, in descending order).
+	fn new_headers(&self, enacted: &[H256], retracted: &[H256]);
 }
 
 /// Something which can be treated as a `LightChainClient`.
@@ -258,6 +261,7 @@ impl Client {
 
 		let _lock = self.import_lock.lock();
 
+		let old_best = self.chain.best_block().hash;
 		let mut bad = Vec::new();
 		let mut good = Vec::new();
 		for verified_header in self.queue.drain(MAX) {
@@ -286,7 +290,42 @@ impl Client {
 		self.queue.mark_as_bad(&bad);
 		self.queue.mark_as_good(&good);
 
-		self.notify(|listener| listener.new_headers(&good));
+		let new_best = self.chain.best_block().hash;
+		let (enacted, retracted) = self.tree_route(old_best, new_best)
+			.unwrap_or_else(|| (good.clone(), Vec::new()));
+
+		self.notify(|listener| listener.new_headers(&enacted, &retracted));
+	}
+
+	// find the headers enacted (ascending) and retracted (descending) when
+	// moving the best block from `from` to `to`.
+	fn tree_route(&self, from: H256, to: H256) -> Option<(Vec<H256>, Vec<H256>)> {
+		let header = |hash| self.chain.block_header(BlockId::Hash(hash));
+		let (mut from, mut to) = match (header(from), header(to)) {
+			(Some(from), Some(to)) => (from, to),
+			_ => return None,
+		};
+
+		let mut enacted = Vec::new();
+		let mut retracted = Vec::new();
+		while from.hash() != to.hash() {
+			if from.number() >= to.number() {
+				retracted.push(from.hash());
+				from = match header(from.parent_hash()) {
+					Some(parent) => parent,
+					None => return None,
+				};
+			} else {
+				enacted.push(to.hash());
+				to = match header(to.parent_hash()) {
+					Some(parent) => parent,
+					None => return None,
+				};
+			}
+		}
+
+		enacted.reverse();
+		Some((enacted, retracted))
 	}
 
 	/// Get a report about blocks imported.
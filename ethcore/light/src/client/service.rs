@@ -25,7 +25,7 @@ use ethcore::db;
 use ethcore::service::ClientIoMessage;
 use ethcore::spec::Spec;
 use io::{IoContext, IoError, IoHandler, IoService};
-use util::kvdb::{Database, DatabaseConfig};
+use util::kvdb::{self, DatabaseConfig};
 
 use cache::Cache;
 use util::Mutex;
@@ -70,11 +70,12 @@ impl Service {
 
 		db_config.compaction = config.db_compaction;
 		db_config.wal = config.db_wal;
+		db_config.backend = config.db_backend;
 
-		let db = Arc::new(Database::open(
+		let db = kvdb::open_database(
 			&db_config,
 			&path.to_str().expect("DB path could not be converted to string.")
-		).map_err(Error::Database)?);
+		).map_err(Error::Database)?;
 
 		let io_service = IoService::<ClientIoMessage>::start().map_err(Error::Io)?;
 		let client = Arc::new(Client::new(config,
@@ -403,6 +403,11 @@ impl LightProtocol {
 		)
 	}
 
+	/// Get the capabilities currently advertised to peers.
+	pub fn capabilities(&self) -> Capabilities {
+		self.capabilities.read().clone()
+	}
+
 	/// Make a request to a peer.
 	///
 	/// Fails on: nonexistent peer, network error, peer not server,
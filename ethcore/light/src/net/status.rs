@@ -199,7 +199,8 @@ impl Capabilities {
 	pub fn update_from(&mut self, announcement: &Announcement) {
 		self.serve_headers = self.serve_headers || announcement.serve_headers;
 		self.serve_state_since = self.serve_state_since.or(announcement.serve_state_since);
-		self.serve_chain_since = self.serve_chain_since.or(announcement.serve_chain_since);
+		// the earliest servable block moves forward as history is pruned.
+		self.serve_chain_since = announcement.serve_chain_since.or(self.serve_chain_since);
 		self.tx_relay = self.tx_relay || announcement.tx_relay;
 	}
 }
@@ -572,4 +573,33 @@ mod tests {
 		assert_eq!(read_capabilities, capabilities);
 		assert!(read_flow.is_none());
 	}
+
+	#[test]
+	fn capabilities_follow_pruned_history() {
+		let mut capabilities = Capabilities {
+			serve_headers: true,
+			serve_chain_since: Some(1),
+			serve_state_since: Some(8),
+			tx_relay: false,
+		};
+
+		let mut announcement = Announcement {
+			head_hash: H256::random(),
+			head_num: 1000,
+			head_td: 1000.into(),
+			reorg_depth: 0,
+			serve_headers: false,
+			serve_state_since: None,
+			serve_chain_since: None,
+			tx_relay: false,
+		};
+
+		capabilities.update_from(&announcement);
+		assert_eq!(capabilities.serve_chain_since, Some(1));
+
+		announcement.serve_chain_since = Some(500);
+		capabilities.update_from(&announcement);
+		assert_eq!(capabilities.serve_chain_since, Some(500));
+		assert_eq!(capabilities.serve_state_since, Some(8));
+	}
 }
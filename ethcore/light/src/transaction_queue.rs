@@ -23,6 +23,7 @@
 //! accounts for which they create transactions, this queue is structured in an
 //! address-wise manner.
 
+use std::fmt;
 use std::collections::{BTreeMap, HashMap};
 use std::collections::hash_map::Entry;
 
@@ -114,11 +115,24 @@ impl AccountTransactions {
 	}
 }
 
+type Listener = Box<Fn(&[H256]) + Send + Sync>;
+
 /// Light transaction queue. See module docs for more details.
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Default)]
 pub struct TransactionQueue {
 	by_account: HashMap<Address, AccountTransactions>,
 	by_hash: H256FastMap<PendingTransaction>,
+	listeners: Vec<Listener>,
+}
+
+impl fmt::Debug for TransactionQueue {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("TransactionQueue")
+			.field("by_account", &self.by_account)
+			.field("by_hash", &self.by_hash)
+			.field("listeners", &self.listeners.len())
+			.finish()
+	}
 }
 
 impl TransactionQueue {
@@ -205,9 +219,22 @@ impl TransactionQueue {
 		};
 
 		self.by_hash.insert(hash, tx);
+		self.notify(&[hash]);
 		Ok(res)
 	}
 
+	/// Add a listener to be notified about newly imported transactions.
+	pub fn add_listener(&mut self, f: Listener) {
+		self.listeners.push(f);
+	}
+
+	/// Notifies all listeners about new pending transactions.
+	fn notify(&self, hashes: &[H256]) {
+		for listener in &self.listeners {
+			listener(hashes)
+		}
+	}
+
 	/// Get pending transaction by hash.
 	pub fn transaction(&self, hash: &H256) -> Option<SignedTransaction> {
 		self.by_hash.get(hash).map(|tx| (&**tx).clone())
@@ -514,4 +541,25 @@ mod tests {
 		assert_eq!(txq.future_transactions(0, 0).len(), 7);
 		assert_eq!(txq.next_nonce(&sender).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn notifies_listeners_of_imported_transactions() {
+		use std::sync::Arc;
+		use util::Mutex;
+
+		let sender = Address::default();
+		let mut txq = TransactionQueue::default();
+		let notified = Arc::new(Mutex::new(Vec::new()));
+
+		let n = notified.clone();
+		txq.add_listener(Box::new(move |hashes| n.lock().extend_from_slice(hashes)));
+
+		let tx = Transaction::default().fake_sign(sender);
+		let hash = tx.hash();
+
+		txq.import(tx.clone().into()).unwrap();
+		assert!(txq.import(tx.into()).is_err());
+
+		assert_eq!(*notified.lock(), vec![hash]);
+	}
 }
@@ -18,10 +18,10 @@
 use util::*;
 use rlp::NULL_RLP;
 
-// combines a key with an address hash to ensure uniqueness.
-// leaves the first 96 bits untouched in order to support partial key lookup.
+/// Combines a key with an address hash to ensure uniqueness.
+/// Leaves the first 96 bits untouched in order to support partial key lookup.
 #[inline]
-fn combine_key<'a>(address_hash: &'a H256, key: &'a H256) -> H256 {
+pub fn combine_key<'a>(address_hash: &'a H256, key: &'a H256) -> H256 {
 	let mut dst = key.clone();
 	{
 		let last_src: &[u8] = &*address_hash;
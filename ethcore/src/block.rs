@@ -485,6 +485,10 @@ impl LockedBlock {
 			return Err(BlockError::InvalidSealArity(Mismatch{expected: engine.seal_fields(), found: seal.len()}));
 		}
 		s.block.header.set_seal(seal);
+		if let Err(e) = engine.on_seal_block(&mut s.block) {
+			warn!("Encountered error on sealing the block: {}", e);
+			return Err(BlockError::InvalidSeal);
+		}
 		Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes })
 	}
 
@@ -498,6 +502,9 @@ impl LockedBlock {
 	) -> Result<SealedBlock, (Error, LockedBlock)> {
 		let mut s = self;
 		s.block.header.set_seal(seal);
+		if let Err(e) = engine.on_seal_block(&mut s.block) {
+			return Err((e, s));
+		}
 		match engine.verify_block_seal(&s.block.header) {
 			Err(e) => Err((e, s)),
 			_ => Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes }),
@@ -0,0 +1,260 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Flat file storage for ancient blocks.
+//!
+//! Canonical blocks which are deep enough below the best block are moved out
+//! of the database into append-only flat files. Headers, bodies and receipts
+//! are kept in separate tables, each made of a data file and an index file
+//! holding the end offset of every entry as a little-endian `u64`.
+//!
+//! Entries are addressed by block number and always start at the genesis block,
+//! so the store can only grow by appending the block following the last one.
+//! Values are stored exactly as they are in the database.
+
+use std::cmp;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use byteorder::{ByteOrder, LittleEndian};
+use header::BlockNumber;
+use util::Bytes;
+
+const INDEX_ENTRY_SIZE: u64 = 8;
+
+/// A single data file and its index.
+struct Table {
+	data: File,
+	index: File,
+	len: u64,
+	data_len: u64,
+}
+
+impl Table {
+	fn open(dir: &Path, name: &str) -> io::Result<Self> {
+		let open = |ext: &str| OpenOptions::new().read(true).write(true).create(true).open(dir.join(format!("{}.{}", name, ext)));
+
+		let data = open("dat")?;
+		let index = open("idx")?;
+		let data_size = data.metadata()?.len();
+		let index_size = index.metadata()?.len();
+
+		let mut table = Table {
+			data: data,
+			index: index,
+			len: index_size / INDEX_ENTRY_SIZE,
+			data_len: 0,
+		};
+
+		// drop entries whose data didn't make it to disk before a crash.
+		while table.len > 0 && table.end_offset(table.len - 1)? > data_size {
+			table.len -= 1;
+		}
+
+		let len = table.len;
+		table.truncate(len)?;
+		Ok(table)
+	}
+
+	fn end_offset(&mut self, n: u64) -> io::Result<u64> {
+		let mut buf = [0u8; INDEX_ENTRY_SIZE as usize];
+		self.index.seek(SeekFrom::Start(n * INDEX_ENTRY_SIZE))?;
+		self.index.read_exact(&mut buf)?;
+		Ok(LittleEndian::read_u64(&buf))
+	}
+
+	fn get(&mut self, n: u64) -> io::Result<Option<Bytes>> {
+		if n >= self.len {
+			return Ok(None);
+		}
+
+		let start = match n {
+			0 => 0,
+			n => self.end_offset(n - 1)?,
+		};
+		let end = self.end_offset(n)?;
+
+		let mut value = vec![0u8; (end - start) as usize];
+		self.data.seek(SeekFrom::Start(start))?;
+		self.data.read_exact(&mut value)?;
+		Ok(Some(value))
+	}
+
+	fn append(&mut self, value: &[u8]) -> io::Result<()> {
+		let mut buf = [0u8; INDEX_ENTRY_SIZE as usize];
+		LittleEndian::write_u64(&mut buf, self.data_len + value.len() as u64);
+
+		self.data.seek(SeekFrom::Start(self.data_len))?;
+		self.data.write_all(value)?;
+		self.index.seek(SeekFrom::Start(self.len * INDEX_ENTRY_SIZE))?;
+		self.index.write_all(&buf)?;
+
+		self.data_len += value.len() as u64;
+		self.len += 1;
+		Ok(())
+	}
+
+	fn truncate(&mut self, len: u64) -> io::Result<()> {
+		let data_len = match len {
+			0 => 0,
+			len => self.end_offset(len - 1)?,
+		};
+
+		self.index.set_len(len * INDEX_ENTRY_SIZE)?;
+		self.data.set_len(data_len)?;
+		self.len = len;
+		self.data_len = data_len;
+		Ok(())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.data.sync_data()?;
+		self.index.sync_data()
+	}
+}
+
+/// Append-only storage of ancient block headers, bodies and receipts.
+pub struct AncientStore {
+	headers: Table,
+	bodies: Table,
+	receipts: Table,
+}
+
+impl AncientStore {
+	/// Open the store in the given directory, creating it if it doesn't exist.
+	/// Any partially written blocks are discarded.
+	pub fn open(path: &Path) -> io::Result<Self> {
+		fs::create_dir_all(path)?;
+
+		let mut store = AncientStore {
+			headers: Table::open(path, "headers")?,
+			bodies: Table::open(path, "bodies")?,
+			receipts: Table::open(path, "receipts")?,
+		};
+
+		let len = cmp::min(store.headers.len, cmp::min(store.bodies.len, store.receipts.len));
+		store.truncate(len)?;
+		Ok(store)
+	}
+
+	/// Number of blocks in the store. This is also the number of the next block to be appended.
+	pub fn len(&self) -> u64 {
+		self.headers.len
+	}
+
+	/// Whether the store holds no blocks.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Get the stored header of the given block.
+	pub fn header(&mut self, number: BlockNumber) -> io::Result<Option<Bytes>> {
+		self.headers.get(number)
+	}
+
+	/// Get the stored body of the given block. Blocks moved after their history
+	/// was pruned have an empty entry, for which `None` is returned.
+	pub fn body(&mut self, number: BlockNumber) -> io::Result<Option<Bytes>> {
+		self.bodies.get(number).map(|b| b.and_then(|b| if b.is_empty() { None } else { Some(b) }))
+	}
+
+	/// Get the stored receipts of the given block. Blocks imported without receipts
+	/// have an empty entry, for which `None` is returned.
+	pub fn receipts(&mut self, number: BlockNumber) -> io::Result<Option<Bytes>> {
+		self.receipts.get(number).map(|r| r.and_then(|r| if r.is_empty() { None } else { Some(r) }))
+	}
+
+	/// Append the next block. Appending a block which is already stored does nothing,
+	/// so that an interrupted move can be safely repeated.
+	pub fn append(&mut self, number: BlockNumber, header: &[u8], body: &[u8], receipts: &[u8]) -> io::Result<()> {
+		if number < self.len() {
+			return Ok(());
+		}
+
+		if number > self.len() {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Ancient block #{} appended, expected #{}", number, self.len())));
+		}
+
+		self.headers.append(header)?;
+		self.bodies.append(body)?;
+		self.receipts.append(receipts)
+	}
+
+	/// Discard all blocks from `len` onwards.
+	pub fn truncate(&mut self, len: u64) -> io::Result<()> {
+		self.headers.truncate(len)?;
+		self.bodies.truncate(len)?;
+		self.receipts.truncate(len)
+	}
+
+	/// Make sure all appended blocks are on disk.
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.headers.flush()?;
+		self.bodies.flush()?;
+		self.receipts.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::OpenOptions;
+	use devtools::RandomTempPath;
+	use super::AncientStore;
+
+	#[test]
+	fn appends_and_reads() {
+		let path = RandomTempPath::create_dir();
+		{
+			let mut store = AncientStore::open(path.as_path()).unwrap();
+			assert!(store.is_empty());
+			store.append(0, b"h0", b"b0", b"").unwrap();
+			store.append(1, b"h1", b"body1", b"r1").unwrap();
+			assert!(store.append(3, b"h3", b"b3", b"r3").is_err());
+			// repeated appends are ignored.
+			store.append(1, b"hx", b"bx", b"rx").unwrap();
+			store.flush().unwrap();
+		}
+
+		let mut store = AncientStore::open(path.as_path()).unwrap();
+		assert_eq!(store.len(), 2);
+		assert_eq!(store.header(0).unwrap(), Some(b"h0".to_vec()));
+		assert_eq!(store.body(1).unwrap(), Some(b"body1".to_vec()));
+		assert_eq!(store.receipts(0).unwrap(), None);
+		assert_eq!(store.receipts(1).unwrap(), Some(b"r1".to_vec()));
+		assert_eq!(store.header(2).unwrap(), None);
+	}
+
+	#[test]
+	fn discards_partial_writes() {
+		let path = RandomTempPath::create_dir();
+		{
+			let mut store = AncientStore::open(path.as_path()).unwrap();
+			store.append(0, b"h0", b"b0", b"r0").unwrap();
+			store.append(1, b"h1", b"b1", b"r1").unwrap();
+		}
+
+		// lose the data of the last receipts entry.
+		let receipts = OpenOptions::new().write(true).open(path.as_path().join("receipts.dat")).unwrap();
+		receipts.set_len(3).unwrap();
+
+		let mut store = AncientStore::open(path.as_path()).unwrap();
+		assert_eq!(store.len(), 1);
+		assert_eq!(store.body(1).unwrap(), None);
+		store.append(1, b"h1", b"b1", b"r1").unwrap();
+		assert_eq!(store.receipts(1).unwrap(), Some(b"r1".to_vec()));
+	}
+}
@@ -31,8 +31,8 @@ use blockchain::best_block::{BestBlock, BestAncientBlock};
 use types::blockchain_info::BlockChainInfo;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
-use blockchain::{CacheSize, ImportRoute, Config};
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use blockchain::{AncientStore, CacheSize, ImportRoute, Config};
+use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use cache_manager::CacheManager;
 use encoded;
 use engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
@@ -141,8 +141,9 @@ pub trait BlockProvider {
 	/// Returns numbers of blocks containing given bloom.
 	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockNumber, to_block: BlockNumber) -> Vec<BlockNumber>;
 
-	/// Returns logs matching given filter.
-	fn logs<F>(&self, blocks: Vec<BlockNumber>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
+	/// Returns logs matching given filter. The block hashes are expected to be in ascending order
+	/// and do not need to be part of the canonical chain.
+	fn logs<F>(&self, blocks: Vec<H256>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
 		where F: Fn(&LogEntry) -> bool, Self: Sized;
 }
 
@@ -198,6 +199,13 @@ pub struct BlockChain {
 
 	db: Arc<KeyValueDB>,
 
+	// flat file storage of canonical blocks deeper than `ancient_depth`.
+	ancient: Option<Mutex<AncientStore>>,
+	ancient_depth: u64,
+	// bodies, receipts and traces of blocks below `history_since` are pruned.
+	history_keep: Option<u64>,
+	history_since: RwLock<BlockNumber>,
+
 	cache_man: Mutex<CacheManager<CacheId>>,
 
 	pending_best_block: RwLock<Option<BestBlock>>,
@@ -260,9 +268,11 @@ impl BlockProvider for BlockChain {
 			}
 		}
 
-		// Read from DB and populate cache
+		// Read from DB or ancient storage and populate cache
 		let opt = self.db.get(db::COL_HEADERS, hash)
-			.expect("Low level database error. Some issue with disk?");
+			.expect("Low level database error. Some issue with disk?")
+			.map(|b| b.into_vec())
+			.or_else(|| self.read_ancient(hash, |ancient, number| ancient.header(number)));
 
 		let result = match opt {
 			Some(b) => {
@@ -296,9 +306,11 @@ impl BlockProvider for BlockChain {
 			}
 		}
 
-		// Read from DB and populate cache
+		// Read from DB or ancient storage and populate cache
 		let opt = self.db.get(db::COL_BODIES, hash)
-			.expect("Low level database error. Some issue with disk?");
+			.expect("Low level database error. Some issue with disk?")
+			.map(|b| b.into_vec())
+			.or_else(|| self.read_ancient(hash, |ancient, number| ancient.body(number)));
 
 		let result = match opt {
 			Some(b) => {
@@ -338,7 +350,13 @@ impl BlockProvider for BlockChain {
 
 	/// Get receipts of block with given hash.
 	fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
-		let result = self.db.read_with_cache(db::COL_EXTRA, &self.block_receipts, hash);
+		let result = self.db.read_with_cache(db::COL_EXTRA, &self.block_receipts, hash).or_else(|| {
+			self.read_ancient(hash, |ancient, number| ancient.receipts(number)).map(|bytes| {
+				let receipts: BlockReceipts = ::rlp::decode(&bytes);
+				self.block_receipts.write().insert(hash.clone(), receipts.clone());
+				receipts
+			})
+		});
 		self.cache_man.lock().note_used(CacheId::BlockReceipts(hash.clone()));
 		result
 	}
@@ -353,14 +371,14 @@ impl BlockProvider for BlockChain {
 			.collect()
 	}
 
-	fn logs<F>(&self, mut blocks: Vec<BlockNumber>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
+	fn logs<F>(&self, mut blocks: Vec<H256>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
 		where F: Fn(&LogEntry) -> bool, Self: Sized {
-		// sort in reverse order
-		blocks.sort_by(|a, b| b.cmp(a));
+		// iterate in reverse order
+		blocks.reverse();
 
 		let mut log_index = 0;
 		let mut logs = blocks.into_iter()
-			.filter_map(|number| self.block_hash(number).map(|hash| (number, hash)))
+			.filter_map(|hash| self.block_number(&hash).map(|number| (number, hash)))
 			.filter_map(|(number, hash)| self.block_receipts(&hash).map(|r| (number, hash, r.receipts)))
 			.filter_map(|(number, hash, receipts)| self.block_body(&hash).map(|ref b| (number, hash, receipts, b.transaction_hashes())))
 			.flat_map(|(number, hash, mut receipts, mut hashes)| {
@@ -490,6 +508,12 @@ impl BlockChain {
 			blocks_blooms: RwLock::new(HashMap::new()),
 			block_receipts: RwLock::new(HashMap::new()),
 			db: db.clone(),
+			ancient: config.ancient_path.as_ref().map(|path| Mutex::new(
+				AncientStore::open(path).expect("Error opening ancient block store. Some issue with disk?")
+			)),
+			ancient_depth: config.ancient_depth,
+			history_keep: config.history_keep_blocks,
+			history_since: RwLock::new(0),
 			cache_man: Mutex::new(cache_man),
 			pending_best_block: RwLock::new(None),
 			pending_block_hashes: RwLock::new(HashMap::new()),
@@ -594,11 +618,164 @@ impl BlockChain {
 					number: number,
 				});
 			}
+
+			if let Some(since) = bc.db.get(db::COL_EXTRA, b"history").unwrap() {
+				*bc.history_since.write() = ::rlp::decode(&since);
+			}
+		}
+
+		// ancient blocks left over from a replaced database, e.g. after restoring
+		// from a snapshot, don't belong to this chain.
+		let ancient_last = match bc.ancient {
+			Some(ref ancient) => {
+				let mut ancient = ancient.lock();
+				match ancient.len() {
+					0 => None,
+					len => Some((len - 1, ancient.header(len - 1).expect("Low level ancient block store error. Some issue with disk?"))),
+				}
+			},
+			None => None,
+		};
+
+		if let Some((number, header)) = ancient_last {
+			let hash = header.map(|h| UntrustedRlp::new(&h).decompress(RlpType::Blocks).into_vec().sha3());
+			if hash.is_none() || hash != bc.block_hash(number) {
+				warn!("Ancient blocks don't match the database. Discarding {} ancient blocks.", number + 1);
+				bc.ancient.as_ref().expect("ancient_last is only set with an ancient store; qed").lock().truncate(0)
+					.expect("Low level ancient block store error. Some issue with disk?");
+			}
 		}
 
 		bc
 	}
 
+	/// Read data of a canonical block from the ancient block store.
+	fn read_ancient<F>(&self, hash: &H256, read: F) -> Option<Bytes>
+		where F: FnOnce(&mut AncientStore, BlockNumber) -> ::std::io::Result<Option<Bytes>>
+	{
+		let ancient = match self.ancient {
+			Some(ref ancient) => ancient,
+			None => return None,
+		};
+
+		let number = match self.block_number(hash) {
+			Some(number) => number,
+			None => return None,
+		};
+
+		if self.block_hash(number).as_ref() != Some(hash) {
+			return None;
+		}
+
+		let mut ancient = ancient.lock();
+		read(&mut *ancient, number).expect("Low level ancient block store error. Some issue with disk?")
+	}
+
+	/// Move canonical blocks which are more than the configured depth below the best
+	/// block from the database into the ancient block store. At most `max` blocks
+	/// are moved at once. Returns the number of blocks moved.
+	pub fn freeze_ancient(&self, max: u64) -> u64 {
+		let ancient = match self.ancient {
+			Some(ref ancient) => ancient,
+			None => return 0,
+		};
+
+		type ReceiptsKey = Key<BlockReceipts, Target=H264>;
+
+		// blocks are only moved once their history is pruned, so that it's never
+		// needed to delete anything from the ancient block store.
+		let depth = ::std::cmp::max(self.ancient_depth, self.history_keep.unwrap_or(0));
+		let history_since = *self.history_since.read();
+		let from = ancient.lock().len();
+		let to = ::std::cmp::min(self.best_block_number().saturating_sub(depth), from + max);
+
+		let mut blocks = Vec::new();
+		for number in from..to {
+			let hash = match self.block_hash(number) {
+				Some(hash) => hash,
+				None => break,
+			};
+
+			let header = self.db.get(db::COL_HEADERS, &hash).expect("Low level database error. Some issue with disk?");
+			let body = self.db.get(db::COL_BODIES, &hash).expect("Low level database error. Some issue with disk?");
+			let receipts = self.db.get(db::COL_EXTRA, &ReceiptsKey::key(&hash)).expect("Low level database error. Some issue with disk?");
+
+			match (header, body) {
+				(Some(header), Some(body)) => blocks.push((number, hash, header, Some(body), receipts)),
+				(Some(header), None) if number < history_since => blocks.push((number, hash, header, None, receipts)),
+				// the gap below a snapshot restored from has to be filled first.
+				_ => break,
+			}
+		}
+
+		if blocks.is_empty() {
+			return 0;
+		}
+
+		// blocks are removed from the database only once they are safely on disk.
+		let mut batch = DBTransaction::new();
+		{
+			let mut ancient = ancient.lock();
+			for &(number, ref hash, ref header, ref body, ref receipts) in &blocks {
+				let body = body.as_ref().map_or(&[][..], |b| &**b);
+				ancient.append(number, header, body, receipts.as_ref().map_or(&[][..], |r| &**r))
+					.expect("Low level ancient block store error. Some issue with disk?");
+
+				batch.delete(db::COL_HEADERS, hash);
+				batch.delete(db::COL_BODIES, hash);
+				batch.delete(db::COL_EXTRA, &ReceiptsKey::key(hash));
+			}
+			ancient.flush().expect("Low level ancient block store error. Some issue with disk?");
+		}
+		self.db.write(batch).expect("Low level database error. Some issue with disk?");
+
+		trace!(target: "blockchain", "Moved blocks #{}..#{} to ancient storage", from, from + blocks.len() as u64 - 1);
+		blocks.len() as u64
+	}
+
+	/// Number of the first block whose body and receipts weren't pruned.
+	pub fn history_since(&self) -> BlockNumber {
+		*self.history_since.read()
+	}
+
+	/// Prune bodies and receipts of canonical blocks which are more than the configured
+	/// number of blocks below the best block. Headers and transaction addresses are kept.
+	/// At most `max` blocks are pruned at once. Returns the hashes of the pruned blocks,
+	/// so that other data kept for them can be removed in the same transaction.
+	pub fn prune_history(&self, batch: &mut DBTransaction, max: u64) -> Vec<H256> {
+		let keep = match self.history_keep {
+			Some(keep) => keep,
+			None => return Vec::new(),
+		};
+
+		type ReceiptsKey = Key<BlockReceipts, Target=H264>;
+
+		let from = *self.history_since.read();
+		let to = ::std::cmp::min(self.best_block_number().saturating_sub(keep), from + max);
+		if to <= from {
+			return Vec::new();
+		}
+
+		// blocks missing below a restored snapshot have nothing to prune.
+		let hashes: Vec<H256> = (from..to).filter_map(|number| self.block_hash(number)).collect();
+		{
+			let mut block_bodies = self.block_bodies.write();
+			let mut block_receipts = self.block_receipts.write();
+			for hash in &hashes {
+				batch.delete(db::COL_BODIES, hash);
+				batch.delete(db::COL_EXTRA, &ReceiptsKey::key(hash));
+				block_bodies.remove(hash);
+				block_receipts.remove(hash);
+			}
+		}
+
+		batch.put(db::COL_EXTRA, b"history", &::rlp::encode(&to));
+		*self.history_since.write() = to;
+
+		trace!(target: "blockchain", "Pruned history of blocks #{}..#{}", from, to - 1);
+		hashes
+	}
+
 	/// Returns true if the given parent block has given child
 	/// (though not necessarily a part of the canon chain).
 	fn is_known_child(&self, parent: &H256, hash: &H256) -> bool {
@@ -2061,8 +2238,8 @@ mod tests {
 		// when
 		let block1 = BlockView::new(&b1);
 		let block2 = BlockView::new(&b2);
-		let logs1 = bc.logs(vec![1, 2], |_| true, None);
-		let logs2 = bc.logs(vec![1, 2], |_| true, Some(1));
+		let logs1 = bc.logs(vec![block1.hash(), block2.hash()], |_| true, None);
+		let logs2 = bc.logs(vec![block1.hash(), block2.hash()], |_| true, Some(1));
 
 		// then
 		assert_eq!(logs1, vec![
@@ -2376,4 +2553,97 @@ mod tests {
 			assert_eq!(bc.epoch_transition_for(fork_hash).unwrap().block_number, 0);
 		}
 	}
+
+	#[test]
+	fn moves_ancient_blocks_to_flat_files() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let genesis_hash = BlockView::new(&genesis).header_view().sha3();
+		let b1 = canon_chain.generate(&mut finalizer).unwrap();
+		let b1_hash = BlockView::new(&b1).header_view().sha3();
+
+		let path = ::devtools::RandomTempPath::create_dir();
+		let config = Config {
+			ancient_path: Some(path.as_path().clone()),
+			ancient_depth: 2,
+			..Default::default()
+		};
+
+		let db = new_db();
+		let b3_hash = {
+			let bc = BlockChain::new(config.clone(), &genesis, db.clone());
+			insert_block(&db, &bc, &b1, vec![Receipt {
+				state_root: Some(H256::default()),
+				gas_used: 10_000.into(),
+				log_bloom: Default::default(),
+				logs: vec![],
+			}]);
+			for _ in 0..4 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				insert_block(&db, &bc, &block, vec![]);
+			}
+
+			assert_eq!(bc.best_block_number(), 5);
+			assert_eq!(bc.freeze_ancient(2), 2);
+			assert_eq!(bc.freeze_ancient(10), 1);
+			assert_eq!(bc.freeze_ancient(10), 0);
+			bc.block_hash(3).unwrap()
+		};
+
+		assert!(db.get(::db::COL_HEADERS, &b1_hash).unwrap().is_none());
+		assert!(db.get(::db::COL_BODIES, &b1_hash).unwrap().is_none());
+		assert!(db.get(::db::COL_HEADERS, &b3_hash).unwrap().is_some());
+
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		assert_eq!(bc.block(&b1_hash).unwrap().into_inner(), b1);
+		assert_eq!(bc.block_receipts(&b1_hash).unwrap().receipts.len(), 1);
+		assert_eq!(bc.block_header(&genesis_hash).unwrap().number(), 0);
+		assert!(bc.block_receipts(&genesis_hash).is_none());
+	}
+
+	#[test]
+	fn prunes_old_history() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let b1 = canon_chain.generate(&mut finalizer).unwrap();
+		let b1_hash = BlockView::new(&b1).header_view().sha3();
+
+		let config = Config {
+			history_keep_blocks: Some(2),
+			..Default::default()
+		};
+
+		let db = new_db();
+		{
+			let bc = BlockChain::new(config.clone(), &genesis, db.clone());
+			insert_block(&db, &bc, &b1, vec![Receipt {
+				state_root: Some(H256::default()),
+				gas_used: 10_000.into(),
+				log_bloom: Default::default(),
+				logs: vec![],
+			}]);
+			for _ in 0..4 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				insert_block(&db, &bc, &block, vec![]);
+			}
+			assert!(bc.block_body(&b1_hash).is_some());
+
+			let mut batch = db.transaction();
+			assert_eq!(bc.prune_history(&mut batch, 10).len(), 3);
+			db.write(batch).unwrap();
+			assert_eq!(bc.history_since(), 3);
+
+			let mut batch = db.transaction();
+			assert!(bc.prune_history(&mut batch, 10).is_empty());
+		}
+
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		assert_eq!(bc.history_since(), 3);
+		assert!(bc.block_header(&b1_hash).is_some());
+		assert!(bc.block_body(&b1_hash).is_none());
+		assert!(bc.block_receipts(&b1_hash).is_none());
+		assert!(bc.block_body(&bc.block_hash(3).unwrap()).is_some());
+	}
 }
@@ -16,6 +16,8 @@
 
 //! Blockchain configuration.
 
+use std::path::PathBuf;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -25,6 +27,14 @@ pub struct Config {
 	pub max_cache_size: usize,
 	/// Backing db cache_size
 	pub db_cache_size: Option<usize>,
+	/// Directory of the ancient block store. `None` keeps all blocks in the database.
+	pub ancient_path: Option<PathBuf>,
+	/// Number of blocks below the best block kept in the database before
+	/// they are moved into the ancient block store.
+	pub ancient_depth: u64,
+	/// Number of recent blocks whose bodies, receipts and traces are kept.
+	/// `None` keeps the whole history.
+	pub history_keep_blocks: Option<u64>,
 }
 
 impl Default for Config {
@@ -33,6 +43,9 @@ impl Default for Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
 			db_cache_size: None,
+			ancient_path: None,
+			ancient_depth: 90_000,
+			history_keep_blocks: None,
 		}
 	}
 }
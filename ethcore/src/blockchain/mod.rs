@@ -16,6 +16,7 @@
 
 //! Blockchain database.
 
+mod ancient;
 mod best_block;
 mod block_info;
 pub mod blockchain;
@@ -28,6 +29,7 @@ mod update;
 #[cfg(test)]
 pub mod generator;
 
+pub use self::ancient::AncientStore;
 pub use self::blockchain::{BlockProvider, BlockChain};
 pub use self::cache::CacheSize;
 pub use self::config::Config;
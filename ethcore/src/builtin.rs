@@ -59,6 +59,12 @@ struct Modexp {
 	divisor: usize,
 }
 
+/// A pricing model for the alt_bn128 pairing check. Each 192-byte point pair costs `pair`.
+struct AltBn128PairingPricer {
+	base: usize,
+	pair: usize,
+}
+
 impl Pricer for Linear {
 	fn cost(&self, input: &[u8]) -> U256 {
 		U256::from(self.base) + U256::from(self.word) * U256::from((input.len() + 31) / 32)
@@ -94,6 +100,12 @@ impl Pricer for Modexp {
 	}
 }
 
+impl Pricer for AltBn128PairingPricer {
+	fn cost(&self, input: &[u8]) -> U256 {
+		U256::from(self.base) + U256::from(self.pair) * U256::from(input.len() / 192)
+	}
+}
+
 /// Pricing scheme, execution definition, and activation block for a built-in contract.
 ///
 /// Call `cost` to compute cost for the given input, `execute` to execute the contract
@@ -138,6 +150,12 @@ impl From<ethjson::spec::Builtin> for Builtin {
 					}
 				})
 			}
+			ethjson::spec::Pricing::AltBn128Pairing(pricing) => {
+				Box::new(AltBn128PairingPricer {
+					base: pricing.base,
+					pair: pricing.pair,
+				})
+			}
 		};
 
 		Builtin {
@@ -948,4 +966,20 @@ mod tests {
 		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..])).expect("Builtin should not fail");
 		assert_eq!(i, o);
 	}
+
+	#[test]
+	fn alt_bn128_pairing_pricing_from_json() {
+		let b = Builtin::from(ethjson::spec::Builtin {
+			name: "bn128_pairing".to_owned(),
+			pricing: ethjson::spec::Pricing::AltBn128Pairing(ethjson::spec::AltBn128Pairing {
+				base: 100_000,
+				pair: 80_000,
+			}),
+			activate_at: None,
+		});
+
+		assert_eq!(b.cost(&[0; 0]), U256::from(100_000));
+		assert_eq!(b.cost(&[0; 192]), U256::from(180_000));
+		assert_eq!(b.cost(&[0; 384]), U256::from(260_000));
+	}
 }
@@ -45,6 +45,11 @@ pub trait ChainNotify : Send + Sync {
 		// does nothing by default
 	}
 
+	/// fires when the status of a snapshot restoration changes
+	fn restoration_status_changed(&self) {
+		// does nothing by default
+	}
+
 	/// fires when chain broadcasts a message
 	fn broadcast(&self, _data: Vec<u8>) {}
 
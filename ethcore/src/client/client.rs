@@ -68,6 +68,7 @@ use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Databa
 use trace::FlatTransactionTraces;
 use transaction::{LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, PendingTransaction, Action};
 use types::filter::Filter;
+use types::state_override::StateOverride;
 use types::mode::Mode as IpcMode;
 use verification;
 use verification::{PreverifiedBlock, Verifier};
@@ -83,6 +84,8 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+const MAX_ANCIENT_FREEZE: u64 = 8192;
+const MAX_HISTORY_PRUNE: u64 = 8192;
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -330,6 +333,11 @@ impl Client {
 		}
 	}
 
+	/// Notify listeners that the status of a snapshot restoration has changed.
+	pub fn notify_restoration_status(&self) {
+		self.notify(|notify| notify.restoration_status_changed());
+	}
+
 	/// Get the Registry object - useful for looking up names.
 	pub fn registrar(&self) -> MutexGuard<Option<Registry>> {
 		self.registrar.lock()
@@ -875,6 +883,34 @@ impl Client {
 		}
 	}
 
+	/// Executes a transaction on top of `state` in a "virtual" context, leaving its effects in `state`.
+	fn virtual_call(&self, state: &mut State<StateDB>, env_info: &EnvInfo, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, call_tracing: analytics.call_tracing, check_nonce: false };
+		let mut ret = Executive::new(state, env_info, &*self.engine).transact_virtual(t, options)?;
+
+		// TODO gav move this into Executive.
+		if let Some(original) = original_state {
+			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
+		}
+
+		Ok(ret)
+	}
+
+	/// Executes a block transaction on top of `state`, as if it were being imported,
+	/// and accumulates its gas usage into `env_info`.
+	fn replay_transaction(&self, state: &mut State<StateDB>, env_info: &mut EnvInfo, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, call_tracing: analytics.call_tracing, check_nonce: false };
+		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+		let mut ret = Executive::new(state, env_info, &*self.engine).transact(t, options)?;
+		if let Some(original) = original_state {
+			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?)
+		}
+		env_info.gas_used = env_info.gas_used + ret.gas_used;
+		Ok(ret)
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> State<StateDB> {
 		let header = self.best_block_header();
@@ -903,6 +939,8 @@ impl Client {
 	pub fn tick(&self) {
 		self.check_garbage();
 		self.check_snooze();
+		self.prune_history();
+		self.freeze_ancient();
 	}
 
 	fn check_garbage(&self) {
@@ -911,6 +949,26 @@ impl Client {
 		self.tracedb.read().collect_garbage();
 	}
 
+	fn prune_history(&self) {
+		let chain = self.chain.read();
+		let mut batch = DBTransaction::new();
+		let pruned = chain.prune_history(&mut batch, MAX_HISTORY_PRUNE);
+		if pruned.is_empty() {
+			return;
+		}
+
+		self.tracedb.read().prune(&mut batch, &pruned);
+		self.db.read().write(batch).expect("Low level database error. Some issue with disk?");
+		debug!(target: "client", "Pruned history of {} blocks", pruned.len());
+	}
+
+	fn freeze_ancient(&self) {
+		let moved = self.chain.read().freeze_ancient(MAX_ANCIENT_FREEZE);
+		if moved > 0 {
+			debug!(target: "client", "Moved {} blocks to ancient storage", moved);
+		}
+	}
+
 	fn check_snooze(&self) {
 		let mode = self.mode.lock().clone();
 		match mode {
@@ -948,6 +1006,24 @@ impl Client {
 	/// Take a snapshot at the given block.
 	/// If the ID given is "latest", this will default to 1000 blocks behind.
 	pub fn take_snapshot<W: snapshot_io::SnapshotWriter + Send>(&self, writer: W, at: BlockId, p: &snapshot::Progress) -> Result<(), EthcoreError> {
+		let (db, start_hash) = self.snapshot_start(at)?;
+		snapshot::take_snapshot(&*self.engine, &self.chain.read(), start_hash, db.as_hashdb(), writer, p)?;
+
+		Ok(())
+	}
+
+	/// Take a delta snapshot at the given block, holding only the state changes since the given
+	/// base snapshot. The state at the base block must still be available in the database.
+	/// If the ID given is "latest", this will default to 1000 blocks behind.
+	pub fn take_delta_snapshot<W: snapshot_io::SnapshotWriter + Send>(&self, writer: W, base: &snapshot::ManifestData, at: BlockId, p: &snapshot::Progress) -> Result<(), EthcoreError> {
+		let (db, start_hash) = self.snapshot_start(at)?;
+		snapshot::take_delta_snapshot(&*self.engine, &self.chain.read(), base, start_hash, db.as_hashdb(), writer, p)?;
+
+		Ok(())
+	}
+
+	// get the state database and the hash of the block a snapshot at the given block should start from.
+	fn snapshot_start(&self, at: BlockId) -> Result<(Box<journaldb::JournalDB>, H256), EthcoreError> {
 		let db = self.state_db.lock().journal_db().boxed_clone();
 		let best_block_number = self.chain_info().best_block_number;
 		let block_number = self.block_number(at).ok_or(snapshot::Error::InvalidStartingBlock(at))?;
@@ -976,9 +1052,7 @@ impl Client {
 			},
 		};
 
-		snapshot::take_snapshot(&*self.engine, &self.chain.read(), start_hash, db.as_hashdb(), writer, p)?;
-
-		Ok(())
+		Ok((db, start_hash))
 	}
 
 	/// Ask the client what the history parameter is.
@@ -995,6 +1069,35 @@ impl Client {
 		}
 	}
 
+	// Hashes of the blocks between `from` and `to` (inclusive, ascending) following the ancestry of `to`.
+	fn ancestry_route(&self, from: BlockId, to: BlockId) -> Vec<H256> {
+		let chain = self.chain.read();
+		let from_number = match from {
+			BlockId::Hash(ref hash) => chain.block_number(hash),
+			BlockId::Number(number) => Some(number),
+			BlockId::Earliest => Some(0),
+			BlockId::Latest | BlockId::Pending => Some(chain.best_block_number()),
+		};
+
+		let (from_number, mut current) = match (from_number, Self::block_hash(&chain, to)) {
+			(Some(number), Some(hash)) => (number, hash),
+			_ => return Vec::new(),
+		};
+
+		let mut blocks = Vec::new();
+		while let Some(details) = chain.block_details(&current) {
+			if details.number < from_number {
+				break;
+			}
+
+			blocks.push(current);
+			current = details.parent;
+		}
+
+		blocks.reverse();
+		blocks
+	}
+
 	fn transaction_address(&self, id: TransactionId) -> Option<TransactionAddress> {
 		match id {
 			TransactionId::Hash(ref hash) => self.chain.read().transaction_address(hash),
@@ -1062,6 +1165,44 @@ impl snapshot::DatabaseRestore for Client {
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
 		Ok(())
 	}
+
+	fn current_db(&self) -> Arc<KeyValueDB> {
+		self.db.read().clone()
+	}
+
+	/// Copy the whole database into the given one.
+	fn copy_db(&self, to: &KeyValueDB) -> Result<(), EthcoreError> {
+		const COPY_BATCH_SIZE: usize = 10_000;
+
+		trace!(target: "snapshot", "Copying client database");
+
+		// no blocks are imported meanwhile, so the copy holds a consistent chain and state.
+		let _import_lock = self.import_lock.lock();
+		let db = self.db.read();
+		db.flush().map_err(::util::UtilError::SimpleString)?;
+
+		let columns: Vec<_> = match ::db::NUM_COLUMNS {
+			Some(columns) => (0..columns).map(Some).collect(),
+			None => vec![None],
+		};
+
+		for col in columns {
+			let mut batch = DBTransaction::new();
+			let mut batch_len = 0;
+			for (key, value) in db.iter(col) {
+				batch.put(col, &key, &value);
+				batch_len += 1;
+				if batch_len == COPY_BATCH_SIZE {
+					to.write(batch).map_err(::util::UtilError::SimpleString)?;
+					batch = DBTransaction::new();
+					batch_len = 0;
+				}
+			}
+			to.write(batch).map_err(::util::UtilError::SimpleString)?;
+		}
+
+		Ok(())
+	}
 }
 
 impl BlockChainClient for Client {
@@ -1071,17 +1212,26 @@ impl BlockChainClient for Client {
 
 		// that's just a copy of the state.
 		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+		self.virtual_call(&mut state, &env_info, t, analytics)
+	}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let mut ret = Executive::new(&mut state, &env_info, &*self.engine).transact_virtual(t, options)?;
+	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], block: BlockId, state_override: &StateOverride) -> Result<Vec<Executed>, CallError> {
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		env_info.gas_limit = U256::max_value();
 
-		// TODO gav move this into Executive.
-		if let Some(original) = original_state {
-			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
+		// that's just a copy of the state.
+		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		state.apply_overrides(state_override).map_err(ExecutionError::from)?;
+		// commit the overrides so that the calls see them as the original state of every account.
+		state.commit().map_err(|_| CallError::StateCorrupt)?;
+
+		let mut results = Vec::with_capacity(transactions.len());
+		for &(ref t, analytics) in transactions {
+			let ret = self.virtual_call(&mut state, &env_info, t, analytics)?;
+			env_info.gas_used = env_info.gas_used + ret.gas_used;
+			results.push(ret);
 		}
-
-		Ok(ret)
+		Ok(results)
 	}
 
 	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError> {
@@ -1096,7 +1246,7 @@ impl BlockChainClient for Client {
 		// that's just a copy of the state.
 		let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
 		let sender = t.sender();
-		let options = TransactOptions { tracing: true, vm_tracing: false, check_nonce: false };
+		let options = TransactOptions { tracing: true, vm_tracing: false, call_tracing: false, check_nonce: false };
 
 		let cond = |gas| {
 			let mut tx = t.as_unsigned().clone();
@@ -1161,22 +1311,28 @@ impl BlockChainClient for Client {
 			return Err(CallError::TransactionNotFound);
 		}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
 		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
 		let rest = txs.split_off(address.index);
 		for t in txs {
 			let t = SignedTransaction::new(t).expect(PROOF);
-			let x = Executive::new(&mut state, &env_info, &*self.engine).transact(&t, Default::default())?;
-			env_info.gas_used = env_info.gas_used + x.gas_used;
+			self.replay_transaction(&mut state, &mut env_info, &t, Default::default())?;
 		}
 		let first = rest.into_iter().next().expect("We split off < `address.index`; Length is checked earlier; qed");
 		let t = SignedTransaction::new(first).expect(PROOF);
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
-		let mut ret = Executive::new(&mut state, &env_info, &*self.engine).transact(&t, options)?;
-		if let Some(original) = original_state {
-			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?)
-		}
-		Ok(ret)
+		self.replay_transaction(&mut state, &mut env_info, &t, analytics)
+	}
+
+	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Vec<(H256, Executed)>, CallError> {
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		body.transactions().into_iter().map(|t| {
+			let t = SignedTransaction::new(t).expect(PROOF);
+			let executed = self.replay_transaction(&mut state, &mut env_info, &t, analytics)?;
+			Ok((t.hash(), executed))
+		}).collect()
 	}
 
 	fn mode(&self) -> IpcMode {
@@ -1504,13 +1660,31 @@ impl BlockChainClient for Client {
 	}
 
 	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry> {
-		let blocks = filter.bloom_possibilities().iter()
-			.filter_map(|bloom| self.blocks_with_bloom(bloom, filter.from_block.clone(), filter.to_block.clone()))
-			.flat_map(|m| m)
-			// remove duplicate elements
-			.collect::<HashSet<u64>>()
-			.into_iter()
-			.collect::<Vec<u64>>();
+		let is_canon = |id: &BlockId| match *id {
+			BlockId::Hash(ref hash) => {
+				let chain = self.chain.read();
+				chain.block_number(hash).and_then(|number| chain.block_hash(number)) == Some(*hash)
+			},
+			_ => true,
+		};
+
+		let blocks = if is_canon(&filter.from_block) && is_canon(&filter.to_block) {
+			let mut numbers = filter.bloom_possibilities().iter()
+				.filter_map(|bloom| self.blocks_with_bloom(bloom, filter.from_block.clone(), filter.to_block.clone()))
+				.flat_map(|m| m)
+				// remove duplicate elements
+				.collect::<HashSet<u64>>()
+				.into_iter()
+				.collect::<Vec<u64>>();
+			numbers.sort();
+
+			let chain = self.chain.read();
+			numbers.into_iter().filter_map(|number| chain.block_hash(number)).collect()
+		} else {
+			// the range leaves the canonical chain (e.g. for retracted blocks),
+			// so blooms are of no use and the ancestry of `to_block` is followed instead.
+			self.ancestry_route(filter.from_block.clone(), filter.to_block.clone())
+		};
 
 		self.chain.read().logs(blocks, |entry| filter.matches(entry), filter.limit)
 	}
@@ -1586,6 +1760,11 @@ impl BlockChainClient for Client {
 		self.miner.ready_transactions(number, timestamp)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		let number = self.chain.read().best_block_number();
+		self.miner.transaction(number, hash)
+	}
+
 	fn queue_consensus_message(&self, message: Bytes) {
 		let channel = self.io_channel.lock().clone();
 		if let Err(e) = channel.send(ClientIoMessage::NewMessage(message)) {
@@ -1609,8 +1788,12 @@ impl BlockChainClient for Client {
 
 	fn pruning_info(&self) -> PruningInfo {
 		PruningInfo {
-			earliest_chain: self.chain.read().first_block_number().unwrap_or(1),
+			earliest_chain: {
+				let chain = self.chain.read();
+				::std::cmp::max(chain.first_block_number().unwrap_or(1), chain.history_since())
+			},
 			earliest_state: self.state_db.lock().journal_db().earliest_era().unwrap_or(0),
+			earliest_history: self.chain.read().history_since(),
 		}
 	}
 
@@ -1795,7 +1978,7 @@ impl ProvingBlockChainClient for Client {
 		let backend = state::backend::Proving::new(jdb.as_hashdb_mut());
 
 		let mut state = state.replace_backend(backend);
-		let options = TransactOptions { tracing: false, vm_tracing: false, check_nonce: false };
+		let options = TransactOptions { tracing: false, vm_tracing: false, call_tracing: false, check_nonce: false };
 		let res = Executive::new(&mut state, &env_info, &*self.engine).transact(&transaction, options);
 
 		match res {
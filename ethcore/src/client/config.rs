@@ -23,7 +23,7 @@ pub use trace::Config as TraceConfig;
 pub use evm::VMType;
 
 use verification::{VerifierType, QueueConfig};
-use util::{journaldb, CompactionProfile};
+use util::{journaldb, CompactionProfile, DatabaseBackend};
 
 /// Client state db compaction profile
 #[derive(Debug, PartialEq, Clone)]
@@ -121,6 +121,8 @@ pub struct ClientConfig {
 	pub db_compaction: DatabaseCompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool,
+	/// Database storage backend.
+	pub db_backend: DatabaseBackend,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
@@ -18,12 +18,16 @@
 
 use std::fmt;
 use std::sync::Arc;
-use util::{self, U256, journaldb, trie};
+use util::{self, U256, H256, journaldb, trie};
 use util::kvdb::{self, KeyValueDB};
-use {state, state_db, client, executive, trace, db, spec};
+use {state, state_db, client, executive, trace, db, spec, ethereum};
 use factory::Factories;
 use evm::{self, VMType};
 use action_params::ActionParams;
+use pod_state::PodState;
+use log_entry::LogEntry;
+use transaction::SignedTransaction;
+use ethjson::state::test::ForkSpec;
 
 /// EVM test Error.
 #[derive(Debug)]
@@ -51,25 +55,92 @@ impl fmt::Display for EvmTestError {
 	}
 }
 
+lazy_static! {
+	static ref FRONTIER: spec::Spec = ethereum::new_frontier_test();
+	static ref HOMESTEAD: spec::Spec = ethereum::new_homestead_test();
+	static ref EIP150: spec::Spec = ethereum::new_eip150_test();
+	static ref EIP161: spec::Spec = ethereum::new_eip161_test();
+	static ref BYZANTIUM: spec::Spec = ethereum::new_byzantium_test();
+	static ref CONSTANTINOPLE: spec::Spec = ethereum::new_constantinople_test();
+}
+
 /// Simplified, single-block EVM test client.
-pub struct EvmTestClient {
-	state_db: state_db::StateDB,
-	factories: Factories,
-	spec: spec::Spec,
+pub struct EvmTestClient<'a> {
+	state: state::State<state_db::StateDB>,
+	spec: &'a spec::Spec,
 }
 
-impl EvmTestClient {
+/// Outcome of a transaction executed by `EvmTestClient::transact`.
+pub enum TransactResult {
+	/// Transaction was executed.
+	Ok {
+		/// State root after the transaction.
+		state_root: H256,
+		/// Amount of gas left.
+		gas_left: U256,
+		/// Output of the top-level call.
+		output: Vec<u8>,
+		/// Logs produced by the transaction.
+		logs: Vec<LogEntry>,
+	},
+	/// Transaction was rejected or failed to execute.
+	Err {
+		/// State root after the transaction.
+		state_root: H256,
+		/// Rejection reason.
+		error: ::error::Error,
+	},
+}
+
+impl<'a> EvmTestClient<'a> {
+	/// Converts a json spec definition into a spec, if the fork is supported.
+	pub fn spec_from_json(spec: &ForkSpec) -> Option<&'static spec::Spec> {
+		match *spec {
+			ForkSpec::Frontier => Some(&*FRONTIER),
+			ForkSpec::Homestead => Some(&*HOMESTEAD),
+			ForkSpec::EIP150 => Some(&*EIP150),
+			ForkSpec::EIP158 => Some(&*EIP161),
+			ForkSpec::Byzantium => Some(&*BYZANTIUM),
+			ForkSpec::Constantinople => Some(&*CONSTANTINOPLE),
+			ForkSpec::Metropolis => None,
+		}
+	}
+
 	/// Creates new EVM test client with in-memory DB initialized with genesis of given Spec.
-	pub fn new(spec: spec::Spec) -> Result<Self, EvmTestError> {
-		let factories = Factories {
+	pub fn new(spec: &'a spec::Spec) -> Result<Self, EvmTestError> {
+		let factories = Self::factories();
+		let state = Self::state_from_spec(spec, &factories)?;
+
+		Ok(EvmTestClient {
+			state,
+			spec,
+		})
+	}
+
+	/// Creates new EVM test client with in-memory DB initialized with given PodState.
+	pub fn from_pod_state(spec: &'a spec::Spec, pod_state: PodState) -> Result<Self, EvmTestError> {
+		let factories = Self::factories();
+		let state = Self::state_from_pod(spec, &factories, pod_state)?;
+
+		Ok(EvmTestClient {
+			state,
+			spec,
+		})
+	}
+
+	fn factories() -> Factories {
+		Factories {
 			vm: evm::Factory::new(VMType::Interpreter, 5 * 1024),
 			trie: trie::TrieFactory::new(trie::TrieSpec::Secure),
 			accountdb: Default::default(),
-		};
+		}
+	}
+
+	fn state_from_spec(spec: &spec::Spec, factories: &Factories) -> Result<state::State<state_db::StateDB>, EvmTestError> {
 		let db = Arc::new(kvdb::in_memory(db::NUM_COLUMNS.expect("We use column-based DB; qed")));
 		let journal_db = journaldb::new(db.clone(), journaldb::Algorithm::EarlyMerge, db::COL_STATE);
 		let mut state_db = state_db::StateDB::new(journal_db, 5 * 1024 * 1024);
-		state_db = spec.ensure_db_good(state_db, &factories).map_err(EvmTestError::Initialization)?;
+		state_db = spec.ensure_db_good(state_db, factories).map_err(EvmTestError::Initialization)?;
 		// Write DB
 		{
 			let mut batch = kvdb::DBTransaction::new();
@@ -77,11 +148,26 @@ impl EvmTestClient {
 			db.write(batch).map_err(EvmTestError::Database)?;
 		}
 
-		Ok(EvmTestClient {
+		state::State::from_existing(
 			state_db,
-			factories,
-			spec,
-		})
+			*spec.genesis_header().state_root(),
+			spec.engine.account_start_nonce(0),
+			factories.clone()
+		).map_err(EvmTestError::Trie)
+	}
+
+	fn state_from_pod(spec: &spec::Spec, factories: &Factories, pod_state: PodState) -> Result<state::State<state_db::StateDB>, EvmTestError> {
+		let db = Arc::new(kvdb::in_memory(db::NUM_COLUMNS.expect("We use column-based DB; qed")));
+		let journal_db = journaldb::new(db.clone(), journaldb::Algorithm::EarlyMerge, db::COL_STATE);
+		let state_db = state_db::StateDB::new(journal_db, 5 * 1024 * 1024);
+		let mut state = state::State::new(
+			state_db,
+			spec.engine.account_start_nonce(0),
+			factories.clone(),
+		);
+		state.populate_from(pod_state);
+		state.commit().map_err(EvmTestError::Initialization)?;
+		Ok(state)
 	}
 
 	/// Call given contract.
@@ -89,21 +175,19 @@ impl EvmTestClient {
 		-> Result<(U256, Vec<u8>), EvmTestError>
 	{
 		let genesis = self.spec.genesis_header();
-		let mut state = state::State::from_existing(self.state_db.boxed_clone(), *genesis.state_root(), self.spec.engine.account_start_nonce(0), self.factories.clone())
-			.map_err(EvmTestError::Trie)?;
 		let info = client::EnvInfo {
 			number: genesis.number(),
 			author: *genesis.author(),
 			timestamp: genesis.timestamp(),
 			difficulty: *genesis.difficulty(),
-			last_hashes: Arc::new([util::H256::default(); 256].to_vec()),
+			last_hashes: Arc::new([H256::default(); 256].to_vec()),
 			gas_used: 0.into(),
 			gas_limit: *genesis.gas_limit(),
 		};
 		let mut substate = state::Substate::new();
 		let mut tracer = trace::NoopTracer;
 		let mut output = vec![];
-		let mut executive = executive::Executive::new(&mut state, &info, &*self.spec.engine);
+		let mut executive = executive::Executive::new(&mut self.state, &info, &*self.spec.engine);
 		let (gas_left, _) = executive.call(
 			params,
 			&mut substate,
@@ -114,4 +198,47 @@ impl EvmTestClient {
 
 		Ok((gas_left, output))
 	}
+
+	/// Executes a SignedTransaction within context of the provided state and `EnvInfo`.
+	/// Changes are committed, so the returned state root includes the transaction.
+	pub fn transact<T: trace::VMTracer>(
+		&mut self,
+		env_info: &client::EnvInfo,
+		transaction: SignedTransaction,
+		vm_tracer: T,
+	) -> TransactResult {
+		let initial_gas = transaction.gas;
+		// Verify transaction
+		let is_ok = transaction.verify_basic(true, None, env_info.number >= self.spec.engine.params().eip86_transition);
+		if let Err(error) = is_ok {
+			return TransactResult::Err {
+				state_root: *self.state.root(),
+				error: error.into(),
+			};
+		}
+
+		// Apply transaction
+		let result = {
+			let mut executive = executive::Executive::new(&mut self.state, env_info, &*self.spec.engine);
+			executive.transact_with_tracer(&transaction, true, trace::NoopTracer, vm_tracer)
+		};
+		let committed = self.state.commit();
+
+		match (result, committed) {
+			(Ok(executed), Ok(())) => TransactResult::Ok {
+				state_root: *self.state.root(),
+				gas_left: initial_gas - executed.gas_used,
+				output: executed.output,
+				logs: executed.logs,
+			},
+			(Err(error), _) => TransactResult::Err {
+				state_root: *self.state.root(),
+				error: error.into(),
+			},
+			(_, Err(error)) => TransactResult::Err {
+				state_root: *self.state.root(),
+				error: error,
+			},
+		}
+	}
 }
@@ -42,6 +42,7 @@ use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::state_override::StateOverride;
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock};
@@ -397,6 +398,11 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], _block: BlockId, _state_override: &StateOverride) -> Result<Vec<Executed>, CallError> {
+		let result = self.execution_result.read().clone().unwrap()?;
+		Ok(transactions.iter().map(|_| result.clone()).collect())
+	}
+
 	fn estimate_gas(&self, _t: &SignedTransaction, _block: BlockId) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
@@ -405,6 +411,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn replay_block_transactions(&self, _block: BlockId, _analytics: CallAnalytics) -> Result<Vec<(H256, Executed)>, CallError> {
+		self.execution_result.read().clone().unwrap().map(|res| vec![(H256::default(), res)])
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -510,8 +520,15 @@ impl BlockChainClient for TestBlockChainClient {
 			.map(encoded::Header::new)
 	}
 
-	fn block_number(&self, _id: BlockId) -> Option<BlockNumber> {
-		unimplemented!()
+	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
+		match id {
+			BlockId::Number(number) => Some(number),
+			BlockId::Earliest => Some(0),
+			BlockId::Latest | BlockId::Pending => Some(self.numbers.read().len() as BlockNumber - 1),
+			BlockId::Hash(hash) => self.numbers.read().iter()
+				.find(|&(_, h)| *h == hash)
+				.map(|(&number, _)| number as BlockNumber),
+		}
 	}
 
 	fn block_body(&self, id: BlockId) -> Option<encoded::Body> {
@@ -718,6 +735,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(info.best_block_number, info.best_block_timestamp)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.miner.transaction(self.chain_info().best_block_number, hash)
+	}
+
 	fn signing_network_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -735,6 +756,7 @@ impl BlockChainClient for TestBlockChainClient {
 		PruningInfo {
 			earliest_chain: 1,
 			earliest_state: self.history.read().as_ref().map(|x| best_num - x).unwrap_or(0),
+			earliest_history: 0,
 		}
 	}
 
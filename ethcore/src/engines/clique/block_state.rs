@@ -0,0 +1,333 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signer set and vote tally of a Clique chain at a given block.
+
+use std::collections::{BTreeMap, BTreeSet};
+use util::*;
+use engines::EngineError;
+use error::{Error, BlockError};
+use header::{Header, BlockNumber};
+use super::{header_nonce, extract_signers, NONCE_AUTH_VOTE, NONCE_DROP_VOTE};
+
+/// Difficulty of a block sealed by the in-turn signer.
+pub const DIFF_INTURN: u64 = 2;
+/// Difficulty of a block sealed by an out-of-turn signer.
+pub const DIFF_NOTURN: u64 = 1;
+
+/// A single vote cast by a signer through the coinbase and nonce of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Vote {
+	signer: Address,
+	beneficiary: Address,
+	authorize: bool,
+}
+
+/// Running tally of the votes for a single beneficiary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tally {
+	authorize: bool,
+	votes: usize,
+}
+
+/// Clique state after a given block: the authorized signers, the outstanding
+/// votes and the signers of the most recent blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliqueBlockState {
+	signers: BTreeSet<Address>,
+	votes: Vec<Vote>,
+	tally: HashMap<Address, Tally>,
+	recents: BTreeMap<BlockNumber, Address>,
+}
+
+impl CliqueBlockState {
+	/// Create the state from a checkpoint header, which carries the full signer list.
+	pub fn from_checkpoint(header: &Header) -> Result<Self, Error> {
+		Ok(CliqueBlockState {
+			signers: extract_signers(header)?.into_iter().collect(),
+			votes: Vec::new(),
+			tally: HashMap::new(),
+			recents: BTreeMap::new(),
+		})
+	}
+
+	/// Record `signer` as the signer of the block with the given number. Used to restore the
+	/// recent signers of a state rebuilt from a checkpoint.
+	pub fn note_recent(&mut self, number: BlockNumber, signer: Address) {
+		self.recents.insert(number, signer);
+	}
+
+	/// Authorized signers, in ascending order.
+	pub fn signers(&self) -> &BTreeSet<Address> {
+		&self.signers
+	}
+
+	/// Whether `signer` is the in-turn signer of the block with the given number.
+	pub fn is_inturn(&self, number: BlockNumber, signer: &Address) -> bool {
+		let len = self.signers.len() as u64;
+		len != 0 && self.signers.iter().nth((number % len) as usize) == Some(signer)
+	}
+
+	/// Difficulty a block with the given number must have when sealed by `signer`.
+	pub fn difficulty(&self, number: BlockNumber, signer: &Address) -> U256 {
+		match self.is_inturn(number, signer) {
+			true => DIFF_INTURN.into(),
+			false => DIFF_NOTURN.into(),
+		}
+	}
+
+	/// Whether `signer` sealed one of the last `signers / 2 + 1` blocks before `number`
+	/// and may therefore not seal it.
+	pub fn is_recently_signed(&self, number: BlockNumber, signer: &Address) -> bool {
+		let limit = self.recent_limit();
+		self.recents.iter().any(|(&seen, recent)| recent == signer && seen + limit > number)
+	}
+
+	/// Apply the block `header` sealed by `signer` on top of this state.
+	///
+	/// Fails if the signer is not authorized or signed too recently.
+	pub fn apply(&mut self, header: &Header, signer: Address, epoch_length: u64) -> Result<(), Error> {
+		let number = header.number();
+
+		if !self.signers.contains(&signer) {
+			return Err(EngineError::NotAuthorized(signer).into());
+		}
+		if self.is_recently_signed(number, &signer) {
+			return Err(EngineError::RecentlySigned(signer).into());
+		}
+
+		// votes are discarded at every checkpoint.
+		if number % epoch_length == 0 {
+			self.votes.clear();
+			self.tally.clear();
+		}
+
+		self.recents.insert(number, signer);
+		self.prune_recents(number);
+
+		let beneficiary = *header.author();
+		let nonce = header_nonce(header)?;
+		let authorize = if nonce == H64::from(NONCE_AUTH_VOTE) {
+			true
+		} else if nonce == H64::from(NONCE_DROP_VOTE) {
+			false
+		} else {
+			return Err(BlockError::InvalidSeal.into());
+		};
+
+		// a signer only has a single outstanding vote per beneficiary.
+		if let Some(position) = self.votes.iter().position(|v| v.signer == signer && v.beneficiary == beneficiary) {
+			let vote = self.votes.remove(position);
+			self.uncast(&vote.beneficiary, vote.authorize);
+		}
+
+		if self.cast(beneficiary, authorize) {
+			self.votes.push(Vote {
+				signer: signer,
+				beneficiary: beneficiary,
+				authorize: authorize,
+			});
+		}
+
+		let passed = match self.tally.get(&beneficiary) {
+			Some(tally) if tally.votes > self.signers.len() / 2 => Some(tally.authorize),
+			_ => None,
+		};
+
+		if let Some(authorize) = passed {
+			if authorize {
+				self.signers.insert(beneficiary);
+			} else {
+				self.signers.remove(&beneficiary);
+				self.prune_recents(number);
+
+				// the votes of a dropped signer no longer count.
+				let (dropped, kept) = self.votes.drain(..).partition::<Vec<_>, _>(|v| v.signer == beneficiary);
+				self.votes = kept;
+				for vote in dropped {
+					self.uncast(&vote.beneficiary, vote.authorize);
+				}
+			}
+
+			self.votes.retain(|v| v.beneficiary != beneficiary);
+			self.tally.remove(&beneficiary);
+		}
+
+		Ok(())
+	}
+
+	/// Number of most recent blocks a signer has to wait for before sealing again.
+	pub fn recent_limit(&self) -> u64 {
+		(self.signers.len() / 2 + 1) as u64
+	}
+
+	// forget signers which are allowed to seal again after block `number`.
+	fn prune_recents(&mut self, number: BlockNumber) {
+		let limit = self.recent_limit();
+		if number + 1 > limit {
+			self.recents = self.recents.split_off(&(number + 1 - limit));
+		}
+	}
+
+	// whether a vote makes sense: only outsiders can be authorized and only signers dropped.
+	fn is_valid_vote(&self, beneficiary: &Address, authorize: bool) -> bool {
+		self.signers.contains(beneficiary) != authorize
+	}
+
+	fn cast(&mut self, beneficiary: Address, authorize: bool) -> bool {
+		if !self.is_valid_vote(&beneficiary, authorize) {
+			return false;
+		}
+
+		self.tally.entry(beneficiary)
+			.or_insert(Tally { authorize: authorize, votes: 0 })
+			.votes += 1;
+		true
+	}
+
+	fn uncast(&mut self, beneficiary: &Address, authorize: bool) {
+		let remove = match self.tally.get_mut(beneficiary) {
+			Some(tally) if tally.authorize == authorize => {
+				tally.votes -= 1;
+				tally.votes == 0
+			}
+			_ => false,
+		};
+
+		if remove {
+			self.tally.remove(beneficiary);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use rlp::encode;
+	use engines::EngineError;
+	use error::Error;
+	use header::{Header, BlockNumber};
+	use super::super::{VANITY_LENGTH, SIGNATURE_LENGTH, NONCE_AUTH_VOTE, NONCE_DROP_VOTE};
+	use super::CliqueBlockState;
+
+	const EPOCH_LENGTH: u64 = 30_000;
+
+	fn checkpoint(signers: &[Address]) -> Header {
+		let mut extra_data = vec![0; VANITY_LENGTH];
+		for signer in signers {
+			extra_data.extend_from_slice(signer);
+		}
+		extra_data.extend_from_slice(&[0u8; SIGNATURE_LENGTH]);
+
+		let mut header = Header::default();
+		header.set_extra_data(extra_data);
+		header
+	}
+
+	fn vote(number: BlockNumber, beneficiary: Address, nonce: [u8; 8]) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_author(beneficiary);
+		header.set_seal(vec![encode(&H256::default()).into_vec(), encode(&H64::from(nonce)).into_vec()]);
+		header
+	}
+
+	#[test]
+	fn rotates_inturn_signer() {
+		let (a, b) = (Address::from(1), Address::from(2));
+		let state = CliqueBlockState::from_checkpoint(&checkpoint(&[b, a])).unwrap();
+
+		assert!(state.is_inturn(0, &a));
+		assert!(state.is_inturn(1, &b));
+		assert!(!state.is_inturn(2, &b));
+		assert_eq!(state.difficulty(2, &a), 2.into());
+		assert_eq!(state.difficulty(2, &b), 1.into());
+	}
+
+	#[test]
+	fn authorizes_signer_on_majority() {
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a, b])).unwrap();
+
+		state.apply(&vote(1, c, NONCE_AUTH_VOTE), a, EPOCH_LENGTH).unwrap();
+		assert!(!state.signers().contains(&c));
+		state.apply(&vote(2, c, NONCE_AUTH_VOTE), b, EPOCH_LENGTH).unwrap();
+		assert!(state.signers().contains(&c));
+	}
+
+	#[test]
+	fn drops_signer_and_discards_its_votes() {
+		let (a, b, c, d) = (Address::from(1), Address::from(2), Address::from(3), Address::from(4));
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a, b, c])).unwrap();
+
+		state.apply(&vote(1, d, NONCE_AUTH_VOTE), c, EPOCH_LENGTH).unwrap();
+		state.apply(&vote(2, c, NONCE_DROP_VOTE), a, EPOCH_LENGTH).unwrap();
+		state.apply(&vote(3, c, NONCE_DROP_VOTE), b, EPOCH_LENGTH).unwrap();
+		assert!(!state.signers().contains(&c));
+
+		// the vote of `c` for `d` is gone, so one more vote is not enough.
+		state.apply(&vote(4, d, NONCE_AUTH_VOTE), a, EPOCH_LENGTH).unwrap();
+		assert!(!state.signers().contains(&d));
+		state.apply(&vote(5, d, NONCE_AUTH_VOTE), b, EPOCH_LENGTH).unwrap();
+		assert!(state.signers().contains(&d));
+	}
+
+	#[test]
+	fn resets_votes_on_checkpoint() {
+		let (a, b, c, d) = (Address::from(1), Address::from(2), Address::from(3), Address::from(4));
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a, b, c])).unwrap();
+
+		state.apply(&vote(1, d, NONCE_AUTH_VOTE), a, 2).unwrap();
+		state.apply(&vote(2, Address::default(), NONCE_DROP_VOTE), b, 2).unwrap();
+		state.apply(&vote(3, d, NONCE_AUTH_VOTE), c, 2).unwrap();
+		assert!(!state.signers().contains(&d));
+	}
+
+	#[test]
+	fn rejects_recent_and_unauthorized_signers() {
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a, b])).unwrap();
+
+		state.apply(&vote(1, Address::default(), NONCE_DROP_VOTE), a, EPOCH_LENGTH).unwrap();
+		match state.clone().apply(&vote(2, Address::default(), NONCE_DROP_VOTE), a, EPOCH_LENGTH) {
+			Err(Error::Engine(EngineError::RecentlySigned(signer))) => assert_eq!(signer, a),
+			other => panic!("expected recently signed error, got {:?}", other),
+		}
+		match state.clone().apply(&vote(2, Address::default(), NONCE_DROP_VOTE), c, EPOCH_LENGTH) {
+			Err(Error::Engine(EngineError::NotAuthorized(signer))) => assert_eq!(signer, c),
+			other => panic!("expected not authorized error, got {:?}", other),
+		}
+
+		state.apply(&vote(2, Address::default(), NONCE_DROP_VOTE), b, EPOCH_LENGTH).unwrap();
+		state.apply(&vote(3, Address::default(), NONCE_DROP_VOTE), a, EPOCH_LENGTH).unwrap();
+	}
+
+	#[test]
+	fn checkpoint_state_keeps_noted_recents() {
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a, b, c])).unwrap();
+		state.note_recent(9, b);
+		state.note_recent(10, a);
+
+		assert!(state.is_recently_signed(11, &a));
+		assert!(!state.is_recently_signed(11, &b));
+		assert!(!state.is_recently_signed(12, &a));
+		match state.apply(&vote(11, Address::default(), NONCE_DROP_VOTE), a, 10) {
+			Err(Error::Engine(EngineError::RecentlySigned(signer))) => assert_eq!(signer, a),
+			other => panic!("expected recently signed error, got {:?}", other),
+		}
+	}
+}
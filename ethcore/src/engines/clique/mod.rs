@@ -0,0 +1,666 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique (EIP-225) proof-of-authority consensus engine.
+//!
+//! Blocks are sealed by a set of authorized signers who append their signature to the
+//! header extra data. Signers vote on adding and removing signers through the coinbase
+//! and nonce of the blocks they seal. Every `epoch` blocks a checkpoint carrying the full
+//! signer list is issued and all pending votes are discarded.
+
+use std::sync::Weak;
+use std::time::{UNIX_EPOCH, Duration};
+
+use account_provider::AccountProvider;
+use block::*;
+use builtin::Builtin;
+use client::{Client, EngineClient, BlockChainClient, BlockId};
+use engines::{Engine, Seal, EngineError};
+use error::{BlockError, Error};
+use ethkey::{recover, public_to_address, Signature};
+use header::{Header, BlockNumber};
+use io::{IoContext, IoHandler, TimerToken, IoService};
+use lru_cache::LruCache;
+use rlp::{UntrustedRlp, encode};
+use spec::CommonParams;
+use util::*;
+
+use super::signer::EngineSigner;
+
+use self::block_state::{CliqueBlockState, DIFF_INTURN, DIFF_NOTURN};
+
+pub use self::params::CliqueParams;
+
+mod block_state;
+mod params;
+
+/// Number of extra data prefix bytes reserved for signer vanity.
+pub const VANITY_LENGTH: usize = 32;
+/// Number of extra data suffix bytes reserved for the signer seal.
+pub const SIGNATURE_LENGTH: usize = 65;
+/// Nonce of a block voting to authorize its beneficiary.
+pub const NONCE_AUTH_VOTE: [u8; 8] = [0xff; 8];
+/// Nonce of a block voting to drop its beneficiary.
+pub const NONCE_DROP_VOTE: [u8; 8] = [0x00; 8];
+
+/// Number of block states kept in memory.
+const STATE_CACHE_SIZE: usize = 128;
+/// Number of recovered block signers kept in memory.
+const SIGNER_CACHE_SIZE: usize = 4096;
+/// Delay per half of the signer set before an out-of-turn signer seals.
+const OUT_OF_TURN_DELAY_MS: u64 = 500;
+/// Interval at which sealing is retried.
+const STEP_INTERVAL_MS: u64 = 1000;
+
+// the vote carried by the nonce seal field.
+fn header_nonce(header: &Header) -> Result<H64, Error> {
+	match header.seal().get(1) {
+		Some(nonce) => Ok(UntrustedRlp::new(nonce).as_val::<H64>()?),
+		None => Err(BlockError::InvalidSealArity(Mismatch { expected: 2, found: header.seal().len() }).into()),
+	}
+}
+
+// the signer list carried by a checkpoint header.
+fn extract_signers(header: &Header) -> Result<Vec<Address>, Error> {
+	let extra_data = header.extra_data();
+	if extra_data.len() < VANITY_LENGTH + SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(
+			OutOfBounds { min: Some(VANITY_LENGTH + SIGNATURE_LENGTH), max: None, found: extra_data.len() }
+		).into());
+	}
+
+	let signers = &extra_data[VANITY_LENGTH..extra_data.len() - SIGNATURE_LENGTH];
+	if signers.len() % 20 != 0 {
+		return Err(EngineError::InvalidCheckpoint("signer list length is not a multiple of 20 bytes".into()).into());
+	}
+
+	Ok(signers.chunks(20).map(Address::from_slice).collect())
+}
+
+// hash signed by the sealer: the full header without the signature itself.
+fn seal_hash(header: &Header) -> H256 {
+	let mut unsigned = header.clone();
+	let unsigned_len = unsigned.extra_data().len().saturating_sub(SIGNATURE_LENGTH);
+	unsigned.extra_data_mut().truncate(unsigned_len);
+	unsigned.rlp_sha3(::header::Seal::With)
+}
+
+// recover the address which sealed the header.
+fn recover_creator(header: &Header) -> Result<Address, Error> {
+	let extra_data = header.extra_data();
+	if extra_data.len() < SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(
+			OutOfBounds { min: Some(SIGNATURE_LENGTH), max: None, found: extra_data.len() }
+		).into());
+	}
+
+	let signature = H520::from_slice(&extra_data[extra_data.len() - SIGNATURE_LENGTH..]);
+	Ok(public_to_address(&recover(&signature.into(), &seal_hash(header))?))
+}
+
+fn unix_now() -> Duration {
+	UNIX_EPOCH.elapsed().expect("Valid time has to be set in your system.")
+}
+
+/// Engine using `Clique` proof-of-authority consensus.
+pub struct Clique {
+	params: CommonParams,
+	gas_limit_bound_divisor: U256,
+	period: u64,
+	epoch_length: u64,
+	builtins: BTreeMap<Address, Builtin>,
+	step_service: IoService<()>,
+	client: RwLock<Option<Weak<EngineClient>>>,
+	signer: EngineSigner,
+	block_states: Mutex<LruCache<H256, CliqueBlockState>>,
+	recovered_signers: Mutex<LruCache<H256, Address>>,
+	proposals: RwLock<BTreeMap<Address, bool>>,
+}
+
+impl Clique {
+	/// Create a new instance of Clique engine.
+	pub fn new(params: CommonParams, our_params: CliqueParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
+		let engine = Arc::new(
+			Clique {
+				params: params,
+				gas_limit_bound_divisor: our_params.gas_limit_bound_divisor,
+				period: our_params.period,
+				epoch_length: our_params.epoch,
+				builtins: builtins,
+				step_service: IoService::<()>::start()?,
+				client: RwLock::new(None),
+				signer: Default::default(),
+				block_states: Mutex::new(LruCache::new(STATE_CACHE_SIZE)),
+				recovered_signers: Mutex::new(LruCache::new(SIGNER_CACHE_SIZE)),
+				proposals: RwLock::new(BTreeMap::new()),
+			});
+
+		let handler = StepHandler { engine: Arc::downgrade(&engine) };
+		engine.step_service.register_handler(Arc::new(handler))?;
+		Ok(engine)
+	}
+
+	fn is_checkpoint(&self, number: BlockNumber) -> bool {
+		number % self.epoch_length == 0
+	}
+
+	fn recover_signer(&self, header: &Header) -> Result<Address, Error> {
+		let hash = header.hash();
+		if let Some(signer) = self.recovered_signers.lock().get_mut(&hash) {
+			return Ok(*signer);
+		}
+
+		let signer = recover_creator(header)?;
+		self.recovered_signers.lock().insert(hash, signer);
+		Ok(signer)
+	}
+
+	/// Clique state after the block with the given hash. States which are not cached are
+	/// rebuilt by replaying the headers since the last cached state or checkpoint.
+	fn state(&self, hash: &H256) -> Result<CliqueBlockState, Error> {
+		if let Some(state) = self.block_states.lock().get_mut(hash) {
+			return Ok(state.clone());
+		}
+
+		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
+			Some(client) => client,
+			None => {
+				debug!(target: "engine", "Unable to load Clique state: missing client ref.");
+				return Err(EngineError::RequiresClient.into());
+			}
+		};
+
+		let mut headers = Vec::new();
+		let mut current = *hash;
+		let mut base = None;
+		while base.is_none() {
+			if let Some(state) = self.block_states.lock().get_mut(&current) {
+				base = Some(state.clone());
+				continue;
+			}
+
+			let header = match client.block_header(BlockId::Hash(current)) {
+				Some(header) => header.decode(),
+				None => return Err(BlockError::UnknownParent(current).into()),
+			};
+
+			if self.is_checkpoint(header.number()) {
+				let mut state = CliqueBlockState::from_checkpoint(&header)?;
+				self.restore_recents(&*client, &header, &mut state)?;
+				self.block_states.lock().insert(current, state.clone());
+				base = Some(state);
+			} else {
+				current = *header.parent_hash();
+				headers.push(header);
+			}
+		}
+
+		let mut state = base.expect("loop only exits once a base state is found; qed");
+		for header in headers.into_iter().rev() {
+			let signer = self.recover_signer(&header)?;
+			state.apply(&header, signer, self.epoch_length)?;
+			self.block_states.lock().insert(header.hash(), state.clone());
+		}
+
+		Ok(state)
+	}
+
+	// the checkpoint only carries the signer list, so the signers of the blocks up to it
+	// which may not seal the next ones are recovered from the headers.
+	fn restore_recents(&self, client: &EngineClient, checkpoint: &Header, state: &mut CliqueBlockState) -> Result<(), Error> {
+		let mut current = checkpoint.clone();
+		for _ in 0..state.recent_limit() {
+			if current.number() == 0 { break }
+
+			state.note_recent(current.number(), self.recover_signer(&current)?);
+			current = match client.block_header(BlockId::Hash(*current.parent_hash())) {
+				Some(header) => header.decode(),
+				// older headers may be missing after a snapshot restoration.
+				None => break,
+			};
+		}
+
+		Ok(())
+	}
+
+	// one of the proposals which is still a valid vote on top of `state`, rotating through
+	// them from block to block.
+	fn proposed_vote(&self, number: BlockNumber, state: &CliqueBlockState) -> Option<Address> {
+		let valid: Vec<Address> = self.proposals.read().iter()
+			.filter(|&(address, &authorize)| state.signers().contains(address) != authorize)
+			.map(|(address, _)| *address)
+			.collect();
+
+		match valid.len() {
+			0 => None,
+			len => Some(valid[(number % len as u64) as usize]),
+		}
+	}
+}
+
+struct StepHandler {
+	engine: Weak<Clique>,
+}
+
+const ENGINE_STEP_TOKEN: TimerToken = 23;
+
+impl IoHandler<()> for StepHandler {
+	fn initialize(&self, io: &IoContext<()>) {
+		io.register_timer(ENGINE_STEP_TOKEN, STEP_INTERVAL_MS)
+			.unwrap_or_else(|e| warn!(target: "engine", "Failed to start Clique step timer: {}.", e))
+	}
+
+	fn timeout(&self, _io: &IoContext<()>, timer: TimerToken) {
+		if timer == ENGINE_STEP_TOKEN {
+			if let Some(engine) = self.engine.upgrade() {
+				engine.step();
+			}
+		}
+	}
+}
+
+impl Engine for Clique {
+	fn name(&self) -> &str { "Clique" }
+
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+
+	/// Two fields - mix hash and nonce. The signature lives in the extra data.
+	fn seal_fields(&self) -> usize { 2 }
+
+	fn params(&self) -> &CommonParams { &self.params }
+
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+
+	fn maximum_uncle_count(&self) -> usize { 0 }
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
+		map![
+			"signer".into() => recover_creator(header).as_ref().map(ToString::to_string).unwrap_or("".into())
+		]
+	}
+
+	fn step(&self) {
+		if self.signer.address() == Address::default() { return }
+
+		if let Some(ref weak) = *self.client.read() {
+			if let Some(c) = weak.upgrade() {
+				c.update_sealing();
+			}
+		}
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.set_timestamp(max(header.timestamp(), parent.timestamp() + self.period));
+
+		// the coinbase of a block carries the vote of its signer, checkpoints don't vote.
+		let (difficulty, beneficiary) = match self.state(&parent.hash()) {
+			Ok(state) => {
+				let beneficiary = match self.is_checkpoint(header.number()) {
+					true => None,
+					false => self.proposed_vote(header.number(), &state),
+				};
+				(state.difficulty(header.number(), &self.signer.address()), beneficiary)
+			},
+			Err(_) => (DIFF_NOTURN.into(), None),
+		};
+		header.set_author(beneficiary.unwrap_or_default());
+		header.set_difficulty(difficulty);
+
+		header.set_gas_limit({
+			let gas_limit = parent.gas_limit().clone();
+			let bound_divisor = self.gas_limit_bound_divisor;
+			if gas_limit < gas_floor_target {
+				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			}
+		});
+	}
+
+	fn seals_internally(&self) -> Option<bool> {
+		Some(self.signer.address() != Address::default())
+	}
+
+	/// Attempt to seal the block internally.
+	///
+	/// The signature itself is added to the extra data in `on_seal_block`.
+	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
+		let header = block.header();
+		let signer = self.signer.address();
+
+		let state = match self.state(header.parent_hash()) {
+			Ok(state) => state,
+			Err(e) => {
+				warn!(target: "engine", "generate_seal: Unable to load parent state: {}", e);
+				return Seal::None;
+			}
+		};
+
+		if !state.signers().contains(&signer) {
+			trace!(target: "engine", "generate_seal: {} is not an authorized signer.", signer);
+			return Seal::None;
+		}
+
+		if state.is_recently_signed(header.number(), &signer) {
+			trace!(target: "engine", "generate_seal: {} signed one of the recent blocks.", signer);
+			return Seal::None;
+		}
+
+		if *header.difficulty() != state.difficulty(header.number(), &signer) {
+			trace!(target: "engine", "generate_seal: Block difficulty is stale.");
+			return Seal::None;
+		}
+
+		// give the in-turn signer a head start.
+		let mut seal_time = Duration::from_secs(header.timestamp());
+		if !state.is_inturn(header.number(), &signer) {
+			seal_time = seal_time + Duration::from_millis(OUT_OF_TURN_DELAY_MS * (state.signers().len() / 2 + 1) as u64);
+		}
+
+		if unix_now() < seal_time {
+			trace!(target: "engine", "generate_seal: Block #{} is not due yet.", header.number());
+			return Seal::None;
+		}
+
+		// the only valid vote on a beneficiary is the one changing its status.
+		let beneficiary = header.author();
+		let nonce = match *beneficiary != Address::default() && !state.signers().contains(beneficiary) {
+			true => NONCE_AUTH_VOTE,
+			false => NONCE_DROP_VOTE,
+		};
+
+		Seal::Regular(vec![encode(&H256::default()).into_vec(), encode(&H64::from(nonce)).into_vec()])
+	}
+
+	fn on_seal_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+		let header = block.fields_mut().header;
+
+		let mut extra_data = header.extra_data().clone();
+		extra_data.resize(VANITY_LENGTH, 0);
+		if self.is_checkpoint(header.number()) {
+			for signer in self.state(header.parent_hash())?.signers() {
+				extra_data.extend_from_slice(signer);
+			}
+		}
+		extra_data.extend_from_slice(&[0u8; SIGNATURE_LENGTH]);
+		header.set_extra_data(extra_data);
+
+		let signature = self.signer.sign(seal_hash(header))?;
+		let extra_data_len = header.extra_data().len();
+		header.extra_data_mut()[extra_data_len - SIGNATURE_LENGTH..].copy_from_slice(&H520::from(signature) as &[u8]);
+		Ok(())
+	}
+
+	/// Check the seal fields and the extra data layout.
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		if header.seal().len() != self.seal_fields() {
+			trace!(target: "engine", "verify_block_basic: wrong number of seal fields");
+			return Err(From::from(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal().len() }
+			)));
+		}
+
+		let is_checkpoint = self.is_checkpoint(header.number());
+		let nonce = header_nonce(header)?;
+		if nonce != H64::from(NONCE_AUTH_VOTE) && nonce != H64::from(NONCE_DROP_VOTE) {
+			trace!(target: "engine", "verify_block_basic: invalid vote nonce {}", nonce);
+			return Err(BlockError::InvalidSeal.into());
+		}
+
+		if is_checkpoint && (*header.author() != Address::default() || nonce != H64::from(NONCE_DROP_VOTE)) {
+			return Err(EngineError::InvalidCheckpoint("checkpoints may not cast votes".into()).into());
+		}
+
+		// the signer list is only allowed on checkpoints.
+		let signers_len = extract_signers(header)?.len();
+		if !is_checkpoint && signers_len != 0 {
+			return Err(BlockError::ExtraDataOutOfBounds(
+				OutOfBounds { min: None, max: Some(VANITY_LENGTH + SIGNATURE_LENGTH), found: header.extra_data().len() }
+			).into());
+		}
+
+		let mix_hash = UntrustedRlp::new(&header.seal()[0]).as_val::<H256>()?;
+		if !mix_hash.is_zero() {
+			return Err(BlockError::MismatchedH256SealElement(Mismatch { expected: H256::default(), found: mix_hash }).into());
+		}
+
+		if *header.uncles_hash() != SHA3_EMPTY_LIST_RLP {
+			return Err(BlockError::InvalidUnclesHash(Mismatch { expected: SHA3_EMPTY_LIST_RLP, found: *header.uncles_hash() }).into());
+		}
+
+		if header.number() > 0 && *header.difficulty() != DIFF_INTURN.into() && *header.difficulty() != DIFF_NOTURN.into() {
+			return Err(BlockError::DifficultyOutOfBounds(
+				OutOfBounds { min: Some(DIFF_NOTURN.into()), max: Some(DIFF_INTURN.into()), found: *header.difficulty() }
+			).into());
+		}
+
+		Ok(())
+	}
+
+	/// Check the signature can be recovered.
+	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		self.recover_signer(header).map(|_| ())
+	}
+
+	/// Check the block period and the gas limit.
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		// Do not calculate difficulty for genesis blocks.
+		if header.number() == 0 {
+			return Err(From::from(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() })));
+		}
+
+		let min_timestamp = parent.timestamp() + self.period;
+		if header.timestamp() < min_timestamp {
+			return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { min: Some(min_timestamp), max: None, found: header.timestamp() })));
+		}
+
+		let gas_limit_divisor = self.gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
+		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
+		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
+			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
+		}
+		Ok(())
+	}
+
+	/// Check the signer against the signer set of the parent and tally the vote.
+	fn verify_block_external(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		let parent_state = self.state(header.parent_hash())?;
+		let signer = self.recover_signer(header)?;
+
+		let mut state = parent_state.clone();
+		state.apply(header, signer, self.epoch_length)?;
+
+		let expected_difficulty = parent_state.difficulty(header.number(), &signer);
+		if *header.difficulty() != expected_difficulty {
+			return Err(BlockError::InvalidDifficulty(Mismatch { expected: expected_difficulty, found: *header.difficulty() }).into());
+		}
+
+		if self.is_checkpoint(header.number()) {
+			let signers = extract_signers(header)?;
+			if !signers.iter().eq(state.signers().iter()) {
+				return Err(EngineError::InvalidCheckpoint("signer list does not match the local one".into()).into());
+			}
+		}
+
+		self.block_states.lock().insert(header.hash(), state);
+		Ok(())
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		*self.client.write() = Some(client.clone());
+	}
+
+	fn set_signer(&self, ap: Arc<AccountProvider>, address: Address, password: String) {
+		self.signer.set(ap, address, password);
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> {
+		self.signer.sign(hash).map_err(Into::into)
+	}
+
+	fn set_vote_proposal(&self, address: Address, authorize: Option<bool>) -> Result<(), Error> {
+		let mut proposals = self.proposals.write();
+		match authorize {
+			Some(authorize) => { proposals.insert(address, authorize); },
+			None => { proposals.remove(&address); },
+		}
+		Ok(())
+	}
+
+	fn snapshot_components(&self) -> Option<Box<::snapshot::SnapshotComponents>> {
+		// a full epoch guarantees the restored chain contains a checkpoint to rebuild the signer set from.
+		Some(Box::new(::snapshot::PowSnapshot(self.epoch_length)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use block::*;
+	use rlp::encode;
+	use error::{Error, BlockError};
+	use header::Header;
+	use tests::helpers::*;
+	use account_provider::AccountProvider;
+	use client::MiningBlockChainClient;
+	use spec::Spec;
+	use engines::{Seal, EngineError};
+	use super::{recover_creator, header_nonce, NONCE_AUTH_VOTE};
+
+	fn empty_seal() -> Vec<Bytes> {
+		vec![encode(&H256::default()).into_vec(), encode(&H64::default()).into_vec()]
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = Spec::new_test_clique().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn verification_fails_on_short_seal() {
+		let engine = Spec::new_test_clique().engine;
+		let header: Header = Header::default();
+
+		let verify_result = engine.verify_block_basic(&header, None);
+
+		match verify_result {
+			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
+			Err(_) => { panic!("should be block seal-arity mismatch error (got {:?})", verify_result); },
+			_ => { panic!("Should be error, got Ok"); },
+		}
+	}
+
+	#[test]
+	fn verification_fails_on_checkpoint_vote() {
+		let engine = Spec::new_test_clique().engine;
+		let mut header: Header = Header::default();
+		header.set_author(Address::from(1));
+		header.set_extra_data(vec![0; 97]);
+		header.set_seal(empty_seal());
+
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Engine(EngineError::InvalidCheckpoint(_))) => {},
+			other => panic!("expected invalid checkpoint error, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn seals_and_verifies_blocks() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let signer0 = tap.insert_account("0".sha3().into(), "0").unwrap();
+		let signer1 = tap.insert_account("1".sha3().into(), "1").unwrap();
+		let outsider = tap.insert_account("2".sha3().into(), "2").unwrap();
+
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_clique, Some(tap.clone()));
+		let engine = client.engine();
+		engine.register_client(Arc::downgrade(&client));
+
+		// "0".sha3() is the in-turn signer of the first block.
+		engine.set_signer(tap.clone(), signer0, "0".into());
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		let seal = match engine.generate_seal(block.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in-turn signer should be able to seal"),
+		};
+		let sealed = block.seal(engine, seal).unwrap();
+		assert_eq!(recover_creator(sealed.header()).unwrap(), signer0);
+		assert_eq!(*sealed.header().difficulty(), 2.into());
+		assert!(engine.verify_block_basic(sealed.header(), None).is_ok());
+		assert!(engine.verify_block_unordered(sealed.header(), None).is_ok());
+		assert!(engine.verify_block_external(sealed.header(), None).is_ok());
+		client.import_sealed_block(sealed).unwrap();
+
+		// the same signer may not seal the next block.
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		assert!(engine.generate_seal(block.block()) == Seal::None);
+		let sealed = block.seal(engine, empty_seal()).unwrap();
+		match engine.verify_block_external(sealed.header(), None) {
+			Err(Error::Engine(EngineError::RecentlySigned(signer))) => assert_eq!(signer, signer0),
+			other => panic!("expected recently signed error, got {:?}", other),
+		}
+
+		// neither may an unauthorized one.
+		engine.set_signer(tap.clone(), outsider, "2".into());
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		assert!(engine.generate_seal(block.block()) == Seal::None);
+		let sealed = block.seal(engine, empty_seal()).unwrap();
+		match engine.verify_block_external(sealed.header(), None) {
+			Err(Error::Engine(EngineError::NotAuthorized(signer))) => assert_eq!(signer, outsider),
+			other => panic!("expected not authorized error, got {:?}", other),
+		}
+
+		engine.set_signer(tap, signer1, "1".into());
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		let seal = match engine.generate_seal(block.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in-turn signer should be able to seal"),
+		};
+		let sealed = block.seal(engine, seal).unwrap();
+		assert!(engine.verify_block_external(sealed.header(), None).is_ok());
+	}
+
+	#[test]
+	fn casts_proposed_votes() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let signer0 = tap.insert_account("0".sha3().into(), "0").unwrap();
+		let outsider = tap.insert_account("2".sha3().into(), "2").unwrap();
+
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_clique, Some(tap.clone()));
+		let engine = client.engine();
+		engine.register_client(Arc::downgrade(&client));
+		engine.set_signer(tap.clone(), signer0, "0".into());
+		engine.set_vote_proposal(outsider, Some(true)).unwrap();
+
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		assert_eq!(*block.header().author(), outsider);
+		let seal = match engine.generate_seal(block.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in-turn signer should be able to seal"),
+		};
+		let sealed = block.seal(engine, seal).unwrap();
+		assert_eq!(header_nonce(sealed.header()).unwrap(), H64::from(NONCE_AUTH_VOTE));
+		assert!(engine.verify_block_basic(sealed.header(), None).is_ok());
+		assert!(engine.verify_block_external(sealed.header(), None).is_ok());
+
+		engine.set_vote_proposal(outsider, None).unwrap();
+		let block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).close_and_lock();
+		assert_eq!(*block.header().author(), Address::default());
+	}
+}
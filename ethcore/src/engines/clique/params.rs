@@ -0,0 +1,49 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique specific parameters.
+
+use ethjson;
+use util::U256;
+
+/// Default number of blocks after which a checkpoint is issued.
+const DEFAULT_EPOCH_LENGTH: u64 = 30_000;
+
+/// Default minimum number of seconds between blocks.
+const DEFAULT_PERIOD: u64 = 15;
+
+/// `Clique` params.
+#[derive(Debug, PartialEq)]
+pub struct CliqueParams {
+	/// Gas limit divisor.
+	pub gas_limit_bound_divisor: U256,
+	/// Minimum number of seconds between two consecutive blocks.
+	pub period: u64,
+	/// Number of blocks after which votes are reset and a checkpoint is written.
+	pub epoch: u64,
+}
+
+impl From<ethjson::spec::CliqueParams> for CliqueParams {
+	fn from(p: ethjson::spec::CliqueParams) -> Self {
+		let epoch = p.epoch.map_or(DEFAULT_EPOCH_LENGTH, Into::into);
+		CliqueParams {
+			gas_limit_bound_divisor: p.gas_limit_bound_divisor.map_or_else(|| 0x0400.into(), Into::into),
+			period: p.period.map_or(DEFAULT_PERIOD, Into::into),
+			// an epoch of zero would make every block a checkpoint and break the modulo arithmetic.
+			epoch: if epoch == 0 { DEFAULT_EPOCH_LENGTH } else { epoch },
+		}
+	}
+}
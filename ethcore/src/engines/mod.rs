@@ -18,6 +18,7 @@
 
 mod authority_round;
 mod basic_authority;
+mod clique;
 mod instant_seal;
 mod null_engine;
 mod signer;
@@ -30,6 +31,7 @@ pub mod epoch;
 
 pub use self::authority_round::AuthorityRound;
 pub use self::basic_authority::BasicAuthority;
+pub use self::clique::Clique;
 pub use self::epoch::{EpochVerifier, Transition as EpochTransition};
 pub use self::instant_seal::InstantSeal;
 pub use self::null_engine::NullEngine;
@@ -79,6 +81,12 @@ pub enum EngineError {
 	FailedSystemCall(String),
 	/// Requires client ref, but none registered.
 	RequiresClient,
+	/// Signer sealed one of the most recent blocks.
+	RecentlySigned(Address),
+	/// Checkpoint block is malformed.
+	InvalidCheckpoint(String),
+	/// Engine does not vote on its authorities.
+	VotingUnsupported,
 }
 
 impl fmt::Display for EngineError {
@@ -93,6 +101,9 @@ impl fmt::Display for EngineError {
 			InsufficientProof(ref msg) => format!("Insufficient validation proof: {}", msg),
 			FailedSystemCall(ref msg) => format!("Failed to make system call: {}", msg),
 			RequiresClient => format!("Call requires client but none registered"),
+			RecentlySigned(ref address) => format!("Signer {} has signed too recently.", address),
+			InvalidCheckpoint(ref msg) => format!("Invalid checkpoint: {}", msg),
+			VotingUnsupported => "This Engine does not vote on its authorities.".into(),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
@@ -188,6 +199,9 @@ pub trait Engine : Sync + Send {
 	/// Additional information.
 	fn additional_params(&self) -> HashMap<String, String> { HashMap::new() }
 
+	/// Block numbers at which engine-specific rules change, in addition to those in `params()`.
+	fn fork_transitions(&self) -> Vec<BlockNumber> { Vec::new() }
+
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
@@ -232,6 +246,12 @@ pub trait Engine : Sync + Send {
 		Ok(())
 	}
 
+	/// Block transformation functions, applied once the seal fields are set.
+	/// Allows engines which sign the header itself to alter it before sealing.
+	fn on_seal_block(&self, _block: &mut ExecutedBlock) -> Result<(), Error> {
+		Ok(())
+	}
+
 	/// None means that it requires external input (e.g. PoW) to seal a block.
 	/// Some(true) means the engine is currently prime for seal generation (i.e. node is the current validator).
 	/// Some(false) means that the node might seal internally but is not qualified now.
@@ -350,6 +370,12 @@ pub trait Engine : Sync + Send {
 	/// Takes a header of a fully verified block.
 	fn is_proposal(&self, _verified_header: &Header) -> bool { false }
 
+	/// Propose a vote on authorizing (`Some(true)`) or dropping (`Some(false)`) an authority,
+	/// to be cast in locally sealed blocks. `None` discards the proposal.
+	fn set_vote_proposal(&self, _address: Address, _authorize: Option<bool>) -> Result<(), Error> {
+		Err(EngineError::VotingUnsupported.into())
+	}
+
 	/// Register an account which signs consensus messages.
 	fn set_signer(&self, _account_provider: Arc<AccountProvider>, _address: Address, _password: String) {}
 
@@ -181,6 +181,26 @@ impl Engine for Arc<Ethash> {
 	fn params(&self) -> &CommonParams { &self.params }
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.ethash_params.registrar.hex()] }
 
+	fn fork_transitions(&self) -> Vec<BlockNumber> {
+		let p = &self.ethash_params;
+		vec![
+			p.homestead_transition,
+			p.dao_hardfork_transition,
+			p.difficulty_hardfork_transition,
+			p.bomb_defuse_transition,
+			p.eip100b_transition,
+			p.eip150_transition,
+			p.eip155_transition,
+			p.eip160_transition,
+			p.eip161abc_transition,
+			p.eip161d_transition,
+			p.ecip1010_pause_transition,
+			p.ecip1010_continue_transition,
+			p.max_gas_limit_transition,
+			p.min_gas_price_transition,
+		]
+	}
+
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
 		&self.builtins
 	}
@@ -78,6 +78,12 @@ pub fn new_mainnet_like() -> Spec { load(include_bytes!("../../res/ethereum/fron
 /// Create a new Foundation Metropolis era spec.
 pub fn new_metropolis_test() -> Spec { load(include_bytes!("../../res/ethereum/metropolis_test.json")) }
 
+/// Create a new Foundation Byzantium era spec.
+pub fn new_byzantium_test() -> Spec { load(include_bytes!("../../res/ethereum/byzantium_test.json")) }
+
+/// Create a new Foundation Constantinople era spec.
+pub fn new_constantinople_test() -> Spec { load(include_bytes!("../../res/ethereum/constantinople_test.json")) }
+
 /// Create a new Foundation Ropsten chain spec.
 pub fn new_ropsten() -> Spec { load(include_bytes!("../../res/ethereum/ropsten.json")) }
 
@@ -48,8 +48,8 @@ pub enum CreateContractAddress {
 	FromSenderAndNonce,
 	/// Address is calculated from code hash. Default since EIP-86
 	FromCodeHash,
-	/// Address is calculated from code hash and sender. Used by CREATE_P2SH instruction.
-	FromSenderAndCodeHash,
+	/// Address is calculated from sender, salt and code hash. Used by CREATE2 instruction (EIP-1014).
+	FromSenderSaltAndCodeHash(H256),
 }
 
 /// Externalities interface for EVMs
@@ -57,6 +57,9 @@ pub trait Ext {
 	/// Returns a value for given key.
 	fn storage_at(&self, key: &H256) -> evm::Result<H256>;
 
+	/// Returns the value for given key as it was at the start of the transaction.
+	fn initial_storage_at(&self, key: &H256) -> evm::Result<H256>;
+
 	/// Stores a value for given key.
 	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()>;
 
@@ -103,6 +106,9 @@ pub trait Ext {
 	/// Returns code size at given address
 	fn extcodesize(&self, address: &Address) -> evm::Result<usize>;
 
+	/// Returns code hash at given address or `None` if the account doesn't exist or is empty.
+	fn extcodehash(&self, address: &Address) -> evm::Result<Option<H256>>;
+
 	/// Creates log entry with given topics and data
 	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> evm::Result<()>;
 
@@ -126,8 +132,11 @@ pub trait Ext {
 	/// then A depth is 0, B is 1, C is 2 and so on.
 	fn depth(&self) -> usize;
 
-	/// Increments sstore refunds count by 1.
-	fn inc_sstore_clears(&mut self);
+	/// Increments sstore refunds counter by `value`.
+	fn add_sstore_refund(&mut self, value: usize);
+
+	/// Decrements sstore refunds counter by `value`.
+	fn sub_sstore_refund(&mut self, value: usize);
 
 	/// Prepare to trace an operation. Passthrough for the VM trace.
 	fn trace_prepare_execute(&mut self, _pc: usize, _instruction: u8, _gas_cost: &U256) -> bool { false }
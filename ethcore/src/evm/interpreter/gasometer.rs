@@ -125,7 +125,10 @@ impl<Gas: CostType> Gasometer<Gas> {
 				let newval = stack.peek(1);
 				let val = U256::from(&*ext.storage_at(&address)?);
 
-				let gas = if val.is_zero() && !newval.is_zero() {
+				let gas = if schedule.eip1283 {
+					let orig = U256::from(&*ext.initial_storage_at(&address)?);
+					calculate_eip1283_sstore_gas(schedule, &orig, &val, &newval)
+				} else if val.is_zero() && !newval.is_zero() {
 					schedule.sstore_set_gas
 				} else {
 					// Refund for below case is added when actually executing sstore
@@ -143,6 +146,9 @@ impl<Gas: CostType> Gasometer<Gas> {
 			instructions::EXTCODESIZE => {
 				Request::Gas(Gas::from(schedule.extcodesize_gas))
 			},
+			instructions::EXTCODEHASH => {
+				Request::Gas(Gas::from(schedule.extcodehash_gas))
+			},
 			instructions::SUICIDE => {
 				let mut gas = Gas::from(schedule.suicide_gas);
 
@@ -223,12 +229,22 @@ impl<Gas: CostType> Gasometer<Gas> {
 
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
-			instructions::CREATE | instructions::CREATE2 => {
+			instructions::CREATE => {
 				let gas = Gas::from(schedule.create_gas);
 				let mem = mem_needed(stack.peek(1), stack.peek(2))?;
 
 				Request::GasMemProvide(gas, mem, None)
 			},
+			instructions::CREATE2 => {
+				// init code has to be hashed to get the address
+				let w = overflowing!(add_gas_usize(Gas::from_u256(*stack.peek(2))?, 31));
+				let words = w >> 5;
+				let word_gas = overflowing!(Gas::from(schedule.sha3_word_gas).overflow_mul(words));
+				let gas = overflowing!(word_gas.overflow_add(Gas::from(schedule.create_gas)));
+				let mem = mem_needed(stack.peek(1), stack.peek(2))?;
+
+				Request::GasMemProvide(gas, mem, None)
+			},
 			instructions::EXP => {
 				let expon = stack.peek(1);
 				let bytes = ((expon.bits() + 7) / 8) as usize;
@@ -336,6 +352,61 @@ fn add_gas_usize<Gas: CostType>(value: Gas, num: usize) -> (Gas, bool) {
 	value.overflow_add(Gas::from(num))
 }
 
+/// Cost of `SSTORE` under net gas metering (EIP-1283), given the value of the slot
+/// at the start of the transaction, its current value and the value being stored.
+fn calculate_eip1283_sstore_gas(schedule: &Schedule, original: &U256, current: &U256, new: &U256) -> usize {
+	if current == new {
+		// no-op
+		schedule.sstore_dirty_gas
+	} else if original == current {
+		// first change of the slot in this transaction
+		if original.is_zero() {
+			schedule.sstore_set_gas
+		} else {
+			schedule.sstore_reset_gas
+		}
+	} else {
+		// the slot is already dirty
+		schedule.sstore_dirty_gas
+	}
+}
+
+/// Adjusts the refund counter for `SSTORE` under net gas metering (EIP-1283).
+pub fn handle_eip1283_sstore_clears_refund(ext: &mut evm::Ext, original: &U256, current: &U256, new: &U256) {
+	let sstore_clears_schedule = ext.schedule().sstore_refund_gas;
+
+	if current == new {
+		// no refund for a no-op
+		return;
+	}
+
+	if original == current {
+		if !original.is_zero() && new.is_zero() {
+			ext.add_sstore_refund(sstore_clears_schedule);
+		}
+		return;
+	}
+
+	if !original.is_zero() {
+		if current.is_zero() {
+			// the slot was cleared earlier in the transaction, take the refund back
+			ext.sub_sstore_refund(sstore_clears_schedule);
+		} else if new.is_zero() {
+			ext.add_sstore_refund(sstore_clears_schedule);
+		}
+	}
+
+	if original == new {
+		// the slot is being reset to its original value
+		let refund = if original.is_zero() {
+			ext.schedule().sstore_set_gas - ext.schedule().sstore_dirty_gas
+		} else {
+			ext.schedule().sstore_reset_gas - ext.schedule().sstore_dirty_gas
+		};
+		ext.add_sstore_refund(refund);
+	}
+}
+
 #[test]
 fn test_mem_gas_cost() {
 	// given
@@ -205,7 +205,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 		if (instruction == instructions::DELEGATECALL && !schedule.have_delegate_call) ||
 			(instruction == instructions::CREATE2 && !schedule.have_create2) ||
 			(instruction == instructions::STATICCALL && !schedule.have_static_call) ||
-			(instruction == instructions::REVERT && !schedule.have_revert) {
+			(instruction == instructions::REVERT && !schedule.have_revert) ||
+			((instruction == instructions::SHL || instruction == instructions::SHR || instruction == instructions::SAR) && !schedule.have_bitwise_shifting) ||
+			(instruction == instructions::EXTCODEHASH && !schedule.have_extcodehash) {
 
 			return Err(evm::Error::BadInstruction {
 				instruction: instruction
@@ -295,7 +297,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let init_off = stack.pop_back();
 				let init_size = stack.pop_back();
 
-				let address_scheme = if instruction == instructions::CREATE { CreateContractAddress::FromSenderAndNonce } else { CreateContractAddress::FromSenderAndCodeHash };
+				let address_scheme = match instruction {
+					instructions::CREATE => CreateContractAddress::FromSenderAndNonce,
+					_ => CreateContractAddress::FromSenderSaltAndCodeHash(stack.pop_back().into()),
+				};
 				let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is `CREATE`; qed");
 
 				let contract_code = self.mem.read_slice(init_off, init_size);
@@ -454,8 +459,12 @@ impl<Cost: CostType> Interpreter<Cost> {
 
 				let current_val = U256::from(&*ext.storage_at(&address)?);
 				// Increase refund for clear
-				if !self.is_zero(&current_val) && self.is_zero(&val) {
-					ext.inc_sstore_clears();
+				if ext.schedule().eip1283 {
+					let original_val = U256::from(&*ext.initial_storage_at(&address)?);
+					gasometer::handle_eip1283_sstore_clears_refund(ext, &original_val, &current_val, &val);
+				} else if !self.is_zero(&current_val) && self.is_zero(&val) {
+					let sstore_clears_schedule = ext.schedule().sstore_refund_gas;
+					ext.add_sstore_refund(sstore_clears_schedule);
 				}
 				ext.set_storage(address, H256::from(&val))?;
 			},
@@ -515,6 +524,11 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let len = ext.extcodesize(&address)?;
 				stack.push(U256::from(len));
 			},
+			instructions::EXTCODEHASH => {
+				let address = u256_to_address(&stack.pop_back());
+				let hash = ext.extcodehash(&address)?.unwrap_or_else(H256::zero);
+				stack.push(U256::from(&*hash));
+			},
 			instructions::CALLDATACOPY => {
 				Self::copy_data_to_memory(&mut self.mem, stack, params.data.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
 			},
@@ -770,6 +784,47 @@ impl<Cost: CostType> Interpreter<Cost> {
 				};
 				stack.push(byte);
 			},
+			instructions::SHL => {
+				let shift = stack.pop_back();
+				let value = stack.pop_back();
+
+				let result = if shift >= U256::from(256) {
+					U256::zero()
+				} else {
+					value << (shift.low_u64() as usize)
+				};
+				stack.push(result);
+			},
+			instructions::SHR => {
+				let shift = stack.pop_back();
+				let value = stack.pop_back();
+
+				let result = if shift >= U256::from(256) {
+					U256::zero()
+				} else {
+					value >> (shift.low_u64() as usize)
+				};
+				stack.push(result);
+			},
+			instructions::SAR => {
+				// get_and_reset_sign can't be used here, negative values round towards negative infinity.
+				let shift = stack.pop_back();
+				let value = stack.pop_back();
+				let sign = value.bit(255);
+
+				let result = if shift >= U256::from(256) {
+					if sign { U256::max_value() } else { U256::zero() }
+				} else {
+					let shift = shift.low_u64() as usize;
+					let shifted = value >> shift;
+					if sign && shift > 0 {
+						shifted | (U256::max_value() << (256 - shift))
+					} else {
+						shifted
+					}
+				};
+				stack.push(result);
+			},
 			instructions::ADDMOD => {
 				let a = stack.pop_back();
 				let b = stack.pop_back();
@@ -53,9 +53,10 @@ pub struct FakeCall {
 /// Can't do recursive calls.
 #[derive(Default)]
 pub struct FakeExt {
-	sstore_clears: usize,
+	sstore_clears: i64,
 	depth: usize,
 	store: HashMap<H256, H256>,
+	initial_store: HashMap<H256, H256>,
 	blockhashes: HashMap<U256, H256>,
 	codes: HashMap<Address, Arc<Bytes>>,
 	logs: Vec<FakeLogEntry>,
@@ -92,6 +93,10 @@ impl Ext for FakeExt {
 		Ok(self.store.get(key).unwrap_or(&H256::new()).clone())
 	}
 
+	fn initial_storage_at(&self, key: &H256) -> evm::Result<H256> {
+		Ok(self.initial_store.get(key).unwrap_or(&H256::new()).clone())
+	}
+
 	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()> {
 		self.store.insert(key, value);
 		Ok(())
@@ -161,6 +166,10 @@ impl Ext for FakeExt {
 		Ok(self.codes.get(address).map_or(0, |c| c.len()))
 	}
 
+	fn extcodehash(&self, address: &Address) -> evm::Result<Option<H256>> {
+		Ok(self.codes.get(address).map(|c| c.sha3()))
+	}
+
 	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> evm::Result<()> {
 		self.logs.push(FakeLogEntry {
 			topics: topics,
@@ -189,8 +198,12 @@ impl Ext for FakeExt {
 		self.depth
 	}
 
-	fn inc_sstore_clears(&mut self) {
-		self.sstore_clears += 1;
+	fn add_sstore_refund(&mut self, value: usize) {
+		self.sstore_clears += value as i64;
+	}
+
+	fn sub_sstore_refund(&mut self, value: usize) {
+		self.sstore_clears -= value as i64;
 	}
 }
 
@@ -972,7 +985,7 @@ fn test_jumps(factory: super::Factory) {
 		test_finalize(vm.exec(params, &mut ext)).unwrap()
 	};
 
-	assert_eq!(ext.sstore_clears, 1);
+	assert_eq!(ext.sstore_clears, ext.schedule.sstore_refund_gas as i64);
 	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000000"); // 5!
 	assert_store(&ext, 1, "0000000000000000000000000000000000000000000000000000000000000078"); // 5!
 	assert_eq!(gas_left, U256::from(54_117));
@@ -1023,6 +1036,272 @@ fn test_calls(factory: super::Factory) {
 	assert_eq!(ext.calls.len(), 2);
 }
 
+fn test_eip1283(code: &str, original: u64, gas_used: u64, refund: i64) {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = code.from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.eip1283 = true;
+	ext.store.insert(H256::new(), H256::from(original));
+	ext.initial_store.insert(H256::new(), H256::from(original));
+
+	let gas_left = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap()
+	};
+
+	assert_eq!(U256::from(100_000) - gas_left, U256::from(gas_used));
+	assert_eq!(ext.sstore_clears, refund);
+}
+
+#[test]
+fn test_eip1283_sstore() {
+	// test vectors from the EIP
+	test_eip1283("60006000556000600055", 0, 412, 0);
+	test_eip1283("60006000556001600055", 0, 20212, 0);
+	test_eip1283("60016000556000600055", 0, 20212, 19800);
+	test_eip1283("60016000556002600055", 0, 20212, 0);
+	test_eip1283("60016000556001600055", 0, 20212, 0);
+	test_eip1283("60006000556000600055", 1, 5212, 15000);
+	test_eip1283("60006000556001600055", 1, 5212, 4800);
+	test_eip1283("60006000556002600055", 1, 5212, 0);
+	test_eip1283("60026000556000600055", 1, 5212, 15000);
+	test_eip1283("60026000556003600055", 1, 5212, 0);
+	test_eip1283("60026000556001600055", 1, 5212, 4800);
+	test_eip1283("60026000556002600055", 1, 5212, 0);
+	test_eip1283("60016000556000600055", 1, 5212, 15000);
+	test_eip1283("60016000556002600055", 1, 5212, 0);
+	test_eip1283("60016000556001600055", 1, 412, 0);
+	test_eip1283("600160005560006000556001600055", 0, 40218, 19800);
+	test_eip1283("600060005560016000556000600055", 1, 10218, 19800);
+}
+
+fn test_constantinople_push_two_pop_one(opcode: u8, push1: &str, push2: &str, result: &str) {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = format!("7f{}7f{}{:02x}600055", push1, push2, opcode).from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule = Schedule::new_constantinople();
+
+	{
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap();
+	}
+
+	assert_store(&ext, 0, result);
+}
+#[test]
+fn test_shl() {
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000002");
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"8000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000101",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1b,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe");
+	test_constantinople_push_two_pop_one(0x1b,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"8000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1b,
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe");
+}
+
+#[test]
+fn test_shr() {
+	test_constantinople_push_two_pop_one(0x1c,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1c,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1c,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"4000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1c,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1c,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1c,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000101",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1c,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1c,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1c,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1c,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1c,
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+}
+
+#[test]
+fn test_sar() {
+	test_constantinople_push_two_pop_one(0x1d,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1d,
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1d,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"c000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1d,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"8000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000101",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+	test_constantinople_push_two_pop_one(0x1d,
+		"0000000000000000000000000000000000000000000000000000000000000000",
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1d,
+		"4000000000000000000000000000000000000000000000000000000000000000",
+		"00000000000000000000000000000000000000000000000000000000000000fe",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1d,
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000f8",
+		"000000000000000000000000000000000000000000000000000000000000007f");
+	test_constantinople_push_two_pop_one(0x1d,
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000fe",
+		"0000000000000000000000000000000000000000000000000000000000000001");
+	test_constantinople_push_two_pop_one(0x1d,
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"00000000000000000000000000000000000000000000000000000000000000ff",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+	test_constantinople_push_two_pop_one(0x1d,
+		"7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		"0000000000000000000000000000000000000000000000000000000000000100",
+		"0000000000000000000000000000000000000000000000000000000000000000");
+}
+
+#[test]
+fn test_extcodehash() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	// EXTCODEHASH of 0x01 and of a missing account 0x02
+	let code = "60013f60005560023f600155".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule = Schedule::new_constantinople();
+	ext.codes.insert(Address::from(1), Arc::new("6000".from_hex().unwrap()));
+
+	let gas_left = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap()
+	};
+
+	assert_store(&ext, 0, &"6000".from_hex().unwrap().sha3().hex());
+	assert_store(&ext, 1, "0000000000000000000000000000000000000000000000000000000000000000");
+	assert_eq!(gas_left, U256::from(78_988));
+}
+
+#[test]
+fn test_bitwise_shifting_and_extcodehash_require_schedule() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+
+	for code in &["600160011b", "600160011c", "600160011d", "60013f"] {
+		let mut params = ActionParams::default();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code.from_hex().unwrap()));
+		let mut ext = FakeExt::new();
+		ext.schedule = Schedule::new_metropolis();
+
+		let mut vm = factory.create(params.gas);
+		match vm.exec(params, &mut ext) {
+			Err(evm::Error::BadInstruction { .. }) => {},
+			_ => panic!("instruction should not be available before Constantinople"),
+		}
+	}
+}
+
 fn assert_set_contains<T : Debug + Eq + PartialEq + Hash>(set: &HashSet<T>, val: &T) {
 	let contains = set.contains(val);
 	if !contains {
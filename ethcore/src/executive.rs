@@ -24,7 +24,7 @@ use env_info::EnvInfo;
 use error::ExecutionError;
 use evm::{self, Ext, Finalize, CreateContractAddress, FinalizationResult, ReturnData, CleanDustMode};
 use externalities::*;
-use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
+use trace::{Tracer, NoopTracer, ExecutiveTracer, CallTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
 use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use types::executed::{Executed, ExecutionResult};
@@ -51,11 +51,13 @@ pub fn contract_address(address_scheme: CreateContractAddress, sender: &Address,
 			&mut buffer[20..].copy_from_slice(&code_hash[..]);
 			(From::from((&buffer[..]).sha3()), Some(code_hash))
 		},
-		CreateContractAddress::FromSenderAndCodeHash => {
+		CreateContractAddress::FromSenderSaltAndCodeHash(salt) => {
 			let code_hash = code.sha3();
-			let mut buffer = [0u8; 20 + 32];
-			&mut buffer[..20].copy_from_slice(&sender[..]);
-			&mut buffer[20..].copy_from_slice(&code_hash[..]);
+			let mut buffer = [0u8; 1 + 20 + 32 + 32];
+			buffer[0] = 0xff;
+			&mut buffer[1..(1+20)].copy_from_slice(&sender[..]);
+			&mut buffer[(1+20)..(1+20+32)].copy_from_slice(&salt[..]);
+			&mut buffer[(1+20+32)..].copy_from_slice(&code_hash[..]);
 			(From::from((&buffer[..]).sha3()), Some(code_hash))
 		},
 	}
@@ -68,6 +70,8 @@ pub struct TransactOptions {
 	pub tracing: bool,
 	/// Enable VM tracing.
 	pub vm_tracing: bool,
+	/// Enable nested call tree tracing.
+	pub call_tracing: bool,
 	/// Check transaction nonce before execution.
 	pub check_nonce: bool,
 }
@@ -121,12 +125,17 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 	/// This function should be used to execute transaction.
 	pub fn transact(&'a mut self, t: &SignedTransaction, options: TransactOptions) -> Result<Executed, ExecutionError> {
 		let check = options.check_nonce;
-		match options.tracing {
-			true => match options.vm_tracing {
+		match (options.call_tracing, options.tracing) {
+			// flat traces are derived from the call tree, so a single tracer is enough
+			(true, flat) => match options.vm_tracing {
+				true => self.transact_with_tracer(t, check, CallTracer::default(), ExecutiveVMTracer::toplevel()),
+				false => self.transact_with_tracer(t, check, CallTracer::default(), NoopVMTracer),
+			}.map(|executed| executed.into_call_tree(flat)),
+			(false, true) => match options.vm_tracing {
 				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::toplevel()),
 				false => self.transact_with_tracer(t, check, ExecutiveTracer::default(), NoopVMTracer),
 			},
-			false => match options.vm_tracing {
+			(false, false) => match options.vm_tracing {
 				true => self.transact_with_tracer(t, check, NoopTracer, ExecutiveVMTracer::toplevel()),
 				false => self.transact_with_tracer(t, check, NoopTracer, NoopVMTracer),
 			},
@@ -155,7 +164,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 		check_nonce: bool,
 		mut tracer: T,
 		mut vm_tracer: V
-	) -> Result<Executed, ExecutionError> where T: Tracer, V: VMTracer {
+	) -> Result<Executed<T::Output>, ExecutionError> where T: Tracer, V: VMTracer {
 		let sender = t.sender();
 		let nonce = self.state.nonce(&sender)?;
 
@@ -382,7 +391,13 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 
 				let traces = subtracer.traces();
 				match res {
-					Ok(ref res) => tracer.trace_call(
+					Ok(ref res) if res.apply_state => tracer.trace_call(
+						trace_info,
+						gas - res.gas_left,
+						trace_output,
+						traces
+					),
+					Ok(ref res) => tracer.trace_reverted_call(
 						trace_info,
 						gas - res.gas_left,
 						trace_output,
@@ -460,7 +475,14 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 		vm_tracer.done_subtrace(subvmtracer, res.is_ok());
 
 		match res {
-			Ok(ref res) => tracer.trace_create(
+			Ok(ref res) if res.apply_state => tracer.trace_create(
+				trace_info,
+				gas - res.gas_left,
+				trace_output,
+				created,
+				subtracer.traces()
+			),
+			Ok(ref res) => tracer.trace_reverted_create(
 				trace_info,
 				gas - res.gas_left,
 				trace_output,
@@ -475,19 +497,19 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 	}
 
 	/// Finalizes the transaction (does refunds and suicides).
-	fn finalize(
+	fn finalize<T>(
 		&mut self,
 		t: &SignedTransaction,
 		mut substate: Substate,
 		result: evm::Result<(U256, ReturnData)>,
 		output: Bytes,
-		trace: Vec<FlatTrace>,
+		trace: Vec<T>,
 		vm_trace: Option<VMTrace>
-	) -> ExecutionResult {
+	) -> Result<Executed<T>, ExecutionError> {
 		let schedule = self.engine.schedule(self.info.number);
 
-		// refunds from SSTORE nonzero -> zero
-		let sstore_refunds = U256::from(schedule.sstore_refund_gas) * substate.sstore_clears_count;
+		// refunds from SSTORE nonzero -> zero (and net gas metering)
+		let sstore_refunds = U256::from(cmp::max(substate.sstore_clears_refund, 0) as u64);
 		// refunds from contract suicides
 		let suicide_refunds = U256::from(schedule.suicide_refund_gas) * U256::from(substate.suicides.len());
 		let refunds_bound = sstore_refunds + suicide_refunds;
@@ -535,6 +557,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					call_tree: None,
 				})
 			},
 			_ => {
@@ -550,6 +573,7 @@ impl<'a, B: 'a + StateBackend, E: Engine + ?Sized> Executive<'a, B, E> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					call_tree: None,
 				})
 			},
 		}
@@ -591,7 +615,7 @@ mod tests {
 	use state::{Substate, CleanupMode};
 	use tests::helpers::*;
 	use trace::trace;
-	use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer};
+	use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, CallTracer, CallFrame};
 	use trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, VMTracer, NoopVMTracer, ExecutiveVMTracer};
 	use transaction::{Action, Transaction};
 
@@ -604,6 +628,24 @@ mod tests {
 		assert_eq!(expected_address, contract_address(CreateContractAddress::FromSenderAndNonce, &address, &U256::from(88), &[]).0);
 	}
 
+	#[test]
+	fn test_create2_contract_address() {
+		// examples from EIP-1014
+		let check = |sender: &str, salt: &str, code: &str, expected: &str| {
+			let sender = Address::from_str(sender).unwrap();
+			let salt = H256::from_str(salt).unwrap();
+			let code = code.from_hex().unwrap();
+			let expected = Address::from_str(expected).unwrap();
+			assert_eq!(expected, contract_address(CreateContractAddress::FromSenderSaltAndCodeHash(salt), &sender, &U256::zero(), &code).0);
+		};
+
+		check("0000000000000000000000000000000000000000", "0000000000000000000000000000000000000000000000000000000000000000", "00", "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38");
+		check("deadbeef00000000000000000000000000000000", "0000000000000000000000000000000000000000000000000000000000000000", "00", "b928f69bb1d91cd65274e3c79d8986362984fda3");
+		check("deadbeef00000000000000000000000000000000", "000000000000000000000000feed000000000000000000000000000000000000", "00", "d04116cdd17bebe565eb2422f2497e06cc1c9833");
+		check("0000000000000000000000000000000000000000", "0000000000000000000000000000000000000000000000000000000000000000", "", "e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0");
+		check("00000000000000000000000000000000deadbeef", "00000000000000000000000000000000000000000000000000000000cafebabe", "deadbeef", "60f3f640a8508fc6a86d45df051962668e1e8ac7");
+	}
+
 	// TODO: replace params with transactions!
 	evm_test!{test_sender_balance: test_sender_balance_jit, test_sender_balance_int}
 	fn test_sender_balance(factory: Factory) {
@@ -1120,7 +1162,7 @@ mod tests {
 
 		let executed = {
 			let mut ex = Executive::new(&mut state, &info, &engine);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, call_tracing: false };
 			ex.transact(&t, opts).unwrap()
 		};
 
@@ -1157,7 +1199,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, call_tracing: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1190,7 +1232,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, call_tracing: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1223,7 +1265,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, call_tracing: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1300,4 +1342,56 @@ mod tests {
 		assert_eq!(output[..], returns[..]);
 		assert_eq!(state.storage_at(&contract_address, &H256::from(&U256::zero())).unwrap(), H256::from(&U256::from(0)));
 	}
+
+	#[test]
+	fn test_call_tree_revert() {
+		let contract_address = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		// EIP-140 test case
+		let code = "6c726576657274656420646174616000557f726576657274206d657373616765000000000000000000000000000000000000600052600e6000fd".from_hex().unwrap();
+		let returns = "726576657274206d657373616765".from_hex().unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = contract_address.clone();
+		params.code_address = contract_address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(20025);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		params.call_type = CallType::Call;
+		let mut state = get_temp_state();
+		state.add_balance(&sender, &U256::from_str("152d02c7e14af68000000").unwrap(), CleanupMode::NoEmpty).unwrap();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new_metropolis();
+		let mut substate = Substate::new();
+		let mut tracer = CallTracer::default();
+
+		let mut output = [0u8; 14];
+		let (gas_left, _) = {
+			let mut ex = Executive::new(&mut state, &info, &engine);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut output), &mut tracer, &mut NoopVMTracer).unwrap()
+		};
+
+		assert_eq!(gas_left, U256::from(1));
+
+		let expected_tree = vec![CallFrame {
+			action: trace::Action::Call(trace::Call {
+				from: sender.clone(),
+				to: contract_address.clone(),
+				value: 0.into(),
+				gas: 20025.into(),
+				input: vec![],
+				call_type: CallType::Call,
+			}),
+			result: trace::Res::Call(trace::CallResult {
+				gas_used: U256::from(20024),
+				output: returns.clone(),
+			}),
+			reverted: true,
+			calls: vec![],
+		}];
+
+		assert_eq!(tracer.traces(), expected_tree);
+	}
 }
@@ -113,6 +113,14 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 		self.state.storage_at(&self.origin_info.address, key).map_err(Into::into)
 	}
 
+	fn initial_storage_at(&self, key: &H256) -> evm::Result<H256> {
+		// the first checkpoint is taken by the outermost call of the transaction.
+		match self.state.checkpoint_storage_at(0, &self.origin_info.address, key)? {
+			Some(value) => Ok(value),
+			None => self.storage_at(key),
+		}
+	}
+
 	fn set_storage(&mut self, key: H256, value: H256) -> evm::Result<()> {
 		if self.static_flag {
 			Err(evm::Error::MutableCallInStaticContext)
@@ -282,6 +290,14 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 		Ok(self.state.code_size(address)?.unwrap_or(0))
 	}
 
+	fn extcodehash(&self, address: &Address) -> evm::Result<Option<H256>> {
+		if self.state.exists_and_not_null(address)? {
+			Ok(Some(self.state.code_hash(address)?))
+		} else {
+			Ok(None)
+		}
+	}
+
 	#[cfg_attr(feature="dev", allow(match_ref_pats))]
 	fn ret(mut self, gas: &U256, data: &ReturnData) -> evm::Result<U256>
 		where Self: Sized {
@@ -375,8 +391,12 @@ impl<'a, T: 'a, V: 'a, B: 'a, E: 'a> Ext for Externalities<'a, T, V, B, E>
 		self.depth
 	}
 
-	fn inc_sstore_clears(&mut self) {
-		self.substate.sstore_clears_count = self.substate.sstore_clears_count + U256::one();
+	fn add_sstore_refund(&mut self, value: usize) {
+		self.substate.sstore_clears_refund += value as i64;
+	}
+
+	fn sub_sstore_refund(&mut self, value: usize) {
+		self.substate.sstore_clears_refund -= value as i64;
 	}
 
 	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
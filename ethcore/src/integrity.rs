@@ -0,0 +1,455 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline database integrity checks.
+
+use std::fmt;
+use std::sync::Arc;
+
+use util::{H256, Hashable, KeyValueDB, HashDB, Trie, TrieDB, TrieError, ordered_trie_root, SHA3_EMPTY};
+use util::journaldb::{self, Algorithm};
+
+use account_db::AccountDB;
+use basic_account::BasicAccount;
+use blockchain::{BlockChain, BlockProvider, Config as BlockChainConfig};
+use db::COL_STATE;
+use header::BlockNumber;
+
+// Number of blocks checked between cache cleanups.
+const GC_INTERVAL: u64 = 1000;
+
+/// Inconsistency found in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+	/// No canonical hash is stored for the block number.
+	MissingBlockHash(BlockNumber),
+	/// Header of a canonical block is missing.
+	MissingHeader(BlockNumber, H256),
+	/// Stored header does not hash to its key or has a different number.
+	InvalidHeader(BlockNumber, H256),
+	/// Body of a canonical block is missing.
+	MissingBody(BlockNumber, H256),
+	/// Transactions root or uncles hash of the body do not match the header.
+	InvalidBody(BlockNumber, H256),
+	/// Receipts of a canonical block are missing.
+	MissingReceipts(BlockNumber, H256),
+	/// Receipts root does not match the header.
+	InvalidReceipts(BlockNumber, H256),
+	/// Details of a canonical block are missing.
+	MissingDetails(BlockNumber, H256),
+	/// Number, parent or total difficulty stored in details do not match the header.
+	InvalidDetails(BlockNumber, H256),
+	/// Block is not listed among the children of its parent.
+	NotAChild(BlockNumber, H256),
+	/// Block lists a child which is unknown or has a different parent.
+	InvalidChild(BlockNumber, H256, H256),
+	/// Transaction is not indexed at its position in the block.
+	InvalidTransactionAddress(BlockNumber, H256, usize),
+	/// Best block pointer does not point to the head of the canonical chain.
+	InvalidBestBlock(BlockNumber, H256),
+	/// Node of the state trie is missing.
+	MissingStateNode(H256),
+	/// Node of the storage trie of the account with given address hash is missing.
+	MissingStorageNode(H256, H256),
+	/// Code of the account with given address hash is missing.
+	MissingCode(H256, H256),
+	/// Account with given address hash could not be decoded.
+	InvalidAccount(H256),
+}
+
+impl Problem {
+	/// Short machine-readable name of the problem.
+	pub fn kind(&self) -> &'static str {
+		match *self {
+			Problem::MissingBlockHash(..) => "missing_block_hash",
+			Problem::MissingHeader(..) => "missing_header",
+			Problem::InvalidHeader(..) => "invalid_header",
+			Problem::MissingBody(..) => "missing_body",
+			Problem::InvalidBody(..) => "invalid_body",
+			Problem::MissingReceipts(..) => "missing_receipts",
+			Problem::InvalidReceipts(..) => "invalid_receipts",
+			Problem::MissingDetails(..) => "missing_details",
+			Problem::InvalidDetails(..) => "invalid_details",
+			Problem::NotAChild(..) => "not_a_child",
+			Problem::InvalidChild(..) => "invalid_child",
+			Problem::InvalidTransactionAddress(..) => "invalid_transaction_address",
+			Problem::InvalidBestBlock(..) => "invalid_best_block",
+			Problem::MissingStateNode(..) => "missing_state_node",
+			Problem::MissingStorageNode(..) => "missing_storage_node",
+			Problem::MissingCode(..) => "missing_code",
+			Problem::InvalidAccount(..) => "invalid_account",
+		}
+	}
+
+	/// Number and hash of the block the problem relates to.
+	pub fn block(&self) -> Option<(BlockNumber, H256)> {
+		match *self {
+			Problem::MissingHeader(n, ref h) | Problem::InvalidHeader(n, ref h) |
+			Problem::MissingBody(n, ref h) | Problem::InvalidBody(n, ref h) |
+			Problem::MissingReceipts(n, ref h) | Problem::InvalidReceipts(n, ref h) |
+			Problem::MissingDetails(n, ref h) | Problem::InvalidDetails(n, ref h) |
+			Problem::NotAChild(n, ref h) | Problem::InvalidChild(n, ref h, _) |
+			Problem::InvalidTransactionAddress(n, ref h, _) | Problem::InvalidBestBlock(n, ref h) => Some((n, h.clone())),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Problem::MissingBlockHash(n) => write!(f, "No canonical hash for block #{}", n),
+			Problem::MissingHeader(n, ref h) => write!(f, "Missing header of block #{} ({})", n, h),
+			Problem::InvalidHeader(n, ref h) => write!(f, "Header stored under block #{} ({}) does not match", n, h),
+			Problem::MissingBody(n, ref h) => write!(f, "Missing body of block #{} ({})", n, h),
+			Problem::InvalidBody(n, ref h) => write!(f, "Body of block #{} ({}) does not match its header", n, h),
+			Problem::MissingReceipts(n, ref h) => write!(f, "Missing receipts of block #{} ({})", n, h),
+			Problem::InvalidReceipts(n, ref h) => write!(f, "Receipts of block #{} ({}) do not match its header", n, h),
+			Problem::MissingDetails(n, ref h) => write!(f, "Missing details of block #{} ({})", n, h),
+			Problem::InvalidDetails(n, ref h) => write!(f, "Details of block #{} ({}) do not match its header", n, h),
+			Problem::NotAChild(n, ref h) => write!(f, "Block #{} ({}) is not a child of its parent", n, h),
+			Problem::InvalidChild(n, ref h, ref c) => write!(f, "Block #{} ({}) lists invalid child {}", n, h, c),
+			Problem::InvalidTransactionAddress(n, ref h, i) => write!(f, "Transaction {} of block #{} ({}) is not indexed", i, n, h),
+			Problem::InvalidBestBlock(n, ref h) => write!(f, "Best block #{} ({}) is not the head of the canonical chain", n, h),
+			Problem::MissingStateNode(ref h) => write!(f, "Missing state trie node {}", h),
+			Problem::MissingStorageNode(ref a, ref h) => write!(f, "Missing storage trie node {} of account {}", h, a),
+			Problem::MissingCode(ref a, ref h) => write!(f, "Missing code {} of account {}", h, a),
+			Problem::InvalidAccount(ref a) => write!(f, "Account {} could not be decoded", a),
+		}
+	}
+}
+
+/// Result of the database verification.
+#[derive(Debug, Default)]
+pub struct Report {
+	/// First verified block.
+	pub from: BlockNumber,
+	/// Best block of the chain.
+	pub best: BlockNumber,
+	/// Number of verified blocks.
+	pub blocks: u64,
+	/// Number of verified accounts, if the state was verified.
+	pub accounts: Option<u64>,
+	/// Problems found.
+	pub problems: Vec<Problem>,
+}
+
+/// Verifies consistency of the blockchain stored in `db` starting from block `from`.
+/// If `state` is given, the state trie of the best block is traversed as well
+/// using the given pruning algorithm.
+pub fn verify_database(db: Arc<KeyValueDB>, chain_config: BlockChainConfig, genesis: &[u8], from: BlockNumber, state: Option<Algorithm>) -> Report {
+	let chain = BlockChain::new(chain_config, genesis, db.clone());
+	let best = chain.best_block_number();
+	let mut report = Report {
+		from: from,
+		best: best,
+		..Default::default()
+	};
+
+	verify_best_block(&chain, &mut report.problems);
+
+	// blocks between the best ancient block and the first block are not
+	// expected to be present after a warp sync.
+	let gap = match (chain.first_block_number(), chain.best_ancient_number()) {
+		(Some(first), ancient) => Some((ancient.map_or(1, |n| n + 1), first)),
+		(None, _) => None,
+	};
+	// bodies and receipts of blocks below it were pruned on purpose.
+	let history_since = chain.history_since();
+
+	for number in from..(best + 1) {
+		if let Some((start, end)) = gap {
+			if number >= start && number < end {
+				continue;
+			}
+		}
+
+		verify_block(&chain, number, number >= history_since, &mut report.problems);
+		report.blocks += 1;
+
+		if number % GC_INTERVAL == 0 {
+			info!("Verified block #{}, {} problems found", number, report.problems.len());
+			chain.collect_garbage();
+		}
+	}
+
+	if let Some(algorithm) = state {
+		let header = chain.best_block_header().decode();
+		let journal = journaldb::new(db, algorithm, COL_STATE);
+		info!("Verifying state {} of block #{}", header.state_root(), best);
+		report.accounts = Some(verify_state(journal.as_hashdb(), header.state_root(), &mut report.problems));
+	}
+
+	report
+}
+
+fn verify_best_block(chain: &BlockChain, problems: &mut Vec<Problem>) {
+	let number = chain.best_block_number();
+	let hash = chain.best_block_hash();
+	if chain.block_hash(number).as_ref() != Some(&hash) || chain.block_hash(number + 1).is_some() {
+		problems.push(Problem::InvalidBestBlock(number, hash));
+	}
+}
+
+fn verify_block(chain: &BlockChain, number: BlockNumber, with_history: bool, problems: &mut Vec<Problem>) {
+	let hash = match chain.block_hash(number) {
+		Some(hash) => hash,
+		None => {
+			problems.push(Problem::MissingBlockHash(number));
+			return;
+		},
+	};
+
+	let header = match chain.block_header(&hash) {
+		Some(header) => header,
+		None => {
+			problems.push(Problem::MissingHeader(number, hash));
+			return;
+		},
+	};
+
+	if header.hash() != hash || header.number() != number {
+		problems.push(Problem::InvalidHeader(number, hash.clone()));
+	}
+
+	if with_history {
+		match chain.block_body(&hash) {
+			Some(body) => {
+				let rlp = body.rlp();
+				let transactions_root = ordered_trie_root(rlp.at(0).iter().map(|r| r.as_raw().to_vec()));
+				let uncles_hash = rlp.at(1).as_raw().sha3();
+				if &transactions_root != header.transactions_root() || &uncles_hash != header.uncles_hash() {
+					problems.push(Problem::InvalidBody(number, hash.clone()));
+				}
+
+				for (index, tx_hash) in body.transaction_hashes().into_iter().enumerate() {
+					let indexed = chain.transaction_address(&tx_hash)
+						.map_or(false, |address| address.block_hash == hash && address.index == index);
+					if !indexed {
+						problems.push(Problem::InvalidTransactionAddress(number, hash.clone(), index));
+					}
+				}
+			},
+			None => problems.push(Problem::MissingBody(number, hash.clone())),
+		}
+	}
+
+	// genesis receipts are never stored.
+	if with_history && number != 0 {
+		match chain.block_receipts(&hash) {
+			Some(receipts) => {
+				let receipts_root = ordered_trie_root(receipts.receipts.iter().map(|r| ::rlp::encode(r).into_vec()));
+				if &receipts_root != header.receipts_root() {
+					problems.push(Problem::InvalidReceipts(number, hash.clone()));
+				}
+			},
+			None => problems.push(Problem::MissingReceipts(number, hash.clone())),
+		}
+	}
+
+	let details = match chain.block_details(&hash) {
+		Some(details) => details,
+		None => {
+			problems.push(Problem::MissingDetails(number, hash));
+			return;
+		},
+	};
+
+	if details.number != number || &details.parent != header.parent_hash() {
+		problems.push(Problem::InvalidDetails(number, hash.clone()));
+	}
+
+	if number == 0 {
+		if &details.total_difficulty != header.difficulty() {
+			problems.push(Problem::InvalidDetails(number, hash.clone()));
+		}
+	} else if let Some(parent) = chain.block_details(header.parent_hash()) {
+		if details.total_difficulty != parent.total_difficulty + *header.difficulty() {
+			problems.push(Problem::InvalidDetails(number, hash.clone()));
+		}
+		if !parent.children.contains(&hash) {
+			problems.push(Problem::NotAChild(number, hash.clone()));
+		}
+	}
+
+	for child in &details.children {
+		let valid = chain.block_details(child).map_or(false, |child| child.parent == hash);
+		if !valid {
+			problems.push(Problem::InvalidChild(number, hash.clone(), child.clone()));
+		}
+	}
+}
+
+fn missing_node(e: Box<TrieError>) -> H256 {
+	match *e {
+		TrieError::InvalidStateRoot(ref hash) | TrieError::IncompleteDatabase(ref hash) => hash.clone(),
+	}
+}
+
+// Collects errors found while walking a storage trie.
+fn storage_errors(db: &HashDB, root: &H256) -> Vec<Box<TrieError>> {
+	let trie = match TrieDB::new(db, root) {
+		Ok(trie) => trie,
+		Err(e) => return vec![e],
+	};
+
+	let mut errors = Vec::new();
+	match trie.iter() {
+		Ok(iter) => errors.extend(iter.filter_map(Result::err)),
+		Err(e) => errors.push(e),
+	}
+	errors
+}
+
+// Walks the state trie and all storage tries, returns the number of accounts.
+fn verify_state(db: &HashDB, root: &H256, problems: &mut Vec<Problem>) -> u64 {
+	let trie = match TrieDB::new(db, root) {
+		Ok(trie) => trie,
+		Err(e) => {
+			problems.push(Problem::MissingStateNode(missing_node(e)));
+			return 0;
+		},
+	};
+
+	let iter = match trie.iter() {
+		Ok(iter) => iter,
+		Err(e) => {
+			problems.push(Problem::MissingStateNode(missing_node(e)));
+			return 0;
+		},
+	};
+
+	let mut accounts = 0;
+	for item in iter {
+		let (key, value) = match item {
+			Ok(item) => item,
+			Err(e) => {
+				problems.push(Problem::MissingStateNode(missing_node(e)));
+				continue;
+			},
+		};
+
+		accounts += 1;
+		let address_hash = H256::from_slice(&key);
+		let account: BasicAccount = match ::rlp::UntrustedRlp::new(&value).as_val() {
+			Ok(account) => account,
+			Err(_) => {
+				problems.push(Problem::InvalidAccount(address_hash));
+				continue;
+			},
+		};
+
+		let account_db = AccountDB::from_hash(db, address_hash.clone());
+		if account.code_hash != SHA3_EMPTY && !account_db.contains(&account.code_hash) {
+			problems.push(Problem::MissingCode(address_hash.clone(), account.code_hash.clone()));
+		}
+
+		for e in storage_errors(&account_db, &account.storage_root) {
+			problems.push(Problem::MissingStorageNode(address_hash.clone(), missing_node(e)));
+		}
+
+		if accounts % 100_000 == 0 {
+			info!("Verified {} accounts, {} problems found", accounts, problems.len());
+		}
+	}
+
+	accounts
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use util::{H256, KeyValueDB, DBTransaction};
+	use blockchain::{BlockChain, BlockProvider, Config};
+	use blockchain::extras::BlockReceipts;
+	use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
+	use db::{Key, COL_BODIES, COL_EXTRA, NUM_COLUMNS};
+	use super::{verify_database, Problem};
+
+	#[test]
+	fn reports_missing_blocks_data() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let db: Arc<KeyValueDB> = Arc::new(::util::kvdb::in_memory(NUM_COLUMNS.unwrap_or(0)));
+		let (hash2, hash3) = {
+			let bc = BlockChain::new(Config::default(), &genesis, db.clone());
+			for _ in 0..5 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				let mut batch = db.transaction();
+				bc.insert_block(&mut batch, &block, vec![]);
+				db.write(batch).unwrap();
+				bc.commit();
+			}
+			(bc.block_hash(2).unwrap(), bc.block_hash(3).unwrap())
+		};
+
+		let report = verify_database(db.clone(), Default::default(), &genesis, 0, None);
+		assert_eq!(report.best, 5);
+		assert_eq!(report.blocks, 6);
+		assert_eq!(report.problems, vec![]);
+
+		let mut batch = DBTransaction::new();
+		batch.delete(COL_BODIES, &hash2);
+		batch.delete(COL_EXTRA, &<H256 as Key<BlockReceipts>>::key(&hash3));
+		db.write(batch).unwrap();
+
+		let report = verify_database(db, Default::default(), &genesis, 1, None);
+		assert_eq!(report.blocks, 5);
+		assert_eq!(report.problems, vec![Problem::MissingBody(2, hash2), Problem::MissingReceipts(3, hash3)]);
+	}
+
+	#[test]
+	fn ignores_pruned_history() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let config = Config {
+			history_keep_blocks: Some(2),
+			..Default::default()
+		};
+
+		let db: Arc<KeyValueDB> = Arc::new(::util::kvdb::in_memory(NUM_COLUMNS.unwrap_or(0)));
+		let hash4 = {
+			let bc = BlockChain::new(config.clone(), &genesis, db.clone());
+			for _ in 0..5 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				let mut batch = db.transaction();
+				bc.insert_block(&mut batch, &block, vec![]);
+				db.write(batch).unwrap();
+				bc.commit();
+			}
+
+			let mut batch = db.transaction();
+			assert_eq!(bc.prune_history(&mut batch, 10).len(), 3);
+			db.write(batch).unwrap();
+			bc.block_hash(4).unwrap()
+		};
+
+		let report = verify_database(db.clone(), config.clone(), &genesis, 0, None);
+		assert_eq!(report.blocks, 6);
+		assert_eq!(report.problems, vec![]);
+
+		let mut batch = DBTransaction::new();
+		batch.delete(COL_BODIES, &hash4);
+		db.write(batch).unwrap();
+
+		let report = verify_database(db, config, &genesis, 0, None);
+		assert_eq!(report.problems, vec![Problem::MissingBody(4, hash4)]);
+	}
+}
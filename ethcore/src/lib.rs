@@ -135,6 +135,8 @@ pub mod pod_state;
 pub mod migrations;
 pub mod miner;
 pub mod snapshot;
+pub mod pruning;
+pub mod integrity;
 pub mod action_params;
 pub mod db;
 pub mod verification;
@@ -0,0 +1,138 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ancient block storage upgrade
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use blockchain::AncientStore;
+use blockchain::extras::{BlockDetails, BlockReceipts, ExtrasIndex};
+use db::{Key, COL_BODIES, COL_EXTRA, COL_HEADERS};
+use header::BlockNumber;
+use util::migration::{Error, Migration, Progress, Batch, Config};
+use util::{H256, H264, Database};
+
+/// Move canonical blocks deeper than `depth` below the best block into the
+/// ancient block store at `path`. Returns the number of blocks in the store.
+pub fn freeze_ancient(source: &Database, path: &Path, depth: u64) -> Result<BlockNumber, Error> {
+	let mut ancient = AncientStore::open(path)?;
+
+	let best_block_hash = match source.get(COL_EXTRA, b"best")? {
+		// no migration needed
+		None => return Ok(ancient.len()),
+		Some(hash) => H256::from_slice(&hash),
+	};
+	let best_block_number = match canon_number(source, &best_block_hash)? {
+		Some(number) => number,
+		None => return Ok(ancient.len()),
+	};
+
+	trace!(target: "migration", "Moving blocks older than #{} to ancient storage", best_block_number.saturating_sub(depth));
+	for number in ancient.len()..best_block_number.saturating_sub(depth) {
+		let hash = match source.get(COL_EXTRA, &<BlockNumber as Key<H256>>::key(&number))? {
+			Some(hash) => H256::from_slice(&hash),
+			None => break,
+		};
+
+		type ReceiptsKey = Key<BlockReceipts, Target=H264>;
+		let header = source.get(COL_HEADERS, &hash)?;
+		let body = source.get(COL_BODIES, &hash)?;
+		let receipts = source.get(COL_EXTRA, &ReceiptsKey::key(&hash))?;
+
+		match (header, body) {
+			(Some(header), Some(body)) => ancient.append(number, &header, &body, receipts.as_ref().map_or(&[][..], |r| &**r))?,
+			// nothing to move past a gap in the chain.
+			_ => break,
+		}
+	}
+
+	ancient.flush()?;
+	Ok(ancient.len())
+}
+
+/// Get the number of a block, if it is part of the canonical chain.
+fn canon_number(source: &Database, hash: &H256) -> Result<Option<BlockNumber>, Error> {
+	type DetailsKey = Key<BlockDetails, Target=H264>;
+	let number = match source.get(COL_EXTRA, &DetailsKey::key(hash))? {
+		Some(details) => ::rlp::decode::<BlockDetails>(&details).number,
+		None => return Ok(None),
+	};
+
+	match source.get(COL_EXTRA, &<BlockNumber as Key<H256>>::key(&number))? {
+		Some(ref canon) if &canon[..] == &hash[..] => Ok(Some(number)),
+		_ => Ok(None),
+	}
+}
+
+/// Ancient block storage migration.
+pub struct ToV13 {
+	path: PathBuf,
+	depth: u64,
+	frozen: Option<BlockNumber>,
+	progress: Progress,
+}
+
+impl ToV13 {
+	/// New v13 migration, moving blocks into the ancient block store at `path`.
+	pub fn new(path: PathBuf, depth: u64) -> ToV13 {
+		ToV13 {
+			path: path,
+			depth: depth,
+			frozen: None,
+			progress: Progress::default(),
+		}
+	}
+
+	/// Whether the value with given key was moved to the ancient block store.
+	fn is_frozen(&self, source: &Database, col: Option<u32>, key: &[u8], frozen: BlockNumber) -> Result<bool, Error> {
+		let hash = match col {
+			COL_HEADERS | COL_BODIES if key.len() == 32 => H256::from_slice(key),
+			COL_EXTRA if key.len() == 33 && key[0] == ExtrasIndex::BlockReceipts as u8 => H256::from_slice(&key[1..]),
+			_ => return Ok(false),
+		};
+
+		Ok(canon_number(source, &hash)?.map_or(false, |number| number < frozen))
+	}
+}
+
+impl Migration for ToV13 {
+	fn version(&self) -> u32 {
+		13
+	}
+
+	fn columns(&self) -> Option<u32> { Some(8) }
+
+	fn migrate(&mut self, source: Arc<Database>, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		let frozen = match self.frozen {
+			Some(frozen) => frozen,
+			None => {
+				let frozen = freeze_ancient(&source, &self.path, self.depth)?;
+				self.frozen = Some(frozen);
+				frozen
+			}
+		};
+
+		let mut batch = Batch::new(config, col);
+		for (key, value) in source.iter(col).into_iter().flat_map(|inner| inner) {
+			self.progress.tick();
+			if frozen > 0 && self.is_frozen(&source, col, &key, frozen)? {
+				continue;
+			}
+			batch.insert(key.into_vec(), value.into_vec(), dest)?;
+		}
+		batch.commit(dest)
+	}
+}
@@ -229,6 +229,7 @@ pub struct Miner {
 	notifiers: RwLock<Vec<Box<NotifyWork>>>,
 	gas_pricer: Mutex<GasPricer>,
 	service_transaction_action: ServiceTransactionAction,
+	transaction_listener: RwLock<Vec<Box<Fn(&[H256]) + Send + Sync>>>,
 }
 
 impl Miner {
@@ -238,6 +239,11 @@ impl Miner {
 		self.sealing_work.lock().enabled = true;
 	}
 
+	/// Set a callback to be notified about imported transactions' hashes.
+	pub fn add_transactions_listener(&self, f: Box<Fn(&[H256]) + Send + Sync>) {
+		self.transaction_listener.write().push(f);
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Arc<Miner> {
 		Arc::new(Miner::new_raw(options, gas_pricer, spec, accounts))
@@ -290,6 +296,7 @@ impl Miner {
 			notifiers: RwLock::new(notifiers),
 			gas_pricer: Mutex::new(gas_pricer),
 			service_transaction_action: service_transaction_action,
+			transaction_listener: RwLock::new(vec![]),
 		}
 	}
 
@@ -615,8 +622,9 @@ impl Miner {
 
 		let best_block_header = client.best_block_header().decode();
 		let insertion_time = client.chain_info().best_block_number;
+		let mut inserted = Vec::with_capacity(transactions.len());
 
-		transactions.into_iter()
+		let results: Vec<_> = transactions.into_iter()
 			.map(|tx| {
 				let hash = tx.hash();
 				if client.transaction_block(TransactionId::Hash(hash)).is_some() {
@@ -642,18 +650,30 @@ impl Miner {
 						self.service_transaction_action.update_from_chain_client(client);
 
 						let details_provider = TransactionDetailsProvider::new(client, &self.service_transaction_action);
-						match origin {
+						let hash = transaction.hash();
+						let result = match origin {
 							TransactionOrigin::Local | TransactionOrigin::RetractedBlock => {
 								transaction_queue.add(transaction, origin, insertion_time, condition.clone(), &details_provider)
 							},
 							TransactionOrigin::External => {
 								transaction_queue.add_with_banlist(transaction, insertion_time, &details_provider)
 							},
+						};
+
+						if result.is_ok() {
+							inserted.push(hash);
 						}
+						result
 					},
 				}
 			})
-			.collect()
+			.collect();
+
+		for listener in &*self.transaction_listener.read() {
+			listener(&inserted);
+		}
+
+		results
 	}
 
 	/// Are we allowed to do a non-mandatory reseal?
@@ -730,7 +750,7 @@ impl MinerService for Miner {
 					state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
 						.map_err(ExecutionError::from)?;
 				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, call_tracing: analytics.call_tracing, check_nonce: false };
 				let mut ret = Executive::new(&mut state, &env_info, &*self.engine).transact(t, options)?;
 
 				// TODO gav move this into Executive.
@@ -807,6 +827,10 @@ impl MinerService for Miner {
 		Ok(())
 	}
 
+	fn set_vote_proposal(&self, address: Address, authorize: Option<bool>) -> Result<(), Error> {
+		self.engine.set_vote_proposal(address, authorize)
+	}
+
 	fn set_extra_data(&self, extra_data: Bytes) {
 		*self.extra_data.write() = extra_data;
 	}
@@ -1404,6 +1428,27 @@ mod tests {
 		assert!(miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_notify_listeners_about_imported_transactions() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let transaction = transaction();
+		let hash = transaction.hash();
+		let notified = Arc::new(Mutex::new(Vec::new()));
+		let n = notified.clone();
+		miner.add_transactions_listener(Box::new(move |hashes| n.lock().extend_from_slice(hashes)));
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction.clone().into()]).pop().unwrap();
+		let res2 = miner.import_external_transactions(&client, vec![transaction.into()]).pop().unwrap();
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+		assert!(res2.is_err());
+		assert_eq!(*notified.lock(), vec![hash]);
+	}
+
 	#[test]
 	fn should_not_seal_unless_enabled() {
 		let miner = miner();
@@ -85,6 +85,10 @@ pub trait MinerService : Send + Sync {
 	/// Set info necessary to sign consensus messages.
 	fn set_engine_signer(&self, address: Address, password: String) -> Result<(), ::account_provider::SignError>;
 
+	/// Propose a vote on authorizing (`Some(true)`) or dropping (`Some(false)`) an authority,
+	/// to be cast in the blocks we seal. `None` discards the proposal.
+	fn set_vote_proposal(&self, address: Address, authorize: Option<bool>) -> Result<(), Error>;
+
 	/// Get the extra_data that we will seal blocks with.
 	fn extra_data(&self) -> Bytes;
 
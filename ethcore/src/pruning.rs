@@ -0,0 +1,413 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline conversion of an archive database into a pruned one.
+//!
+//! The state of the oldest kept block is copied as ancient data. Differences between
+//! the states of consecutive newer blocks are journalled the same way `OverlayRecentDB`
+//! journals imported blocks, so the client can keep pruning them as usual.
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::Arc;
+
+use util::{H256, DBValue, DBTransaction, KeyValueDB, TrieError, UtilError, SHA3_EMPTY, SHA3_NULL_RLP};
+use util::journaldb::{self, Algorithm};
+use util::nibblevec::NibbleVec;
+use util::trie::node::Node;
+use rlp::{Rlp, UntrustedRlp};
+
+use account_db::combine_key;
+use blockchain::{BlockChain, BlockProvider};
+use db::{COL_STATE, NUM_COLUMNS};
+use error::Error;
+use basic_account::BasicAccount;
+
+// Number of items written to the new database in a single batch.
+const BATCH_SIZE: usize = 4096;
+
+// Reference to a trie node: hash of a node stored in the database or an inline node.
+#[derive(PartialEq)]
+enum NodeRef<'a> {
+	Hash(H256),
+	Inline(&'a [u8]),
+}
+
+impl<'a> NodeRef<'a> {
+	// Parses a child reference of a branch or extension node.
+	fn from_raw(raw: &'a [u8]) -> Option<Self> {
+		let r = Rlp::new(raw);
+		if r.is_empty() {
+			None
+		} else if r.is_data() && r.size() == 32 {
+			Some(NodeRef::Hash(r.as_val()))
+		} else {
+			Some(NodeRef::Inline(raw))
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum TrieKind {
+	State,
+	// Storage trie of the account with given address hash.
+	Storage(H256),
+}
+
+impl TrieKind {
+	fn db_key(&self, hash: &H256) -> H256 {
+		match *self {
+			TrieKind::State => hash.clone(),
+			TrieKind::Storage(ref address_hash) => combine_key(address_hash, hash),
+		}
+	}
+}
+
+/// Database keys which differ between two states.
+#[derive(Default)]
+struct Changes {
+	removed: HashSet<H256>,
+	inserted: HashMap<H256, DBValue>,
+}
+
+impl Changes {
+	// drops nodes which are present in both states, but at different places.
+	fn normalize(&mut self) {
+		let moved: Vec<H256> = self.removed.iter().filter(|k| self.inserted.contains_key(k)).cloned().collect();
+		for key in moved {
+			self.removed.remove(&key);
+			self.inserted.remove(&key);
+		}
+	}
+}
+
+fn push_slice(path: &mut NibbleVec, slice: &::util::NibbleSlice) {
+	for i in 0..slice.len() {
+		path.push(slice.at(i));
+	}
+}
+
+fn pop_slice(path: &mut NibbleVec, slice: &::util::NibbleSlice) {
+	for _ in 0..slice.len() {
+		path.pop();
+	}
+}
+
+/// Walks state and storage tries stored in the state column of a database.
+struct StateWalker<'a> {
+	db: &'a KeyValueDB,
+}
+
+impl<'a> StateWalker<'a> {
+	fn get(&self, key: &H256) -> Result<DBValue, Error> {
+		self.db.get(COL_STATE, key).map_err(UtilError::SimpleString)?
+			.ok_or_else(|| TrieError::IncompleteDatabase(key.clone()).into())
+	}
+
+	// Loads node data. Database key is returned for hashed nodes. Empty tries yield `None`.
+	fn load(&self, kind: TrieKind, node: &NodeRef) -> Result<Option<(Option<H256>, DBValue)>, Error> {
+		match *node {
+			NodeRef::Hash(ref hash) if *hash == SHA3_NULL_RLP => Ok(None),
+			NodeRef::Hash(ref hash) => {
+				let key = kind.db_key(hash);
+				let data = self.get(&key)?;
+				Ok(Some((Some(key), data)))
+			},
+			NodeRef::Inline(data) => Ok(Some((None, DBValue::from_slice(data)))),
+		}
+	}
+
+	/// Calls `f` with every database item reachable from `node`, including storage
+	/// tries and code of the visited accounts.
+	fn visit(&self, kind: TrieKind, node: &NodeRef, path: &mut NibbleVec, f: &mut FnMut(H256, DBValue)) -> Result<(), Error> {
+		let data = match self.load(kind, node)? {
+			Some((key, data)) => {
+				if let Some(key) = key {
+					f(key, data.clone());
+				}
+				data
+			},
+			None => return Ok(()),
+		};
+
+		match Node::decoded(&data) {
+			Node::Empty => {},
+			Node::Leaf(slice, value) => {
+				if let TrieKind::State = kind {
+					push_slice(path, &slice);
+					self.visit_account(path, value, f)?;
+					pop_slice(path, &slice);
+				}
+			},
+			Node::Extension(slice, child) => {
+				if let Some(child) = NodeRef::from_raw(child) {
+					push_slice(path, &slice);
+					self.visit(kind, &child, path, f)?;
+					pop_slice(path, &slice);
+				}
+			},
+			Node::Branch(children, _) => {
+				for (i, child) in children.iter().enumerate() {
+					if let Some(child) = NodeRef::from_raw(child) {
+						path.push(i as u8);
+						self.visit(kind, &child, path, f)?;
+						path.pop();
+					}
+				}
+			},
+		}
+		Ok(())
+	}
+
+	fn visit_account(&self, path: &NibbleVec, account: &[u8], f: &mut FnMut(H256, DBValue)) -> Result<(), Error> {
+		let address_hash = H256::from_slice(path.inner());
+		let account: BasicAccount = UntrustedRlp::new(account).as_val()?;
+		self.visit(TrieKind::Storage(address_hash), &NodeRef::Hash(account.storage_root), &mut NibbleVec::new(), f)?;
+		if account.code_hash != SHA3_EMPTY {
+			let key = combine_key(&address_hash, &account.code_hash);
+			let code = self.get(&key)?;
+			f(key, code);
+		}
+		Ok(())
+	}
+
+	/// Collects database items which differ between tries rooted at `old` and `new`.
+	/// Subtrees with equal hashes at the same path are skipped.
+	fn diff(&self, kind: TrieKind, old: Option<&NodeRef>, new: Option<&NodeRef>, path: &mut NibbleVec, changes: &mut Changes) -> Result<(), Error> {
+		if old == new {
+			return Ok(());
+		}
+
+		let (old, new) = match (old, new) {
+			(Some(old), Some(new)) => (old, new),
+			(Some(old), None) => return self.visit(kind, old, path, &mut |key, _| { changes.removed.insert(key); }),
+			(None, Some(new)) => return self.visit(kind, new, path, &mut |key, value| { changes.inserted.insert(key, value); }),
+			(None, None) => return Ok(()),
+		};
+
+		let (old_data, new_data) = match (self.load(kind, old)?, self.load(kind, new)?) {
+			(Some((old_key, old_data)), Some((new_key, new_data))) => {
+				if let Some(key) = old_key {
+					changes.removed.insert(key);
+				}
+				if let Some(key) = new_key {
+					changes.inserted.insert(key, new_data.clone());
+				}
+				(old_data, new_data)
+			},
+			// one of the tries is empty.
+			_ => return self.replace(kind, old, new, path, changes),
+		};
+
+		match (Node::decoded(&old_data), Node::decoded(&new_data)) {
+			(Node::Branch(old_children, _), Node::Branch(new_children, _)) => {
+				for (i, (old_child, new_child)) in old_children.iter().zip(new_children.iter()).enumerate() {
+					path.push(i as u8);
+					self.diff(kind, NodeRef::from_raw(old_child).as_ref(), NodeRef::from_raw(new_child).as_ref(), path, changes)?;
+					path.pop();
+				}
+				Ok(())
+			},
+			(Node::Extension(old_slice, old_child), Node::Extension(new_slice, new_child)) if old_slice == new_slice => {
+				push_slice(path, &old_slice);
+				self.diff(kind, NodeRef::from_raw(old_child).as_ref(), NodeRef::from_raw(new_child).as_ref(), path, changes)?;
+				pop_slice(path, &old_slice);
+				Ok(())
+			},
+			(Node::Leaf(old_slice, old_value), Node::Leaf(new_slice, new_value)) if old_slice == new_slice => {
+				if let TrieKind::State = kind {
+					push_slice(path, &old_slice);
+					self.diff_account(path, old_value, new_value, changes)?;
+					pop_slice(path, &old_slice);
+				}
+				Ok(())
+			},
+			_ => self.replace(kind, old, new, path, changes),
+		}
+	}
+
+	// Marks the whole `old` subtree as removed and the whole `new` one as inserted.
+	fn replace(&self, kind: TrieKind, old: &NodeRef, new: &NodeRef, path: &mut NibbleVec, changes: &mut Changes) -> Result<(), Error> {
+		self.visit(kind, old, path, &mut |key, _| { changes.removed.insert(key); })?;
+		self.visit(kind, new, path, &mut |key, value| { changes.inserted.insert(key, value); })
+	}
+
+	fn diff_account(&self, path: &NibbleVec, old: &[u8], new: &[u8], changes: &mut Changes) -> Result<(), Error> {
+		let address_hash = H256::from_slice(path.inner());
+		let old: BasicAccount = UntrustedRlp::new(old).as_val()?;
+		let new: BasicAccount = UntrustedRlp::new(new).as_val()?;
+		let kind = TrieKind::Storage(address_hash);
+
+		self.diff(kind, Some(&NodeRef::Hash(old.storage_root)), Some(&NodeRef::Hash(new.storage_root)), &mut NibbleVec::new(), changes)?;
+
+		if old.code_hash != new.code_hash {
+			if old.code_hash != SHA3_EMPTY {
+				changes.removed.insert(combine_key(&address_hash, &old.code_hash));
+			}
+			if new.code_hash != SHA3_EMPTY {
+				let key = combine_key(&address_hash, &new.code_hash);
+				let code = self.get(&key)?;
+				changes.inserted.insert(key, code);
+			}
+		}
+		Ok(())
+	}
+}
+
+fn canonical_state(chain: &BlockChain, number: u64) -> Result<(H256, H256), Error> {
+	let hash = chain.block_hash(number)
+		.ok_or_else(|| UtilError::SimpleString(format!("Missing canonical block #{}", number)))?;
+	let header = chain.block_header(&hash)
+		.ok_or_else(|| UtilError::SimpleString(format!("Missing header of block #{}", number)))?;
+	Ok((hash, header.state_root().clone()))
+}
+
+/// Copies an archive database `source` into an empty database `dest` using `OverlayRecentDB`
+/// layout for the state, keeping the state of `keep` most recent canonical blocks journalled.
+/// Other columns are copied as they are.
+pub fn prune_archive(source: Arc<KeyValueDB>, dest: Arc<KeyValueDB>, genesis: &[u8], keep: u64) -> Result<(), Error> {
+	let chain = BlockChain::new(Default::default(), genesis, source.clone());
+	let best = chain.best_block_number();
+	let ancient = best.saturating_sub(keep);
+
+	info!("Copying blocks and extras");
+	let mut columns = vec![None];
+	columns.extend((0..NUM_COLUMNS.unwrap_or(0)).map(Some).filter(|col| *col != COL_STATE));
+	for col in columns {
+		let mut batch = DBTransaction::new();
+		let mut count = 0;
+		for (key, value) in source.iter(col) {
+			batch.put_vec(col, &key, value.into_vec());
+			count += 1;
+			if count % BATCH_SIZE == 0 {
+				dest.write(mem::replace(&mut batch, DBTransaction::new())).map_err(UtilError::SimpleString)?;
+			}
+		}
+		dest.write(batch).map_err(UtilError::SimpleString)?;
+	}
+
+	let walker = StateWalker { db: &*source };
+
+	info!("Copying state of block #{}", ancient);
+	let (_, ancient_root) = canonical_state(&chain, ancient)?;
+	{
+		let mut batch = DBTransaction::new();
+		let mut count = 0;
+		let mut result = Ok(());
+		walker.visit(TrieKind::State, &NodeRef::Hash(ancient_root), &mut NibbleVec::new(), &mut |key, value| {
+			batch.put(COL_STATE, &key, &value);
+			count += 1;
+			if count % BATCH_SIZE == 0 && result.is_ok() {
+				result = dest.write(mem::replace(&mut batch, DBTransaction::new()));
+			}
+		})?;
+		result.map_err(UtilError::SimpleString)?;
+		dest.write(batch).map_err(UtilError::SimpleString)?;
+		info!("Copied {} state items", count);
+	}
+
+	let mut journal = journaldb::new(dest.clone(), Algorithm::OverlayRecent, COL_STATE);
+	let mut parent_root = ancient_root;
+	for number in (ancient + 1)..(best + 1) {
+		let (hash, root) = canonical_state(&chain, number)?;
+		let mut changes = Changes::default();
+		walker.diff(TrieKind::State, Some(&NodeRef::Hash(parent_root)), Some(&NodeRef::Hash(root.clone())), &mut NibbleVec::new(), &mut changes)?;
+		changes.normalize();
+		trace!(target: "pruning", "Journalling block #{}: +{} -{}", number, changes.inserted.len(), changes.removed.len());
+
+		for (key, value) in changes.inserted {
+			journal.emplace(key, value);
+		}
+		for key in changes.removed {
+			journal.remove(&key);
+		}
+
+		let mut batch = DBTransaction::new();
+		journal.journal_under(&mut batch, number, &hash)?;
+		dest.write(batch).map_err(UtilError::SimpleString)?;
+		parent_root = root;
+	}
+	info!("Journalled state of {} recent blocks", best - ancient);
+
+	dest.flush().map_err(UtilError::SimpleString)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use std::sync::Arc;
+	use util::{H256, U256, Address, KeyValueDB, DBTransaction};
+	use util::journaldb::{self, Algorithm};
+	use util::nibblevec::NibbleVec;
+	use util::kvdb::in_memory;
+	use state::{State, CleanupMode};
+	use state_db::StateDB;
+	use db::{COL_STATE, NUM_COLUMNS};
+	use super::{StateWalker, NodeRef, TrieKind, Changes};
+
+	fn reachable(walker: &StateWalker, root: &H256) -> HashSet<H256> {
+		let mut keys = HashSet::new();
+		walker.visit(TrieKind::State, &NodeRef::Hash(root.clone()), &mut NibbleVec::new(), &mut |key, _| { keys.insert(key); }).unwrap();
+		keys
+	}
+
+	fn commit(db: &Arc<KeyValueDB>, state: State<StateDB>, era: u64) -> (H256, StateDB) {
+		let mut state = state;
+		state.commit().unwrap();
+		let (root, mut state_db) = state.drop();
+		let mut batch = DBTransaction::new();
+		state_db.journal_under(&mut batch, era, &H256::from(era)).unwrap();
+		db.write(batch).unwrap();
+		(root, state_db)
+	}
+
+	#[test]
+	fn diff_matches_reachable_nodes() {
+		let db: Arc<KeyValueDB> = Arc::new(in_memory(NUM_COLUMNS.unwrap_or(0)));
+		let state_db = StateDB::new(journaldb::new(db.clone(), Algorithm::Archive, COL_STATE), 1024 * 1024);
+
+		let mut state = State::new(state_db, U256::zero(), Default::default());
+		for i in 1..32u64 {
+			let address = Address::from(i);
+			state.add_balance(&address, &U256::from(i), CleanupMode::ForceCreate).unwrap();
+			state.set_storage(&address, H256::from(1u64), H256::from(i)).unwrap();
+		}
+		state.init_code(&Address::from(1u64), vec![0x60, 0x00]).unwrap();
+		let (old_root, state_db) = commit(&db, state, 0);
+
+		let mut state = State::from_existing(state_db, old_root.clone(), U256::zero(), Default::default()).unwrap();
+		state.add_balance(&Address::from(2u64), &U256::from(5), CleanupMode::ForceCreate).unwrap();
+		state.set_storage(&Address::from(3u64), H256::from(2u64), H256::from(7u64)).unwrap();
+		state.kill_account(&Address::from(1u64));
+		state.add_balance(&Address::from(100u64), &U256::from(1), CleanupMode::ForceCreate).unwrap();
+		let (new_root, _) = commit(&db, state, 1);
+
+		let walker = StateWalker { db: &*db };
+		let mut changes = Changes::default();
+		walker.diff(TrieKind::State, Some(&NodeRef::Hash(old_root.clone())), Some(&NodeRef::Hash(new_root.clone())), &mut NibbleVec::new(), &mut changes).unwrap();
+		changes.normalize();
+
+		let old = reachable(&walker, &old_root);
+		let new = reachable(&walker, &new_root);
+		let expected_removed: HashSet<_> = old.difference(&new).cloned().collect();
+		let expected_inserted: HashSet<_> = new.difference(&old).cloned().collect();
+
+		assert!(!expected_removed.is_empty());
+		assert_eq!(changes.removed, expected_removed);
+		assert_eq!(changes.inserted.keys().cloned().collect::<HashSet<_>>(), expected_inserted);
+	}
+}
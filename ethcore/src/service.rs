@@ -20,11 +20,12 @@ use util::*;
 use io::*;
 use spec::Spec;
 use error::*;
-use client::{Client, ClientConfig, ChainNotify};
+use client::{BlockChainClient, Client, ClientConfig, ChainNotify};
+use ids::BlockId;
 use miner::Miner;
 
 use snapshot::ManifestData;
-use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams};
+use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams, ManifestSigner};
 use std::sync::atomic::AtomicBool;
 
 #[cfg(feature="ipc")]
@@ -56,7 +57,7 @@ pub struct ClientService {
 	io_service: Arc<IoService<ClientIoMessage>>,
 	client: Arc<Client>,
 	snapshot: Arc<SnapshotService>,
-	database: Arc<Database>,
+	database: Arc<KeyValueDB>,
 	_stop_guard: ::devtools::StopGuard,
 }
 
@@ -69,6 +70,7 @@ impl ClientService {
 		snapshot_path: &Path,
 		ipc_path: &Path,
 		miner: Arc<Miner>,
+		snapshot_signer: Option<ManifestSigner>,
 		) -> Result<ClientService, Error>
 	{
 		let io_service = IoService::<ClientIoMessage>::start()?;
@@ -85,11 +87,12 @@ impl ClientService {
 
 		db_config.compaction = config.db_compaction.compaction_profile(client_path);
 		db_config.wal = config.db_wal;
+		db_config.backend = config.db_backend;
 
-		let db = Arc::new(Database::open(
+		let db = open_database(
 			&db_config,
 			&client_path.to_str().expect("DB path could not be converted to string.")
-		).map_err(::client::Error::Database)?);
+		).map_err(::client::Error::Database)?;
 
 
 		let pruning = config.pruning;
@@ -103,6 +106,7 @@ impl ClientService {
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
 			db_restore: client.clone(),
+			signer: snapshot_signer,
 		};
 		let snapshot = Arc::new(SnapshotService::new(snapshot_params)?);
 
@@ -171,6 +175,18 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
 		io.register_timer(CLIENT_TICK_TIMER, CLIENT_TICK_MS).expect("Error registering client timer");
 		io.register_timer(SNAPSHOT_TICK_TIMER, SNAPSHOT_TICK_MS).expect("Error registering snapshot timer");
+
+		// resume a snapshot interrupted by the last shutdown, as long as its block is still canonical.
+		if let Some(hash) = self.snapshot.interrupted_snapshot() {
+			let num = self.client.block_number(BlockId::Hash(hash))
+				.and_then(|num| if self.client.block_hash(BlockId::Number(num)) == Some(hash) { Some(num) } else { None });
+			if let Some(num) = num {
+				info!("Resuming interrupted snapshot at #{}", num);
+				if let Err(e) = io.message(ClientIoMessage::TakeSnapshot(num)) {
+					debug!(target: "snapshot", "Failed to resume snapshot: {:?}", e);
+				}
+			}
+		}
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
@@ -194,9 +210,16 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 				if let Err(e) = self.snapshot.init_restore(manifest.clone(), true) {
 					warn!("Failed to initialize snapshot restoration: {}", e);
 				}
+				self.client.notify_restoration_status();
+			}
+			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => {
+				self.snapshot.feed_state_chunk(*hash, chunk);
+				self.client.notify_restoration_status();
+			}
+			ClientIoMessage::FeedBlockChunk(ref hash, ref chunk) => {
+				self.snapshot.feed_block_chunk(*hash, chunk);
+				self.client.notify_restoration_status();
 			}
-			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => self.snapshot.feed_state_chunk(*hash, chunk),
-			ClientIoMessage::FeedBlockChunk(ref hash, ref chunk) => self.snapshot.feed_block_chunk(*hash, chunk),
 			ClientIoMessage::TakeSnapshot(num) => {
 				let client = self.client.clone();
 				let snapshot = self.snapshot.clone();
@@ -284,6 +307,7 @@ mod tests {
 			&snapshot_path,
 			&path,
 			Arc::new(Miner::with_spec(&spec)),
+			None,
 		);
 		assert!(service.is_ok());
 		drop(service.unwrap());
@@ -20,10 +20,11 @@ use account_db::{AccountDB, AccountDBMut};
 use basic_account::BasicAccount;
 use snapshot::Error;
 
-use util::{U256, H256, Bytes, HashDB, SHA3_EMPTY, SHA3_NULL_RLP};
-use util::trie::{TrieDB, Trie};
+use util::{U256, H256, Bytes, HashDB, DBValue, SHA3_EMPTY, SHA3_NULL_RLP};
+use util::trie::{TrieDB, Trie, Result as TrieResult};
 use rlp::{RlpStream, UntrustedRlp};
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 // An empty account -- these were replaced with RLP null data for a space optimization in v1.
@@ -203,6 +204,192 @@ pub fn from_fat_rlp(
 	Ok((acc, new_code))
 }
 
+// get the next item of a trie iterator, if any.
+fn next_item<I>(iter: &mut I) -> Result<Option<(Bytes, DBValue)>, Error>
+	where I: Iterator<Item = TrieResult<(Bytes, DBValue)>>
+{
+	match iter.next() {
+		Some(item) => Ok(Some(item?)),
+		None => Ok(None),
+	}
+}
+
+// walk two tries in key order, calling `f` with every key whose value differs
+// between them, along with the value in each trie.
+pub fn diff_tries<F>(old: &Trie, new: &Trie, mut f: F) -> Result<(), Error>
+	where F: FnMut(&[u8], Option<&[u8]>, Option<&[u8]>) -> Result<(), Error>
+{
+	let mut old_iter = old.iter()?;
+	let mut new_iter = new.iter()?;
+
+	let mut old_item = next_item(&mut old_iter)?;
+	let mut new_item = next_item(&mut new_iter)?;
+
+	loop {
+		match (old_item.take(), new_item.take()) {
+			(None, None) => return Ok(()),
+			(Some((old_key, old_val)), None) => {
+				f(&old_key, Some(&*old_val), None)?;
+				old_item = next_item(&mut old_iter)?;
+			}
+			(None, Some((new_key, new_val))) => {
+				f(&new_key, None, Some(&*new_val))?;
+				new_item = next_item(&mut new_iter)?;
+			}
+			(Some((old_key, old_val)), Some((new_key, new_val))) => match old_key.cmp(&new_key) {
+				Ordering::Less => {
+					f(&old_key, Some(&*old_val), None)?;
+					old_item = next_item(&mut old_iter)?;
+					new_item = Some((new_key, new_val));
+				}
+				Ordering::Greater => {
+					f(&new_key, None, Some(&*new_val))?;
+					old_item = Some((old_key, old_val));
+					new_item = next_item(&mut new_iter)?;
+				}
+				Ordering::Equal => {
+					if &*old_val != &*new_val {
+						f(&new_key, Some(&*old_val), Some(&*new_val))?;
+					}
+					old_item = next_item(&mut old_iter)?;
+					new_item = next_item(&mut new_iter)?;
+				}
+			}
+		}
+	}
+}
+
+// encode the changes to an account since `base` as a vector of delta records.
+// a removed account is a single record with empty data in place of the account.
+// otherwise each record carries the account properties and a part of the changed
+// storage, with removed storage keys mapped to empty data. code is only included
+// inline in the first record, and only if it differs from the base.
+pub fn to_delta_rlps(account_hash: &H256, base: Option<&BasicAccount>, acc: Option<&BasicAccount>, acct_db: &AccountDB, max_chunk_size: usize) -> Result<Vec<Bytes>, Error> {
+	let acc = match acc {
+		Some(acc) => acc,
+		None => {
+			let mut stream = RlpStream::new_list(2);
+			stream.append(account_hash).append_empty_data();
+			return Ok(vec![stream.out()]);
+		}
+	};
+
+	let base_storage_root = base.map_or(SHA3_NULL_RLP, |base| base.storage_root);
+	let mut pairs = Vec::new();
+	if base_storage_root != acc.storage_root {
+		let old = TrieDB::new(acct_db, &base_storage_root)?;
+		let new = TrieDB::new(acct_db, &acc.storage_root)?;
+		diff_tries(&old, &new, |k, _, v| {
+			let mut stream = RlpStream::new_list(2);
+			stream.append(&k).append(&v.unwrap_or(&[]));
+			pairs.push(stream.out());
+			Ok(())
+		})?;
+	}
+
+	let mut code = if acc.code_hash == SHA3_EMPTY || base.map_or(false, |base| base.code_hash == acc.code_hash) {
+		None
+	} else {
+		Some(acct_db.get(&acc.code_hash).ok_or_else(|| Error::MissingCode(vec![acc.code_hash]))?)
+	};
+
+	let mut records = Vec::new();
+	let mut pairs = pairs.into_iter().peekable();
+	loop {
+		let mut account_stream = RlpStream::new_list(2);
+		account_stream.append(account_hash);
+		account_stream.begin_list(5);
+
+		account_stream.append(&acc.nonce)
+					  .append(&acc.balance);
+
+		// [has_code, code_hash].
+		match code.take() {
+			Some(c) => { account_stream.append(&CodeState::Inline.raw()).append(&&*c); }
+			None if acc.code_hash == SHA3_EMPTY => { account_stream.append(&CodeState::Empty.raw()).append_empty_data(); }
+			None => { account_stream.append(&CodeState::Hash.raw()).append(&acc.code_hash); }
+		}
+
+		account_stream.begin_unbounded_list();
+		let mut pushed = false;
+		loop {
+			let fits = match pairs.peek() {
+				Some(pair) => !pushed || account_stream.len() + pair.len() <= max_chunk_size,
+				None => break,
+			};
+			if !fits { break }
+
+			let pair = pairs.next().expect("peeked item exists; qed");
+			account_stream.append_raw(&pair, 1);
+			pushed = true;
+		}
+		account_stream.complete_unbounded_list();
+		records.push(account_stream.out());
+
+		if pairs.peek().is_none() {
+			return Ok(records);
+		}
+	}
+}
+
+// apply a delta record on top of the account's state in `base`, updating the
+// storage trie and writing new code as we go. returns the updated account, or
+// `None` if the account was removed.
+pub fn from_delta_rlp(
+	acct_db: &mut AccountDBMut,
+	rlp: UntrustedRlp,
+	base: Option<BasicAccount>,
+) -> Result<Option<BasicAccount>, Error> {
+	use util::{TrieDBMut, TrieMut};
+
+	if rlp.is_empty() {
+		return Ok(None);
+	}
+
+	let nonce = rlp.val_at(0)?;
+	let balance = rlp.val_at(1)?;
+	let code_state: CodeState = {
+		let raw: u8 = rlp.val_at(2)?;
+		CodeState::from(raw)?
+	};
+
+	let code_hash = match code_state {
+		CodeState::Empty => SHA3_EMPTY,
+		CodeState::Inline => {
+			let code: Bytes = rlp.val_at(3)?;
+			acct_db.insert(&code)
+		}
+		CodeState::Hash => rlp.val_at(3)?,
+	};
+
+	let mut storage_root = base.map_or(SHA3_NULL_RLP, |base| base.storage_root);
+	{
+		let mut storage_trie = if storage_root == SHA3_NULL_RLP {
+			TrieDBMut::new(acct_db, &mut storage_root)
+		} else {
+			TrieDBMut::from_existing(acct_db, &mut storage_root)?
+		};
+		let pairs = rlp.at(4)?;
+		for pair_rlp in pairs.iter() {
+			let k: Bytes = pair_rlp.val_at(0)?;
+			let v: Bytes = pair_rlp.val_at(1)?;
+
+			if v.is_empty() {
+				storage_trie.remove(&k)?;
+			} else {
+				storage_trie.insert(&k, &v)?;
+			}
+		}
+	}
+
+	Ok(Some(BasicAccount {
+		nonce: nonce,
+		balance: balance,
+		storage_root: storage_root,
+		code_hash: code_hash,
+	}))
+}
+
 #[cfg(test)]
 mod tests {
 	use account_db::{AccountDB, AccountDBMut};
@@ -211,12 +398,12 @@ mod tests {
 	use snapshot::tests::helpers::fill_storage;
 
 	use util::sha3::{SHA3_EMPTY, SHA3_NULL_RLP};
-	use util::{Address, H256, HashDB, DBValue, Hashable};
+	use util::{Address, H256, HashDB, DBValue, Hashable, TrieDBMut, TrieMut};
 	use rlp::UntrustedRlp;
 
 	use std::collections::HashSet;
 
-	use super::{ACC_EMPTY, to_fat_rlps, from_fat_rlp};
+	use super::{ACC_EMPTY, to_fat_rlps, from_fat_rlp, to_delta_rlps, from_delta_rlp};
 
 	#[test]
 	fn encoding_basic() {
@@ -348,4 +535,60 @@ mod tests {
 		let mut db = get_temp_state_db();
 		assert_eq!(from_fat_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &Address::default()), UntrustedRlp::new(&::rlp::NULL_RLP), H256::zero()).unwrap(), (ACC_EMPTY, None));
 	}
+
+	#[test]
+	fn encoding_delta() {
+		let mut db = get_temp_state_db();
+		let addr = Address::random();
+
+		let mut base_root = SHA3_NULL_RLP;
+		let mut root = SHA3_NULL_RLP;
+		{
+			let mut acct_db = AccountDBMut::new(db.as_hashdb_mut(), &addr);
+			{
+				let mut trie = TrieDBMut::new(&mut acct_db, &mut base_root);
+				for i in 0..100u64 {
+					trie.insert(&H256::from(i), &[1u8; 40]).unwrap();
+				}
+			}
+
+			// remove the first half, change a quarter and add a hundred new keys.
+			let mut trie = TrieDBMut::new(&mut acct_db, &mut root);
+			for i in 50..200u64 {
+				let value = if i < 75 { [1u8; 40] } else { [2u8; 40] };
+				trie.insert(&H256::from(i), &value).unwrap();
+			}
+		}
+
+		let base = BasicAccount {
+			nonce: 25.into(),
+			balance: 987654321.into(),
+			storage_root: base_root,
+			code_hash: SHA3_EMPTY,
+		};
+
+		let code_hash = AccountDBMut::new(db.as_hashdb_mut(), &addr).insert(b"this is definitely code");
+		let account = BasicAccount {
+			nonce: 26.into(),
+			balance: 123456789.into(),
+			storage_root: root,
+			code_hash: code_hash,
+		};
+
+		let delta_rlps = to_delta_rlps(&addr.sha3(), Some(&base), Some(&account), &AccountDB::new(db.as_hashdb(), &addr), 1000).unwrap();
+		assert!(delta_rlps.len() > 1);
+
+		let mut restored_account = Some(base);
+		for rlp in delta_rlps {
+			let delta_rlp = UntrustedRlp::new(&rlp).at(1).unwrap();
+			restored_account = from_delta_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), delta_rlp, restored_account).unwrap();
+		}
+		assert_eq!(restored_account, Some(account.clone()));
+
+		let delta_rlps = to_delta_rlps(&addr.sha3(), Some(&account), None, &AccountDB::new(db.as_hashdb(), &addr), 1000).unwrap();
+		assert_eq!(delta_rlps.len(), 1);
+
+		let delta_rlp = UntrustedRlp::new(&delta_rlps[0]).at(1).unwrap();
+		assert_eq!(from_delta_rlp(&mut AccountDBMut::new(db.as_hashdb_mut(), &addr), delta_rlp, Some(account)).unwrap(), None);
+	}
 }
@@ -20,19 +20,47 @@
 //! Packed snapshots are written to a single file, and loose snapshots are
 //! written to multiple files in one directory.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 
 use util::Bytes;
-use util::hash::H256;
+use util::hash::{H256, H520};
 use rlp::{self, Encodable, RlpStream, UntrustedRlp};
 
 use super::ManifestData;
 
 const SNAPSHOT_VERSION: u64 = 2;
 
+// Identifier of the block chunks part in the parts file.
+const BLOCKS_PART_ID: u8 = 0xff;
+
+/// Part of a snapshot which is produced independently of the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotPart {
+	/// State chunks of the accounts whose address hash starts with the given nibble.
+	State(u8),
+	/// Block chunks.
+	Blocks,
+}
+
+impl SnapshotPart {
+	fn id(&self) -> u8 {
+		match *self {
+			SnapshotPart::State(nibble) => nibble,
+			SnapshotPart::Blocks => BLOCKS_PART_ID,
+		}
+	}
+
+	fn from_id(id: u8) -> Self {
+		match id {
+			BLOCKS_PART_ID => SnapshotPart::Blocks,
+			nibble => SnapshotPart::State(nibble),
+		}
+	}
+}
+
 /// Something which can write snapshots.
 /// Writing the same chunk multiple times will lead to implementation-defined
 /// behavior, and is not advised.
@@ -43,6 +71,18 @@ pub trait SnapshotWriter {
 	/// Write a compressed block chunk.
 	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()>;
 
+	/// Note that all chunks of a part of the snapshot at block `block_hash` were written.
+	/// Writers which support resumption keep this information until the snapshot is finished.
+	fn complete_part(&mut self, _block_hash: &H256, _part: SnapshotPart, _hashes: &[H256]) -> io::Result<()> {
+		Ok(())
+	}
+
+	/// Get chunk hashes of the parts of the snapshot at block `block_hash` completed
+	/// by an interrupted run.
+	fn completed_parts(&self, _block_hash: &H256) -> HashMap<SnapshotPart, Vec<H256>> {
+		HashMap::new()
+	}
+
 	/// Complete writing. The manifest's chunk lists must be consistent
 	/// with the chunks written.
 	fn finish(self, manifest: ManifestData) -> io::Result<()> where Self: Sized;
@@ -120,7 +160,13 @@ impl SnapshotWriter for PackedWriter {
 	fn finish(mut self, manifest: ManifestData) -> io::Result<()> {
 		// we ignore the hashes fields of the manifest under the assumption that
 		// they are consistent with ours.
-		let mut stream = RlpStream::new_list(6);
+		let len = match (manifest.signature.is_some(), manifest.base.is_some()) {
+			(_, true) => 8,
+			(true, false) => 7,
+			(false, false) => 6,
+		};
+
+		let mut stream = RlpStream::new_list(len);
 		stream
 			.append(&SNAPSHOT_VERSION)
 			.append_list(&self.state_hashes)
@@ -128,6 +174,14 @@ impl SnapshotWriter for PackedWriter {
 			.append(&manifest.state_root)
 			.append(&manifest.block_number)
 			.append(&manifest.block_hash);
+		match manifest.signature {
+			Some(ref signature) => { stream.append(signature); },
+			None if manifest.base.is_some() => { stream.append_empty_data(); },
+			None => {},
+		}
+		if let Some(ref base) = manifest.base {
+			stream.append(base);
+		}
 
 		let manifest_rlp = stream.out();
 
@@ -153,9 +207,55 @@ impl SnapshotWriter for PackedWriter {
 	}
 }
 
+/// A writer which signs the manifest before finishing the inner writer.
+///
+/// The signing function is given the manifest's signing hash.
+pub struct SigningWriter<W, F> {
+	inner: W,
+	sign: F,
+}
+
+impl<W, F> SigningWriter<W, F> where W: SnapshotWriter, F: FnOnce(H256) -> io::Result<H520> {
+	/// Create a new signing writer around `inner`.
+	pub fn new(inner: W, sign: F) -> Self {
+		SigningWriter {
+			inner: inner,
+			sign: sign,
+		}
+	}
+}
+
+impl<W, F> SnapshotWriter for SigningWriter<W, F> where W: SnapshotWriter, F: FnOnce(H256) -> io::Result<H520> {
+	fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.inner.write_state_chunk(hash, chunk)
+	}
+
+	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.inner.write_block_chunk(hash, chunk)
+	}
+
+	fn complete_part(&mut self, block_hash: &H256, part: SnapshotPart, hashes: &[H256]) -> io::Result<()> {
+		self.inner.complete_part(block_hash, part, hashes)
+	}
+
+	fn completed_parts(&self, block_hash: &H256) -> HashMap<SnapshotPart, Vec<H256>> {
+		self.inner.completed_parts(block_hash)
+	}
+
+	fn finish(self, mut manifest: ManifestData) -> io::Result<()> {
+		let signature = (self.sign)(manifest.signing_hash())?;
+		manifest.signature = Some(signature);
+		self.inner.finish(manifest)
+	}
+}
+
 /// A "loose" writer writes chunk files into a directory.
+///
+/// Completed parts of the snapshot are recorded in the directory, so that
+/// an interrupted snapshot of the same block can be resumed.
 pub struct LooseWriter {
 	dir: PathBuf,
+	parts: Option<(H256, HashMap<SnapshotPart, Vec<H256>>)>,
 }
 
 impl LooseWriter {
@@ -164,9 +264,70 @@ impl LooseWriter {
 	pub fn new(path: PathBuf) -> io::Result<Self> {
 		fs::create_dir_all(&path)?;
 
-		Ok(LooseWriter {
+		let mut writer = LooseWriter {
 			dir: path,
-		})
+			parts: None,
+		};
+		writer.parts = writer.read_parts();
+
+		Ok(writer)
+	}
+
+	/// Get the hash of the block whose snapshot was interrupted after
+	/// completing some parts, if any.
+	pub fn resumable_block(&self) -> Option<H256> {
+		self.parts.as_ref().map(|&(ref hash, _)| hash.clone())
+	}
+
+	fn parts_path(&self) -> PathBuf {
+		let mut path = self.dir.clone();
+		path.push("PARTS");
+		path
+	}
+
+	// read the parts left by an interrupted snapshot. unreadable data is ignored.
+	fn read_parts(&self) -> Option<(H256, HashMap<SnapshotPart, Vec<H256>>)> {
+		let mut buf = Vec::new();
+		if File::open(self.parts_path()).and_then(|mut file| file.read_to_end(&mut buf)).is_err() {
+			return None;
+		}
+
+		let decode = |r: UntrustedRlp| -> Result<_, rlp::DecoderError> {
+			let block_hash = r.val_at(0)?;
+			let mut parts = HashMap::new();
+			for part in r.at(1)?.iter() {
+				parts.insert(SnapshotPart::from_id(part.val_at(0)?), part.list_at(1)?);
+			}
+			Ok((block_hash, parts))
+		};
+
+		match decode(UntrustedRlp::new(&buf)) {
+			Ok(parts) => Some(parts),
+			Err(e) => {
+				warn!(target: "snapshot_io", "Ignoring invalid snapshot parts file: {}", e);
+				None
+			},
+		}
+	}
+
+	fn write_parts(&self) -> io::Result<()> {
+		let (ref block_hash, ref parts) = *self.parts.as_ref().expect("only called after parts are set; qed");
+
+		let mut stream = RlpStream::new_list(2);
+		stream.append(block_hash);
+		stream.begin_list(parts.len());
+		for (part, hashes) in parts {
+			stream.begin_list(2).append(&part.id()).append_list(hashes);
+		}
+
+		let path = self.parts_path();
+		let temp_path = path.with_extension("tmp");
+		{
+			let mut file = File::create(&temp_path)?;
+			file.write_all(&stream.out())?;
+			file.sync_all()?;
+		}
+		fs::rename(temp_path, path)
 	}
 
 	// writing logic is the same for both kinds of chunks.
@@ -190,7 +351,29 @@ impl SnapshotWriter for LooseWriter {
 		self.write_chunk(hash, chunk)
 	}
 
+	fn complete_part(&mut self, block_hash: &H256, part: SnapshotPart, hashes: &[H256]) -> io::Result<()> {
+		// parts of a snapshot of another block are discarded.
+		if self.parts.as_ref().map_or(true, |&(ref hash, _)| hash != block_hash) {
+			self.parts = Some((block_hash.clone(), HashMap::new()));
+		}
+
+		if let Some((_, ref mut parts)) = self.parts {
+			parts.insert(part, hashes.to_vec());
+		}
+
+		self.write_parts()
+	}
+
+	fn completed_parts(&self, block_hash: &H256) -> HashMap<SnapshotPart, Vec<H256>> {
+		match self.parts {
+			Some((ref hash, ref parts)) if hash == block_hash => parts.clone(),
+			_ => HashMap::new(),
+		}
+	}
+
 	fn finish(self, manifest: ManifestData) -> io::Result<()> {
+		let used: HashSet<String> = manifest.state_hashes.iter().chain(&manifest.block_hashes).map(|h| h.hex()).collect();
+
 		let rlp = manifest.into_rlp();
 		let mut path = self.dir.clone();
 		path.push("MANIFEST");
@@ -198,6 +381,21 @@ impl SnapshotWriter for LooseWriter {
 		let mut file = File::create(path)?;
 		file.write_all(&rlp[..])?;
 
+		// remove chunks left by interrupted runs of other snapshots.
+		for entry in fs::read_dir(&self.dir)? {
+			let entry = entry?;
+			let name = entry.file_name().to_string_lossy().into_owned();
+			if name.len() == 64 && !used.contains(&name) {
+				fs::remove_file(entry.path())?;
+			}
+		}
+
+		if let Err(e) = fs::remove_file(self.parts_path()) {
+			if e.kind() != io::ErrorKind::NotFound {
+				return Err(e);
+			}
+		}
+
 		Ok(())
 	}
 }
@@ -278,6 +476,8 @@ impl PackedReader {
 			state_root: rlp.val_at(2 + start)?,
 			block_number: rlp.val_at(3 + start)?,
 			block_hash: rlp.val_at(4 + start)?,
+			signature: if rlp.item_count()? > 5 + start && !rlp.at(5 + start)?.is_empty() { Some(rlp.val_at(5 + start)?) } else { None },
+			base: if rlp.item_count()? > 6 + start { Some(rlp.val_at(6 + start)?) } else { None },
 		};
 
 		Ok(Some(PackedReader {
@@ -360,7 +560,7 @@ mod tests {
 	use util::sha3::Hashable;
 
 	use snapshot::ManifestData;
-	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader, SNAPSHOT_VERSION};
+	use super::{SnapshotWriter, SnapshotReader, SnapshotPart, PackedWriter, PackedReader, LooseWriter, LooseReader, SigningWriter, SNAPSHOT_VERSION};
 
 	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world", b"hi", b"notarealchunk"];
 	const BLOCK_CHUNKS: &'static [&'static [u8]] = &[b"hello!", b"goodbye!", b"abcdefg", b"hijklmnop", b"qrstuvwxy", b"and", b"z"];
@@ -392,6 +592,8 @@ mod tests {
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			signature: None,
+			base: None,
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -404,6 +606,40 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn signing_writer_signs_manifest() {
+		use ethkey::{sign, Generator, Random};
+
+		let path = RandomTempPath::new();
+		let keypair = Random.generate().unwrap();
+		let mut writer = SigningWriter::new(PackedWriter::new(path.as_path()).unwrap(), |hash| {
+			Ok(sign(keypair.secret(), &hash).unwrap().into())
+		});
+
+		let mut state_hashes = Vec::new();
+		for chunk in STATE_CHUNKS {
+			let hash = chunk.sha3();
+			state_hashes.push(hash.clone());
+			writer.write_state_chunk(hash, chunk).unwrap();
+		}
+
+		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			state_hashes: state_hashes,
+			block_hashes: Vec::new(),
+			state_root: b"notarealroot".sha3(),
+			block_number: 12345678987654321,
+			block_hash: b"notarealblock".sha3(),
+			signature: None,
+			base: None,
+		};
+
+		writer.finish(manifest).unwrap();
+
+		let reader = PackedReader::new(path.as_path()).unwrap().unwrap();
+		assert_eq!(reader.manifest().signer(), Some(keypair.address()));
+	}
+
 	#[test]
 	fn loose_write_and_read() {
 		let path = RandomTempPath::new();
@@ -431,6 +667,8 @@ mod tests {
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			signature: None,
+			base: None,
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -442,4 +680,45 @@ mod tests {
 			reader.chunk(hash.clone()).unwrap();
 		}
 	}
+
+	#[test]
+	fn loose_writer_resumes_parts() {
+		let path = RandomTempPath::new();
+		let block_hash = b"notarealblock".sha3();
+		let state_hashes: Vec<_> = STATE_CHUNKS.iter().map(|c| c.sha3()).collect();
+		let stale = b"stale".sha3();
+
+		{
+			let mut writer = LooseWriter::new(path.as_path().into()).unwrap();
+			for chunk in STATE_CHUNKS {
+				writer.write_state_chunk(chunk.sha3(), chunk).unwrap();
+			}
+			writer.write_state_chunk(stale.clone(), b"stale").unwrap();
+			writer.complete_part(&block_hash, SnapshotPart::State(3), &state_hashes).unwrap();
+			writer.complete_part(&block_hash, SnapshotPart::Blocks, &[]).unwrap();
+		}
+
+		let writer = LooseWriter::new(path.as_path().into()).unwrap();
+		assert!(writer.completed_parts(&b"otherblock".sha3()).is_empty());
+		let parts = writer.completed_parts(&block_hash);
+		assert_eq!(parts.len(), 2);
+		assert_eq!(parts[&SnapshotPart::State(3)], state_hashes);
+		assert!(parts[&SnapshotPart::Blocks].is_empty());
+
+		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			state_hashes: state_hashes,
+			block_hashes: Vec::new(),
+			state_root: b"notarealroot".sha3(),
+			block_number: 12345678987654321,
+			block_hash: block_hash,
+			signature: None,
+			base: None,
+		};
+		writer.finish(manifest.clone()).unwrap();
+
+		let reader = LooseReader::new(path.as_path().into()).unwrap();
+		assert!(reader.chunk(stale).is_err());
+		assert!(LooseWriter::new(path.as_path().into()).unwrap().completed_parts(&manifest.block_hash).is_empty());
+	}
 }
@@ -19,6 +19,7 @@
 //! Documentation of the format can be found at
 //! https://github.com/paritytech/parity/wiki/Warp-Sync-Snapshot-Format
 
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -34,12 +35,12 @@ use util::Mutex;
 use util::hash::{H256};
 use util::journaldb::{self, Algorithm, JournalDB};
 use util::kvdb::KeyValueDB;
-use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
+use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut, TrieIterator};
 use util::sha3::SHA3_NULL_RLP;
 use rlp::{RlpStream, UntrustedRlp};
 use bloom_journal::Bloom;
 
-use self::io::SnapshotWriter;
+use self::io::{SnapshotWriter, SnapshotPart};
 
 use super::state_db::StateDB;
 use super::state::Account as StateAccount;
@@ -50,11 +51,12 @@ use rand::{Rng, OsRng};
 pub use self::error::Error;
 
 pub use self::consensus::*;
-pub use self::service::{Service, DatabaseRestore};
+pub use self::service::{Service, DatabaseRestore, ManifestSigner};
 pub use self::traits::SnapshotService;
 pub use self::watcher::Watcher;
 pub use types::snapshot_manifest::ManifestData;
 pub use types::restoration_status::RestorationStatus;
+pub use types::creation_status::CreationStatus;
 pub use types::basic_account::BasicAccount;
 
 pub mod io;
@@ -83,6 +85,9 @@ mod traits {
 // Try to have chunks be around 4MB (before compression)
 const PREFERRED_CHUNK_SIZE: usize = 4 * 1024 * 1024;
 
+// Number of parts the account trie is split into by the first nibble of the address hash.
+const STATE_PARTS: u8 = 16;
+
 // Minimum supported state chunk version.
 const MIN_SUPPORTED_STATE_CHUNK_VERSION: u64 = 1;
 // current state chunk version.
@@ -123,6 +128,9 @@ impl Progress {
 
 }
 /// Take a snapshot using the given blockchain, starting block hash, and database, writing into the given writer.
+///
+/// The account trie is split into parts which are chunked in parallel. Parts completed
+/// by an interrupted snapshot of the same block are taken from the writer and not produced again.
 pub fn take_snapshot<W: SnapshotWriter + Send>(
 	engine: &Engine,
 	chain: &BlockChain,
@@ -136,21 +144,75 @@ pub fn take_snapshot<W: SnapshotWriter + Send>(
 	let state_root = start_header.state_root();
 	let number = start_header.number();
 
-	info!("Taking snapshot starting at block {}", number);
+	let completed = writer.completed_parts(&block_at);
+	if completed.is_empty() {
+		info!("Taking snapshot starting at block {}", number);
+	} else {
+		info!("Resuming snapshot starting at block {}, {} parts already written", number, completed.len());
+	}
+
+	let pending_parts: Vec<u8> = (0..STATE_PARTS).rev().filter(|part| !completed.contains_key(&SnapshotPart::State(*part))).collect();
+	let block_part = completed.get(&SnapshotPart::Blocks).cloned();
+	let workers = cmp::max(1, cmp::min(::num_cpus::get() / 2, pending_parts.len()));
 
 	let writer = Mutex::new(writer);
+	let pending_parts = Mutex::new(pending_parts);
+	let state_parts = Mutex::new(completed);
+	let failed = AtomicBool::new(false);
 	let chunker = engine.snapshot_components().ok_or(Error::SnapshotsUnsupported)?;
 	let snapshot_version = chunker.current_version();
-	let (state_hashes, block_hashes) = scope(|scope| {
+	let block_hashes = scope(|scope| {
 		let writer = &writer;
-		let block_guard = scope.spawn(move || chunk_secondary(chunker, chain, block_at, writer, p));
-		let state_res = chunk_state(state_db, state_root, writer, p);
+		let pending_parts = &pending_parts;
+		let state_parts = &state_parts;
+		let failed = &failed;
+
+		let block_guard = scope.spawn(move || -> Result<Vec<H256>, Error> {
+			match block_part {
+				Some(hashes) => Ok(hashes),
+				None => {
+					let hashes = chunk_secondary(chunker, chain, block_at, writer, p)?;
+					writer.lock().complete_part(&block_at, SnapshotPart::Blocks, &hashes)?;
+					Ok(hashes)
+				},
+			}
+		});
+
+		let state_guards: Vec<_> = (0..workers).map(|_| scope.spawn(move || -> Result<(), Error> {
+			loop {
+				if failed.load(Ordering::SeqCst) {
+					return Ok(());
+				}
+
+				let part = match pending_parts.lock().pop() {
+					Some(part) => part,
+					None => return Ok(()),
+				};
+
+				let res = chunk_state_part(state_db, state_root, Some(part), writer, p).and_then(|hashes| {
+					writer.lock().complete_part(&block_at, SnapshotPart::State(part), &hashes)?;
+					state_parts.lock().insert(SnapshotPart::State(part), hashes);
+					Ok(())
+				});
 
-		state_res.and_then(|state_hashes| {
-			block_guard.join().map(|block_hashes| (state_hashes, block_hashes))
-		})
+				if let Err(e) = res {
+					failed.store(true, Ordering::SeqCst);
+					return Err(e);
+				}
+			}
+		})).collect();
+
+		let state_res = state_guards.into_iter().fold(Ok(()), |res, guard| res.and(guard.join()));
+		let block_res = block_guard.join();
+		state_res.and(block_res)
 	})?;
 
+	let mut state_parts = state_parts.into_inner();
+	let mut state_hashes = Vec::new();
+	for part in 0..STATE_PARTS {
+		state_hashes.extend(state_parts.remove(&SnapshotPart::State(part)).expect("all parts are chunked before reaching here; qed"));
+	}
+
 	info!("produced {} state chunks and {} block chunks.", state_hashes.len(), block_hashes.len());
 
 	let manifest_data = ManifestData {
@@ -160,6 +222,64 @@ pub fn take_snapshot<W: SnapshotWriter + Send>(
 		state_root: *state_root,
 		block_number: number,
 		block_hash: block_at,
+		signature: None,
+		base: None,
+	};
+
+	writer.into_inner().finish(manifest_data)?;
+
+	p.done.store(true, Ordering::SeqCst);
+
+	Ok(())
+}
+
+/// Take a delta snapshot of the state at the given block relative to the state of a base snapshot,
+/// writing into the given writer.
+///
+/// Only accounts which changed since the base block are written, so the state at the base block
+/// must still be available in the database. Block chunks are produced as for a full snapshot,
+/// so the blocks since the base can be imported along with the state.
+pub fn take_delta_snapshot<W: SnapshotWriter + Send>(
+	engine: &Engine,
+	chain: &BlockChain,
+	base: &ManifestData,
+	block_at: H256,
+	state_db: &HashDB,
+	writer: W,
+	p: &Progress
+) -> Result<(), Error> {
+	let start_header = chain.block_header(&block_at)
+		.ok_or(Error::InvalidStartingBlock(BlockId::Hash(block_at)))?;
+	let state_root = start_header.state_root();
+	let number = start_header.number();
+
+	if number <= base.block_number {
+		return Err(Error::InvalidStartingBlock(BlockId::Hash(block_at)));
+	}
+
+	if !state_db.contains(&base.state_root) {
+		return Err(Error::OldBlockPrunedDB);
+	}
+
+	info!("Taking delta snapshot starting at block {} against base block {}", number, base.block_number);
+
+	let chunker = engine.snapshot_components().ok_or(Error::SnapshotsUnsupported)?;
+
+	let writer = Mutex::new(writer);
+	let state_hashes = chunk_state_delta(state_db, &base.state_root, state_root, &writer, p)?;
+	let block_hashes = chunk_secondary(chunker, chain, block_at, &writer, p)?;
+
+	info!("produced {} delta state chunks and {} block chunks.", state_hashes.len(), block_hashes.len());
+
+	let manifest_data = ManifestData {
+		version: base.version,
+		state_hashes: state_hashes,
+		block_hashes: block_hashes,
+		state_root: *state_root,
+		block_number: number,
+		block_hash: block_at,
+		signature: None,
+		base: Some(base.block_hash),
 	};
 
 	writer.into_inner().finish(manifest_data)?;
@@ -266,6 +386,15 @@ impl<'a> StateChunker<'a> {
 /// Returns a list of hashes of chunks created, or any error it may
 /// have encountered.
 pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	chunk_state_part(db, root, None, writer, progress)
+}
+
+/// Walk the accounts whose address hash starts with nibble `part`, or the whole state
+/// if no part is given, creating chunks and writing them out.
+///
+/// Returns a list of hashes of chunks created, or any error it may
+/// have encountered.
+pub fn chunk_state_part<'a>(db: &HashDB, root: &H256, part: Option<u8>, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
 	let account_trie = TrieDB::new(db, &root)?;
 
 	let mut chunker = StateChunker {
@@ -279,9 +408,20 @@ pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter +
 
 	let mut used_code = HashSet::new();
 
+	let mut accounts = account_trie.iter()?;
+	if let Some(part) = part {
+		accounts.seek(&[part << 4])?;
+	}
+
 	// account_key here is the address' hash.
-	for item in account_trie.iter()? {
+	for item in accounts {
 		let (account_key, account_data) = item?;
+		if let Some(part) = part {
+			if account_key[0] >> 4 != part {
+				break;
+			}
+		}
+
 		let account = ::rlp::decode(&*account_data);
 		let account_key_hash = H256::from_slice(&account_key);
 
@@ -303,6 +443,49 @@ pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter +
 	Ok(chunker.hashes)
 }
 
+/// Walk the accounts which differ between the state at `base_root` and the state at `root`,
+/// creating delta chunks and writing them out.
+///
+/// Returns a list of hashes of chunks created, or any error it may
+/// have encountered.
+pub fn chunk_state_delta<'a>(db: &HashDB, base_root: &H256, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	let base_trie = TrieDB::new(db, base_root)?;
+	let account_trie = TrieDB::new(db, root)?;
+
+	let mut chunker = StateChunker {
+		hashes: Vec::new(),
+		rlps: Vec::new(),
+		cur_size: 0,
+		snappy_buffer: vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)],
+		writer: writer,
+		progress: progress,
+	};
+
+	account::diff_tries(&base_trie, &account_trie, |account_key, base_data, account_data| {
+		let base: Option<BasicAccount> = base_data.map(::rlp::decode);
+		let account: Option<BasicAccount> = account_data.map(::rlp::decode);
+		let account_key_hash = H256::from_slice(account_key);
+
+		let account_db = AccountDB::from_hash(db, account_key_hash);
+
+		let delta_rlps = account::to_delta_rlps(&account_key_hash, base.as_ref(), account.as_ref(), &account_db, PREFERRED_CHUNK_SIZE)?;
+		for delta_rlp in delta_rlps {
+			if chunker.chunk_size() != 0 && chunker.chunk_size() + delta_rlp.len() > PREFERRED_CHUNK_SIZE {
+				chunker.write_chunk()?;
+			}
+			chunker.push(delta_rlp)?;
+		}
+
+		Ok(())
+	})?;
+
+	if chunker.cur_size != 0 {
+		chunker.write_chunk()?;
+	}
+
+	Ok(chunker.hashes)
+}
+
 /// Used to rebuild the state trie piece by piece.
 pub struct StateRebuilder {
 	db: Box<JournalDB>,
@@ -326,6 +509,15 @@ impl StateRebuilder {
 		}
 	}
 
+	/// Create a state rebuilder which applies delta chunks on top of the already restored
+	/// state with the given root in the backing DB.
+	pub fn from_existing(db: Arc<KeyValueDB>, pruning: Algorithm, base_root: H256) -> Self {
+		StateRebuilder {
+			state_root: base_root,
+			..StateRebuilder::new(db, pruning)
+		}
+	}
+
 	/// Feed an uncompressed state chunk into the rebuilder.
 	pub fn feed(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
@@ -387,6 +579,76 @@ impl StateRebuilder {
 		Ok(())
 	}
 
+	/// Feed an uncompressed delta state chunk into the rebuilder, applying the changed
+	/// accounts on top of the current state.
+	pub fn feed_delta(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
+		let rlp = UntrustedRlp::new(chunk);
+		let empty_rlp = StateAccount::new_basic(U256::zero(), U256::zero()).rlp();
+
+		// load the current state of every account touched by this chunk.
+		let mut accounts = HashMap::new();
+		{
+			let account_trie = TrieDB::new(self.db.as_hashdb(), &self.state_root)?;
+			for delta_rlp in rlp.iter() {
+				let hash: H256 = delta_rlp.val_at(0)?;
+				if !accounts.contains_key(&hash) {
+					let account = account_trie.get(&hash)?.map(|thin_rlp| ::rlp::decode::<BasicAccount>(&thin_rlp));
+					accounts.insert(hash, account);
+				}
+			}
+		}
+
+		// records are applied in order, since large accounts may be split over several.
+		for delta_rlp in rlp.iter() {
+			if !flag.load(Ordering::SeqCst) { return Err(Error::RestorationAborted.into()) }
+
+			let hash: H256 = delta_rlp.val_at(0)?;
+			let base = accounts.get(&hash).cloned().unwrap_or(None);
+			let account = {
+				let mut acct_db = AccountDBMut::from_hash(self.db.as_hashdb_mut(), hash);
+				account::from_delta_rlp(&mut acct_db, delta_rlp.at(1)?, base)?
+			};
+
+			accounts.insert(hash, account);
+		}
+
+		let backing = self.db.backing().clone();
+
+		// batch trie writes
+		{
+			let mut account_trie = if self.state_root != SHA3_NULL_RLP {
+				TrieDBMut::from_existing(self.db.as_hashdb_mut(), &mut self.state_root)?
+			} else {
+				TrieDBMut::new(self.db.as_hashdb_mut(), &mut self.state_root)
+			};
+
+			for (hash, account) in accounts {
+				if !flag.load(Ordering::SeqCst) { return Err(Error::RestorationAborted.into()) }
+
+				match account {
+					Some(account) => {
+						let thin_rlp = ::rlp::encode(&account);
+						if &thin_rlp[..] != &empty_rlp[..] {
+							self.bloom.set(&*hash);
+						}
+						account_trie.insert(&hash, &thin_rlp)?;
+					}
+					None => {
+						account_trie.remove(&hash)?;
+					}
+				}
+			}
+		}
+
+		let bloom_journal = self.bloom.drain_journal();
+		let mut batch = backing.transaction();
+		StateDB::commit_bloom(&mut batch, bloom_journal)?;
+		self.db.inject(&mut batch)?;
+		backing.write_buffered(batch);
+		trace!(target: "snapshot", "current state root: {:?}", self.state_root);
+		Ok(())
+	}
+
 	/// Finalize the restoration. Check for accounts missing code and make a dummy
 	/// journal entry.
 	/// Once all chunks have been fed, there should be nothing missing.
@@ -23,9 +23,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use super::{ManifestData, StateRebuilder, Rebuilder, RestorationStatus, SnapshotService};
-use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
+use super::{ManifestData, StateRebuilder, Rebuilder, RestorationStatus, CreationStatus, SnapshotService};
+use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter, SigningWriter};
 
+use account_provider::AccountProvider;
 use blockchain::BlockChain;
 use client::{BlockChainClient, Client};
 use engines::Engine;
@@ -35,9 +36,9 @@ use service::ClientIoMessage;
 
 use io::IoChannel;
 
-use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
-use util::journaldb::Algorithm;
-use util::kvdb::{Database, DatabaseConfig};
+use util::{Bytes, Address, H256, H520, Mutex, RwLock, RwLockReadGuard, UtilError};
+use util::journaldb::{self, Algorithm};
+use util::kvdb::{self, KeyValueDB, DatabaseConfig};
 use util::snappy;
 
 /// Helper for removing directories in case of error.
@@ -61,6 +62,12 @@ impl Drop for Guard {
 pub trait DatabaseRestore: Send + Sync {
 	/// Restart with a new backend. Takes ownership of passed database and moves it to a new location.
 	fn restore_db(&self, new_db: &str) -> Result<(), Error>;
+
+	/// Get the database currently in use, to look up the base state of delta snapshots in.
+	fn current_db(&self) -> Arc<KeyValueDB>;
+
+	/// Copy the database currently in use into the given one, for a delta snapshot to be applied to.
+	fn copy_db(&self, to: &KeyValueDB) -> Result<(), Error>;
 }
 
 /// State restoration manager.
@@ -74,7 +81,8 @@ struct Restoration {
 	snappy_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
-	db: Arc<Database>,
+	db: Arc<KeyValueDB>,
+	delta: bool,
 }
 
 struct RestorationParams<'a> {
@@ -86,6 +94,7 @@ struct RestorationParams<'a> {
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
 	engine: &'a Engine,
+	existing: Option<(&'a DatabaseRestore, H256)>, // database to copy and state root to apply a delta snapshot to.
 }
 
 impl Restoration {
@@ -96,8 +105,17 @@ impl Restoration {
 		let state_chunks = manifest.state_hashes.iter().cloned().collect();
 		let block_chunks = manifest.block_hashes.iter().cloned().collect();
 
-		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
-			.map_err(UtilError::SimpleString)?);
+		let raw_db = kvdb::open_database(params.db_config, &*params.db_path.to_string_lossy())
+			.map_err(UtilError::SimpleString)?;
+
+		// a delta is applied to a copy of the client's database, which replaces it once it's done.
+		let base_root = match params.existing {
+			Some((db_restore, root)) => {
+				db_restore.copy_db(&*raw_db)?;
+				Some(root)
+			},
+			None => None,
+		};
 
 		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
 		let components = params.engine.snapshot_components()
@@ -106,18 +124,23 @@ impl Restoration {
 		let secondary = components.rebuilder(chain, raw_db.clone(), &manifest)?;
 
 		let root = manifest.state_root.clone();
+		let state = match base_root {
+			Some(base_root) => StateRebuilder::from_existing(raw_db.clone(), params.pruning, base_root),
+			None => StateRebuilder::new(raw_db.clone(), params.pruning),
+		};
 
 		Ok(Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
-			state: StateRebuilder::new(raw_db.clone(), params.pruning),
+			state: state,
 			secondary: secondary,
 			writer: params.writer,
 			snappy_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
 			db: raw_db,
+			delta: base_root.is_some(),
 		})
 	}
 
@@ -126,7 +149,10 @@ impl Restoration {
 		if self.state_chunks_left.remove(&hash) {
 			let len = snappy::decompress_into(chunk, &mut self.snappy_buffer)?;
 
-			self.state.feed(&self.snappy_buffer[..len], flag)?;
+			match self.delta {
+				true => self.state.feed_delta(&self.snappy_buffer[..len], flag)?,
+				false => self.state.feed(&self.snappy_buffer[..len], flag)?,
+			}
 
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				writer.write_state_chunk(hash, chunk)?;
@@ -203,6 +229,26 @@ pub struct ServiceParams {
 	pub snapshot_root: PathBuf,
 	/// A handle for database restoration.
 	pub db_restore: Arc<DatabaseRestore>,
+	/// The account to sign the manifests of snapshots taken with.
+	pub signer: Option<ManifestSigner>,
+}
+
+/// An unlockable account the manifests of snapshots are signed with.
+pub struct ManifestSigner {
+	/// The account provider holding the account.
+	pub accounts: Arc<AccountProvider>,
+	/// The signing account.
+	pub address: Address,
+	/// The password of the signing account.
+	pub password: String,
+}
+
+impl ManifestSigner {
+	fn sign(&self, hash: H256) -> ::std::io::Result<H520> {
+		self.accounts.sign(self.address, Some(self.password.clone()), hash)
+			.map(Into::into)
+			.map_err(|e| ::std::io::Error::new(ErrorKind::Other, format!("Failed to sign snapshot manifest: {}", e)))
+	}
 }
 
 /// `SnapshotService` implementation.
@@ -222,7 +268,9 @@ pub struct Service {
 	db_restore: Arc<DatabaseRestore>,
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
+	taking_snapshot_at: AtomicUsize,
 	restoring_snapshot: AtomicBool,
+	signer: Option<ManifestSigner>,
 }
 
 impl Service {
@@ -243,7 +291,9 @@ impl Service {
 			db_restore: params.db_restore,
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
+			taking_snapshot_at: AtomicUsize::new(0),
 			restoring_snapshot: AtomicBool::new(false),
+			signer: params.signer,
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -260,12 +310,7 @@ impl Service {
 			}
 		}
 
-		// delete the temporary snapshot dir if it does exist.
-		if let Err(e) = fs::remove_dir_all(service.temp_snapshot_dir()) {
-			if e.kind() != ErrorKind::NotFound {
-				return Err(e.into())
-			}
-		}
+		// the temporary snapshot dir is kept, so an interrupted snapshot can be resumed.
 
 		let reader = LooseReader::new(service.snapshot_dir()).ok();
 		*service.reader.get_mut() = reader;
@@ -316,6 +361,24 @@ impl Service {
 		Ok(())
 	}
 
+	// get the state root at the given block in the client's database, if that state
+	// is available for a delta snapshot to be applied on top of.
+	fn base_state(&self, base: &H256) -> Option<H256> {
+		let db = self.db_restore.current_db();
+		let root = {
+			let chain = BlockChain::new(Default::default(), &self.genesis_block, db.clone());
+			match chain.block_header(base) {
+				Some(header) => *header.state_root(),
+				None => return None,
+			}
+		};
+
+		match journaldb::new(db, self.pruning, ::db::COL_STATE).as_hashdb().contains(&root) {
+			true => Some(root),
+			false => None,
+		}
+	}
+
 	/// Get a reference to the snapshot reader.
 	pub fn reader(&self) -> RwLockReadGuard<Option<LooseReader>> {
 		self.reader.read()
@@ -330,6 +393,15 @@ impl Service {
 		info!("Snapshot: {} accounts {} blocks {} bytes", p.accounts(), p.blocks(), p.size());
 	}
 
+	/// Get the hash of the block an interrupted snapshot was being taken at, if any.
+	/// Taking a snapshot at that block again resumes it.
+	pub fn interrupted_snapshot(&self) -> Option<H256> {
+		let temp_dir = self.temp_snapshot_dir();
+		if !temp_dir.exists() { return None }
+
+		LooseWriter::new(temp_dir).ok().and_then(|writer| writer.resumable_block())
+	}
+
 	/// Take a snapshot at the block with the given number.
 	/// calling this while a restoration is in progress or vice versa
 	/// will lead to a race condition where the first one to finish will
@@ -342,16 +414,23 @@ impl Service {
 
 		info!("Taking snapshot at #{}", num);
 		self.progress.reset();
+		self.taking_snapshot_at.store(num as usize, Ordering::SeqCst);
 
 		let temp_dir = self.temp_snapshot_dir();
 		let snapshot_dir = self.snapshot_dir();
 
-		let _ = fs::remove_dir_all(&temp_dir);
-
+		// chunks already in the temporary dir are reused if they belong to this snapshot,
+		// and removed when it's finished otherwise.
 		let writer = LooseWriter::new(temp_dir.clone())?;
 
 		let guard = Guard::new(temp_dir.clone());
-		let res = client.take_snapshot(writer, BlockId::Number(num), &self.progress);
+		let res = match self.signer {
+			Some(ref signer) => {
+				let writer = SigningWriter::new(writer, |hash| signer.sign(hash));
+				client.take_snapshot(writer, BlockId::Number(num), &self.progress)
+			},
+			None => client.take_snapshot(writer, BlockId::Number(num), &self.progress),
+		};
 
 		self.taking_snapshot.store(false, Ordering::SeqCst);
 		if let Err(e) = res {
@@ -389,6 +468,16 @@ impl Service {
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
+		// delta snapshots only hold the changes since their base and can't be restored from scratch.
+		// they're applied on top of the state at the base block in a copy of the client's database instead.
+		let base_root = match manifest.base {
+			Some(base) => match self.base_state(&base) {
+				Some(root) => Some(root),
+				None => return Err(::snapshot::Error::DeltaSnapshot(base).into()),
+			},
+			None => None,
+		};
+
 		let rest_dir = self.restoration_dir();
 
 		let mut res = self.restoration.lock();
@@ -410,7 +499,8 @@ impl Service {
 		fs::create_dir_all(&rest_dir)?;
 
 		// make new restoration.
-		let writer = match recover {
+		// a delta can't be served to peers on its own, so it's never recovered.
+		let writer = match recover && base_root.is_none() {
 			true => Some(LooseWriter::new(self.temp_recovery_dir())?),
 			false => None
 		};
@@ -424,6 +514,7 @@ impl Service {
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
 			engine: &*self.engine,
+			existing: base_root.map(|root| (&*self.db_restore, root)),
 		};
 
 		let state_chunks = params.manifest.state_hashes.len();
@@ -567,6 +658,10 @@ impl SnapshotService for Service {
 		self.reader.read().as_ref().and_then(|r| r.chunk(hash).ok())
 	}
 
+	fn can_apply_delta(&self, base: H256) -> bool {
+		self.base_state(&base).is_some()
+	}
+
 	fn status(&self) -> RestorationStatus {
 		let mut cur_status = self.status.lock();
 		if let RestorationStatus::Ongoing { ref mut state_chunks_done, ref mut block_chunks_done, .. } = *cur_status {
@@ -577,6 +672,26 @@ impl SnapshotService for Service {
 		cur_status.clone()
 	}
 
+	fn creation_status(&self) -> CreationStatus {
+		if !self.taking_snapshot.load(Ordering::SeqCst) {
+			return CreationStatus::Inactive;
+		}
+
+		let p = &self.progress;
+		CreationStatus::Ongoing {
+			block_number: self.taking_snapshot_at.load(Ordering::SeqCst) as u64,
+			accounts: p.accounts() as u64,
+			blocks: p.blocks() as u64,
+			size: p.size() as u64,
+		}
+	}
+
+	fn request_snapshot(&self, num: u64) {
+		if let Err(e) = self.io_channel.lock().send(ClientIoMessage::TakeSnapshot(num)) {
+			trace!("Error sending snapshot service message: {:?}", e);
+		}
+	}
+
 	fn begin_restore(&self, manifest: ManifestData) {
 		if let Err(e) = self.io_channel.lock().send(ClientIoMessage::BeginRestoration(manifest)) {
 			trace!("Error sending snapshot service message: {:?}", e);
@@ -625,6 +740,14 @@ mod tests {
 		fn restore_db(&self, _new_db: &str) -> Result<(), Error> {
 			Ok(())
 		}
+
+		fn current_db(&self) -> Arc<::util::kvdb::KeyValueDB> {
+			Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)))
+		}
+
+		fn copy_db(&self, _to: &::util::kvdb::KeyValueDB) -> Result<(), Error> {
+			Ok(())
+		}
 	}
 
 	#[test]
@@ -646,6 +769,7 @@ mod tests {
 			channel: service.channel(),
 			snapshot_root: dir,
 			db_restore: Arc::new(NoopDBRestore),
+			signer: None,
 		};
 
 		let service = Service::new(snapshot_params).unwrap();
@@ -661,6 +785,8 @@ mod tests {
 			state_root: Default::default(),
 			block_number: 0,
 			block_hash: Default::default(),
+			signature: None,
+			base: None,
 		};
 
 		service.begin_restore(manifest);
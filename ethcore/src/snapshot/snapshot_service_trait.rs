@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{ManifestData, RestorationStatus};
+use super::{ManifestData, RestorationStatus, CreationStatus};
 use util::{Bytes, H256};
 use ipc::IpcConfig;
 
@@ -34,9 +34,20 @@ pub trait SnapshotService : Sync + Send {
 	/// Get raw chunk for a given hash.
 	fn chunk(&self, hash: H256) -> Option<Bytes>;
 
+	/// Whether a delta snapshot against the given base block can be applied,
+	/// i.e. whether the state at that block is available locally.
+	fn can_apply_delta(&self, base: H256) -> bool;
+
 	/// Ask the snapshot service for the restoration status.
 	fn status(&self) -> RestorationStatus;
 
+	/// Ask the snapshot service for the status of snapshot creation.
+	fn creation_status(&self) -> CreationStatus;
+
+	/// Request a snapshot at the given block to be taken in the background.
+	/// If there is an interrupted snapshot at this block, it will be resumed.
+	fn request_snapshot(&self, num: u64);
+
 	/// Begin snapshot restoration.
 	/// If restoration in-progress, this will reset it.
 	/// From this point on, any previous snapshot may become unavailable.
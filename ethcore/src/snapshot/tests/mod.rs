@@ -34,7 +34,69 @@ fn manifest_rlp() {
 		block_number: 1234567,
 		state_root: Default::default(),
 		block_hash: Default::default(),
+		signature: None,
+		base: None,
 	};
 	let raw = manifest.clone().into_rlp();
 	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
 }
+
+#[test]
+fn signed_manifest_rlp() {
+	use ethkey::{Generator, Random};
+	use util::H256;
+
+	let keypair = Random.generate().unwrap();
+	let mut manifest = ManifestData {
+		version: 2,
+		block_hashes: vec![H256::from(1u64)],
+		state_hashes: vec![H256::from(2u64), H256::from(3u64)],
+		block_number: 1234567,
+		state_root: H256::from(4u64),
+		block_hash: H256::from(5u64),
+		signature: None,
+		base: None,
+	};
+	assert_eq!(manifest.signer(), None);
+
+	manifest.sign(keypair.secret()).unwrap();
+	assert_eq!(manifest.signer(), Some(keypair.address()));
+
+	let raw = manifest.clone().into_rlp();
+	let decoded = ManifestData::from_rlp(&raw).unwrap();
+	assert_eq!(decoded, manifest);
+	assert_eq!(decoded.signer(), Some(keypair.address()));
+
+	// any change to the covered data invalidates the signature.
+	manifest.state_hashes.pop();
+	assert!(manifest.signer() != Some(keypair.address()));
+}
+
+#[test]
+fn delta_manifest_rlp() {
+	use ethkey::{Generator, Random};
+	use util::H256;
+
+	let mut manifest = ManifestData {
+		version: 2,
+		block_hashes: Vec::new(),
+		state_hashes: vec![H256::from(2u64)],
+		block_number: 1234567,
+		state_root: H256::from(4u64),
+		block_hash: H256::from(5u64),
+		signature: None,
+		base: Some(H256::from(6u64)),
+	};
+	assert!(manifest.is_delta());
+
+	let raw = manifest.clone().into_rlp();
+	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
+
+	let keypair = Random.generate().unwrap();
+	manifest.sign(keypair.secret()).unwrap();
+
+	let raw = manifest.clone().into_rlp();
+	let decoded = ManifestData::from_rlp(&raw).unwrap();
+	assert_eq!(decoded, manifest);
+	assert_eq!(decoded.signer(), Some(keypair.address()));
+}
@@ -74,6 +74,8 @@ fn chunk_and_restore(amount: u64) {
 		state_root: ::util::sha3::SHA3_NULL_RLP,
 		block_number: amount,
 		block_hash: best_hash,
+		signature: None,
+		base: None,
 	};
 
 	writer.into_inner().finish(manifest.clone()).unwrap();
@@ -137,6 +139,8 @@ fn checks_flag() {
 		state_root: ::util::sha3::SHA3_NULL_RLP,
 		block_number: 102,
 		block_hash: H256::default(),
+		signature: None,
+		base: None,
 	};
 
 	let mut rebuilder = SNAPSHOT_MODE.rebuilder(chain, db.clone(), &manifest).unwrap();
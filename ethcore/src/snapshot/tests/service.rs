@@ -18,16 +18,20 @@
 
 use std::sync::Arc;
 
-use client::{BlockChainClient, Client};
+use account_provider::AccountProvider;
+use client::{BlockChainClient, Client, ClientConfig};
 use ids::BlockId;
-use snapshot::service::{Service, ServiceParams};
+use snapshot::io::{LooseReader, LooseWriter, SnapshotReader};
+use snapshot::service::{Service, ServiceParams, ManifestSigner};
 use snapshot::{self, ManifestData, SnapshotService};
 use spec::Spec;
-use tests::helpers::generate_dummy_client_with_spec_and_data;
+use tests::helpers::{generate_dummy_client_with_spec_and_data, generate_dummy_client_with_config_and_data};
 
 use devtools::RandomTempPath;
 use io::IoChannel;
-use util::kvdb::{Database, DatabaseConfig};
+use util::H256;
+use util::journaldb::Algorithm;
+use util::kvdb::{Database, DatabaseConfig, KeyValueDB};
 
 struct NoopDBRestore;
 
@@ -35,6 +39,14 @@ impl snapshot::DatabaseRestore for NoopDBRestore {
 	fn restore_db(&self, _new_db: &str) -> Result<(), ::error::Error> {
 		Ok(())
 	}
+
+	fn current_db(&self) -> Arc<KeyValueDB> {
+		Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)))
+	}
+
+	fn copy_db(&self, _to: &KeyValueDB) -> Result<(), ::error::Error> {
+		Ok(())
+	}
 }
 
 #[test]
@@ -73,6 +85,7 @@ fn restored_is_equivalent() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path,
 		db_restore: client2.clone(),
+		signer: None,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -103,6 +116,187 @@ fn restored_is_equivalent() {
 	}
 }
 
+#[test]
+fn signs_periodic_snapshots() {
+	const NUM_BLOCKS: u32 = 40;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 0, &[]);
+
+	let accounts = Arc::new(AccountProvider::transient_provider());
+	let address = accounts.new_account("password").unwrap();
+
+	let spec = Spec::new_null();
+	let path = RandomTempPath::create_dir();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		signer: Some(ManifestSigner {
+			accounts: accounts,
+			address: address,
+			password: "password".into(),
+		}),
+	};
+
+	let service = Service::new(service_params).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	assert_eq!(service.manifest().unwrap().signer(), Some(address));
+}
+
+#[test]
+fn restores_delta_on_top_of_base() {
+	const NUM_BLOCKS: u32 = 400;
+	const BASE_BLOCK: u64 = 350;
+	const TX_PER: usize = 5;
+
+	let gas_prices = vec![1.into(), 2.into(), 3.into(), 999.into()];
+
+	// keep every state around so the base block can be snapshotted and diffed against.
+	let mut config = ClientConfig::default();
+	config.pruning = Algorithm::Archive;
+	let client = generate_dummy_client_with_config_and_data(config, Spec::new_null, NUM_BLOCKS, TX_PER, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+	let mut delta_dir = path.clone();
+
+	client_db.push("client_db");
+	delta_dir.push("delta");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, client_db.to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let mut config = ClientConfig::default();
+	config.pruning = Algorithm::Archive;
+	let client2 = Client::new(
+		config,
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config,
+		pruning: Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path,
+		db_restore: client2.clone(),
+		signer: None,
+	};
+
+	let service = Service::new(service_params).unwrap();
+
+	// restore the base snapshot first.
+	service.take_snapshot(&client, BASE_BLOCK).unwrap();
+	let base = service.manifest().unwrap();
+
+	service.init_restore(base.clone(), false).unwrap();
+
+	for hash in base.state_hashes.iter().cloned() {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_state_chunk(hash, &chunk);
+	}
+
+	for hash in base.block_hashes.iter().cloned() {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert_eq!(client2.chain_info().best_block_number, BASE_BLOCK);
+
+	// then apply a delta snapshot of the head of the chain on top of it.
+	let writer = LooseWriter::new(delta_dir.clone()).unwrap();
+	client.take_delta_snapshot(writer, &base, BlockId::Number(NUM_BLOCKS as u64), &Default::default()).unwrap();
+
+	let reader = LooseReader::new(delta_dir).unwrap();
+	let delta = reader.manifest().clone();
+	assert_eq!(delta.base, Some(base.block_hash));
+	assert!(service.can_apply_delta(base.block_hash));
+
+	// an aborted delta restoration leaves the client's database untouched.
+	service.init_restore(delta.clone(), false).unwrap();
+
+	for hash in delta.block_hashes.iter().cloned() {
+		let chunk = reader.chunk(hash).unwrap();
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	service.abort_restore();
+	assert_eq!(client2.chain_info().best_block_number, BASE_BLOCK);
+	assert!(client2.block(BlockId::Number(BASE_BLOCK + 1)).is_none());
+	assert!(client2.state_at(BlockId::Latest).is_some());
+
+	service.init_restore(delta.clone(), false).unwrap();
+
+	for hash in delta.state_hashes {
+		let chunk = reader.chunk(hash).unwrap();
+		service.feed_state_chunk(hash, &chunk);
+	}
+
+	for hash in delta.block_hashes {
+		let chunk = reader.chunk(hash).unwrap();
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert_eq!(client2.chain_info().best_block_number, NUM_BLOCKS as u64);
+	assert_eq!(client2.chain_info().best_block_hash, client.chain_info().best_block_hash);
+
+	for x in 0..(NUM_BLOCKS + 1) {
+		let block1 = client.block(BlockId::Number(x as u64)).unwrap();
+		let block2 = client2.block(BlockId::Number(x as u64)).unwrap();
+
+		assert_eq!(block1, block2);
+	}
+
+	assert!(client2.state_at(BlockId::Latest).is_some());
+}
+
+#[test]
+fn rejects_delta_without_base() {
+	let spec = Spec::new_null();
+	let path = RandomTempPath::create_dir();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		signer: None,
+	};
+
+	let service = Service::new(service_params).unwrap();
+
+	let manifest = ManifestData {
+		version: 2,
+		state_hashes: vec![],
+		block_hashes: vec![],
+		block_number: 10,
+		block_hash: H256::from(10u64),
+		signature: None,
+		base: Some(H256::from(5u64)),
+		state_root: Default::default(),
+	};
+
+	assert!(!service.can_apply_delta(H256::from(5u64)));
+	assert!(service.init_restore(manifest, false).is_err());
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+}
+
 #[test]
 fn guards_delete_folders() {
 	let spec = Spec::new_null();
@@ -116,6 +310,7 @@ fn guards_delete_folders() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path.clone(),
 		db_restore: Arc::new(NoopDBRestore),
+		signer: None,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -127,6 +322,8 @@ fn guards_delete_folders() {
 		block_hashes: vec![],
 		block_number: 0,
 		block_hash: Default::default(),
+		signature: None,
+		base: None,
 		state_root: Default::default(),
 	};
 
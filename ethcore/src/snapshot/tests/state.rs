@@ -18,7 +18,7 @@
 
 use basic_account::BasicAccount;
 use snapshot::account;
-use snapshot::{chunk_state, Error as SnapshotError, Progress, StateRebuilder};
+use snapshot::{chunk_state, chunk_state_part, chunk_state_delta, Error as SnapshotError, Progress, StateRebuilder};
 use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
 use super::helpers::{compare_dbs, StateProducer};
 
@@ -64,6 +64,8 @@ fn snap_and_restore() {
 		state_root: state_root,
 		block_number: 1000,
 		block_hash: H256::default(),
+		signature: None,
+		base: None,
 	}).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
@@ -94,6 +96,156 @@ fn snap_and_restore() {
 	compare_dbs(&old_db, new_db.as_hashdb());
 }
 
+#[test]
+fn parts_cover_whole_state() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut snap_file = snap_dir.as_path().to_owned();
+	snap_file.push("SNAP");
+
+	let state_root = producer.state_root();
+	let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+
+	let mut state_hashes = Vec::new();
+	for part in 0..16 {
+		state_hashes.extend(chunk_state_part(&old_db, &state_root, Some(part), &writer, &Progress::default()).unwrap());
+	}
+
+	writer.into_inner().finish(::snapshot::ManifestData {
+		version: 2,
+		state_hashes: state_hashes,
+		block_hashes: Vec::new(),
+		state_root: state_root,
+		block_number: 1000,
+		block_hash: H256::default(),
+		signature: None,
+		base: None,
+	}).unwrap();
+
+	let mut db_path = snap_dir.as_path().to_owned();
+	db_path.push("db");
+	let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+	let mut rebuilder = StateRebuilder::new(new_db.clone(), Algorithm::OverlayRecent);
+	let reader = PackedReader::new(&snap_file).unwrap().unwrap();
+
+	let flag = AtomicBool::new(true);
+
+	for chunk_hash in &reader.manifest().state_hashes {
+		let raw = reader.chunk(*chunk_hash).unwrap();
+		let chunk = ::util::snappy::decompress(&raw).unwrap();
+
+		rebuilder.feed(&chunk, &flag).unwrap();
+	}
+
+	assert_eq!(rebuilder.state_root(), state_root);
+}
+
+#[test]
+fn delta_snap_and_restore() {
+	use util::HashDB;
+
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([9, 10, 11, 12]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let base_root = producer.state_root();
+	let base_db = old_db.clone();
+
+	for _ in 0..10 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let state_root = producer.state_root();
+
+	// the producer drops nodes of the base state, so diff in a database holding both.
+	let mut both_db = base_db.clone();
+	for key in old_db.keys().keys() {
+		if let Some(value) = old_db.get(key) {
+			both_db.emplace(*key, value);
+		}
+	}
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut base_file = snap_dir.as_path().to_owned();
+	base_file.push("BASE");
+	let mut delta_file = snap_dir.as_path().to_owned();
+	delta_file.push("DELTA");
+
+	let writer = Mutex::new(PackedWriter::new(&base_file).unwrap());
+	let state_hashes = chunk_state(&base_db, &base_root, &writer, &Progress::default()).unwrap();
+
+	writer.into_inner().finish(::snapshot::ManifestData {
+		version: 2,
+		state_hashes: state_hashes,
+		block_hashes: Vec::new(),
+		state_root: base_root,
+		block_number: 1000,
+		block_hash: H256::default(),
+		signature: None,
+		base: None,
+	}).unwrap();
+
+	let writer = Mutex::new(PackedWriter::new(&delta_file).unwrap());
+	let state_hashes = chunk_state_delta(&both_db, &base_root, &state_root, &writer, &Progress::default()).unwrap();
+
+	writer.into_inner().finish(::snapshot::ManifestData {
+		version: 2,
+		state_hashes: state_hashes,
+		block_hashes: Vec::new(),
+		state_root: state_root,
+		block_number: 1010,
+		block_hash: H256::from(1010u64),
+		signature: None,
+		base: Some(H256::default()),
+	}).unwrap();
+
+	let mut db_path = snap_dir.as_path().to_owned();
+	db_path.push("db");
+	let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+	let flag = AtomicBool::new(true);
+
+	let mut rebuilder = StateRebuilder::new(new_db.clone(), Algorithm::OverlayRecent);
+	let reader = PackedReader::new(&base_file).unwrap().unwrap();
+	for chunk_hash in &reader.manifest().state_hashes {
+		let raw = reader.chunk(*chunk_hash).unwrap();
+		let chunk = ::util::snappy::decompress(&raw).unwrap();
+
+		rebuilder.feed(&chunk, &flag).unwrap();
+	}
+
+	assert_eq!(rebuilder.state_root(), base_root);
+	rebuilder.finalize(1000, H256::default()).unwrap();
+
+	let mut rebuilder = StateRebuilder::from_existing(new_db.clone(), Algorithm::OverlayRecent, base_root);
+	let reader = PackedReader::new(&delta_file).unwrap().unwrap();
+	assert_eq!(reader.manifest().base, Some(H256::default()));
+	for chunk_hash in &reader.manifest().state_hashes {
+		let raw = reader.chunk(*chunk_hash).unwrap();
+		let chunk = ::util::snappy::decompress(&raw).unwrap();
+
+		rebuilder.feed_delta(&chunk, &flag).unwrap();
+	}
+
+	assert_eq!(rebuilder.state_root(), state_root);
+	rebuilder.finalize(1010, H256::from(1010u64)).unwrap();
+
+	let new_db = journaldb::new(new_db, Algorithm::OverlayRecent, ::db::COL_STATE);
+	compare_dbs(&old_db, new_db.as_hashdb());
+}
+
 #[test]
 fn get_code_from_prev_chunk() {
 	use std::collections::HashSet;
@@ -176,6 +328,8 @@ fn checks_flag() {
 		state_root: state_root,
 		block_number: 0,
 		block_hash: H256::default(),
+		signature: None,
+		base: None,
 	}).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
@@ -22,7 +22,7 @@ use super::seal::Generic as GenericSeal;
 
 use action_params::{ActionValue, ActionParams};
 use builtin::Builtin;
-use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, DEFAULT_BLOCKHASH_CONTRACT};
+use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, Clique, DEFAULT_BLOCKHASH_CONTRACT};
 use env_info::EnvInfo;
 use error::Error;
 use ethereum;
@@ -39,6 +39,9 @@ use trace::{NoopTracer, NoopVMTracer};
 use types::executed::CallType;
 use util::*;
 
+/// Transitions at or above this block number are never expected to activate.
+const DISABLED_TRANSITION: BlockNumber = 0x7fffffffffffff;
+
 /// Parameters common to all engines.
 #[derive(Debug, PartialEq, Default)]
 #[cfg_attr(test, derive(Clone))]
@@ -77,6 +80,14 @@ pub struct CommonParams {
 	pub eip211_transition: BlockNumber,
 	/// Number of first block where EIP-214 rules begin.
 	pub eip214_transition: BlockNumber,
+	/// Number of first block where EIP-145 (bitwise shifting instructions) rules begin.
+	pub eip145_transition: BlockNumber,
+	/// Number of first block where EIP-1014 (CREATE2 instruction) rules begin.
+	pub eip1014_transition: BlockNumber,
+	/// Number of first block where EIP-1052 (EXTCODEHASH instruction) rules begin.
+	pub eip1052_transition: BlockNumber,
+	/// Number of first block where EIP-1283 (net gas metering for SSTORE) rules begin.
+	pub eip1283_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -107,6 +118,10 @@ impl From<ethjson::spec::Params> for CommonParams {
 			eip210_contract_gas: p.eip210_contract_gas.map_or(1000000.into(), Into::into),
 			eip211_transition: p.eip211_transition.map_or(BlockNumber::max_value(), Into::into),
 			eip214_transition: p.eip214_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip145_transition: p.eip145_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip1014_transition: p.eip1014_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip1052_transition: p.eip1052_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip1283_transition: p.eip1283_transition.map_or(BlockNumber::max_value(), Into::into),
 			dust_protection_transition: p.dust_protection_transition.map_or(BlockNumber::max_value(), Into::into),
 			nonce_cap_increment: p.nonce_cap_increment.map_or(64, Into::into),
 			remove_dust_contracts: p.remove_dust_contracts.unwrap_or(false),
@@ -210,6 +225,7 @@ impl Spec {
 			ethjson::spec::Engine::BasicAuthority(basic_authority) => Arc::new(BasicAuthority::new(params, From::from(basic_authority.params), builtins)),
 			ethjson::spec::Engine::AuthorityRound(authority_round) => AuthorityRound::new(params, From::from(authority_round.params), builtins).expect("Failed to start AuthorityRound consensus engine."),
 			ethjson::spec::Engine::Tendermint(tendermint) => Tendermint::new(params, From::from(tendermint.params), builtins).expect("Failed to start the Tendermint consensus engine."),
+			ethjson::spec::Engine::Clique(clique) => Clique::new(params, From::from(clique.params), builtins).expect("Failed to start the Clique consensus engine."),
 		}
 	}
 
@@ -317,6 +333,31 @@ impl Spec {
 	/// Get the configured network fork block.
 	pub fn fork_block(&self) -> Option<(BlockNumber, H256)> { self.params().fork_block }
 
+	/// Get the sorted block numbers of all scheduled rule changes, excluding
+	/// those active from genesis and those disabled with a placeholder block number.
+	pub fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let p = self.params();
+		let mut blocks = vec![
+			p.eip98_transition,
+			p.validate_receipts_transition,
+			p.eip86_transition,
+			p.eip140_transition,
+			p.eip210_transition,
+			p.eip211_transition,
+			p.eip214_transition,
+			p.eip145_transition,
+			p.eip1014_transition,
+			p.eip1052_transition,
+			p.eip1283_transition,
+			p.dust_protection_transition,
+		];
+		blocks.extend(self.engine.fork_transitions());
+		blocks.retain(|b| *b != 0 && *b < DISABLED_TRANSITION);
+		blocks.sort();
+		blocks.dedup();
+		blocks
+	}
+
 	/// Get the header of the genesis block.
 	pub fn genesis_header(&self) -> Header {
 		let mut header: Header = Default::default();
@@ -426,6 +467,10 @@ impl Spec {
 	/// Account "0".sha3() and "1".sha3() are a authorities.
 	pub fn new_test_tendermint() -> Self { load_bundled!("tendermint") }
 
+	/// Create a new Spec with Clique consensus which does internal sealing (not requiring work).
+	/// Accounts with secrets "0".sha3() and "1".sha3() are the signers.
+	pub fn new_test_clique() -> Self { load_bundled!("clique") }
+
 	/// TestList.sol used in both specs: https://github.com/paritytech/contracts/pull/30/files
 	/// Accounts with secrets "0".sha3() and "1".sha3() are initially the validators.
 	/// Create a new Spec with BasicAuthority which uses a contract at address 5 to determine the current validators using `getValidators`.
@@ -450,6 +495,12 @@ mod tests {
 	use state::State;
 	use super::*;
 
+	#[test]
+	fn fork_blocks() {
+		let spec = ::ethereum::new_foundation();
+		assert_eq!(spec.fork_blocks(), vec![1150000, 1920000, 2463000, 2675000]);
+	}
+
 	// https://github.com/paritytech/parity/issues/1840
 	#[test]
 	fn test_load_empty() {
@@ -199,6 +199,12 @@ impl Account {
 	/// return the nonce associated with this account.
 	pub fn nonce(&self) -> &U256 { &self.nonce }
 
+	/// Overwrite the balance of this account.
+	pub fn set_balance(&mut self, balance: U256) { self.balance = balance; }
+
+	/// Overwrite the nonce of this account.
+	pub fn set_nonce(&mut self, nonce: U256) { self.nonce = nonce; }
+
 	/// return the code hash associated with this account.
 	pub fn code_hash(&self) -> H256 {
 		self.code_hash.clone()
@@ -323,6 +329,9 @@ impl Account {
 	/// Return the storage root associated with this account or None if it has been altered via the overlay.
 	pub fn storage_root(&self) -> Option<&H256> { if self.storage_is_clean() {Some(&self.storage_root)} else {None} }
 
+	/// Return the storage root this account's pending storage changes are applied on top of.
+	pub fn base_storage_root(&self) -> &H256 { &self.storage_root }
+
 	/// Return the storage overlay.
 	pub fn storage_changes(&self) -> &HashMap<H256, H256> { &self.storage_changes }
 
@@ -34,6 +34,7 @@ use pod_state::{self, PodState};
 use types::basic_account::BasicAccount;
 use types::executed::{Executed, ExecutionError};
 use types::state_diff::StateDiff;
+use types::state_override::{StateOverride, AccountOverride};
 use transaction::SignedTransaction;
 use state_db::StateDB;
 use evm::{Factory as EvmFactory};
@@ -497,18 +498,22 @@ impl<B: Backend> State<B> {
 					_ => return Ok(H256::new()),
 				}
 			}
+			// the global cache can't be used if the local account has had its storage replaced.
+			let local_root = local_account.and_then(|acc| acc.account.as_ref().map(|a| a.base_storage_root().clone()));
+
 			// check the global cache and and cache storage key there if found,
 			let trie_res = self.db.get_cached(address, |acc| match acc {
-				None => Ok(H256::new()),
+				None => Some(Ok(H256::new())),
+				Some(ref a) if local_root.as_ref().map_or(false, |root| root != a.base_storage_root()) => None,
 				Some(a) => {
 					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
-					a.storage_at(account_db.as_hashdb(), key)
+					Some(a.storage_at(account_db.as_hashdb(), key))
 				}
 			});
 
 			match trie_res {
-				None => {}
-				Some(res) => return res,
+				Some(Some(res)) => return res,
+				_ => {}
 			}
 
 			// otherwise cache the account localy and cache storage key there.
@@ -536,6 +541,56 @@ impl<B: Backend> State<B> {
 		r
 	}
 
+	/// Get the value of storage of account `address` at `key` as it was when the checkpoint
+	/// with the given index was created. Returns `None` if there is no such checkpoint.
+	pub fn checkpoint_storage_at(&self, checkpoint_index: usize, address: &Address, key: &H256) -> trie::Result<Option<H256>> {
+		enum ValueAt {
+			// the account hasn't been touched since the checkpoint.
+			Current,
+			// the account wasn't cached when the checkpoint was created.
+			Committed,
+		}
+
+		let value_at = {
+			let checkpoints = self.checkpoints.borrow();
+			if checkpoint_index >= checkpoints.len() {
+				return Ok(None);
+			}
+
+			let mut value_at = ValueAt::Current;
+			for checkpoint in checkpoints.iter().skip(checkpoint_index) {
+				match checkpoint.get(address) {
+					// the account was modified after this checkpoint and the entry holds its previous state.
+					Some(&Some(ref entry)) => return match entry.account {
+						Some(ref account) => {
+							let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(address));
+							account.storage_at(account_db.as_hashdb(), key).map(Some)
+						},
+						None => Ok(Some(H256::new())),
+					},
+					Some(&None) => {
+						value_at = ValueAt::Committed;
+						break;
+					},
+					None => {},
+				}
+			}
+			value_at
+		};
+
+		match value_at {
+			ValueAt::Current => self.storage_at(address, key).map(Some),
+			ValueAt::Committed => {
+				let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root).expect(SEC_TRIE_DB_UNWRAP_STR);
+				let maybe_acc = db.get_with(address, Account::from_rlp)?;
+				maybe_acc.map_or(Ok(H256::new()), |a| {
+					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
+					a.storage_at(account_db.as_hashdb(), key)
+				}).map(Some)
+			},
+		}
+	}
+
 	/// Get accounts' code.
 	pub fn code(&self, a: &Address) -> trie::Result<Option<Arc<Bytes>>> {
 		self.ensure_cached(a, RequireCache::Code, true,
@@ -604,6 +659,54 @@ impl<B: Backend> State<B> {
 		Ok(())
 	}
 
+	/// Apply temporary account overrides on top of the current state.
+	/// Meant for virtual calls only; the overridden accounts should never be committed.
+	pub fn apply_overrides(&mut self, overrides: &StateOverride) -> trie::Result<()> {
+		for (address, account) in overrides.iter() {
+			self.apply_override(address, account)?;
+		}
+		Ok(())
+	}
+
+	fn apply_override(&mut self, a: &Address, o: &AccountOverride) -> trie::Result<()> {
+		trace!(target: "state", "apply_override({}): {:?}", a, o);
+		if let Some(ref storage) = o.state {
+			// the whole storage is replaced, so start over with a fresh account.
+			let balance = match o.balance {
+				Some(balance) => balance,
+				None => self.balance(a)?,
+			};
+			let nonce = match o.nonce {
+				Some(nonce) => nonce,
+				None => self.nonce(a)?,
+			};
+			let code = match o.code {
+				Some(ref code) => code.clone(),
+				None => self.code(a)?.map_or_else(Vec::new, |c| (*c).clone()),
+			};
+			let account = Account::from_pod(PodAccount::new(balance, nonce, code, storage.clone()));
+			self.insert_cache(a, AccountEntry::new_dirty(Some(account)));
+		} else {
+			let mut account = self.require(a, false)?;
+			if let Some(balance) = o.balance {
+				account.set_balance(balance);
+			}
+			if let Some(nonce) = o.nonce {
+				account.set_nonce(nonce);
+			}
+			if let Some(ref code) = o.code {
+				account.reset_code(code.clone());
+			}
+		}
+
+		if let Some(ref diff) = o.state_diff {
+			for (key, value) in diff {
+				self.set_storage(a, key.clone(), value.clone())?;
+			}
+		}
+		Ok(())
+	}
+
 	/// Initialise the code of account `a` so that it is `code`.
 	/// NOTE: Account should have been created with `new_contract`.
 	pub fn init_code(&mut self, a: &Address, code: Bytes) -> trie::Result<()> {
@@ -642,7 +745,7 @@ impl<B: Backend> State<B> {
 	fn execute(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool, virt: bool)
 		-> Result<Executed, ExecutionError>
 	{
-		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
+		let options = TransactOptions { tracing: tracing, vm_tracing: false, call_tracing: false, check_nonce: true };
 		let mut e = Executive::new(self, env_info, engine);
 
 		match virt {
@@ -2050,6 +2153,116 @@ mod tests {
 		new_state.diff_from(state).unwrap();
 	}
 
+	#[test]
+	fn checkpoint_storage_at() {
+		let mut state = get_temp_state();
+		let a = Address::zero();
+		let k = H256::from(U256::from(0));
+		let c1 = H256::from(U256::from(1));
+		let c2 = H256::from(U256::from(2));
+		let c3 = H256::from(U256::from(3));
+
+		state.set_storage(&a, k, c1).unwrap();
+		state.commit().unwrap();
+		state.clear();
+
+		assert_eq!(state.checkpoint_storage_at(0, &a, &k).unwrap(), None);
+		state.checkpoint();
+		state.set_storage(&a, k, c2).unwrap();
+		state.checkpoint();
+		state.set_storage(&a, k, c3).unwrap();
+		state.checkpoint();
+
+		assert_eq!(state.checkpoint_storage_at(0, &a, &k).unwrap(), Some(c1));
+		assert_eq!(state.checkpoint_storage_at(1, &a, &k).unwrap(), Some(c2));
+		assert_eq!(state.checkpoint_storage_at(2, &a, &k).unwrap(), Some(c3));
+		assert_eq!(state.checkpoint_storage_at(3, &a, &k).unwrap(), None);
+
+		state.discard_checkpoint();
+		state.discard_checkpoint();
+		assert_eq!(state.checkpoint_storage_at(0, &a, &k).unwrap(), Some(c1));
+		state.revert_to_checkpoint();
+		assert_eq!(state.storage_at(&a, &k).unwrap(), c1);
+	}
+
+	#[test]
+	fn should_apply_state_overrides() {
+		use std::collections::BTreeMap;
+
+		let a: Address = 0xa.into();
+		let b: Address = 0xb.into();
+		let db = get_temp_state_db();
+		let (root, db) = {
+			let mut state = State::new(db, U256::from(0), Default::default());
+			state.add_balance(&a, &100.into(), CleanupMode::NoEmpty).unwrap();
+			state.set_storage(&a, 1.into(), 1.into()).unwrap();
+			state.add_balance(&b, &100.into(), CleanupMode::NoEmpty).unwrap();
+			state.set_storage(&b, 1.into(), 1.into()).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let mut storage = BTreeMap::new();
+		storage.insert(2.into(), 2.into());
+		let mut overrides = StateOverride::default();
+		overrides.raw.insert(a, AccountOverride {
+			nonce: Some(5.into()),
+			state: Some(storage.clone()),
+			..Default::default()
+		});
+		overrides.raw.insert(b, AccountOverride {
+			balance: Some(1.into()),
+			code: Some(vec![0x60, 0x00]),
+			state_diff: Some(storage),
+			..Default::default()
+		});
+
+		let mut state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		state.apply_overrides(&overrides).unwrap();
+
+		assert_eq!(state.balance(&a).unwrap(), 100.into());
+		assert_eq!(state.nonce(&a).unwrap(), 5.into());
+		assert_eq!(state.storage_at(&a, &1.into()).unwrap(), H256::zero());
+		assert_eq!(state.storage_at(&a, &2.into()).unwrap(), 2.into());
+		assert_eq!(state.balance(&b).unwrap(), 1.into());
+		assert_eq!(state.code(&b).unwrap().map(|c| (*c).clone()), Some(vec![0x60, 0x00]));
+		assert_eq!(state.storage_at(&b, &1.into()).unwrap(), 1.into());
+		assert_eq!(state.storage_at(&b, &2.into()).unwrap(), 2.into());
+	}
+
+	#[test]
+	fn checkpoint_storage_at_committed_overrides() {
+		use std::collections::BTreeMap;
+
+		let a: Address = 0xa.into();
+		let k: H256 = 1.into();
+		let db = get_temp_state_db();
+		let (root, db) = {
+			let mut state = State::new(db, U256::from(0), Default::default());
+			state.set_storage(&a, k, 1.into()).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let mut diff = BTreeMap::new();
+		diff.insert(k, 2.into());
+		let mut overrides = StateOverride::default();
+		overrides.raw.insert(a, AccountOverride {
+			state_diff: Some(diff),
+			..Default::default()
+		});
+
+		let mut state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		state.apply_overrides(&overrides).unwrap();
+		state.commit().unwrap();
+		state.clear();
+
+		state.checkpoint();
+		state.set_storage(&a, k, 3.into()).unwrap();
+		assert_eq!(state.checkpoint_storage_at(0, &a, &k).unwrap(), Some(2.into()));
+		assert_eq!(state.storage_at(&a, &k).unwrap(), 3.into());
+	}
+
 	#[test]
 	fn should_kill_garbage() {
 		let a = 10.into();
@@ -16,7 +16,7 @@
 
 //! Execution environment substate.
 use std::collections::HashSet;
-use util::{Address, U256};
+use util::Address;
 use log_entry::LogEntry;
 use evm::{Schedule, CleanDustMode};
 use super::CleanupMode;
@@ -34,8 +34,9 @@ pub struct Substate {
 	/// Any logs.
 	pub logs: Vec<LogEntry>,
 
-	/// Refund counter of SSTORE nonzero -> zero.
-	pub sstore_clears_count: U256,
+	/// Refund counter of SSTORE. With net gas metering (EIP-1283) a refund granted
+	/// by a parent call can be taken back by a subcall, so it may be negative.
+	pub sstore_clears_refund: i64,
 
 	/// Created contracts.
 	pub contracts_created: Vec<Address>,
@@ -52,7 +53,7 @@ impl Substate {
 		self.suicides.extend(s.suicides.into_iter());
 		self.touched.extend(s.touched.into_iter());
 		self.logs.extend(s.logs.into_iter());
-		self.sstore_clears_count = self.sstore_clears_count + s.sstore_clears_count;
+		self.sstore_clears_refund += s.sstore_clears_refund;
 		self.contracts_created.extend(s.contracts_created.into_iter());
 	}
 
@@ -87,7 +88,7 @@ mod tests {
 			topics: vec![],
 			data: vec![]
 		});
-		sub_state.sstore_clears_count = 5.into();
+		sub_state.sstore_clears_refund = 5 * 15000;
 		sub_state.suicides.insert(10u64.into());
 
 		let mut sub_state_2 = Substate::new();
@@ -97,11 +98,11 @@ mod tests {
 			topics: vec![],
 			data: vec![]
 		});
-		sub_state_2.sstore_clears_count = 7.into();
+		sub_state_2.sstore_clears_refund = -7 * 15000;
 
 		sub_state.accrue(sub_state_2);
 		assert_eq!(sub_state.contracts_created.len(), 2);
-		assert_eq!(sub_state.sstore_clears_count, 12.into());
+		assert_eq!(sub_state.sstore_clears_refund, -2 * 15000);
 		assert_eq!(sub_state.suicides.len(), 1);
 	}
 }
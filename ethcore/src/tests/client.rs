@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId, CallAnalytics};
 use state::{self, State, CleanupMode};
 use executive::Executive;
 use ethereum;
@@ -227,6 +227,19 @@ fn corpus_is_sorted() {
 	assert!(corpus[0] < corpus[1]);
 }
 
+#[test]
+fn can_replay_block_transactions() {
+	let client = generate_dummy_client_with_data(2, 2, slice_into![1, 2]);
+	let analytics = CallAnalytics { transaction_tracing: true, ..Default::default() };
+
+	let hashes = client.block_body(BlockId::Number(2)).unwrap().transaction_hashes();
+	let results = client.replay_block_transactions(BlockId::Number(2), analytics).unwrap();
+
+	assert_eq!(results.iter().map(|r| r.0.clone()).collect::<Vec<_>>(), hashes);
+	assert!(results.iter().all(|r| r.1.exception.is_none() && r.1.trace.len() == 1));
+	assert_eq!(results[1].1.cumulative_gas_used, results[0].1.gas_used + results[1].1.gas_used);
+}
+
 #[test]
 fn can_handle_long_fork() {
 	let client = generate_dummy_client(1200);
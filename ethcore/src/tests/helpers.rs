@@ -157,11 +157,19 @@ pub fn generate_dummy_client_with_spec_and_accounts<F>(get_test_spec: F, account
 }
 
 pub fn generate_dummy_client_with_spec_accounts_and_data<F>(get_test_spec: F, accounts: Option<Arc<AccountProvider>>, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> Arc<Client> where F: Fn()->Spec {
+	generate_dummy_client_with_config(ClientConfig::default(), get_test_spec, accounts, block_number, txs_per_block, tx_gas_prices)
+}
+
+pub fn generate_dummy_client_with_config_and_data<F>(config: ClientConfig, get_test_spec: F, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> Arc<Client> where F: Fn()->Spec {
+	generate_dummy_client_with_config(config, get_test_spec, None, block_number, txs_per_block, tx_gas_prices)
+}
+
+fn generate_dummy_client_with_config<F>(config: ClientConfig, get_test_spec: F, accounts: Option<Arc<AccountProvider>>, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> Arc<Client> where F: Fn()->Spec {
 	let test_spec = get_test_spec();
 	let client_db = new_db();
 
 	let client = Client::new(
-		ClientConfig::default(),
+		config,
 		&test_spec,
 		client_db,
 		Arc::new(Miner::with_spec_and_accounts(&test_spec, accounts)),
@@ -0,0 +1,137 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Call tree tracer.
+
+use util::{Bytes, Address, U256};
+use action_params::ActionParams;
+use trace::trace::{Call, Create, Action, Res, CreateResult, CallResult, Suicide};
+use trace::{Tracer, CallFrame, TraceError};
+
+/// Tracer which builds a nested tree of calls and creates. Unlike `ExecutiveTracer`
+/// it keeps track of frames which ended with `REVERT`.
+#[derive(Default)]
+pub struct CallTracer {
+	frames: Vec<CallFrame>,
+}
+
+impl CallTracer {
+	fn push_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, subs: Vec<CallFrame>, reverted: bool) {
+		let frame = CallFrame {
+			action: Action::Call(call.expect("self.prepare_trace_call().is_some(): so we must be tracing: qed")),
+			result: Res::Call(CallResult {
+				gas_used: gas_used,
+				output: output.expect("self.prepare_trace_output().is_some(): so we must be tracing: qed")
+			}),
+			reverted: reverted,
+			calls: subs,
+		};
+		debug!(target: "trace", "Traced call frame {:?}", frame);
+		self.frames.push(frame);
+	}
+
+	fn push_create(&mut self, create: Option<Create>, gas_used: U256, code: Option<Bytes>, address: Address, subs: Vec<CallFrame>, reverted: bool) {
+		let frame = CallFrame {
+			action: Action::Create(create.expect("self.prepare_trace_create().is_some(): so we must be tracing: qed")),
+			result: Res::Create(CreateResult {
+				gas_used: gas_used,
+				code: code.expect("self.prepare_trace_output.is_some(): so we must be tracing: qed"),
+				address: address
+			}),
+			reverted: reverted,
+			calls: subs,
+		};
+		debug!(target: "trace", "Traced create frame {:?}", frame);
+		self.frames.push(frame);
+	}
+}
+
+impl Tracer for CallTracer {
+	type Output = CallFrame;
+
+	fn prepare_trace_call(&self, params: &ActionParams) -> Option<Call> {
+		Some(Call::from(params.clone()))
+	}
+
+	fn prepare_trace_create(&self, params: &ActionParams) -> Option<Create> {
+		Some(Create::from(params.clone()))
+	}
+
+	fn prepare_trace_output(&self) -> Option<Bytes> {
+		Some(vec![])
+	}
+
+	fn trace_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, subs: Vec<CallFrame>) {
+		self.push_call(call, gas_used, output, subs, false);
+	}
+
+	fn trace_create(&mut self, create: Option<Create>, gas_used: U256, code: Option<Bytes>, address: Address, subs: Vec<CallFrame>) {
+		self.push_create(create, gas_used, code, address, subs, false);
+	}
+
+	fn trace_reverted_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, subs: Vec<CallFrame>) {
+		self.push_call(call, gas_used, output, subs, true);
+	}
+
+	fn trace_reverted_create(&mut self, create: Option<Create>, gas_used: U256, code: Option<Bytes>, address: Address, subs: Vec<CallFrame>) {
+		self.push_create(create, gas_used, code, address, subs, true);
+	}
+
+	fn trace_failed_call(&mut self, call: Option<Call>, subs: Vec<CallFrame>, error: TraceError) {
+		let frame = CallFrame {
+			action: Action::Call(call.expect("self.prepare_trace_call().is_some(): so we must be tracing: qed")),
+			result: Res::FailedCall(error),
+			reverted: false,
+			calls: subs,
+		};
+		debug!(target: "trace", "Traced failed call frame {:?}", frame);
+		self.frames.push(frame);
+	}
+
+	fn trace_failed_create(&mut self, create: Option<Create>, subs: Vec<CallFrame>, error: TraceError) {
+		let frame = CallFrame {
+			action: Action::Create(create.expect("self.prepare_trace_create().is_some(): so we must be tracing: qed")),
+			result: Res::FailedCreate(error),
+			reverted: false,
+			calls: subs,
+		};
+		debug!(target: "trace", "Traced failed create frame {:?}", frame);
+		self.frames.push(frame);
+	}
+
+	fn trace_suicide(&mut self, address: Address, balance: U256, refund_address: Address) {
+		let frame = CallFrame {
+			action: Action::Suicide(Suicide {
+				address: address,
+				refund_address: refund_address,
+				balance: balance,
+			}),
+			result: Res::None,
+			reverted: false,
+			calls: vec![],
+		};
+		debug!(target: "trace", "Traced suicide frame {:?}", frame);
+		self.frames.push(frame);
+	}
+
+	fn subtracer(&self) -> Self {
+		CallTracer::default()
+	}
+
+	fn traces(self) -> Vec<CallFrame> {
+		self.frames
+	}
+}
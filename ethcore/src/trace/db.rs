@@ -179,6 +179,18 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		});
 	}
 
+	/// Removes traces of the given blocks. Their blooms are kept, blocks
+	/// without traces are skipped when filtering.
+	pub fn prune(&self, batch: &mut DBTransaction, hashes: &[H256]) {
+		type TracesKey = Key<FlatBlockTraces, Target=H264>;
+
+		let mut traces = self.traces.write();
+		for hash in hashes {
+			batch.delete(db::COL_TRACE, &TracesKey::key(hash));
+			traces.remove(hash);
+		}
+	}
+
 	/// Returns traces for block with hash.
 	fn traces(&self, block_hash: &H256) -> Option<FlatBlockTraces> {
 		let result = self.tracesdb.read_with_cache(db::COL_TRACE, &self.traces, block_hash);
@@ -392,9 +404,11 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				let number = n as BlockNumber;
 				let hash = self.extras.block_hash(number)
 					.expect("Expected to find block hash. Extras db is probably corrupted");
-				let traces = self.traces(&hash)
-					.expect("Expected to find a trace. Db is probably corrupted.");
-				self.matching_block_traces(filter, traces, hash, number)
+				match self.traces(&hash) {
+					Some(traces) => self.matching_block_traces(filter, traces, hash, number),
+					// traces of blocks with pruned history are gone.
+					None => Vec::new(),
+				}
 			})
 			.collect()
 	}
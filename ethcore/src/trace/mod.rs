@@ -17,6 +17,7 @@
 //! Tracing
 
 mod bloom;
+mod call_tracer;
 mod config;
 mod db;
 mod executive_tracer;
@@ -29,6 +30,8 @@ pub use self::config::Config;
 pub use self::db::TraceDB;
 pub use types::trace_types::trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff};
 pub use types::trace_types::flat::{FlatTrace, FlatTransactionTraces, FlatBlockTraces};
+pub use types::trace_types::call_tree::CallFrame;
+pub use self::call_tracer::CallTracer;
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
 pub use types::trace_types::filter::{Filter, AddressesFilter};
@@ -41,6 +44,9 @@ use header::BlockNumber;
 
 /// This trait is used by executive to build traces.
 pub trait Tracer: Send {
+	/// Data produced by the tracer for each traced action.
+	type Output;
+
 	/// Prepares call trace for given params. Noop tracer should return None.
 	fn prepare_trace_call(&self, params: &ActionParams) -> Option<Call>;
 
@@ -56,7 +62,7 @@ pub trait Tracer: Send {
 		call: Option<Call>,
 		gas_used: U256,
 		output: Option<Bytes>,
-		subs: Vec<FlatTrace>,
+		subs: Vec<Self::Output>,
 	);
 
 	/// Stores trace create info.
@@ -66,14 +72,39 @@ pub trait Tracer: Send {
 		gas_used: U256,
 		code: Option<Bytes>,
 		address: Address,
-		subs: Vec<FlatTrace>
+		subs: Vec<Self::Output>
 	);
 
+	/// Stores trace of a call which ended with `REVERT`. `output` holds the revert data.
+	/// By default it is traced the same way as a successful call.
+	fn trace_reverted_call(
+		&mut self,
+		call: Option<Call>,
+		gas_used: U256,
+		output: Option<Bytes>,
+		subs: Vec<Self::Output>,
+	) {
+		self.trace_call(call, gas_used, output, subs)
+	}
+
+	/// Stores trace of a create which ended with `REVERT`. `code` holds the revert data.
+	/// By default it is traced the same way as a successful create.
+	fn trace_reverted_create(
+		&mut self,
+		create: Option<Create>,
+		gas_used: U256,
+		code: Option<Bytes>,
+		address: Address,
+		subs: Vec<Self::Output>
+	) {
+		self.trace_create(create, gas_used, code, address, subs)
+	}
+
 	/// Stores failed call trace.
-	fn trace_failed_call(&mut self, call: Option<Call>, subs: Vec<FlatTrace>, error: TraceError);
+	fn trace_failed_call(&mut self, call: Option<Call>, subs: Vec<Self::Output>, error: TraceError);
 
 	/// Stores failed create trace.
-	fn trace_failed_create(&mut self, create: Option<Create>, subs: Vec<FlatTrace>, error: TraceError);
+	fn trace_failed_create(&mut self, create: Option<Create>, subs: Vec<Self::Output>, error: TraceError);
 
 	/// Stores suicide info.
 	fn trace_suicide(&mut self, address: Address, balance: U256, refund_address: Address);
@@ -82,7 +113,7 @@ pub trait Tracer: Send {
 	fn subtracer(&self) -> Self where Self: Sized;
 
 	/// Consumes self and returns all traces.
-	fn traces(self) -> Vec<FlatTrace>;
+	fn traces(self) -> Vec<Self::Output>;
 }
 
 /// Used by executive to build VM traces.
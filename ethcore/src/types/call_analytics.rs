@@ -26,4 +26,6 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Make a nested call tree.
+	pub call_tracing: bool,
 }
@@ -0,0 +1,36 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshot creation status type definition
+
+/// Statuses for snapshot creation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "ipc", binary)]
+pub enum CreationStatus {
+	/// No snapshot is being taken.
+	Inactive,
+	/// Ongoing snapshot creation.
+	Ongoing {
+		/// Number of the block the snapshot is taken at.
+		block_number: u64,
+		/// Number of accounts written so far.
+		accounts: u64,
+		/// Number of blocks written so far.
+		blocks: u64,
+		/// Size of the chunks written so far, in bytes.
+		size: u64,
+	},
+}
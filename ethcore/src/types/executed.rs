@@ -19,7 +19,7 @@
 use util::{Bytes, U256, Address, U512, trie};
 use rlp::*;
 use evm;
-use trace::{VMTrace, FlatTrace};
+use trace::{VMTrace, FlatTrace, CallFrame};
 use types::log_entry::LogEntry;
 use types::state_diff::StateDiff;
 use std::fmt;
@@ -69,7 +69,7 @@ impl Decodable for CallType {
 /// Transaction execution receipt.
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "ipc", binary)]
-pub struct Executed {
+pub struct Executed<T = FlatTrace> {
 	/// True if the outer call/create resulted in an exceptional exit.
 	pub exception: Option<evm::Error>,
 
@@ -103,11 +103,40 @@ pub struct Executed {
 	/// Transaction output.
 	pub output: Bytes,
 	/// The trace of this transaction.
-	pub trace: Vec<FlatTrace>,
+	pub trace: Vec<T>,
 	/// The VM trace of this transaction.
 	pub vm_trace: Option<VMTrace>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
+	/// The nested call tree of this transaction, if we traced it.
+	pub call_tree: Option<CallFrame>,
+}
+
+impl Executed<CallFrame> {
+	/// Moves the call tree produced by `CallTracer` into `call_tree`.
+	/// If `flat_trace` is true, `trace` is filled with the flattened tree.
+	pub fn into_call_tree(self, flat_trace: bool) -> Executed {
+		let call_tree = self.trace.into_iter().next();
+		let trace = match (flat_trace, call_tree.as_ref()) {
+			(true, Some(tree)) => tree.clone().flatten(),
+			_ => vec![],
+		};
+
+		Executed {
+			exception: self.exception,
+			gas: self.gas,
+			gas_used: self.gas_used,
+			refunded: self.refunded,
+			cumulative_gas_used: self.cumulative_gas_used,
+			logs: self.logs,
+			contracts_created: self.contracts_created,
+			output: self.output,
+			trace: trace,
+			vm_trace: self.vm_trace,
+			state_diff: self.state_diff,
+			call_tree: call_tree,
+		}
+	}
 }
 
 /// Result of executing the transaction.
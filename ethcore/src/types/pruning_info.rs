@@ -28,4 +28,6 @@ pub struct PruningInfo {
 	pub earliest_chain: u64,
 	/// The first block where state requests may be served.
 	pub earliest_state: u64,
+	/// The first block whose body, receipts and traces were not pruned by history pruning.
+	pub earliest_history: u64,
 }
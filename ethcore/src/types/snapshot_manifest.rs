@@ -16,9 +16,11 @@
 
 //! Snapshot manifest type definition
 
-use util::hash::H256;
+use util::hash::{H256, H520};
+use util::sha3::Hashable;
 use rlp::*;
-use util::Bytes;
+use util::{Address, Bytes};
+use ethkey::{self, Secret, Signature, recover, public_to_address};
 
 /// Manifest data.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,22 +38,76 @@ pub struct ManifestData {
 	pub block_number: u64,
 	/// Block hash this snapshot was taken at.
 	pub block_hash: H256,
+	/// Signature of the snapshot publisher over the signing hash, if the snapshot is signed.
+	pub signature: Option<H520>,
+	/// Hash of the block the base snapshot was taken at, if this is a delta snapshot.
+	/// Delta snapshots only contain the state changes since the base block.
+	pub base: Option<H256>,
 }
 
 impl ManifestData {
+	/// Whether this is a delta snapshot, which can only be applied on top of the state
+	/// restored from its base.
+	pub fn is_delta(&self) -> bool {
+		self.base.is_some()
+	}
+
 	/// Encode the manifest data to rlp.
 	pub fn into_rlp(self) -> Bytes {
-		let mut stream = RlpStream::new_list(6);
+		let len = match (self.signature.is_some(), self.base.is_some()) {
+			(_, true) => 8,
+			(true, false) => 7,
+			(false, false) => 6,
+		};
+
+		let mut stream = RlpStream::new_list(len);
 		stream.append(&self.version);
 		stream.append_list(&self.state_hashes);
 		stream.append_list(&self.block_hashes);
 		stream.append(&self.state_root);
 		stream.append(&self.block_number);
 		stream.append(&self.block_hash);
+		match self.signature {
+			Some(ref signature) => { stream.append(signature); },
+			None if self.base.is_some() => { stream.append_empty_data(); },
+			None => {},
+		}
+		if let Some(ref base) = self.base {
+			stream.append(base);
+		}
 
 		stream.out()
 	}
 
+	/// Hash signed by snapshot publishers. Covers the block hash, state root,
+	/// all chunk hashes and the base block of delta snapshots.
+	pub fn signing_hash(&self) -> H256 {
+		let mut stream = RlpStream::new_list(if self.base.is_some() { 5 } else { 4 });
+		stream.append(&self.block_hash);
+		stream.append(&self.state_root);
+		stream.append_list(&self.state_hashes);
+		stream.append_list(&self.block_hashes);
+		if let Some(ref base) = self.base {
+			stream.append(base);
+		}
+
+		stream.out().sha3()
+	}
+
+	/// Sign the manifest with the given secret, replacing any previous signature.
+	pub fn sign(&mut self, secret: &Secret) -> Result<(), ethkey::Error> {
+		let signature = ethkey::sign(secret, &self.signing_hash())?;
+		self.signature = Some(signature.into());
+		Ok(())
+	}
+
+	/// Recover the address of the publisher who signed the manifest.
+	/// `None` if the manifest is unsigned or the signature is invalid.
+	pub fn signer(&self) -> Option<Address> {
+		self.signature.and_then(|signature| recover(&Signature::from(signature), &self.signing_hash()).ok())
+			.map(|public| public_to_address(&public))
+	}
+
 	/// Try to restore manifest data from raw bytes, interpreted as RLP.
 	pub fn from_rlp(raw: &[u8]) -> Result<Self, DecoderError> {
 		let decoder = UntrustedRlp::new(raw);
@@ -66,6 +122,16 @@ impl ManifestData {
 		let state_root: H256 = decoder.val_at(start + 2)?;
 		let block_number: u64 = decoder.val_at(start + 3)?;
 		let block_hash: H256 = decoder.val_at(start + 4)?;
+		let signature: Option<H520> = if decoder.item_count()? > start + 5 && !decoder.at(start + 5)?.is_empty() {
+			Some(decoder.val_at(start + 5)?)
+		} else {
+			None
+		};
+		let base: Option<H256> = if decoder.item_count()? > start + 6 {
+			Some(decoder.val_at(start + 6)?)
+		} else {
+			None
+		};
 
 		Ok(ManifestData {
 			version: version,
@@ -74,6 +140,8 @@ impl ManifestData {
 			state_root: state_root,
 			block_number: block_number,
 			block_hash: block_hash,
+			signature: signature,
+			base: base,
 		})
 	}
 }
@@ -0,0 +1,55 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State override module.
+
+use std::ops::*;
+use std::collections::BTreeMap;
+use util::{U256, H256, Address, Bytes};
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+/// Temporary changes to a single account, applied to the state before
+/// executing a virtual call.
+pub struct AccountOverride {
+	/// Replacement balance.
+	pub balance: Option<U256>,
+	/// Replacement nonce.
+	pub nonce: Option<U256>,
+	/// Replacement code.
+	pub code: Option<Bytes>,
+	/// Replacement for the whole storage. Slots not listed here are cleared.
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage slots to change. Slots not listed here are left intact.
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+/// Temporary changes to any number of accounts, applied to the state before
+/// executing a virtual call.
+pub struct StateOverride {
+	/// Raw overrides key-value
+	pub raw: BTreeMap<Address, AccountOverride>
+}
+
+impl Deref for StateOverride {
+	type Target = BTreeMap<Address, AccountOverride>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.raw
+	}
+}
@@ -0,0 +1,114 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Nested call tree module
+
+use std::collections::VecDeque;
+use super::flat::FlatTrace;
+use super::trace::{Action, Res};
+
+/// Single frame of a nested call tree produced by a transaction.
+///
+/// Unlike `FlatTrace`, subcalls are stored inside their parent frame and
+/// frames which ended with `REVERT` are marked as such.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct CallFrame {
+	/// Type of action performed by this frame.
+	pub action: Action,
+	/// Result of this action. For reverted frames it holds the revert data.
+	pub result: Res,
+	/// True if the frame ended with `REVERT` and its state changes were discarded.
+	pub reverted: bool,
+	/// Calls, creates and suicides made by this frame in execution order.
+	pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+	/// Converts the tree into flat traces ordered the same way `ExecutiveTracer` orders them.
+	pub fn flatten(self) -> Vec<FlatTrace> {
+		let mut traces = Vec::new();
+		self.flatten_into(VecDeque::new(), &mut traces);
+		traces
+	}
+
+	fn flatten_into(self, trace_address: VecDeque<usize>, traces: &mut Vec<FlatTrace>) {
+		traces.push(FlatTrace {
+			action: self.action,
+			result: self.result,
+			subtraces: self.calls.len(),
+			trace_address: trace_address.clone(),
+		});
+
+		for (index, call) in self.calls.into_iter().enumerate() {
+			let mut address = trace_address.clone();
+			address.push_back(index);
+			call.flatten_into(address, traces);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use types::executed::CallType;
+	use types::trace_types::flat::FlatTrace;
+	use types::trace_types::trace::{Action, Res, Call, CallResult, Suicide};
+	use super::CallFrame;
+
+	fn call(to: u64) -> Action {
+		Action::Call(Call {
+			from: 1.into(),
+			to: to.into(),
+			value: 0.into(),
+			gas: 100.into(),
+			input: vec![],
+			call_type: CallType::Call,
+		})
+	}
+
+	fn frame(to: u64, calls: Vec<CallFrame>) -> CallFrame {
+		CallFrame {
+			action: call(to),
+			result: Res::Call(CallResult { gas_used: 10.into(), output: vec![] }),
+			reverted: false,
+			calls: calls,
+		}
+	}
+
+	#[test]
+	fn should_flatten_call_tree() {
+		let suicide = CallFrame {
+			action: Action::Suicide(Suicide {
+				address: 3.into(),
+				refund_address: 1.into(),
+				balance: 0.into(),
+			}),
+			result: Res::None,
+			reverted: false,
+			calls: vec![],
+		};
+		let tree = frame(2, vec![frame(3, vec![suicide]), frame(4, vec![])]);
+
+		let flat = tree.flatten();
+		let addresses: Vec<Vec<usize>> = flat.iter().map(|t| t.trace_address.iter().cloned().collect()).collect();
+		let subtraces: Vec<usize> = flat.iter().map(|t: &FlatTrace| t.subtraces).collect();
+
+		assert_eq!(addresses, vec![vec![], vec![0], vec![0, 0], vec![1]]);
+		assert_eq!(subtraces, vec![2, 1, 0, 0]);
+		assert_eq!(flat[2].result, Res::None);
+		assert_eq!(flat[3].action, call(4));
+	}
+}
@@ -76,6 +76,20 @@ pub fn curve_order() -> U256 {
 	H256::from_slice(&CURVE_ORDER).into()
 }
 
+/// Serialize public key in the 33-byte compressed form
+pub fn public_to_compressed(public: &Public) -> Result<Vec<u8>, Error> {
+	let key_public = to_secp256k1_public(public)?;
+	Ok(key_public.serialize_vec(&SECP256K1, true).to_vec())
+}
+
+/// Parse public key from its compressed (33 bytes) or uncompressed (65 bytes) serialized form
+pub fn public_from_slice(data: &[u8]) -> Result<Public, Error> {
+	let key_public = key::PublicKey::from_slice(&SECP256K1, data)?;
+	let mut public = Public::default();
+	set_public(&mut public, &key_public);
+	Ok(public)
+}
+
 fn to_secp256k1_public(public: &Public) -> Result<key::PublicKey, Error> {
 	let public_data = {
 		let mut temp = [4u8; 65];
@@ -94,7 +108,7 @@ fn set_public(public: &mut Public, key_public: &key::PublicKey) {
 #[cfg(test)]
 mod tests {
 	use super::super::{Random, Generator};
-	use super::{public_add, public_sub};
+	use super::{public_add, public_sub, public_to_compressed, public_from_slice};
 
 	#[test]
 	fn public_addition_is_commutative() {
@@ -121,4 +135,12 @@ mod tests {
 
 		assert_eq!(sum, public1);
 	}
+
+	#[test]
+	fn public_compression_roundtrip() {
+		let public = Random.generate().unwrap().public().clone();
+		let compressed = public_to_compressed(&public).unwrap();
+		assert_eq!(compressed.len(), 33);
+		assert_eq!(public_from_slice(&compressed).unwrap(), public);
+	}
 }
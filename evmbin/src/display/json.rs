@@ -24,7 +24,7 @@ use display;
 use vm;
 
 /// JSON formatting informant.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Informant {
 	code: Vec<u8>,
 	depth: usize,
@@ -78,6 +78,40 @@ impl vm::Informant for Informant {
 			),
 		}
 	}
+
+	fn state_test(&mut self, result: &vm::StateTestResult) {
+		let error = match result.error {
+			Some(ref error) => format!("\"{}\"", error.replace('"', "\\\"")),
+			None => "null".into(),
+		};
+		let expected_logs = match result.expected_logs {
+			Some(ref logs) => format!("\"0x{:?}\"", logs),
+			None => "null".into(),
+		};
+
+		println!(
+			"{{\"name\":\"{name}\",\"fork\":\"{fork}\",\"index\":{index},\"pass\":{pass},\"stateRoot\":\"0x{root:?}\",\"expectedStateRoot\":\"0x{expected_root:?}\",\"logsHash\":\"0x{logs:?}\",\"expectedLogsHash\":{expected_logs},\"gasUsed\":{gas},\"error\":{error},\"time\":{time}}}",
+			name = result.name,
+			fork = result.fork,
+			index = result.index,
+			pass = result.passed(),
+			root = result.root,
+			expected_root = result.expected_root,
+			logs = result.logs,
+			expected_logs = expected_logs,
+			gas = display::u256_as_str(&result.gas_used),
+			error = error,
+			time = display::as_micros(&result.time),
+		);
+	}
+
+	fn state_test_unsupported(&mut self, name: &str, fork: &str) {
+		println!("{{\"name\":\"{name}\",\"fork\":\"{fork}\",\"unsupported\":true}}", name = name, fork = fork);
+	}
+
+	fn state_tests_summary(&mut self, passed: usize, failed: usize) {
+		println!("{{\"passed\":{passed},\"failed\":{failed}}}", passed = passed, failed = failed);
+	}
 }
 
 impl trace::VMTracer for Informant {
@@ -21,6 +21,7 @@ use util::U256;
 
 pub mod json;
 pub mod simple;
+pub mod std_json;
 
 /// Formats duration into human readable format.
 pub fn format_time(time: &Duration) -> String {
@@ -23,7 +23,7 @@ use display;
 use vm;
 
 /// Simple formatting informant.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Informant;
 
 impl vm::Informant for Informant {
@@ -40,6 +40,30 @@ impl vm::Informant for Informant {
 			},
 		}
 	}
+
+	fn state_test(&mut self, result: &vm::StateTestResult) {
+		let status = if result.passed() { "OK" } else { "FAIL" };
+		println!("{} [{}:{}] {} ({})", result.name, result.fork, result.index, status, display::format_time(&result.time));
+		if let Some(ref error) = result.error {
+			println!("  Error: {}", error);
+		}
+		if result.root != result.expected_root {
+			println!("  State root mismatch (got: 0x{:?}, expected: 0x{:?})", result.root, result.expected_root);
+		}
+		if let Some(ref expected) = result.expected_logs {
+			if *expected != result.logs {
+				println!("  Logs hash mismatch (got: 0x{:?}, expected: 0x{:?})", result.logs, expected);
+			}
+		}
+	}
+
+	fn state_test_unsupported(&mut self, name: &str, fork: &str) {
+		println!("{} [{}] UNSUPPORTED", name, fork);
+	}
+
+	fn state_tests_summary(&mut self, passed: usize, failed: usize) {
+		println!("Passed: {}, failed: {}", passed, failed);
+	}
 }
 
 impl trace::VMTracer for Informant {
@@ -0,0 +1,180 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Standard JSON VM output.
+//!
+//! Emits one JSON object per executed instruction, in the format shared with other
+//! EVM implementations, so that traces can be diffed line by line. Step lines go to
+//! stderr and the final summary goes to stdout.
+
+use std::io::{self, Write};
+use std::collections::HashMap;
+use ethcore::{evm, trace};
+use util::{U256, H256, ToPretty};
+
+use display;
+use vm;
+
+/// Standard JSON output configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+	/// Include memory in each step.
+	pub memory: bool,
+	/// Include storage in each step.
+	pub storage: bool,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			memory: true,
+			storage: true,
+		}
+	}
+}
+
+/// Standard JSON formatting informant.
+#[derive(Default, Clone)]
+pub struct Informant {
+	config: Config,
+	depth: usize,
+	pc: usize,
+	instruction: u8,
+	gas_cost: U256,
+	stack: Vec<U256>,
+	memory: Vec<u8>,
+	storage: HashMap<H256, H256>,
+}
+
+impl Informant {
+	/// Creates new informant with given output configuration.
+	pub fn new(config: Config) -> Self {
+		Informant {
+			config: config,
+			..Default::default()
+		}
+	}
+
+	fn stack(&self) -> String {
+		let items = self.stack.iter().map(display::u256_as_str).collect::<Vec<_>>();
+		format!("[{}]", items.join(","))
+	}
+
+	fn memory(&self) -> String {
+		format!(",\"memory\":\"0x{}\",\"memSize\":{}", self.memory.to_hex(), self.memory.len())
+	}
+
+	fn storage(&self) -> String {
+		let vals = self.storage.iter()
+			.map(|(k, v)| format!("\"0x{:?}\":\"0x{:?}\"", k, v))
+			.collect::<Vec<_>>();
+		format!(",\"storage\":{{{}}}", vals.join(","))
+	}
+}
+
+impl vm::Informant for Informant {
+	fn finish(&mut self, result: Result<vm::Success, vm::Failure>) {
+		match result {
+			Ok(success) => println!(
+				"{{\"output\":\"0x{output}\",\"gasUsed\":{gas},\"time\":{time}}}",
+				output = success.output.to_hex(),
+				gas = display::u256_as_str(&success.gas_used),
+				time = display::as_micros(&success.time),
+			),
+			Err(failure) => println!(
+				"{{\"error\":\"{error}\",\"gasUsed\":{gas},\"time\":{time}}}",
+				error = failure.error,
+				gas = display::u256_as_str(&failure.gas_used),
+				time = display::as_micros(&failure.time),
+			),
+		}
+	}
+
+	fn state_test(&mut self, result: &vm::StateTestResult) {
+		println!(
+			"{{\"name\":\"{name}\",\"fork\":\"{fork}\",\"index\":{index},\"pass\":{pass},\"stateRoot\":\"0x{root:?}\",\"gasUsed\":{gas},\"time\":{time}}}",
+			name = result.name,
+			fork = result.fork,
+			index = result.index,
+			pass = result.passed(),
+			root = result.root,
+			gas = display::u256_as_str(&result.gas_used),
+			time = display::as_micros(&result.time),
+		);
+	}
+
+	fn state_test_unsupported(&mut self, name: &str, fork: &str) {
+		println!("{{\"name\":\"{name}\",\"fork\":\"{fork}\",\"unsupported\":true}}", name = name, fork = fork);
+	}
+
+	fn state_tests_summary(&mut self, passed: usize, failed: usize) {
+		println!("{{\"passed\":{passed},\"failed\":{failed}}}", passed = passed, failed = failed);
+	}
+}
+
+impl trace::VMTracer for Informant {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.pc = pc;
+		self.instruction = instruction;
+		self.gas_cost = *gas_cost;
+		true
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let info = evm::INSTRUCTIONS[self.instruction as usize];
+
+		let _ = writeln!(
+			io::stderr(),
+			"{{\"pc\":{pc},\"op\":{op},\"gas\":{gas},\"gasCost\":{gas_cost}{memory},\"stack\":{stack}{storage},\"depth\":{depth},\"opName\":\"{name}\"}}",
+			pc = self.pc,
+			op = self.instruction,
+			gas = display::u256_as_str(&(gas_used + self.gas_cost)),
+			gas_cost = display::u256_as_str(&self.gas_cost),
+			memory = if self.config.memory { self.memory() } else { String::new() },
+			stack = self.stack(),
+			storage = if self.config.storage { self.storage() } else { String::new() },
+			depth = self.depth,
+			name = info.name,
+		);
+
+		let len = self.stack.len();
+		self.stack.truncate(len - info.args);
+		self.stack.extend_from_slice(stack_push);
+
+		if let Some((pos, data)) = mem_diff {
+			let end = pos + data.len();
+			if self.memory.len() < end {
+				// memory is always expanded to whole words
+				self.memory.resize((end + 31) / 32 * 32, 0);
+			}
+			self.memory[pos..end].copy_from_slice(data);
+		}
+
+		if let Some((pos, val)) = store_diff {
+			self.storage.insert(pos.into(), val.into());
+		}
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self where Self: Sized {
+		let mut vm = Informant::new(self.config);
+		vm.depth = self.depth + 1;
+		vm
+	}
+
+	fn done_subtrace(&mut self, _sub: Self, _is_successful: bool) where Self: Sized {}
+
+	fn drain(self) -> Option<trace::VMTrace> { None }
+}
@@ -25,6 +25,8 @@ extern crate serde;
 extern crate serde_derive;
 extern crate docopt;
 extern crate ethcore_util as util;
+extern crate ethjson;
+extern crate rlp;
 
 use std::sync::Arc;
 use std::{fmt, fs};
@@ -33,6 +35,8 @@ use rustc_hex::FromHex;
 use util::{U256, Bytes, Address};
 use ethcore::spec;
 use ethcore::action_params::ActionParams;
+use ethcore::client::{EvmTestClient, EnvInfo};
+use ethcore::pod_state::PodState;
 
 mod vm;
 mod display;
@@ -45,6 +49,7 @@ EVM implementation for Parity.
 
 Usage:
     evmbin stats [options]
+    evmbin state-test <file> [--only NAME --fork FORK --json --std-json --no-memory --no-storage]
     evmbin [options]
     evmbin [-h | --help]
 
@@ -54,8 +59,18 @@ Transaction options:
     --input DATA       Input data as hex (without 0x).
     --gas GAS          Supplied gas as hex (without 0x).
 
+State test options:
+    --only NAME        Runs only a single test matching the name.
+    --fork FORK        Runs only the post states of given fork
+                       (e.g. Frontier, Homestead, EIP150, EIP158,
+                       Byzantium, Constantinople).
+
 General options:
     --json             Display verbose results in JSON.
+    --std-json         Display results in standard JSON format. Each executed
+                       instruction is written to stderr as a single line.
+    --no-memory        Omit memory from --std-json output.
+    --no-storage       Omit storage from --std-json output.
     --chain CHAIN      Chain spec file path.
     -h, --help         Display this message and exit.
 "#;
@@ -64,10 +79,77 @@ General options:
 fn main() {
 	let args: Args = Docopt::new(USAGE).and_then(|d| d.deserialize()).unwrap_or_else(|e| e.exit());
 
-	if args.flag_json {
-		run(args, display::json::Informant::default())
-	} else {
-		run(args, display::simple::Informant::default())
+	let config = display::std_json::Config {
+		memory: !args.flag_no_memory,
+		storage: !args.flag_no_storage,
+	};
+
+	match (args.cmd_state_test, args.flag_std_json, args.flag_json) {
+		(true, true, _) => run_state_test(args, display::std_json::Informant::new(config)),
+		(true, false, true) => run_state_test(args, display::json::Informant::default()),
+		(true, false, false) => run_state_test(args, display::simple::Informant::default()),
+		(false, true, _) => run(args, display::std_json::Informant::new(config)),
+		(false, false, true) => run(args, display::json::Informant::default()),
+		(false, false, false) => run(args, display::simple::Informant::default()),
+	}
+}
+
+fn run_state_test<T: Informant + Clone>(args: Args, mut informant: T) {
+	let path = args.arg_file.expect("FILE is required by docopt; qed");
+	let file = fs::File::open(&path).unwrap_or_else(|e| die(format!("Unable to open {}: {}", path, e)));
+	let tests = ethjson::state::test::Test::load(file).unwrap_or_else(|e| die(format!("Unable to load the test file: {}", e)));
+	let only_test = args.flag_only.map(|name| name.to_lowercase());
+	let only_fork = args.flag_fork.map(|fork| fork.to_lowercase());
+
+	let (mut passed, mut failed) = (0, 0);
+	for (name, test) in tests {
+		if let Some(false) = only_test.as_ref().map(|only| &name.to_lowercase() == only) {
+			continue;
+		}
+
+		let multitransaction = test.transaction;
+		let env_info: EnvInfo = test.env.into();
+		let pre_state: PodState = test.pre_state.into();
+
+		for (fork_spec, states) in test.post_states {
+			let fork = format!("{:?}", fork_spec);
+			if let Some(false) = only_fork.as_ref().map(|only| &fork.to_lowercase() == only) {
+				continue;
+			}
+
+			let spec = match EvmTestClient::spec_from_json(&fork_spec) {
+				Some(spec) => spec,
+				None => {
+					informant.state_test_unsupported(&name, &fork);
+					continue;
+				},
+			};
+
+			for (index, state) in states.into_iter().enumerate() {
+				let state_test = vm::StateTest {
+					name: &name,
+					fork: &fork,
+					index: index,
+					pre_state: &pre_state,
+					post_root: state.hash.into(),
+					logs: state.logs.map(Into::into),
+					env_info: &env_info,
+				};
+				let transaction = multitransaction.select(&state.indexes).into();
+				let result = vm::run_transaction(state_test, spec, transaction, informant.clone());
+				if result.passed() {
+					passed += 1;
+				} else {
+					failed += 1;
+				}
+				informant.state_test(&result);
+			}
+		}
+	}
+
+	informant.state_tests_summary(passed, failed);
+	if failed > 0 {
+		::std::process::exit(1);
 	}
 }
 
@@ -93,12 +175,19 @@ fn run<T: Informant>(args: Args, mut informant: T) {
 #[derive(Debug, Deserialize)]
 struct Args {
 	cmd_stats: bool,
+	cmd_state_test: bool,
+	arg_file: Option<String>,
+	flag_only: Option<String>,
+	flag_fork: Option<String>,
 	flag_from: Option<String>,
 	flag_code: Option<String>,
 	flag_gas: Option<String>,
 	flag_input: Option<String>,
 	flag_spec: Option<String>,
 	flag_json: bool,
+	flag_std_json: bool,
+	flag_no_memory: bool,
+	flag_no_storage: bool,
 }
 
 impl Args {
@@ -17,10 +17,13 @@
 //! VM runner.
 
 use std::time::{Instant, Duration};
-use util::U256;
-use ethcore::{trace, spec};
-use ethcore::client::{EvmTestClient, EvmTestError};
+use rlp;
+use util::{U256, H256, Hashable};
+use ethcore::{trace, spec, pod_state};
+use ethcore::client::{EvmTestClient, EvmTestError, TransactResult, EnvInfo};
 use ethcore::action_params::ActionParams;
+use ethcore::log_entry::LogEntry;
+use ethcore::transaction::SignedTransaction;
 
 /// VM execution informant
 pub trait Informant: trace::VMTracer {
@@ -28,6 +31,12 @@ pub trait Informant: trace::VMTracer {
 	fn set_gas(&mut self, _gas: U256) {}
 	/// Display final result.
 	fn finish(&mut self, result: Result<Success, Failure>);
+	/// Display result of a single state test.
+	fn state_test(&mut self, result: &StateTestResult);
+	/// Display post states of a fork that can't be executed.
+	fn state_test_unsupported(&mut self, name: &str, fork: &str);
+	/// Display summary of all executed state tests.
+	fn state_tests_summary(&mut self, passed: usize, failed: usize);
 }
 
 /// Execution finished correctly
@@ -50,9 +59,110 @@ pub struct Failure {
 	pub time: Duration,
 }
 
+/// Outcome of a single state test
+pub struct StateTestResult {
+	/// Test name
+	pub name: String,
+	/// Fork the test was executed with
+	pub fork: String,
+	/// Index of the post state within the fork
+	pub index: usize,
+	/// Expected post state root
+	pub expected_root: H256,
+	/// Actual post state root
+	pub root: H256,
+	/// Expected logs hash, if present in the fixture
+	pub expected_logs: Option<H256>,
+	/// Hash of RLP-encoded logs
+	pub logs: H256,
+	/// Used gas
+	pub gas_used: U256,
+	/// Rejection or execution error, if any
+	pub error: Option<String>,
+	/// Time Taken
+	pub time: Duration,
+}
+
+impl StateTestResult {
+	/// Returns true if post state root and logs match the expectations.
+	pub fn passed(&self) -> bool {
+		self.root == self.expected_root && self.expected_logs.map_or(true, |logs| logs == self.logs)
+	}
+}
+
+/// State test to execute.
+pub struct StateTest<'a> {
+	/// Test name
+	pub name: &'a str,
+	/// Fork name
+	pub fork: &'a str,
+	/// Index of the post state within the fork
+	pub index: usize,
+	/// Pre state
+	pub pre_state: &'a pod_state::PodState,
+	/// Expected post state root
+	pub post_root: H256,
+	/// Expected logs hash
+	pub logs: Option<H256>,
+	/// Environment
+	pub env_info: &'a EnvInfo,
+}
+
+/// Execute given transaction on top of the test's pre state and compare the outcome with expectations.
+pub fn run_transaction<T: trace::VMTracer>(
+	test: StateTest,
+	spec: &spec::Spec,
+	transaction: SignedTransaction,
+	vm_tracer: T,
+) -> StateTestResult {
+	let mut result = StateTestResult {
+		name: test.name.into(),
+		fork: test.fork.into(),
+		index: test.index,
+		expected_root: test.post_root,
+		root: H256::default(),
+		expected_logs: test.logs,
+		logs: logs_hash(&[]),
+		gas_used: 0.into(),
+		error: None,
+		time: Duration::from_secs(0),
+	};
+
+	let mut test_client = match EvmTestClient::from_pod_state(spec, test.pre_state.clone()) {
+		Ok(client) => client,
+		Err(error) => {
+			result.error = Some(format!("{}", error));
+			return result;
+		},
+	};
+
+	let initial_gas = transaction.gas;
+	let start = Instant::now();
+	let outcome = test_client.transact(test.env_info, transaction, vm_tracer);
+	result.time = start.elapsed();
+
+	match outcome {
+		TransactResult::Ok { state_root, gas_left, logs, .. } => {
+			result.root = state_root;
+			result.gas_used = initial_gas - gas_left;
+			result.logs = logs_hash(&logs);
+		},
+		TransactResult::Err { state_root, error } => {
+			result.root = state_root;
+			result.error = Some(format!("{}", error));
+		},
+	}
+
+	result
+}
+
+fn logs_hash(logs: &[LogEntry]) -> H256 {
+	rlp::encode_list::<LogEntry, _>(logs).to_vec().sha3()
+}
+
 /// Execute VM with given `ActionParams`
 pub fn run<T: trace::VMTracer>(vm_tracer: &mut T, spec: spec::Spec, params: ActionParams) -> Result<Success, Failure> {
-	let mut test_client = EvmTestClient::new(spec).map_err(|error| Failure {
+	let mut test_client = EvmTestClient::new(&spec).map_err(|error| Failure {
 		gas_used: 0.into(),
 		error,
 		time: Duration::from_secs(0)
@@ -798,6 +798,40 @@ export default {
     }
   },
 
+  snapshotStatus: {
+    section: SECTION_NODE,
+    desc: 'Returns the progress of the snapshot currently being taken.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'The progress object, or `null` if no snapshot is being taken.',
+      details: {
+        blockNumber: {
+          type: Quantity,
+          desc: 'Number of the block the snapshot is taken at.'
+        },
+        accounts: {
+          type: Quantity,
+          desc: 'Number of accounts written so far.'
+        },
+        blocks: {
+          type: Quantity,
+          desc: 'Number of blocks written so far.'
+        },
+        size: {
+          type: Quantity,
+          desc: 'Size of the chunks written so far, in bytes.'
+        }
+      },
+      example: {
+        blockNumber: fromDecimal(3000000),
+        accounts: fromDecimal(1205672),
+        blocks: fromDecimal(0),
+        size: fromDecimal(134217728)
+      }
+    }
+  },
+
   transactionsLimit: {
     section: SECTION_MINING,
     desc: 'Changes limit for transactions in queue.',
@@ -1795,6 +1829,24 @@ export default {
     }
   },
 
+  takeSnapshot: {
+    subdoc: SUBDOC_SET,
+    desc: 'Starts taking a snapshot at the given block in the background. A snapshot of the same block interrupted by a restart is resumed. The progress can be tracked with [parity_snapshotStatus](#parity_snapshotstatus).',
+    params: [
+      {
+        type: BlockNumber,
+        desc: 'integer block number, or the string `\'latest\'`, `\'earliest\'` or `\'pending\'`.',
+        format: 'inputDefaultBlockNumberFormatter',
+        example: fromDecimal(3000000)
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: '`true` if the snapshot was started, `false` if another one is currently being taken.',
+      example: true
+    }
+  },
+
   /*
    * `parity_signing` trait methods (rolled into `parity` module)
    * ============================================================
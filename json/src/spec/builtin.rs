@@ -34,6 +34,15 @@ pub struct Modexp {
 	pub divisor: usize,
 }
 
+/// Pricing for alt_bn128 pairing check.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AltBn128Pairing {
+	/// Base price.
+	pub base: usize,
+	/// Price per point pair.
+	pub pair: usize,
+}
+
 /// Pricing variants.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 pub enum Pricing {
@@ -43,6 +52,9 @@ pub enum Pricing {
 	/// Pricing for modular exponentiation.
 	#[serde(rename="modexp")]
 	Modexp(Modexp),
+	/// Pricing for alt_bn128 pairing check.
+	#[serde(rename="alt_bn128_pairing")]
+	AltBn128Pairing(AltBn128Pairing),
 }
 
 /// Spec builtin.
@@ -59,7 +71,7 @@ pub struct Builtin {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use spec::builtin::{Builtin, Pricing, Linear, Modexp};
+	use spec::builtin::{Builtin, Pricing, Linear, Modexp, AltBn128Pairing};
 	use uint::Uint;
 
 	#[test]
@@ -87,4 +99,15 @@ mod tests {
 		assert_eq!(deserialized.pricing, Pricing::Modexp(Modexp { divisor: 5 }));
 		assert_eq!(deserialized.activate_at, Some(Uint(100000.into())));
 	}
+
+	#[test]
+	fn alt_bn128_pairing_deserialization() {
+		let s = r#"{
+			"name": "bn128_pairing",
+			"pricing": { "alt_bn128_pairing": { "base": 100000, "pair": 80000 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.pricing, Pricing::AltBn128Pairing(AltBn128Pairing { base: 100000, pair: 80000 }));
+	}
 }
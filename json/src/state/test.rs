@@ -100,6 +100,8 @@ impl MultiTransaction {
 /// State test transaction deserialization.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
 pub enum ForkSpec {
+	Byzantium,
+	Constantinople,
 	EIP150,
 	EIP158,
 	Frontier,
@@ -123,14 +125,17 @@ pub struct PostStateIndexes {
 pub struct PostStateResult {
 	/// Post state hash
 	pub hash: H256,
+	/// Hash of RLP-encoded logs produced by the transaction.
+	pub logs: Option<H256>,
 	/// Indexes
 	pub indexes: PostStateIndexes,
 }
 
 #[cfg(test)]
 mod tests {
+	use std::collections::BTreeMap;
 	use serde_json;
-	use super::{MultiTransaction, State};
+	use super::{MultiTransaction, State, ForkSpec, PostStateResult};
 
 	#[test]
 	fn multi_transaction_deserialization() {
@@ -222,4 +227,27 @@ mod tests {
 		let _deserialized: State = serde_json::from_str(s).unwrap();
 		// TODO: validate all fields
 	}
+
+	#[test]
+	fn post_state_result_deserialization() {
+		let s = r#"{
+			"Byzantium" : [
+				{
+					"hash" : "0x3e6dacc1575c6a8c76422255eca03529bbf4c0dda75dfc110b22d6dc4152396f",
+					"indexes" : { "data" : 0, "gas" : 1, "value" : 0 },
+					"logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+				}
+			],
+			"EIP158" : [
+				{
+					"hash" : "0x3e6dacc1575c6a8c76422255eca03529bbf4c0dda75dfc110b22d6dc4152396f",
+					"indexes" : { "data" : 0, "gas" : 0, "value" : 0 }
+				}
+			]
+		}"#;
+		let deserialized: BTreeMap<ForkSpec, Vec<PostStateResult>> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized[&ForkSpec::Byzantium][0].indexes.gas, 1);
+		assert!(deserialized[&ForkSpec::Byzantium][0].logs.is_some());
+		assert!(deserialized[&ForkSpec::EIP158][0].logs.is_none());
+	}
 }
@@ -17,22 +17,34 @@
 use std::str::{FromStr, from_utf8};
 use std::{io, fs};
 use std::io::{BufReader, BufRead};
+use std::path::Path;
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
+use std::collections::HashSet;
 use rustc_hex::FromHex;
-use util::{ToPretty, U256, H256, Address, Hashable};
-use rlp::PayloadInfo;
+use util::{ToPretty, Bytes, U256, H256, Address, Hashable, DatabaseBackend, DatabaseConfig, ordered_trie_root};
+use util::journaldb::Algorithm;
+use util::kvdb::open_database;
+use rlp::{PayloadInfo, UntrustedRlp};
+use serde_json::{self, Value, Map};
 use ethcore::service::ClientService;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
+use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockChainConfig, BlockId};
 use ethcore::error::ImportError;
 use ethcore::miner::Miner;
+use ethcore::db::NUM_COLUMNS;
+use ethcore::pruning::prune_archive;
+use ethcore::integrity::verify_database;
 use ethcore::verification::queue::VerifierSettings;
+use ethcore::block::Block;
+use ethcore::receipt::Receipt;
 use cache::CacheConfig;
 use informant::{Informant, MillisecondDuration};
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
 use helpers::{to_client_config, execute_upgrades};
+use migration::{update_version, check_backend};
 use dir::Directories;
+use era::{EraEntry, EraReader, EraWriter, era_files};
 use user_defaults::UserDefaults;
 use fdlimit;
 
@@ -40,6 +52,7 @@ use fdlimit;
 pub enum DataFormat {
 	Hex,
 	Binary,
+	Era,
 }
 
 impl Default for DataFormat {
@@ -55,6 +68,7 @@ impl FromStr for DataFormat {
 		match s {
 			"binary" | "bin" => Ok(DataFormat::Binary),
 			"hex" => Ok(DataFormat::Hex),
+			"era" => Ok(DataFormat::Era),
 			x => Err(format!("Invalid format: {}", x))
 		}
 	}
@@ -63,6 +77,8 @@ impl FromStr for DataFormat {
 #[derive(Debug, PartialEq)]
 pub enum BlockchainCmd {
 	Kill(KillBlockchain),
+	Prune(PruneBlockchain),
+	Verify(VerifyBlockchain),
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
@@ -75,6 +91,29 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct PruneBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub target: Algorithm,
+	pub keep: u64,
+	pub compaction: DatabaseCompactionProfile,
+	pub db_backend: DatabaseBackend,
+	pub ancient_depth: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub from: u64,
+	pub state: bool,
+	pub compaction: DatabaseCompactionProfile,
+	pub db_backend: DatabaseBackend,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -85,8 +124,10 @@ pub struct ImportBlockchain {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub ancient_depth: u64,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub tracing: Switch,
 	pub fat_db: Switch,
 	pub vm_type: VMType,
@@ -105,8 +146,10 @@ pub struct ExportBlockchain {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub ancient_depth: u64,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub fat_db: Switch,
 	pub tracing: Switch,
 	pub from_block: BlockId,
@@ -124,8 +167,10 @@ pub struct ExportState {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub ancient_depth: u64,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub fat_db: Switch,
 	pub tracing: Switch,
 	pub at: BlockId,
@@ -133,11 +178,17 @@ pub struct ExportState {
 	pub code: bool,
 	pub min_balance: Option<U256>,
 	pub max_balance: Option<U256>,
+	pub accounts: Option<Vec<Address>>,
+	pub code_only: bool,
+	pub storage_only: bool,
+	pub resume_after: Option<Address>,
 }
 
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
+		BlockchainCmd::Prune(prune_cmd) => prune_db(prune_cmd),
+		BlockchainCmd::Verify(verify_cmd) => verify_db(verify_cmd),
 		BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
@@ -178,7 +229,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()), cmd.db_backend, cmd.ancient_depth)?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(false, false, false)?;
@@ -192,11 +243,14 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 		fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.vm_type,
 		"".into(),
 		algorithm,
 		cmd.pruning_history,
 		cmd.pruning_memory,
+		db_dirs.ancient_path(algorithm),
+		cmd.ancient_depth,
 		cmd.check_seal
 	);
 
@@ -210,6 +264,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 		&snapshot_path,
 		&cmd.dirs.ipc_path(),
 		Arc::new(Miner::with_spec(&spec)),
+		None,
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
 	// free up the spec in memory.
@@ -217,8 +272,16 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	// era archives are read directly from their files.
+	let format = match cmd.format {
+		Some(format) => Some(format),
+		None if cmd.file_path.as_ref().map_or(false, |f| Path::new(f).is_dir()) => Some(DataFormat::Era),
+		None => None,
+	};
+
 	let mut instream: Box<io::Read> = match cmd.file_path {
-		Some(f) => Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?),
+		Some(_) if format == Some(DataFormat::Era) => Box::new(io::empty()),
+		Some(ref f) => Box::new(fs::File::open(f).map_err(|_| format!("Cannot open given file: {}", f))?),
 		None => Box::new(io::stdin()),
 	};
 
@@ -227,7 +290,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	let mut first_bytes: Vec<u8> = vec![0; READAHEAD_BYTES];
 	let mut first_read = 0;
 
-	let format = match cmd.format {
+	let format = match format {
 		Some(format) => format,
 		None => {
 			first_read = instream.read(&mut first_bytes).map_err(|_| "Error reading from the file/stream.")?;
@@ -281,6 +344,10 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 				do_import(bytes)?;
 			}
 		}
+		DataFormat::Era => {
+			let path = cmd.file_path.clone().ok_or("Importing era archives requires a file or directory.")?;
+			import_era(&*client, Path::new(&path), &do_import)?;
+		}
 	}
 	client.flush_queue();
 
@@ -305,16 +372,95 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+// import blocks from era files. blocks filling the gap below a restored snapshot are
+// inserted along with their receipts without being executed, if the receipts match
+// the receipts root of the block. all other blocks are imported as usual.
+fn import_era<F>(client: &BlockChainClient, path: &Path, do_import: &F) -> Result<(), String>
+	where F: Fn(Bytes) -> Result<(), String>
+{
+	let files = if path.is_dir() { era_files(path)? } else { vec![path.to_owned()] };
+	let genesis_hash = client.chain_info().genesis_hash;
+
+	for file in files {
+		let mut reader = EraReader::open(&file)?;
+		if reader.genesis_hash() != genesis_hash {
+			return Err(format!("Era file {} belongs to a different chain.", file.display()));
+		}
+
+		info!("Importing {} blocks from #{} out of {}", reader.len(), reader.first_block(), file.display());
+
+		for i in 0..reader.len() {
+			let number = reader.first_block() + i as u64;
+			let entry = reader.entry(i)?;
+			// era files come from untrusted sources, so the block may be malformed.
+			let header = UntrustedRlp::new(&entry.block).as_val::<Block>()
+				.map_err(|e| format!("Invalid block #{} in era file {}: {}", number, file.display(), e))?
+				.header;
+			if header.number() != number {
+				return Err(format!("Era file {} holds block #{} in place of #{}.", file.display(), header.number(), number));
+			}
+
+			let chain_info = client.chain_info();
+			let in_gap = match (chain_info.ancient_block_number, chain_info.first_block_number) {
+				(Some(ancient), Some(first)) => number > ancient && number < first,
+				_ => false,
+			};
+
+			let receipts = match entry.receipts {
+				Some(receipts) => receipts,
+				None => {
+					do_import(entry.block)?;
+					continue;
+				}
+			};
+
+			if !in_gap {
+				do_import(entry.block)?;
+				continue;
+			}
+
+			{
+				let receipts_rlp = UntrustedRlp::new(&receipts);
+				receipts_rlp.item_count().and_then(|_| receipts_rlp.as_list::<Receipt>())
+					.map_err(|e| format!("Invalid receipts of block #{} in era file {}: {}", number, file.display(), e))?;
+				let receipts_root = ordered_trie_root(receipts_rlp.iter().map(|r| r.as_raw().to_vec()));
+				if &receipts_root != header.receipts_root() {
+					return Err(format!("Receipts of block #{} do not match its receipts root.", number));
+				}
+			}
+
+			match client.import_block_with_receipts(entry.block, receipts) {
+				Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
+					trace!("Skipping block already in chain.");
+					continue;
+				}
+				Err(e) => {
+					return Err(format!("Cannot import block: {:?}", e));
+				},
+				Ok(_) => {},
+			}
+
+			if client.block_total_difficulty(BlockId::Hash(header.hash())) != Some(entry.total_difficulty) {
+				return Err(format!("Total difficulty of block #{} does not match the era file.", number));
+			}
+		}
+	}
+
+	Ok(())
+}
+
 fn start_client(
 	dirs: Directories,
 	spec: SpecType,
 	pruning: Pruning,
 	pruning_history: u64,
 	pruning_memory: usize,
+	ancient_depth: u64,
 	tracing: Switch,
 	fat_db: Switch,
 	compaction: DatabaseCompactionProfile,
 	wal: bool,
+	db_backend: DatabaseBackend,
 	cache_config: CacheConfig,
 	require_fat_db: bool,
 ) -> Result<ClientService, String> {
@@ -353,7 +499,7 @@ fn start_client(
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&dirs.base, &db_dirs, algorithm, compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&dirs.base, &db_dirs, algorithm, compaction.compaction_profile(db_dirs.db_root_path().as_path()), db_backend, ancient_depth)?;
 
 	// create dirs used by parity
 	dirs.create_dirs(false, false, false)?;
@@ -367,11 +513,14 @@ fn start_client(
 		fat_db,
 		compaction,
 		wal,
+		db_backend,
 		VMType::default(),
 		"".into(),
 		algorithm,
 		pruning_history,
 		pruning_memory,
+		db_dirs.ancient_path(algorithm),
+		ancient_depth,
 		true,
 	);
 
@@ -382,6 +531,7 @@ fn start_client(
 		&snapshot_path,
 		&dirs.ipc_path(),
 		Arc::new(Miner::with_spec(&spec)),
+		None,
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
 	drop(spec);
@@ -395,10 +545,12 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		cmd.pruning,
 		cmd.pruning_history,
 		cmd.pruning_memory,
+		cmd.ancient_depth,
 		cmd.tracing,
 		cmd.fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.cache_config,
 		false,
 	)?;
@@ -406,14 +558,19 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	if format == DataFormat::Era {
+		let dir = cmd.file_path.ok_or("Exporting era archives requires a directory.")?;
+		return export_era(&*client, Path::new(&dir), from, to);
+	}
+
 	let mut out: Box<io::Write> = match cmd.file_path {
 		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
 		None => Box::new(io::stdout()),
 	};
 
-	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
-	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
-
 	for i in from..(to + 1) {
 		if i % 10000 == 0 {
 			info!("#{}", i);
@@ -422,6 +579,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		match format {
 			DataFormat::Binary => { out.write(&b).expect("Couldn't write to stream."); }
 			DataFormat::Hex => { out.write_fmt(format_args!("{}", b.pretty())).expect("Couldn't write to stream."); }
+			DataFormat::Era => unreachable!("era archives are exported above; qed"),
 		}
 	}
 
@@ -429,58 +587,120 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+// export blocks along with their receipts and total difficulty into era files.
+fn export_era(client: &BlockChainClient, dir: &Path, from: u64, to: u64) -> Result<(), String> {
+	let mut writer = EraWriter::new(dir, client.chain_info().genesis_hash)?;
+
+	for i in from..(to + 1) {
+		if i % 10000 == 0 {
+			info!("#{}", i);
+		}
+		let hash = client.block_hash(BlockId::Number(i)).ok_or("Error exporting incomplete chain")?;
+		let entry = EraEntry {
+			block: client.block(BlockId::Hash(hash)).ok_or("Error exporting incomplete chain")?.into_inner(),
+			receipts: client.block_receipts(&hash),
+			total_difficulty: client.block_total_difficulty(BlockId::Hash(hash)).ok_or("Error exporting incomplete chain")?,
+		};
+		writer.write(i, &entry)?;
+	}
+
+	writer.finish()?;
+	info!("Export completed.");
+	Ok(())
+}
+
 fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	let service = start_client(
-		cmd.dirs,
-		cmd.spec,
-		cmd.pruning,
+		cmd.dirs.clone(),
+		cmd.spec.clone(),
+		cmd.pruning.clone(),
 		cmd.pruning_history,
 		cmd.pruning_memory,
+		cmd.ancient_depth,
 		cmd.tracing,
 		cmd.fat_db,
-		cmd.compaction,
+		cmd.compaction.clone(),
 		cmd.wal,
-		cmd.cache_config,
+		cmd.db_backend,
+		cmd.cache_config.clone(),
 		true
 	)?;
 
 	let client = service.client();
 
 	let mut out: Box<io::Write> = match cmd.file_path {
-		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
+		Some(ref f) => Box::new(fs::File::create(f).map_err(|_| format!("Cannot write to file given: {}", f))?),
 		None => Box::new(io::stdout()),
 	};
 
-	let mut last: Option<Address> = None;
+	export_state(&*client, &cmd, &mut *out)?;
+	info!("Export completed.");
+	Ok(())
+}
+
+fn export_state(client: &BlockChainClient, cmd: &ExportState, out: &mut io::Write) -> Result<(), String> {
 	let at = cmd.at;
+	let mut last: Option<Address> = cmd.resume_after;
 	let mut i = 0usize;
 
+	// the given accounts without duplicates, following the one to resume after.
+	let mut given = match cmd.accounts {
+		Some(ref accounts) => {
+			let mut seen = HashSet::new();
+			let accounts: Vec<Address> = accounts.iter().filter(|a| seen.insert(**a)).cloned().collect();
+			let from = match last {
+				Some(ref last) => accounts.iter().position(|a| a == last)
+					.ok_or_else(|| format!("Account 0x{} to resume after is not among the given accounts", last.hex()))? + 1,
+				None => 0,
+			};
+			Some(accounts[from..].to_vec())
+		},
+		None => None,
+	};
+
+	if let Some(ref account) = last {
+		info!("Resuming export after account 0x{}", account.hex());
+	}
+
 	out.write_fmt(format_args!("{{ \"state\": {{", )).expect("Couldn't write to stream.");
 	loop {
-		let accounts = client.list_accounts(at, last.as_ref(), 1000).ok_or("Specified block not found")?;
+		let accounts = match cmd.accounts {
+			// the given accounts are exported in a single pass.
+			Some(_) => given.take().unwrap_or_default(),
+			None => client.list_accounts(at, last.as_ref(), 1000).ok_or("Specified block not found")?,
+		};
 		if accounts.is_empty() {
 			break;
 		}
 
 		for account in accounts.into_iter() {
+			last = Some(account);
+
 			let balance = client.balance(&account, at).unwrap_or_else(U256::zero);
 			if cmd.min_balance.map_or(false, |m| balance < m) || cmd.max_balance.map_or(false, |m| balance > m) {
-				last = Some(account);
 				continue; //filtered out
 			}
 
+			let code = client.code(&account, at).unwrap_or(None).unwrap_or_else(Vec::new);
+			if cmd.code_only && code.is_empty() {
+				continue;
+			}
+
+			let storage_root = client.storage_root(&account, at).unwrap_or(::util::SHA3_NULL_RLP);
+			if cmd.storage_only && storage_root == ::util::SHA3_NULL_RLP {
+				continue;
+			}
+
 			if i != 0 {
 				out.write(b",").expect("Write error");
 			}
 			out.write_fmt(format_args!("\n\"0x{}\": {{\"balance\": \"{:x}\", \"nonce\": \"{:x}\"", account.hex(), balance, client.nonce(&account, at).unwrap_or_else(U256::zero))).expect("Write error");
-			let code = client.code(&account, at).unwrap_or(None).unwrap_or_else(Vec::new);
 			if !code.is_empty() {
 				out.write_fmt(format_args!(", \"code_hash\": \"0x{}\"", code.sha3().hex())).expect("Write error");
 				if cmd.code {
 					out.write_fmt(format_args!(", \"code\": \"{}\"", code.to_hex())).expect("Write error");
 				}
 			}
-			let storage_root = client.storage_root(&account, at).unwrap_or(::util::SHA3_NULL_RLP);
 			if storage_root != ::util::SHA3_NULL_RLP {
 				out.write_fmt(format_args!(", \"storage_root\": \"0x{}\"", storage_root.hex())).expect("Write error");
 				if cmd.storage {
@@ -506,13 +726,11 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 			out.write(b"}").expect("Write error");
 			i += 1;
 			if i % 10000 == 0 {
-				info!("Account #{}", i);
+				info!("Account #{}: 0x{}", i, account.hex());
 			}
-			last = Some(account);
 		}
 	}
 	out.write_fmt(format_args!("\n}}}}")).expect("Write error");
-	info!("Export completed.");
 	Ok(())
 }
 
@@ -529,14 +747,207 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+pub fn prune_db(cmd: PruneBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec()?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir.clone());
+	let user_defaults_path = db_dirs.user_defaults_path();
+	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	if algorithm != Algorithm::Archive {
+		return Err(format!("Only archive databases can be pruned, found {}.", algorithm));
+	}
+	if cmd.target != Algorithm::OverlayRecent {
+		return Err(format!("Pruning to {} is not supported. Use --to fast.", cmd.target));
+	}
+	if user_defaults.fat_db {
+		return Err("Databases with fat-db enabled cannot be pruned.".into());
+	}
+
+	let target_path = db_dirs.client_path(cmd.target);
+	if fs::metadata(&target_path).is_ok() {
+		return Err(format!("Database with {} pruning already exists at {}. Remove it with `parity db kill --pruning {}` first.", cmd.target, target_path.display(), cmd.target));
+	}
+
+	fdlimit::raise_fd_limit();
+
+	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction, cmd.db_backend, cmd.ancient_depth)?;
+
+	let mut db_config = DatabaseConfig::with_columns(NUM_COLUMNS);
+	db_config.compaction = compaction;
+	db_config.backend = cmd.db_backend;
+
+	let mut temp_path = db_dirs.db_path(cmd.target);
+	temp_path.push("prune");
+	if fs::metadata(&temp_path).is_ok() {
+		fs::remove_dir_all(&temp_path).map_err(|e| format!("Error removing stale temporary database: {}", e))?;
+	}
+	fs::create_dir_all(&temp_path).map_err(|e| format!("Error creating temporary database directory: {}", e))?;
+	let temp_path = temp_path.to_str().expect("DB path could not be converted to string.").to_owned();
+
+	info!("Pruning database to {}, keeping state of {} recent blocks", cmd.target, cmd.keep);
+	{
+		let source_path = db_dirs.client_path(algorithm);
+		let source = open_database(&db_config, source_path.to_str().expect("DB path could not be converted to string."))
+			.map_err(|e| format!("Error opening database: {}", e))?;
+		let dest = open_database(&db_config, &temp_path)
+			.map_err(|e| format!("Error creating temporary database: {}", e))?;
+		prune_archive(source, dest, &spec.genesis_block(), cmd.keep)
+			.map_err(|e| format!("Error pruning database: {}", e))?;
+	}
+
+	// blocks already moved out of the database live in the ancient block store.
+	let ancient_path = db_dirs.ancient_path(algorithm);
+	if fs::metadata(&ancient_path).is_ok() {
+		info!("Copying ancient blocks");
+		copy_dir(&ancient_path, &db_dirs.ancient_path(cmd.target))
+			.map_err(|e| format!("Error copying ancient blocks: {}", e))?;
+	}
+
+	let target = open_database(&db_config, target_path.to_str().expect("DB path could not be converted to string."))
+		.map_err(|e| format!("Error creating database: {}", e))?;
+	target.restore(&temp_path).map_err(|e| format!("Error replacing database: {}", e))?;
+	update_version(&db_dirs.db_path(cmd.target), cmd.db_backend).map_err(|e| format!("{}", e))?;
+
+	user_defaults.pruning = cmd.target;
+	user_defaults.save(&user_defaults_path)?;
+
+	info!("Database pruned. The archive database can be removed with `parity db kill --pruning archive`.");
+	Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+	fs::create_dir_all(to)?;
+	for entry in fs::read_dir(from)? {
+		let entry = entry?;
+		fs::copy(entry.path(), to.join(entry.file_name()))?;
+	}
+	Ok(())
+}
+
+pub fn verify_db(cmd: VerifyBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec()?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir.clone());
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	let client_path = db_dirs.client_path(algorithm);
+	if fs::metadata(&client_path).is_err() {
+		return Err(format!("Database not found at {}.", client_path.display()));
+	}
+
+	check_backend(&db_dirs.db_path(algorithm), cmd.db_backend).map_err(|e| format!("{}", e))?;
+
+	fdlimit::raise_fd_limit();
+
+	let mut db_config = DatabaseConfig::with_columns(NUM_COLUMNS);
+	db_config.compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+	db_config.backend = cmd.db_backend;
+	let db = open_database(&db_config, client_path.to_str().expect("DB path could not be converted to string."))
+		.map_err(|e| format!("Error opening database: {}", e))?;
+
+	let state = if cmd.state { Some(algorithm) } else { None };
+	let chain_config = BlockChainConfig {
+		ancient_path: Some(db_dirs.ancient_path(algorithm)),
+		..Default::default()
+	};
+	let report = verify_database(db, chain_config, &spec.genesis_block(), cmd.from, state);
+
+	let problems: Vec<Value> = report.problems.iter().map(|problem| {
+		let mut entry = Map::new();
+		entry.insert("kind".into(), Value::String(problem.kind().into()));
+		if let Some((number, hash)) = problem.block() {
+			entry.insert("number".into(), Value::from(number));
+			entry.insert("hash".into(), Value::String(format!("0x{}", hash.hex())));
+		}
+		entry.insert("message".into(), Value::String(problem.to_string()));
+		Value::Object(entry)
+	}).collect();
+	let problem_count = problems.len();
+
+	let mut out = Map::new();
+	out.insert("from".into(), Value::from(report.from));
+	out.insert("best".into(), Value::from(report.best));
+	out.insert("blocks".into(), Value::from(report.blocks));
+	out.insert("accounts".into(), report.accounts.map_or(Value::Null, Value::from));
+	out.insert("problems".into(), Value::Array(problems));
+	println!("{}", serde_json::to_string_pretty(&Value::Object(out)).expect("Serialization of JSON values never fails; qed"));
+
+	match problem_count {
+		0 => Ok(()),
+		n => Err(format!("Database verification found {} problems.", n)),
+	}
+}
+
 #[cfg(test)]
 mod test {
-	use super::DataFormat;
+	use std::str::from_utf8;
+	use ethcore::client::{TestBlockChainClient, BlockId};
+	use util::{Address, U256, ToPretty};
+	use super::{DataFormat, ExportState, export_state};
+
+	fn export_state_cmd(accounts: Vec<Address>, resume_after: Option<Address>) -> ExportState {
+		ExportState {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: None,
+			format: None,
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			ancient_depth: 0,
+			compaction: Default::default(),
+			wal: true,
+			db_backend: Default::default(),
+			fat_db: Default::default(),
+			tracing: Default::default(),
+			at: BlockId::Latest,
+			storage: false,
+			code: false,
+			min_balance: None,
+			max_balance: None,
+			accounts: Some(accounts),
+			code_only: false,
+			storage_only: false,
+			resume_after: resume_after,
+		}
+	}
 
 	#[test]
 	fn test_data_format_parsing() {
 		assert_eq!(DataFormat::Binary, "binary".parse().unwrap());
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
+		assert_eq!(DataFormat::Era, "era".parse().unwrap());
+	}
+
+	#[test]
+	fn exports_given_accounts_once_after_resume_point() {
+		let client = TestBlockChainClient::new();
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		client.set_balance(a, U256::from(10));
+		client.set_balance(b, U256::from(20));
+		client.set_balance(c, U256::from(30));
+
+		let mut out: Vec<u8> = Vec::new();
+		export_state(&client, &export_state_cmd(vec![a, b, a, c, b], Some(a)), &mut out).unwrap();
+
+		let out = from_utf8(&out).unwrap();
+		assert!(!out.contains(&format!("\"0x{}\"", a.hex())));
+		assert_eq!(out.matches(&format!("\"0x{}\"", b.hex())).count(), 1);
+		assert_eq!(out.matches(&format!("\"0x{}\"", c.hex())).count(), 1);
+		assert!(out.ends_with("\n}}"));
+	}
+
+	#[test]
+	fn rejects_unknown_resume_point() {
+		let client = TestBlockChainClient::new();
+		let cmd = export_state_cmd(vec![Address::from(1), Address::from(2)], Some(Address::from(3)));
+		let mut out: Vec<u8> = Vec::new();
+		assert!(export_state(&client, &cmd, &mut out).is_err());
 	}
 }
@@ -41,6 +41,8 @@ usage! {
 		cmd_tools: bool,
 		cmd_hash: bool,
 		cmd_kill: bool,
+		cmd_prune: bool,
+		cmd_verify: bool,
 		cmd_db: bool,
 
 		// Arguments
@@ -162,6 +164,20 @@ usage! {
 			or |c: &Config| otry!(c.network).reserved_peers.clone().map(Some),
 		flag_reserved_only: bool = false,
 			or |c: &Config| otry!(c.network).reserved_only.clone(),
+		flag_enr_tree: Option<String> = None,
+			or |c: &Config| otry!(c.network).enr_tree.clone().map(Some),
+		flag_enr_tree_file: Option<String> = None,
+			or |c: &Config| otry!(c.network).enr_tree_file.clone().map(Some),
+		flag_upload_limit: Option<u64> = None,
+			or |c: &Config| otry!(c.network).upload_limit.clone().map(Some),
+		flag_download_limit: Option<u64> = None,
+			or |c: &Config| otry!(c.network).download_limit.clone().map(Some),
+		flag_peer_upload_limit: Option<u64> = None,
+			or |c: &Config| otry!(c.network).peer_upload_limit.clone().map(Some),
+		flag_peer_download_limit: Option<u64> = None,
+			or |c: &Config| otry!(c.network).peer_download_limit.clone().map(Some),
+		flag_peer_ban_duration: u64 = 3600u64,
+			or |c: &Config| otry!(c.network).peer_ban_duration.clone(),
 		flag_no_ancient_blocks: bool = false, or |_| None,
 		flag_no_serve_light: bool = false,
 			or |c: &Config| otry!(c.network).no_serve_light.clone(),
@@ -309,6 +325,10 @@ usage! {
 			or |c: &Config| otry!(c.footprint).pruning_history.clone(),
 		flag_pruning_memory: usize = 32usize,
 			or |c: &Config| otry!(c.footprint).pruning_memory.clone(),
+		flag_ancient_depth: u64 = 90000u64,
+			or |c: &Config| otry!(c.footprint).ancient_depth.clone(),
+		flag_history_keep_blocks: Option<u64> = None,
+			or |c: &Config| otry!(c.footprint).history_keep_blocks.clone().map(Some),
 		flag_cache_size_db: u32 = 32u32,
 			or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 		flag_cache_size_blocks: u32 = 8u32,
@@ -323,6 +343,8 @@ usage! {
 			or |c: &Config| otry!(c.footprint).fast_and_loose.clone(),
 		flag_db_compaction: String = "auto",
 			or |c: &Config| otry!(c.footprint).db_compaction.clone(),
+		flag_db_backend: String = "rocksdb",
+			or |c: &Config| otry!(c.footprint).db_backend.clone(),
 		flag_fat_db: String = "auto",
 			or |c: &Config| otry!(c.footprint).fat_db.clone(),
 		flag_scale_verifiers: bool = false,
@@ -339,11 +361,24 @@ usage! {
 		flag_no_code: bool = false, or |_| None,
 		flag_min_balance: Option<String> = None, or |_| None,
 		flag_max_balance: Option<String> = None, or |_| None,
+		flag_accounts: Option<String> = None, or |_| None,
+		flag_code_only: bool = false, or |_| None,
+		flag_storage_only: bool = false, or |_| None,
+		flag_resume_after: Option<String> = None, or |_| None,
 
 		// -- Snapshot Optons
 		flag_at: String = "latest", or |_| None,
 		flag_no_periodic_snapshot: bool = false,
 			or |c: &Config| otry!(c.snapshots).disable_periodic.clone(),
+		flag_trusted_snapshot_publishers: Option<String> = None,
+			or |c: &Config| otry!(c.snapshots).trusted_publishers.as_ref().map(|vec| Some(vec.join(","))),
+		flag_sign_with: Option<String> = None,
+			or |c: &Config| otry!(c.snapshots).sign_with.clone().map(Some),
+		flag_base_snapshot: Option<String> = None, or |_| None,
+
+		// -- Database Options
+		flag_keep: u64 = 64u64, or |_| None,
+		flag_state: bool = false, or |_| None,
 
 		// -- Virtual Machine Options
 		flag_jitvm: bool = false,
@@ -455,6 +490,13 @@ struct Network {
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
+	enr_tree: Option<String>,
+	enr_tree_file: Option<String>,
+	upload_limit: Option<u64>,
+	download_limit: Option<u64>,
+	peer_upload_limit: Option<u64>,
+	peer_download_limit: Option<u64>,
+	peer_ban_duration: Option<u64>,
 	no_serve_light: Option<bool>,
 }
 
@@ -560,6 +602,8 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	ancient_depth: Option<u64>,
+	history_keep_blocks: Option<u64>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -567,6 +611,7 @@ struct Footprint {
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
 	db_compaction: Option<String>,
+	db_backend: Option<String>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
@@ -575,6 +620,8 @@ struct Footprint {
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	trusted_publishers: Option<Vec<String>>,
+	sign_with: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -676,6 +723,8 @@ mod tests {
 			cmd_hash: false,
 			cmd_db: false,
 			cmd_kill: false,
+			cmd_prune: false,
+			cmd_verify: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
@@ -735,6 +784,13 @@ mod tests {
 			flag_node_key: None,
 			flag_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
+			flag_enr_tree: None,
+			flag_enr_tree_file: None,
+			flag_upload_limit: None,
+			flag_download_limit: None,
+			flag_peer_upload_limit: None,
+			flag_peer_download_limit: None,
+			flag_peer_ban_duration: 3600u64,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
 
@@ -817,6 +873,8 @@ mod tests {
 			flag_pruning: "auto".into(),
 			flag_pruning_history: 64u64,
 			flag_pruning_memory: 500usize,
+			flag_ancient_depth: 90000u64,
+			flag_history_keep_blocks: None,
 			flag_cache_size_db: 64u32,
 			flag_cache_size_blocks: 8u32,
 			flag_cache_size_queue: 50u32,
@@ -824,6 +882,7 @@ mod tests {
 			flag_cache_size: Some(128),
 			flag_fast_and_loose: false,
 			flag_db_compaction: "ssd".into(),
+			flag_db_backend: "rocksdb".into(),
 			flag_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			flag_num_verifiers: Some(6),
@@ -837,10 +896,21 @@ mod tests {
 			flag_no_storage: false,
 			flag_min_balance: None,
 			flag_max_balance: None,
+			flag_accounts: None,
+			flag_code_only: false,
+			flag_storage_only: false,
+			flag_resume_after: None,
 
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			flag_trusted_snapshot_publishers: None,
+			flag_sign_with: None,
+			flag_base_snapshot: None,
+
+			// -- Database Options
+			flag_keep: 64u64,
+			flag_state: false,
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
@@ -956,6 +1026,13 @@ mod tests {
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
+				enr_tree: None,
+				enr_tree_file: None,
+				upload_limit: None,
+				download_limit: None,
+				peer_upload_limit: None,
+				peer_download_limit: None,
+				peer_ban_duration: None,
 				no_serve_light: None,
 			}),
 			websockets: Some(Ws {
@@ -1038,6 +1115,8 @@ mod tests {
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				ancient_depth: None,
+				history_keep_blocks: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -1045,12 +1124,15 @@ mod tests {
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
 				db_compaction: Some("ssd".into()),
+				db_backend: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				trusted_publishers: None,
+				sign_with: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
@@ -44,11 +44,14 @@ use ipfs::Configuration as IpfsConfiguration;
 use secretstore::Configuration as SecretStoreConfiguration;
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, PruneBlockchain, VerifyBlockchain, ExportState, DataFormat};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
 
+// Blocks which may still be reorganised need their bodies, so at least this much history is kept.
+const MIN_HISTORY_KEEP_BLOCKS: u64 = 30_000;
+
 #[derive(Debug, PartialEq)]
 pub enum Cmd {
 	Run(RunCmd),
@@ -121,6 +124,7 @@ impl Configuration {
 		let tracing = self.args.flag_tracing.parse()?;
 		let fat_db = self.args.flag_fat_db.parse()?;
 		let compaction = self.args.flag_db_compaction.parse()?;
+		let db_backend = self.args.flag_db_backend.parse()?;
 		let wal = !self.args.flag_fast_and_loose;
 		match self.args.flag_warp {
 			// Logging is not initialized yet, so we print directly to stderr
@@ -181,6 +185,32 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_prune {
+			// `--to` defaults to `latest` for exports.
+			let target = match self.args.flag_to.as_ref() {
+				"latest" => Algorithm::OverlayRecent,
+				to => to.parse()?,
+			};
+			Cmd::Blockchain(BlockchainCmd::Prune(PruneBlockchain {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				target: target,
+				keep: self.args.flag_keep,
+				compaction: compaction,
+				db_backend: db_backend,
+				ancient_depth: self.args.flag_ancient_depth,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_verify {
+			Cmd::Blockchain(BlockchainCmd::Verify(VerifyBlockchain {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				from: self.args.flag_from.parse().map_err(|_| format!("Invalid block number: {}", self.args.flag_from))?,
+				state: self.args.flag_state,
+				compaction: compaction,
+				db_backend: db_backend,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_new {
 				let new_acc = NewAccount {
@@ -235,8 +265,10 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				ancient_depth: self.args.flag_ancient_depth,
 				compaction: compaction,
 				wal: wal,
+				db_backend: db_backend,
 				tracing: tracing,
 				fat_db: fat_db,
 				vm_type: vm_type,
@@ -256,8 +288,10 @@ impl Configuration {
 					pruning: pruning,
 					pruning_history: pruning_history,
 					pruning_memory: self.args.flag_pruning_memory,
+					ancient_depth: self.args.flag_ancient_depth,
 					compaction: compaction,
 					wal: wal,
+					db_backend: db_backend,
 					tracing: tracing,
 					fat_db: fat_db,
 					from_block: to_block_id(&self.args.flag_from)?,
@@ -275,8 +309,10 @@ impl Configuration {
 					pruning: pruning,
 					pruning_history: pruning_history,
 					pruning_memory: self.args.flag_pruning_memory,
+					ancient_depth: self.args.flag_ancient_depth,
 					compaction: compaction,
 					wal: wal,
+					db_backend: db_backend,
 					tracing: tracing,
 					fat_db: fat_db,
 					at: to_block_id(&self.args.flag_at)?,
@@ -284,6 +320,16 @@ impl Configuration {
 					code: !self.args.flag_no_code,
 					min_balance: self.args.flag_min_balance.and_then(|s| to_u256(&s).ok()),
 					max_balance: self.args.flag_max_balance.and_then(|s| to_u256(&s).ok()),
+					accounts: match self.args.flag_accounts {
+						Some(_) => Some(to_addresses(&self.args.flag_accounts)?),
+						None => None,
+					},
+					code_only: self.args.flag_code_only,
+					storage_only: self.args.flag_storage_only,
+					resume_after: match self.args.flag_resume_after {
+						Some(ref account) => Some(to_address(Some(account.clone()))?),
+						None => None,
+					},
 				};
 				Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
 			} else {
@@ -297,13 +343,18 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				ancient_depth: self.args.flag_ancient_depth,
 				tracing: tracing,
 				fat_db: fat_db,
 				compaction: compaction,
 				file_path: self.args.arg_file.clone(),
 				wal: wal,
+				db_backend: db_backend,
 				kind: snapshot::Kind::Take,
 				block_at: to_block_id(&self.args.flag_at)?,
+				sign_with: self.snapshot_sign_with()?,
+				password_files: self.args.flag_password.clone(),
+				base_snapshot: self.args.flag_base_snapshot.clone(),
 			};
 			Cmd::Snapshot(snapshot_cmd)
 		} else if self.args.cmd_restore {
@@ -314,13 +365,18 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				ancient_depth: self.args.flag_ancient_depth,
 				tracing: tracing,
 				fat_db: fat_db,
 				compaction: compaction,
 				file_path: self.args.arg_file.clone(),
 				wal: wal,
+				db_backend: db_backend,
 				kind: snapshot::Kind::Restore,
 				block_at: to_block_id("latest")?, // unimportant.
+				sign_with: None,
+				password_files: Vec::new(),
+				base_snapshot: None,
 			};
 			Cmd::Snapshot(restore_cmd)
 		} else {
@@ -345,6 +401,8 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				ancient_depth: self.args.flag_ancient_depth,
+				history_keep_blocks: self.history_keep_blocks()?,
 				daemon: daemon,
 				logger_config: logger_config.clone(),
 				miner_options: miner_options,
@@ -363,6 +421,7 @@ impl Configuration {
 				fat_db: fat_db,
 				compaction: compaction,
 				wal: wal,
+				db_backend: db_backend,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
 				public_node: public_node,
@@ -377,8 +436,10 @@ impl Configuration {
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				trusted_snapshot_publishers: to_addresses(&self.args.flag_trusted_snapshot_publishers)?,
+				snapshot_sign_with: self.snapshot_sign_with()?,
 				check_seal: !self.args.flag_no_seal_check,
-				download_old_blocks: !self.args.flag_no_ancient_blocks,
+				download_old_blocks: !self.args.flag_no_ancient_blocks && self.args.flag_history_keep_blocks.is_none(),
 				verifier_settings: verifier_settings,
 				serve_light: !self.args.flag_no_serve_light,
 				light: self.args.flag_light,
@@ -401,6 +462,14 @@ impl Configuration {
 		}
 	}
 
+	fn history_keep_blocks(&self) -> Result<Option<u64>, String> {
+		let min = max(MIN_HISTORY_KEEP_BLOCKS, self.args.flag_pruning_history);
+		match self.args.flag_history_keep_blocks {
+			Some(blocks) if blocks < min => Err(format!("--history-keep-blocks must be at least {}, got {}.", min, blocks)),
+			other => Ok(other),
+		}
+	}
+
 	fn miner_extras(&self) -> Result<MinerExtras, String> {
 		let extras = MinerExtras {
 			author: self.author()?,
@@ -418,6 +487,13 @@ impl Configuration {
 		to_address(self.args.flag_etherbase.clone().or(self.args.flag_author.clone()))
 	}
 
+	fn snapshot_sign_with(&self) -> Result<Option<Address>, String> {
+		match self.args.flag_sign_with.clone() {
+			Some(account) => to_address(Some(account)).map(Some),
+			None => Ok(None),
+		}
+	}
+
 	fn engine_signer(&self) -> Result<Address, String> {
 		to_address(self.args.flag_engine_signer.clone())
 	}
@@ -710,6 +786,16 @@ impl Configuration {
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes()?;
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
+		ret.enr_tree = self.args.flag_enr_tree.clone();
+		ret.enr_tree_file = self.args.flag_enr_tree_file.clone();
+		if ret.enr_tree.is_some() != ret.enr_tree_file.is_some() {
+			return Err("--enr-tree and --enr-tree-file must be used together".into());
+		}
+		ret.upload_limit = self.args.flag_upload_limit.map(|l| l * 1024);
+		ret.download_limit = self.args.flag_download_limit.map(|l| l * 1024);
+		ret.peer_upload_limit = self.args.flag_peer_upload_limit.map(|l| l * 1024);
+		ret.peer_download_limit = self.args.flag_peer_download_limit.map(|l| l * 1024);
+		ret.ban_duration = self.args.flag_peer_ban_duration;
 		Ok(ret)
 	}
 
@@ -1068,7 +1154,7 @@ mod tests {
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 
 	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, PruneBlockchain, VerifyBlockchain, DataFormat, ExportState};
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
 	use helpers::{default_network_config};
@@ -1157,8 +1243,10 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			ancient_depth: 90000,
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			vm_type: VMType::Interpreter,
@@ -1180,9 +1268,11 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			ancient_depth: 90000,
 			format: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			from_block: BlockId::Number(1),
@@ -1203,9 +1293,11 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			ancient_depth: 90000,
 			format: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			at: BlockId::Latest,
@@ -1213,9 +1305,85 @@ mod tests {
 			code: true,
 			min_balance: None,
 			max_balance: None,
+			accounts: None,
+			code_only: false,
+			storage_only: false,
+			resume_after: None,
+		})));
+	}
+
+	#[test]
+	fn test_command_state_export_filters() {
+		let args = vec!["parity", "export", "state", "--code-only", "--accounts", "0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002", "--resume-after", "0x0000000000000000000000000000000000000001"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Blockchain(BlockchainCmd::ExportState(cmd)) => {
+				assert_eq!(cmd.accounts, Some(vec![Address::from(1u64), Address::from(2u64)]));
+				assert!(cmd.code_only);
+				assert!(!cmd.storage_only);
+				assert_eq!(cmd.resume_after, Some(Address::from(1u64)));
+			},
+			_ => panic!("Expected state export command"),
+		}
+	}
+
+	#[test]
+	fn test_command_db_prune() {
+		let args = vec!["parity", "db", "prune", "--to", "fast", "--keep", "128"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Prune(PruneBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			target: Algorithm::OverlayRecent,
+			keep: 128,
+			compaction: Default::default(),
+			db_backend: Default::default(),
+			ancient_depth: 90000,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_verify() {
+		let args = vec!["parity", "db", "verify", "--from", "1000", "--state"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Verify(VerifyBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			from: 1000,
+			state: true,
+			compaction: Default::default(),
+			db_backend: Default::default(),
 		})));
 	}
 
+	#[test]
+	fn test_command_snapshot_sign_with() {
+		let args = vec!["parity", "snapshot", "snapshot.bin", "--sign-with", "0x0000000000000000000000000000000000000001", "--password", "pass.txt"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Snapshot(cmd) => {
+				assert_eq!(cmd.sign_with, Some(1u64.into()));
+				assert_eq!(cmd.password_files, vec!["pass.txt".to_owned()]);
+			},
+			_ => panic!("Should be snapshot command"),
+		}
+	}
+
+	#[test]
+	fn test_command_snapshot_base() {
+		let args = vec!["parity", "snapshot", "delta.bin", "--base-snapshot", "base.bin"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Snapshot(cmd) => {
+				assert_eq!(cmd.file_path, Some("delta.bin".to_owned()));
+				assert_eq!(cmd.base_snapshot, Some("base.bin".to_owned()));
+			},
+			_ => panic!("Should be snapshot command"),
+		}
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1228,9 +1396,11 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			ancient_depth: 90000,
 			format: Some(DataFormat::Hex),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			from_block: BlockId::Number(1),
@@ -1273,6 +1443,8 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			ancient_depth: 90000,
+			history_keep_blocks: None,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
@@ -1291,6 +1463,7 @@ mod tests {
 			tracing: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			vm_type: Default::default(),
 			geth_compatibility: false,
 			net_settings: Default::default(),
@@ -1304,6 +1477,8 @@ mod tests {
 			custom_bootnodes: false,
 			fat_db: Default::default(),
 			no_periodic_snapshot: false,
+			trusted_snapshot_publishers: Vec::new(),
+			snapshot_sign_with: None,
 			stratum: None,
 			check_seal: true,
 			download_old_blocks: true,
@@ -1316,6 +1491,30 @@ mod tests {
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Run(expected));
 	}
 
+	#[test]
+	fn should_disable_old_blocks_when_keeping_recent_history() {
+		let args = vec!["parity", "--history-keep-blocks", "50000"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(cmd) => {
+				assert_eq!(cmd.history_keep_blocks, Some(50000));
+				assert!(!cmd.download_old_blocks);
+			},
+			_ => panic!("Expected run command"),
+		}
+	}
+
+	#[test]
+	fn should_reject_too_little_history() {
+		let args = vec!["parity", "--history-keep-blocks", "1000"];
+		let conf = parse(&args);
+		assert!(conf.into_command().is_err());
+
+		let args = vec!["parity", "--history-keep-blocks", "40000", "--pruning-history", "50000"];
+		let conf = parse(&args);
+		assert!(conf.into_command().is_err());
+	}
+
 	#[test]
 	fn should_parse_mining_options() {
 		// given
@@ -1378,6 +1577,34 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn should_parse_bandwidth_limits() {
+		// given
+
+		// when
+		let conf = parse(&["parity", "--upload-limit", "512", "--peer-download-limit", "64"]);
+
+		// then
+		let net = conf.net_config().unwrap();
+		assert_eq!(net.upload_limit, Some(512 * 1024));
+		assert_eq!(net.download_limit, None);
+		assert_eq!(net.peer_upload_limit, None);
+		assert_eq!(net.peer_download_limit, Some(64 * 1024));
+	}
+
+	#[test]
+	fn should_parse_peer_ban_duration() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--peer-ban-duration", "0"]);
+
+		// then
+		assert_eq!(conf0.net_config().unwrap().ban_duration, 3600);
+		assert_eq!(conf1.net_config().unwrap().ban_duration, 0);
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
@@ -38,7 +38,7 @@ use app_dirs::{AppInfo, get_app_root, AppDataType};
 // but we still use it for backwards compatibility
 const LEGACY_CLIENT_DB_VER_STR: &'static str = "5.3";
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Directories {
 	pub base: String,
 	pub db: String,
@@ -160,6 +160,13 @@ impl DatabaseDirectories {
 		dir
 	}
 
+	/// Get the ancient block store path
+	pub fn ancient_path(&self, pruning: Algorithm) -> PathBuf {
+		let mut dir = self.db_path(pruning);
+		dir.push("ancient");
+		dir
+	}
+
 	/// Get the root path for database
 	// TODO: remove in 1.7
 	pub fn legacy_version_path(&self, pruning: Algorithm) -> PathBuf {
@@ -0,0 +1,380 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Era archives of chain data.
+//!
+//! The chain is split into eras of `ERA_LENGTH` blocks, each stored in its own file
+//! named after the era number. A file holds a consecutive range of blocks of its era as
+//! RLP entries `[block, receipts, total_difficulty]`, where receipts are empty data when
+//! they were not available. The entries are followed by the index
+//! `[version, genesis_hash, first_block, [[offset, entry_hash], ...]]`, the hash of the
+//! index and finally the offset of the index as 8 little-endian bytes.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use rlp::{RlpStream, UntrustedRlp, DecoderError};
+use util::{Bytes, H256, U256, Hashable};
+
+/// Number of blocks in an era.
+pub const ERA_LENGTH: u64 = 8192;
+
+/// File extension of era files.
+pub const ERA_EXTENSION: &'static str = "era";
+
+// current version of the era format.
+const ERA_VERSION: u64 = 1;
+
+// size of the trailer: index hash and index offset.
+const TRAILER_LEN: u64 = 32 + 8;
+
+/// A block along with its receipts and total difficulty.
+#[derive(Debug, PartialEq)]
+pub struct EraEntry {
+	/// RLP of the block.
+	pub block: Bytes,
+	/// RLP list of the block's receipts, if known.
+	pub receipts: Option<Bytes>,
+	/// Total difficulty of the chain up to and including the block.
+	pub total_difficulty: U256,
+}
+
+impl EraEntry {
+	fn to_rlp(&self) -> Bytes {
+		let mut stream = RlpStream::new_list(3);
+		stream.append_raw(&self.block, 1);
+		match self.receipts {
+			Some(ref receipts) => { stream.append_raw(receipts, 1); },
+			None => { stream.append_empty_data(); },
+		}
+		stream.append(&self.total_difficulty);
+		stream.out()
+	}
+
+	fn from_rlp(raw: &[u8]) -> Result<Self, String> {
+		let rlp = UntrustedRlp::new(raw);
+		let decode = |e: DecoderError| format!("Invalid era entry: {:?}", e);
+
+		let receipts = rlp.at(1).map_err(&decode)?;
+		Ok(EraEntry {
+			block: rlp.at(0).map_err(&decode)?.as_raw().to_vec(),
+			receipts: if receipts.is_list() { Some(receipts.as_raw().to_vec()) } else { None },
+			total_difficulty: rlp.val_at(2).map_err(&decode)?,
+		})
+	}
+}
+
+/// Path of the file of the given era in a directory.
+pub fn era_path(dir: &Path, era: u64) -> PathBuf {
+	let mut path = dir.to_owned();
+	path.push(format!("{:08}.{}", era, ERA_EXTENSION));
+	path
+}
+
+/// Era files in a directory, ordered by era.
+pub fn era_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+	let mut files = Vec::new();
+	for entry in fs::read_dir(dir).map_err(|e| format!("Cannot read directory {}: {}", dir.display(), e))? {
+		let path = entry.map_err(|e| format!("Cannot read directory {}: {}", dir.display(), e))?.path();
+		if path.extension().map_or(false, |ext| ext == ERA_EXTENSION) {
+			files.push(path);
+		}
+	}
+
+	files.sort();
+	Ok(files)
+}
+
+// an era file being written.
+struct EraFile {
+	era: u64,
+	file: File,
+	first_block: u64,
+	entries: Vec<(u64, H256)>,
+	cur_len: u64,
+}
+
+/// Writes consecutive blocks into era files in a directory.
+pub struct EraWriter {
+	dir: PathBuf,
+	genesis_hash: H256,
+	current: Option<EraFile>,
+}
+
+impl EraWriter {
+	/// Create a new writer, creating the directory if necessary.
+	pub fn new(dir: &Path, genesis_hash: H256) -> Result<Self, String> {
+		fs::create_dir_all(dir).map_err(|e| format!("Cannot create directory {}: {}", dir.display(), e))?;
+
+		Ok(EraWriter {
+			dir: dir.to_owned(),
+			genesis_hash: genesis_hash,
+			current: None,
+		})
+	}
+
+	/// Write the entry of block `number`, which must follow the previously written block.
+	pub fn write(&mut self, number: u64, entry: &EraEntry) -> Result<(), String> {
+		let era = number / ERA_LENGTH;
+		if self.current.as_ref().map_or(true, |current| current.era != era) {
+			self.finish_current()?;
+
+			let path = era_path(&self.dir, era);
+			let file = File::create(&path).map_err(|e| format!("Cannot create era file {}: {}", path.display(), e))?;
+			self.current = Some(EraFile {
+				era: era,
+				file: file,
+				first_block: number,
+				entries: Vec::new(),
+				cur_len: 0,
+			});
+		}
+
+		let current = self.current.as_mut().expect("era file is opened above; qed");
+		if number != current.first_block + current.entries.len() as u64 {
+			return Err(format!("Block #{} does not follow the previously written block", number));
+		}
+
+		let raw = entry.to_rlp();
+		current.file.write_all(&raw).map_err(|e| format!("Cannot write era file: {}", e))?;
+		current.entries.push((current.cur_len, raw.sha3()));
+		current.cur_len += raw.len() as u64;
+
+		Ok(())
+	}
+
+	/// Write out the index of the last era file.
+	pub fn finish(mut self) -> Result<(), String> {
+		self.finish_current()
+	}
+
+	fn finish_current(&mut self) -> Result<(), String> {
+		let mut current = match self.current.take() {
+			Some(current) => current,
+			None => return Ok(()),
+		};
+
+		let mut stream = RlpStream::new_list(4);
+		stream
+			.append(&ERA_VERSION)
+			.append(&self.genesis_hash)
+			.append(&current.first_block)
+			.begin_list(current.entries.len());
+
+		for &(offset, ref hash) in &current.entries {
+			stream.begin_list(2).append(&offset).append(hash);
+		}
+
+		let index = stream.out();
+		let off = current.cur_len;
+		let off_bytes: [u8; 8] =
+			[
+				off as u8,
+				(off >> 8) as u8,
+				(off >> 16) as u8,
+				(off >> 24) as u8,
+				(off >> 32) as u8,
+				(off >> 40) as u8,
+				(off >> 48) as u8,
+				(off >> 56) as u8,
+			];
+
+		let write = |file: &mut File| -> ::std::io::Result<()> {
+			file.write_all(&index)?;
+			file.write_all(&index.sha3())?;
+			file.write_all(&off_bytes[..])?;
+			file.flush()
+		};
+
+		write(&mut current.file).map_err(|e| format!("Cannot write era file index: {}", e))
+	}
+}
+
+/// Reads blocks from a single era file.
+pub struct EraReader {
+	file: File,
+	genesis_hash: H256,
+	first_block: u64,
+	entries: Vec<(u64, H256)>,
+	index_offset: u64,
+}
+
+impl EraReader {
+	/// Open an era file, checking its index.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		let invalid = |msg: &str| format!("Invalid era file {}: {}", path.display(), msg);
+		let io_err = |e: ::std::io::Error| format!("Cannot read era file {}: {}", path.display(), e);
+
+		let mut file = File::open(path).map_err(&io_err)?;
+		let file_len = file.metadata().map_err(&io_err)?.len();
+		if file_len < TRAILER_LEN {
+			return Err(invalid("file too short"));
+		}
+
+		let mut trailer = [0u8; TRAILER_LEN as usize];
+		file.seek(SeekFrom::End(-(TRAILER_LEN as i64))).map_err(&io_err)?;
+		file.read_exact(&mut trailer).map_err(&io_err)?;
+
+		let index_hash = H256::from_slice(&trailer[..32]);
+		let index_offset = trailer[32..].iter().rev().fold(0u64, |off, &b| (off << 8) | b as u64);
+		if index_offset > file_len - TRAILER_LEN {
+			return Err(invalid("bad index offset"));
+		}
+
+		let mut index = vec![0; (file_len - TRAILER_LEN - index_offset) as usize];
+		file.seek(SeekFrom::Start(index_offset)).map_err(&io_err)?;
+		file.read_exact(&mut index).map_err(&io_err)?;
+
+		if index.sha3() != index_hash {
+			return Err(invalid("index checksum mismatch"));
+		}
+
+		let rlp = UntrustedRlp::new(&index);
+		let decode = |e: DecoderError| invalid(&format!("{:?}", e));
+
+		let version: u64 = rlp.val_at(0).map_err(&decode)?;
+		if version > ERA_VERSION {
+			return Err(invalid(&format!("unsupported version {}", version)));
+		}
+
+		let mut entries = Vec::new();
+		for entry in rlp.at(3).map_err(&decode)?.iter() {
+			let offset: u64 = entry.val_at(0).map_err(&decode)?;
+			let hash: H256 = entry.val_at(1).map_err(&decode)?;
+			if offset > index_offset || entries.last().map_or(false, |&(last, _)| offset <= last) {
+				return Err(invalid("bad entry offset"));
+			}
+			entries.push((offset, hash));
+		}
+
+		Ok(EraReader {
+			file: file,
+			genesis_hash: rlp.val_at(1).map_err(&decode)?,
+			first_block: rlp.val_at(2).map_err(&decode)?,
+			entries: entries,
+			index_offset: index_offset,
+		})
+	}
+
+	/// Hash of the genesis block of the chain the blocks belong to.
+	pub fn genesis_hash(&self) -> H256 {
+		self.genesis_hash
+	}
+
+	/// Number of the first block in the file.
+	pub fn first_block(&self) -> u64 {
+		self.first_block
+	}
+
+	/// Number of blocks in the file.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Read the entry at position `i`, checking it against the index.
+	pub fn entry(&mut self, i: usize) -> Result<EraEntry, String> {
+		let (offset, hash) = self.entries[i];
+		let end = self.entries.get(i + 1).map_or(self.index_offset, |&(next, _)| next);
+
+		let mut raw = vec![0; (end - offset) as usize];
+		self.file.seek(SeekFrom::Start(offset))
+			.and_then(|_| self.file.read_exact(&mut raw))
+			.map_err(|e| format!("Cannot read era file: {}", e))?;
+
+		if raw.sha3() != hash {
+			return Err(format!("Checksum mismatch for block #{}", self.first_block + i as u64));
+		}
+
+		EraEntry::from_rlp(&raw)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::OpenOptions;
+	use std::io::{Seek, SeekFrom, Write};
+
+	use devtools::RandomTempPath;
+	use util::{H256, U256};
+
+	use super::{EraEntry, EraWriter, EraReader, ERA_LENGTH, era_files, era_path};
+
+	fn entry(n: u64) -> EraEntry {
+		EraEntry {
+			block: ::rlp::encode(&n).into_vec(),
+			receipts: if n % 2 == 0 { Some(::rlp::EMPTY_LIST_RLP.to_vec()) } else { None },
+			total_difficulty: U256::from(n * 1000),
+		}
+	}
+
+	#[test]
+	fn writes_and_reads_eras() {
+		let dir = RandomTempPath::create_dir();
+		let genesis = H256::from(1u64);
+
+		let mut writer = EraWriter::new(dir.as_path(), genesis).unwrap();
+		for n in (ERA_LENGTH - 10)..(ERA_LENGTH + 5) {
+			writer.write(n, &entry(n)).unwrap();
+		}
+		writer.finish().unwrap();
+
+		let files = era_files(dir.as_path()).unwrap();
+		assert_eq!(files, vec![era_path(dir.as_path(), 0), era_path(dir.as_path(), 1)]);
+
+		let mut reader = EraReader::open(&files[0]).unwrap();
+		assert_eq!(reader.genesis_hash(), genesis);
+		assert_eq!(reader.first_block(), ERA_LENGTH - 10);
+		assert_eq!(reader.len(), 10);
+		for i in 0..reader.len() {
+			assert_eq!(reader.entry(i).unwrap(), entry(ERA_LENGTH - 10 + i as u64));
+		}
+
+		let mut reader = EraReader::open(&files[1]).unwrap();
+		assert_eq!(reader.first_block(), ERA_LENGTH);
+		assert_eq!(reader.len(), 5);
+		assert_eq!(reader.entry(4).unwrap(), entry(ERA_LENGTH + 4));
+	}
+
+	#[test]
+	fn rejects_gaps() {
+		let dir = RandomTempPath::create_dir();
+		let mut writer = EraWriter::new(dir.as_path(), H256::default()).unwrap();
+		writer.write(5, &entry(5)).unwrap();
+		assert!(writer.write(7, &entry(7)).is_err());
+	}
+
+	#[test]
+	fn detects_corruption() {
+		let dir = RandomTempPath::create_dir();
+		let mut writer = EraWriter::new(dir.as_path(), H256::default()).unwrap();
+		for n in 0..3 {
+			writer.write(n, &entry(n)).unwrap();
+		}
+		writer.finish().unwrap();
+
+		let path = era_path(dir.as_path(), 0);
+		{
+			let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+			file.seek(SeekFrom::Start(1)).unwrap();
+			file.write_all(&[0xff]).unwrap();
+		}
+
+		let mut reader = EraReader::open(&path).unwrap();
+		assert!(reader.entry(0).is_err());
+		assert_eq!(reader.entry(1).unwrap(), entry(1));
+	}
+}
@@ -18,7 +18,8 @@ use std::{io, env};
 use std::io::{Write, BufReader, BufRead};
 use std::time::Duration;
 use std::fs::File;
-use util::{clean_0x, U256, Address, CompactionProfile};
+use std::path::PathBuf;
+use util::{clean_0x, U256, Address, CompactionProfile, DatabaseBackend};
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
 use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy};
@@ -209,6 +210,13 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		allow_ips: AllowIP::All,
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
+		enr_tree: None,
+		enr_tree_file: None,
+		upload_limit: None,
+		download_limit: None,
+		peer_upload_limit: None,
+		peer_download_limit: None,
+		ban_duration: 3600,
 	}
 }
 
@@ -221,11 +229,14 @@ pub fn to_client_config(
 		fat_db: bool,
 		compaction: DatabaseCompactionProfile,
 		wal: bool,
+		backend: DatabaseBackend,
 		vm_type: VMType,
 		name: String,
 		pruning: Algorithm,
 		pruning_history: u64,
 		pruning_memory: usize,
+		ancient_path: PathBuf,
+		ancient_depth: u64,
 		check_seal: bool,
 	) -> ClientConfig {
 	let mut client_config = ClientConfig::default();
@@ -257,8 +268,11 @@ pub fn to_client_config(
 	client_config.fat_db = fat_db;
 	client_config.pruning = pruning;
 	client_config.history = pruning_history;
+	client_config.blockchain.ancient_path = Some(ancient_path);
+	client_config.blockchain.ancient_depth = ancient_depth;
 	client_config.db_compaction = compaction;
 	client_config.db_wal = wal;
+	client_config.db_backend = backend;
 	client_config.vm_type = vm_type;
 	client_config.name = name;
 	client_config.verifier_type = if check_seal { VerifierType::Canon } else { VerifierType::CanonNoSeal };
@@ -270,7 +284,9 @@ pub fn execute_upgrades(
 	base_path: &str,
 	dirs: &DatabaseDirectories,
 	pruning: Algorithm,
-	compaction_profile: CompactionProfile
+	compaction_profile: CompactionProfile,
+	backend: DatabaseBackend,
+	ancient_depth: u64
 ) -> Result<(), String> {
 
 	upgrade_data_paths(base_path, dirs, pruning);
@@ -286,7 +302,7 @@ pub fn execute_upgrades(
 	}
 
 	let client_path = dirs.db_path(pruning);
-	migrate(&client_path, pruning, compaction_profile).map_err(|e| format!("{}", e))
+	migrate(&client_path, pruning, compaction_profile, backend, ancient_depth).map_err(|e| format!("{}", e))
 }
 
 /// Prompts user asking for password.
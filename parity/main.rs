@@ -93,6 +93,7 @@ mod dapps;
 mod ipfs;
 mod deprecated;
 mod dir;
+mod era;
 mod helpers;
 mod informant;
 mod light_helpers;
@@ -22,7 +22,7 @@ use std::fmt::{Display, Formatter, Error as FmtError};
 use std::sync::Arc;
 use util::journaldb::Algorithm;
 use util::migration::{Manager as MigrationManager, Config as MigrationConfig, Error as MigrationError, Migration};
-use util::kvdb::{CompactionProfile, Database, DatabaseConfig};
+use util::kvdb::{CompactionProfile, Database, DatabaseConfig, DatabaseBackend};
 use ethcore::migrations;
 use ethcore::db;
 use ethcore::migrations::Extract;
@@ -30,7 +30,7 @@ use ethcore::migrations::Extract;
 /// Database is assumed to be at default version, when no version file is found.
 const DEFAULT_VERSION: u32 = 5;
 /// Current version of database models.
-const CURRENT_VERSION: u32 = 12;
+const CURRENT_VERSION: u32 = 13;
 /// First version of the consolidated database.
 const CONSOLIDATION_VERSION: u32 = 9;
 /// Defines how many items are migrated to the new version of database at once.
@@ -47,6 +47,8 @@ pub enum Error {
 	UnsupportedPruningMethod,
 	/// Existing DB is newer than the known one.
 	FutureDBVersion,
+	/// Existing DB was created with a different storage backend.
+	BackendMismatch(DatabaseBackend, DatabaseBackend),
 	/// Migration is not possible.
 	MigrationImpossible,
 	/// Migration unexpectadly failed.
@@ -64,6 +66,7 @@ impl Display for Error {
 			Error::UnknownDatabaseVersion => "Current database version cannot be read".into(),
 			Error::UnsupportedPruningMethod => "Unsupported pruning method for database migration. Delete DB and resync.".into(),
 			Error::FutureDBVersion => "Database was created with newer client version. Upgrade your client or delete DB and resync.".into(),
+			Error::BackendMismatch(existing, requested) => format!("Database was created with {} backend, but {} was requested. Use --db-backend {} or delete DB and resync.", existing, requested, existing),
 			Error::MigrationImpossible => format!("Database migration to version {} is not possible.", CURRENT_VERSION),
 			Error::MigrationFailed => "Database migration unexpectedly failed".into(),
 			Error::Internal(ref err) => format!("{}", err),
@@ -96,26 +99,35 @@ fn version_file_path(path: &Path) -> PathBuf {
 	file_path
 }
 
-/// Reads current database version from the file at given path.
-/// If the file does not exist returns `DEFAULT_VERSION`.
-fn current_version(path: &Path) -> Result<u32, Error> {
+/// Reads current database version and backend from the file at given path.
+/// If the file does not exist returns `DEFAULT_VERSION` and no backend.
+/// Version files without a backend line were written for RocksDB databases.
+fn current_version(path: &Path) -> Result<(u32, Option<DatabaseBackend>), Error> {
 	match File::open(version_file_path(path)) {
-		Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(DEFAULT_VERSION),
+		Err(ref err) if err.kind() == ErrorKind::NotFound => Ok((DEFAULT_VERSION, None)),
 		Err(_) => Err(Error::UnknownDatabaseVersion),
 		Ok(mut file) => {
 			let mut s = String::new();
 			file.read_to_string(&mut s).map_err(|_| Error::UnknownDatabaseVersion)?;
-			u32::from_str_radix(&s, 10).map_err(|_| Error::UnknownDatabaseVersion)
+			let mut lines = s.lines();
+			let version = lines.next()
+				.and_then(|v| u32::from_str_radix(v, 10).ok())
+				.ok_or(Error::UnknownDatabaseVersion)?;
+			let backend = match lines.next() {
+				Some(backend) => backend.parse().map_err(|_| Error::UnknownDatabaseVersion)?,
+				None => DatabaseBackend::RocksDB,
+			};
+			Ok((version, Some(backend)))
 		},
 	}
 }
 
-/// Writes current database version to the file.
+/// Writes current database version and backend to the file.
 /// Creates a new file if the version file does not exist yet.
-fn update_version(path: &Path) -> Result<(), Error> {
+pub fn update_version(path: &Path, backend: DatabaseBackend) -> Result<(), Error> {
 	fs::create_dir_all(path)?;
 	let mut file = File::create(version_file_path(path))?;
-	file.write_all(format!("{}", CURRENT_VERSION).as_bytes())?;
+	file.write_all(format!("{}\n{}", CURRENT_VERSION, backend).as_bytes())?;
 	Ok(())
 }
 
@@ -126,6 +138,13 @@ fn consolidated_database_path(path: &Path) -> PathBuf {
 	state_path
 }
 
+/// Ancient block store path
+fn ancient_path(path: &Path) -> PathBuf {
+	let mut ancient_path = path.to_owned();
+	ancient_path.push("ancient");
+	ancient_path
+}
+
 /// Database backup
 fn backup_database_path(path: &Path) -> PathBuf {
 	let mut backup_path = path.to_owned();
@@ -143,11 +162,12 @@ pub fn default_migration_settings(compaction_profile: &CompactionProfile) -> Mig
 }
 
 /// Migrations on the consolidated database.
-fn consolidated_database_migrations(compaction_profile: &CompactionProfile) -> Result<MigrationManager, Error> {
+fn consolidated_database_migrations(compaction_profile: &CompactionProfile, ancient_path: PathBuf, ancient_depth: u64) -> Result<MigrationManager, Error> {
 	let mut manager = MigrationManager::new(default_migration_settings(compaction_profile));
 	manager.add_migration(migrations::ToV10::new()).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(migrations::TO_V11).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(migrations::TO_V12).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::ToV13::new(ancient_path, ancient_depth)).map_err(|_| Error::MigrationImpossible)?;
 	Ok(manager)
 }
 
@@ -171,6 +191,7 @@ fn consolidate_database(
 		compaction: config.compaction_profile,
 		columns: None,
 		wal: true,
+		backend: Default::default(),
 	};
 
 	let old_path_str = old_db_path.to_str().ok_or(Error::MigrationImpossible)?;
@@ -224,10 +245,33 @@ fn exists(path: &Path) -> bool {
 	fs::metadata(path).is_ok()
 }
 
+/// Returns the backend the database at given path was created with, if any,
+/// making sure it is the given one.
+fn ensure_backend(path: &Path, recorded_backend: Option<DatabaseBackend>, backend: DatabaseBackend) -> Result<Option<DatabaseBackend>, Error> {
+	// databases created before the backend was recorded are always RocksDB.
+	let existing_backend = recorded_backend.or_else(|| {
+		match exists(&consolidated_database_path(path)) || exists(&legacy::blocks_database_path(path)) {
+			true => Some(DatabaseBackend::RocksDB),
+			false => None,
+		}
+	});
+
+	match existing_backend {
+		Some(existing) if existing != backend => Err(Error::BackendMismatch(existing, backend)),
+		existing => Ok(existing),
+	}
+}
+
+/// Checks that the database at given path was created with the given backend.
+pub fn check_backend(path: &Path, backend: DatabaseBackend) -> Result<(), Error> {
+	let (_, recorded_backend) = current_version(path)?;
+	ensure_backend(path, recorded_backend, backend).map(|_| ())
+}
+
 /// Migrates the database.
-pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionProfile) -> Result<(), Error> {
+pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionProfile, backend: DatabaseBackend, ancient_depth: u64) -> Result<(), Error> {
 	// read version file.
-	let version = current_version(path)?;
+	let (version, recorded_backend) = current_version(path)?;
 
 	// migrate the databases.
 	// main db directory may already exists, so let's check if we have blocks dir
@@ -235,11 +279,23 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 		return Err(Error::FutureDBVersion);
 	}
 
+	let existing_backend = ensure_backend(path, recorded_backend, backend)?;
+
 	// We are in the latest version, yay!
 	if version == CURRENT_VERSION {
 		return Ok(())
 	}
 
+	// Other backends leave moving existing blocks into ancient storage to the client.
+	if version == 12 && backend != DatabaseBackend::RocksDB {
+		return update_version(path, backend);
+	}
+
+	// Migrations operate on RocksDB databases only.
+	if backend != DatabaseBackend::RocksDB && existing_backend.is_some() {
+		return Err(Error::MigrationImpossible);
+	}
+
 	// Perform pre-consolidation migrations
 	if version < CONSOLIDATION_VERSION && exists(&legacy::blocks_database_path(path)) {
 		println!("Migrating database from version {} to {}", version, CONSOLIDATION_VERSION);
@@ -269,12 +325,12 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 	// Further migrations
 	if version >= CONSOLIDATION_VERSION && version < CURRENT_VERSION && exists(&consolidated_database_path(path)) {
 		println!("Migrating database from version {} to {}", ::std::cmp::max(CONSOLIDATION_VERSION, version), CURRENT_VERSION);
-		migrate_database(version, consolidated_database_path(path), consolidated_database_migrations(&compaction_profile)?)?;
+		migrate_database(version, consolidated_database_path(path), consolidated_database_migrations(&compaction_profile, ancient_path(path), ancient_depth)?)?;
 		println!("Migration finished");
 	}
 
 	// update version file.
-	update_version(path)
+	update_version(path, backend)
 }
 
 /// Old migrations utilities
@@ -18,6 +18,8 @@ use std::sync::Arc;
 use std::path::Path;
 
 use ethcore::client::BlockChainClient;
+use util::Address;
+use ethcore::header::BlockNumber;
 use hypervisor::Hypervisor;
 use ethsync::{SyncConfig, NetworkConfiguration, NetworkError, Params};
 use ethcore::snapshot::SnapshotService;
@@ -96,10 +98,12 @@ pub fn hypervisor(_: &Path) -> Option<Hypervisor> {
 }
 
 #[cfg(feature="ipc")]
-fn sync_arguments(io_path: &str, sync_cfg: SyncConfig, net_cfg: NetworkConfiguration, log_settings: &LogConfig) -> BootArgs {
+fn sync_arguments(io_path: &str, sync_cfg: SyncConfig, net_cfg: NetworkConfiguration, trusted_snapshot_publishers: Vec<Address>, fork_blocks: Vec<BlockNumber>, log_settings: &LogConfig) -> BootArgs {
 	let service_config = ServiceConfiguration {
 		sync: sync_cfg,
 		net: net_cfg,
+		trusted_snapshot_publishers: trusted_snapshot_publishers,
+		fork_blocks: fork_blocks,
 		io_path: io_path.to_owned(),
 	};
 
@@ -147,13 +151,15 @@ pub fn sync(
 	hypervisor_ref: &mut Option<Hypervisor>,
 	sync_cfg: SyncConfig,
 	net_cfg: NetworkConfiguration,
+	trusted_snapshot_publishers: Vec<Address>,
+	fork_blocks: Vec<BlockNumber>,
 	_client: Arc<BlockChainClient>,
 	_snapshot_service: Arc<SnapshotService>,
 	_provider: Arc<Provider>,
 	log_settings: &LogConfig,
 ) -> Result<SyncModules, NetworkError> {
 	let mut hypervisor = hypervisor_ref.take().expect("There should be hypervisor for ipc configuration");
-	let args = sync_arguments(&hypervisor.io_path, sync_cfg, net_cfg, log_settings);
+	let args = sync_arguments(&hypervisor.io_path, sync_cfg, net_cfg, trusted_snapshot_publishers, fork_blocks, log_settings);
 	hypervisor = hypervisor.module(SYNC_MODULE_ID, args);
 
 	hypervisor.start();
@@ -177,6 +183,8 @@ pub fn sync(
 	_hypervisor: &mut Option<Hypervisor>,
 	sync_cfg: SyncConfig,
 	net_cfg: NetworkConfiguration,
+	trusted_snapshot_publishers: Vec<Address>,
+	fork_blocks: Vec<BlockNumber>,
 	client: Arc<BlockChainClient>,
 	snapshot_service: Arc<SnapshotService>,
 	provider: Arc<Provider>,
@@ -188,6 +196,8 @@ pub fn sync(
 		provider: provider,
 		snapshot_service: snapshot_service,
 		network_config: net_cfg,
+		trusted_snapshot_publishers: trusted_snapshot_publishers,
+		fork_blocks: fork_blocks,
 	})?;
 
 	Ok((eth_sync.clone() as Arc<SyncProvider>, eth_sync.clone() as Arc<ManageNetwork>, eth_sync.clone() as Arc<ChainNotify>))
@@ -24,7 +24,7 @@ use ethcore::client::Mode;
 use ethcore::miner::{GasPricer, GasPriceCalibratorOptions};
 use user_defaults::UserDefaults;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SpecType {
 	Foundation,
 	Morden,
@@ -105,7 +105,7 @@ impl SpecType {
 	}
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Pruning {
 	Specific(Algorithm),
 	Auto,
@@ -30,6 +30,7 @@ use ethcore::snapshot::SnapshotService;
 use parity_rpc::{Metadata, NetworkSettings};
 use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
 use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
+use parity_rpc::full_sync_status;
 use ethsync::{ManageNetwork, SyncProvider, LightSync};
 use hash_fetch::fetch::Client as FetchClient;
 use jsonrpc_core::{self as core, MetaIoHandler};
@@ -246,7 +247,7 @@ impl FullDependencies {
 					handler.extend_with(NetClient::new(&self.sync).to_delegate());
 				},
 				Api::Eth => {
-					let client = EthClient::new(
+					let client = || EthClient::new(
 						&self.client,
 						&self.snapshot,
 						&self.sync,
@@ -259,7 +260,8 @@ impl FullDependencies {
 							send_block_number_in_get_work: !self.geth_compatibility,
 						}
 					);
-					handler.extend_with(client.to_delegate());
+					handler.extend_with(Eth::to_delegate(client()));
+					handler.extend_with(EthCall::to_delegate(client()));
 
 					if !for_generic_pubsub {
 						let filter_client = EthFilterClient::new(self.client.clone(), self.miner.clone());
@@ -269,7 +271,17 @@ impl FullDependencies {
 					}
 				},
 				Api::EthPubSub => {
-					let client = EthPubSubClient::new(self.client.clone(), self.remote.clone());
+					let sync_status = {
+						let client = self.client.clone();
+						let snapshot = self.snapshot.clone();
+						let sync = self.sync.clone();
+						move || full_sync_status(&*client, &*snapshot, &*sync)
+					};
+					let client = EthPubSubClient::with_sync_status(self.client.clone(), self.remote.clone(), sync_status);
+					let h = Arc::downgrade(&client.handler());
+					self.miner.add_transactions_listener(Box::new(move |hashes| if let Some(h) = h.upgrade() {
+						h.notify_new_transactions(hashes);
+					}));
 					self.client.add_notify(client.handler());
 					handler.extend_with(client.to_delegate());
 				},
@@ -290,6 +302,7 @@ impl FullDependencies {
 						&self.sync,
 						&self.updater,
 						&self.net_service,
+						&self.snapshot,
 						&self.secret_store,
 						self.logger.clone(),
 						self.settings.clone(),
@@ -318,12 +331,14 @@ impl FullDependencies {
 						&self.miner,
 						&self.updater,
 						&self.net_service,
+						&self.snapshot,
 						self.dapps_service.clone(),
 						self.fetch.clone(),
 					).to_delegate())
 				},
 				Api::Traces => {
-					handler.extend_with(TracesClient::new(&self.client, &self.miner).to_delegate())
+					handler.extend_with(Traces::to_delegate(TracesClient::new(&self.client, &self.miner)));
+					handler.extend_with(TracesCall::to_delegate(TracesClient::new(&self.client, &self.miner)));
 				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
@@ -436,6 +451,7 @@ impl LightDependencies {
 						self.cache.clone(),
 					);
 					handler.extend_with(Eth::to_delegate(client.clone()));
+					handler.extend_with(EthCall::to_delegate(client.clone()));
 
 					if !for_generic_pubsub {
 						handler.extend_with(EthFilter::to_delegate(client));
@@ -453,6 +469,10 @@ impl LightDependencies {
 					self.client.add_listener(
 						Arc::downgrade(&client.handler()) as Weak<::light::client::LightChainNotify>
 					);
+					let h = Arc::downgrade(&client.handler());
+					self.transaction_queue.write().add_listener(Box::new(move |hashes| if let Some(h) = h.upgrade() {
+						h.notify_new_transactions(hashes);
+					}));
 					handler.extend_with(EthPubSub::to_delegate(client));
 				},
 				Api::Personal => {
@@ -502,7 +522,8 @@ impl LightDependencies {
 					).to_delegate())
 				},
 				Api::Traces => {
-					handler.extend_with(light::TracesClient.to_delegate())
+					handler.extend_with(Traces::to_delegate(light::TracesClient));
+					handler.extend_with(TracesCall::to_delegate(light::TracesClient));
 				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
@@ -20,7 +20,7 @@ use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use parity_rpc::{NetworkSettings, informant, is_major_importing};
 use ethsync::NetworkConfiguration;
-use util::{Colour, version, Mutex, Condvar};
+use util::{Colour, version, Address, Mutex, Condvar, DatabaseBackend};
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore::miner::{StratumOptions, Stratum};
 use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
@@ -76,6 +76,8 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub ancient_depth: u64,
+	pub history_keep_blocks: Option<u64>,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
@@ -96,6 +98,7 @@ pub struct RunCmd {
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub vm_type: VMType,
 	pub geth_compatibility: bool,
 	pub net_settings: NetworkSettings,
@@ -109,6 +112,8 @@ pub struct RunCmd {
 	pub custom_bootnodes: bool,
 	pub stratum: Option<StratumOptions>,
 	pub no_periodic_snapshot: bool,
+	pub trusted_snapshot_publishers: Vec<Address>,
+	pub snapshot_sign_with: Option<Address>,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
@@ -188,7 +193,7 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
 
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction.clone())?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction.clone(), cmd.db_backend, cmd.ancient_depth)?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.ui_conf.enabled, cmd.secretstore_conf.enabled)?;
@@ -209,6 +214,7 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 		db_cache_size: Some(cmd.cache_config.blockchain() as usize * 1024 * 1024),
 		db_compaction: compaction,
 		db_wal: cmd.wal,
+		db_backend: cmd.db_backend,
 	};
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
@@ -231,10 +237,13 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
+	let network_id = cmd.network_id.unwrap_or(spec.network_id());
+	let mut network_config = net_conf.into_basic().map_err(|e| format!("Failed to produce network config: {}", e))?;
+	network_config.chain_identity = Some((network_id, genesis_hash));
 	let sync_params = LightSyncParams {
-		network_config: net_conf.into_basic().map_err(|e| format!("Failed to produce network config: {}", e))?,
+		network_config: network_config,
 		client: Arc::new(provider),
-		network_id: cmd.network_id.unwrap_or(spec.network_id()),
+		network_id: network_id,
 		subprotocol_name: ::ethsync::LIGHT_PROTOCOL,
 		handlers: vec![on_demand.clone()],
 	};
@@ -388,7 +397,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()), cmd.db_backend, cmd.ancient_depth)?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.ui_conf.enabled, cmd.secretstore_conf.enabled)?;
@@ -433,6 +442,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	let fork_blocks = spec.fork_blocks();
 	sync_config.warp_sync = spec.engine.supports_warp() && cmd.warp_sync;
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
@@ -454,6 +464,26 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	miner.recalibrate_minimal_gas_price();
 	let engine_signer = cmd.miner_extras.engine_signer;
 
+	// prepare the account to sign the manifests of periodic snapshots with.
+	let snapshot_signer = match cmd.snapshot_sign_with {
+		Some(address) => {
+			if !account_provider.has_account(address).unwrap_or(false) {
+				return Err(format!("Snapshot signer account not found for the current chain. {}", build_create_account_hint(&cmd.spec, &cmd.dirs.keys)));
+			}
+
+			let password = passwords.iter()
+				.find(|p| account_provider.test_password(&address, p).unwrap_or(false))
+				.ok_or_else(|| format!("No valid password for the snapshot signer {}. {}", address, VERIFY_PASSWORD_HINT))?;
+
+			Some(snapshot::ManifestSigner {
+				accounts: account_provider.clone(),
+				address: address,
+				password: password.clone(),
+			})
+		},
+		None => None,
+	};
+
 	if engine_signer != Default::default() {
 		// Check if engine signer exists
 		if !account_provider.has_account(engine_signer).unwrap_or(false) {
@@ -480,15 +510,19 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.vm_type,
 		cmd.name,
 		algorithm,
 		cmd.pruning_history,
 		cmd.pruning_memory,
+		db_dirs.ancient_path(algorithm),
+		cmd.ancient_depth,
 		cmd.check_seal,
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.blockchain.history_keep_blocks = cmd.history_keep_blocks;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -510,6 +544,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		&snapshot_path,
 		&cmd.dirs.ipc_path(),
 		miner.clone(),
+		snapshot_signer,
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
 	// drop the spec to free up genesis state.
@@ -571,6 +606,8 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		&mut hypervisor,
 		sync_config,
 		net_conf.into(),
+		cmd.trusted_snapshot_publishers.clone(),
+		fork_blocks,
 		client.clone(),
 		snapshot_service.clone(),
 		client.clone(),
@@ -16,21 +16,27 @@
 
 //! Snapshot and restoration commands.
 
+use std::io;
 use std::time::Duration;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
-use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
+use ethcore::error::Error as EthcoreError;
+use ethcore::snapshot::{ManifestData, Progress, RestorationStatus, SnapshotService as SS};
+use ethcore::snapshot::io::{SnapshotReader, SnapshotWriter, PackedReader, PackedWriter, SigningWriter};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType};
 use ethcore::miner::Miner;
 use ethcore::ids::BlockId;
+use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
+use ethcore::ethstore::EthStore;
+use ethcore::ethstore::dir::RootDiskDirectory;
+use util::{Address, DatabaseBackend};
 
 use cache::CacheConfig;
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
-use helpers::{to_client_config, execute_upgrades};
+use helpers::{to_client_config, execute_upgrades, passwords_from_files};
 use dir::Directories;
 use user_defaults::UserDefaults;
 use fdlimit;
@@ -53,13 +59,18 @@ pub struct SnapshotCommand {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub ancient_depth: u64,
 	pub tracing: Switch,
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
 	pub file_path: Option<String>,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub kind: Kind,
 	pub block_at: BlockId,
+	pub sign_with: Option<Address>,
+	pub password_files: Vec<String>,
+	pub base_snapshot: Option<String>,
 }
 
 // helper for reading chunks from arbitrary reader and feeding them into the
@@ -163,7 +174,7 @@ impl SnapshotCommand {
 		let snapshot_path = db_dirs.snapshot_path();
 
 		// execute upgrades
-		execute_upgrades(&self.dirs.base, &db_dirs, algorithm, self.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+		execute_upgrades(&self.dirs.base, &db_dirs, algorithm, self.compaction.compaction_profile(db_dirs.db_root_path().as_path()), self.db_backend, self.ancient_depth)?;
 
 		// prepare client config
 		let client_config = to_client_config(
@@ -174,11 +185,14 @@ impl SnapshotCommand {
 			fat_db,
 			self.compaction,
 			self.wal,
+			self.db_backend,
 			VMType::default(),
 			"".into(),
 			algorithm,
 			self.pruning_history,
 			self.pruning_memory,
+			db_dirs.ancient_path(algorithm),
+			self.ancient_depth,
 			true
 		);
 
@@ -188,7 +202,8 @@ impl SnapshotCommand {
 			&client_path,
 			&snapshot_path,
 			&self.dirs.ipc_path(),
-			Arc::new(Miner::with_spec(&spec))
+			Arc::new(Miner::with_spec(&spec)),
+			None,
 		).map_err(|e| format!("Client service error: {:?}", e))?;
 
 		Ok(service)
@@ -226,11 +241,45 @@ impl SnapshotCommand {
 		Ok(())
 	}
 
+	// open the account the manifest is signed with and find its password.
+	fn signer(&self, address: Address) -> Result<(AccountProvider, String), String> {
+		let spec = self.spec.spec()?;
+		let dir = RootDiskDirectory::create(self.dirs.keys_path(&spec.data_dir))
+			.map_err(|e| format!("Could not open keys directory: {}", e))?;
+		let store = EthStore::open(Box::new(dir))
+			.map_err(|e| format!("Could not open keys directory: {}", e))?;
+		let accounts = AccountProvider::new(Box::new(store), AccountProviderSettings::default());
+
+		if !accounts.has_account(address).unwrap_or(false) {
+			return Err(format!("Account {} to sign the snapshot with not found.", address));
+		}
+
+		let password = passwords_from_files(&self.password_files)?.into_iter()
+			.find(|password| accounts.test_password(&address, password).unwrap_or(false))
+			.ok_or_else(|| format!("No valid password to unlock account {}. Provide one with --password.", address))?;
+
+		Ok((accounts, password))
+	}
+
 	/// Take a snapshot from the head of the chain.
 	pub fn take_snapshot(self) -> Result<(), String> {
 		let file_path = self.file_path.clone().ok_or("No file path provided.".to_owned())?;
 		let file_path: PathBuf = file_path.into();
 		let block_at = self.block_at;
+		let signer = match self.sign_with {
+			Some(address) => Some((address, self.signer(address)?)),
+			None => None,
+		};
+		let base = match self.base_snapshot {
+			Some(ref file) => {
+				let reader = PackedReader::new(Path::new(file))
+					.map_err(|e| format!("Couldn't open base snapshot file: {}", e))
+					.and_then(|x| x.ok_or("Base snapshot file has invalid format.".into()));
+
+				Some(reader?.manifest().clone())
+			},
+			None => None,
+		};
 		let service = self.start_service()?;
 
 		warn!("Snapshots are currently experimental. File formats may be subject to change.");
@@ -256,7 +305,20 @@ impl SnapshotCommand {
 			}
  		});
 
-		if let Err(e) = service.client().take_snapshot(writer, block_at, &*progress) {
+		let res = match signer {
+			Some((address, (accounts, password))) => {
+				info!("Signing snapshot manifest with account {}", address);
+				let writer = SigningWriter::new(writer, move |hash| {
+					accounts.sign(address, Some(password), hash)
+						.map(Into::into)
+						.map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Failed to sign snapshot manifest: {}", e)))
+				});
+				take_using(&service, writer, base.as_ref(), block_at, &*progress)
+			},
+			None => take_using(&service, writer, base.as_ref(), block_at, &*progress),
+		};
+
+		if let Err(e) = res {
 			let _ = ::std::fs::remove_file(&file_path);
 			return Err(format!("Encountered fatal error while creating snapshot: {}", e));
 		}
@@ -270,6 +332,14 @@ impl SnapshotCommand {
 	}
 }
 
+// take a full snapshot, or a delta snapshot against the given base.
+fn take_using<W: SnapshotWriter + Send>(service: &ClientService, writer: W, base: Option<&ManifestData>, block_at: BlockId, progress: &Progress) -> Result<(), EthcoreError> {
+	match base {
+		Some(base) => service.client().take_delta_snapshot(writer, base, block_at, progress),
+		None => service.client().take_snapshot(writer, block_at, progress),
+	}
+}
+
 /// Execute this snapshot command.
 pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
@@ -56,7 +56,9 @@ pub fn main() {
 		chain: remote_client.service().clone(), 
 		snapshot_service: remote_snapshot.service().clone(), 
 		provider: remote_provider.service().clone(),
-		network_config: service_config.net
+		network_config: service_config.net,
+		trusted_snapshot_publishers: service_config.trusted_snapshot_publishers,
+		fork_blocks: service_config.fork_blocks,
 	}).unwrap();
 
 	let _ = boot::main_thread();
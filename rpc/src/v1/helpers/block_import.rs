@@ -16,8 +16,12 @@
 
 //! Block import analysis functions.
 
-use ethcore::client::BlockQueueInfo;
-use ethsync::SyncState;
+use ethcore::client::{BlockChainClient, BlockQueueInfo};
+use ethcore::snapshot::{SnapshotService, RestorationStatus};
+use ethsync::{SyncProvider, SyncState, LightSync};
+use light::client::LightChainClient;
+use util::U256;
+use v1::types::{SyncStatus, SyncInfo};
 
 /// Check if client is during major sync or during block import.
 pub fn is_major_importing(sync_state: Option<SyncState>, queue_info: BlockQueueInfo) -> bool {
@@ -28,6 +32,57 @@ pub fn is_major_importing(sync_state: Option<SyncState>, queue_info: BlockQueueI
 	is_verifying || is_syncing_state
 }
 
+/// Get the sync status of a full node.
+pub fn full_sync_status<C, SN: ?Sized, S: ?Sized>(client: &C, snapshot: &SN, sync: &S) -> SyncStatus where
+	C: BlockChainClient,
+	SN: SnapshotService,
+	S: SyncProvider,
+{
+	let status = sync.status();
+
+	let (warping, warp_chunks_amount, warp_chunks_processed) = match snapshot.status() {
+		RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
+			(true, Some(block_chunks + state_chunks), Some(block_chunks_done + state_chunks_done)),
+		_ => (false, None, None),
+	};
+
+	if warping || is_major_importing(Some(status.state), client.queue_info()) {
+		let chain_info = client.chain_info();
+		let current_block = U256::from(chain_info.best_block_number);
+		let highest_block = U256::from(status.highest_block_number.unwrap_or(status.start_block_number));
+
+		SyncStatus::Info(SyncInfo {
+			starting_block: status.start_block_number.into(),
+			current_block: current_block.into(),
+			highest_block: highest_block.into(),
+			warp_chunks_amount: warp_chunks_amount.map(|x| U256::from(x as u64)).map(Into::into),
+			warp_chunks_processed: warp_chunks_processed.map(|x| U256::from(x as u64)).map(Into::into),
+		})
+	} else {
+		SyncStatus::None
+	}
+}
+
+/// Get the sync status of a light node.
+pub fn light_sync_status(client: &LightChainClient, sync: &LightSync) -> SyncStatus {
+	if sync.is_major_importing() {
+		let chain_info = client.chain_info();
+		let current_block = U256::from(chain_info.best_block_number);
+		let highest_block = sync.highest_block().map(U256::from)
+			.unwrap_or_else(|| current_block.clone());
+
+		SyncStatus::Info(SyncInfo {
+			starting_block: U256::from(sync.start_block()).into(),
+			current_block: current_block.into(),
+			highest_block: highest_block.into(),
+			warp_chunks_amount: None,
+			warp_chunks_processed: None,
+		})
+	} else {
+		SyncStatus::None
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use ethcore::client::BlockQueueInfo;
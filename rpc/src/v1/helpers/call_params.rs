@@ -0,0 +1,83 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parameters of the calling methods, which take an optional block number
+//! followed by an optional state override.
+
+use serde::de::DeserializeOwned;
+use serde_json;
+use jsonrpc_core::{Error, Params};
+
+use v1::helpers::errors;
+use v1::types::{BlockNumber, StateOverride};
+
+/// Parses `required` leading parameters into `T`, followed by an optional block number
+/// (latest by default) and an optional state override (none by default).
+pub fn parse<T: DeserializeOwned>(params: Params, required: usize) -> Result<(T, BlockNumber, StateOverride), Error> {
+	let mut values = match params {
+		Params::Array(values) => values,
+		Params::None => Vec::new(),
+		_ => return Err(errors::invalid_params("params", "`params` should be an array")),
+	};
+
+	if values.len() < required || values.len() > required + 2 {
+		return Err(errors::invalid_params("params", format!("Expected {} to {} parameters, got {}.", required, required + 2, values.len())));
+	}
+
+	let state_override = match values.len() == required + 2 {
+		true => serde_json::from_value(values.pop().expect("more than required values; qed"))
+			.map_err(|e| errors::invalid_params("state override", e))?,
+		false => StateOverride::default(),
+	};
+	let block = match values.len() == required + 1 {
+		true => serde_json::from_value(values.pop().expect("more than required values; qed"))
+			.map_err(|e| errors::invalid_params("block number", e))?,
+		false => BlockNumber::default(),
+	};
+
+	Params::Array(values).parse().map(|head| (head, block, state_override))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use jsonrpc_core::Params;
+	use v1::types::{BlockNumber, StateOverride};
+	use super::parse;
+
+	fn params(json: &str) -> Params {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn parses_optional_block_and_state_override() {
+		let (head, block, state_override) = parse::<(u64,)>(params("[1]"), 1).unwrap();
+		assert_eq!((head, block, state_override), ((1,), BlockNumber::Latest, StateOverride::default()));
+
+		let (_, block, state_override) = parse::<(u64,)>(params(r#"[1, "0x5"]"#), 1).unwrap();
+		assert_eq!((block, state_override), (BlockNumber::Num(5), StateOverride::default()));
+
+		let (_, block, state_override) = parse::<(u64,)>(params(r#"[1, "pending", {"0x0000000000000000000000000000000000000001": {"balance": "0x10"}}]"#), 1).unwrap();
+		assert_eq!(block, BlockNumber::Pending);
+		assert!(state_override != StateOverride::default());
+	}
+
+	#[test]
+	fn rejects_wrong_number_of_params() {
+		assert!(parse::<(u64, u64)>(params("[1]"), 2).is_err());
+		assert!(parse::<(u64,)>(params(r#"[1, "latest", {}, 4]"#), 1).is_err());
+	}
+}
@@ -137,6 +137,14 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn history_pruned() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request is not supported because your node is running with history pruning. Run without --history-keep-blocks.".into(),
+		data: None
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
@@ -27,7 +27,6 @@ use ethcore::transaction::{Action, Transaction as EthTransaction};
 
 use futures::{future, Future, BoxFuture};
 use jsonrpc_core::Error;
-use jsonrpc_macros::Trailing;
 
 use light::cache::Cache;
 use light::client::LightChainClient;
@@ -158,12 +157,12 @@ impl LightFetch {
 	}
 
 	/// helper for getting proved execution.
-	pub fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<ExecutionResult, Error> {
+	pub fn proved_execution(&self, req: CallRequest, num: BlockNumber) -> BoxFuture<ExecutionResult, Error> {
 		const DEFAULT_GAS_PRICE: U256 = U256([0, 0, 0, 21_000_000]);
 
 		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
 		let req: CallRequestHelper = req.into();
-		let id = num.unwrap_or_default().into();
+		let id = num.into();
 
 		let from = req.from.unwrap_or(Address::zero());
 		let nonce_fut = match req.nonce {
@@ -278,19 +277,20 @@ impl LightFetch {
 			BlockId::Number(x) => Some(x),
 		};
 
-		match (block_number(filter.to_block), block_number(filter.from_block)) {
+		let from_number = match (block_number(filter.to_block), block_number(filter.from_block)) {
 			(Some(to), Some(from)) if to < from => return future::ok(Vec::new()).boxed(),
-			(Some(_), Some(_)) => {},
+			(Some(_), Some(from)) => from,
 			_ => return future::err(errors::unknown_block()).boxed(),
-		}
+		};
 
 		let maybe_future = self.sync.with_context(move |ctx| {
 			// find all headers which match the filter, and fetch the receipts for each one.
 			// match them with their numbers for easy sorting later.
 			let bit_combos = filter.bloom_possibilities();
+			// walking back the ancestry of `to_block` also covers non-canonical ranges,
+			// e.g. for logs of retracted blocks.
 			let receipts_futures: Vec<_> = self.client.ancestry_iter(filter.to_block)
-				.take_while(|ref hdr| BlockId::Number(hdr.number()) != filter.from_block)
-				.take_while(|ref hdr| BlockId::Hash(hdr.hash()) != filter.from_block)
+				.take_while(|ref hdr| hdr.number() >= from_number)
 				.filter(|ref hdr| {
 					let hdr_bloom = hdr.log_bloom();
 					bit_combos.iter().find(|&bloom| hdr_bloom & *bloom == *bloom).is_some()
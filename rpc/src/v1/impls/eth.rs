@@ -45,12 +45,12 @@ use jsonrpc_macros::Trailing;
 
 use v1::helpers::{errors, limit_logs, fake_sign};
 use v1::helpers::dispatch::{Dispatcher, FullDispatcher, default_gas_price};
-use v1::helpers::block_import::is_major_importing;
+use v1::helpers::block_import::full_sync_status;
 use v1::helpers::accounts::unwrap_provider;
-use v1::traits::Eth;
+use v1::traits::{Eth, EthCall};
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, StateOverride, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -58,6 +58,7 @@ use v1::metadata::Metadata;
 const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
 /// Eth RPC options
+#[derive(Clone, Copy)]
 pub struct EthClientOptions {
 	/// Return nonce from transaction queue when pending block not available.
 	pub pending_nonce_from_queue: bool,
@@ -142,9 +143,22 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 		unwrap_provider(&self.accounts)
 	}
 
+	/// Turn missing block data into an error if the block is known, but its body
+	/// and receipts were pruned. Blocks missing since a warp restoration are not an error.
+	fn check_history<T>(&self, id: BlockId, value: Option<T>) -> Result<Option<T>, Error> {
+		match value {
+			None => match self.client.block_header(id) {
+				Some(ref header) if header.number() < self.client.pruning_info().earliest_history => Err(errors::history_pruned()),
+				_ => Ok(None),
+			},
+			value => Ok(value),
+		}
+	}
+
 	fn block(&self, id: BlockId, include_txs: bool) -> Result<Option<RichBlock>, Error> {
 		let client = &self.client;
-		match (client.block(id.clone()), client.block_total_difficulty(id)) {
+		let block = self.check_history(id.clone(), client.block(id.clone()))?;
+		match (block, client.block_total_difficulty(id)) {
 			(Some(block), Some(total_difficulty)) => {
 				let view = block.header_view();
 				Ok(Some(RichBlock {
@@ -181,17 +195,30 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 	}
 
 	fn transaction(&self, id: TransactionId) -> Result<Option<Transaction>, Error> {
-		match self.client.transaction(id) {
+		match self.client.transaction(id.clone()) {
 			Some(t) => Ok(Some(Transaction::from_localized(t, self.eip86_transition))),
-			None => Ok(None),
+			None => match id {
+				TransactionId::Hash(hash) => match self.client.transaction_block(TransactionId::Hash(hash)) {
+					Some(hash) => self.check_history(BlockId::Hash(hash), None),
+					None => Ok(None),
+				},
+				TransactionId::Location(block, _) => match self.client.block_body(block.clone()) {
+					Some(_) => Ok(None),
+					None => self.check_history(block, None),
+				},
+			},
 		}
 	}
 
 	fn uncle(&self, id: UncleId) -> Result<Option<RichBlock>, Error> {
 		let client = &self.client;
+		let block = id.block.clone();
 		let uncle: BlockHeader = match client.uncle(id) {
 			Some(hdr) => hdr.decode(),
-			None => { return Ok(None); }
+			None => match client.block_body(block.clone()) {
+				Some(_) => { return Ok(None); },
+				None => { return self.check_history(block, None); },
+			},
 		};
 		let parent_difficulty = match client.block_total_difficulty(BlockId::Hash(uncle.parent_hash().clone())) {
 			Some(difficulty) => difficulty,
@@ -285,35 +312,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn syncing(&self) -> Result<SyncStatus, Error> {
-		use ethcore::snapshot::RestorationStatus;
-
-		let status = self.sync.status();
-		let client = &self.client;
-		let snapshot_status = self.snapshot.status();
-
-		let (warping, warp_chunks_amount, warp_chunks_processed) = match snapshot_status {
-			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
-				(true, Some(block_chunks + state_chunks), Some(block_chunks_done + state_chunks_done)),
-			_ => (false, None, None),
-		};
-
-
-		if warping || is_major_importing(Some(status.state), client.queue_info()) {
-			let chain_info = client.chain_info();
-			let current_block = U256::from(chain_info.best_block_number);
-			let highest_block = U256::from(status.highest_block_number.unwrap_or(status.start_block_number));
-
-			let info = SyncInfo {
-				starting_block: status.start_block_number.into(),
-				current_block: current_block.into(),
-				highest_block: highest_block.into(),
-				warp_chunks_amount: warp_chunks_amount.map(|x| U256::from(x as u64)).map(Into::into),
-				warp_chunks_processed: warp_chunks_processed.map(|x| U256::from(x as u64)).map(Into::into),
-			};
-			Ok(SyncStatus::Info(info))
-		} else {
-			Ok(SyncStatus::None)
-		}
+		Ok(full_sync_status(&*self.client, &*self.snapshot, &*self.sync))
 	}
 
 	fn author(&self, meta: Metadata) -> BoxFuture<RpcH160, Error> {
@@ -435,33 +434,38 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn block_transaction_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-		future::ok(self.client.block(BlockId::Hash(hash.into()))
-			.map(|block| block.transactions_count().into())).boxed()
+		let id = BlockId::Hash(hash.into());
+		future::done(self.check_history(id.clone(), self.client.block(id)
+			.map(|block| block.transactions_count().into()))).boxed()
 	}
 
 	fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		future::ok(match num {
-			BlockNumber::Pending => Some(
+		future::done(match num {
+			BlockNumber::Pending => Ok(Some(
 				self.miner.status().transactions_in_pending_block.into()
-			),
-			_ =>
-				self.client.block(num.into())
-					.map(|block| block.transactions_count().into())
+			)),
+			_ => {
+				let id: BlockId = num.into();
+				self.check_history(id.clone(), self.client.block(id)
+					.map(|block| block.transactions_count().into()))
+			}
 		}).boxed()
 	}
 
 	fn block_uncles_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-			future::ok(self.client.block(BlockId::Hash(hash.into()))
-				.map(|block| block.uncles_count().into()))
-				.boxed()
+		let id = BlockId::Hash(hash.into());
+		future::done(self.check_history(id.clone(), self.client.block(id)
+			.map(|block| block.uncles_count().into()))).boxed()
 	}
 
 	fn block_uncles_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		future::ok(match num {
-			BlockNumber::Pending => Some(0.into()),
-			_ => self.client.block(num.into())
-					.map(|block| block.uncles_count().into()
-			),
+		future::done(match num {
+			BlockNumber::Pending => Ok(Some(0.into())),
+			_ => {
+				let id: BlockId = num.into();
+				self.check_history(id.clone(), self.client.block(id)
+					.map(|block| block.uncles_count().into()))
+			},
 		}).boxed()
 	}
 
@@ -515,8 +519,14 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		match (self.miner.pending_receipt(best_block, &hash), self.options.allow_pending_receipt_query) {
 			(Some(receipt), true) => Ok(Some(receipt.into())),
 			_ => {
-				let receipt = self.client.transaction_receipt(TransactionId::Hash(hash));
-				Ok(receipt.map(Into::into))
+				let id = TransactionId::Hash(hash);
+				match self.client.transaction_receipt(id.clone()) {
+					Some(receipt) => Ok(Some(receipt.into())),
+					None => match self.client.transaction_block(id) {
+						Some(block) => self.check_history(BlockId::Hash(block), None),
+						None => Ok(None),
+					},
+				}
 			}
 		}
 	}
@@ -631,24 +641,6 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		self.send_raw_transaction(raw)
 	}
 
-	fn call(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		let request = CallRequest::into(request);
-		let signed = match fake_sign::sign_call(&self.client, &self.miner, request) {
-			Ok(signed) => signed,
-			Err(e) => return future::err(e).boxed(),
-		};
-
-		let result = match num.unwrap_or_default() {
-			BlockNumber::Pending => self.miner.call(&*self.client, &signed, Default::default()),
-			num => self.client.call(&signed, num.into(), Default::default()),
-		};
-
-		future::done(result
-			.map(|b| b.output.into())
-			.map_err(errors::call)
-		).boxed()
-	}
-
 	fn estimate_gas(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
 		let request = CallRequest::into(request);
 		let signed = match fake_sign::sign_call(&self.client, &self.miner, request) {
@@ -673,3 +665,36 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		Err(errors::deprecated("Compilation of Solidity via RPC is deprecated".to_string()))
 	}
 }
+
+impl<C, SN: ?Sized, S: ?Sized, M, EM> EthCall for EthClient<C, SN, S, M, EM> where
+	C: MiningBlockChainClient + 'static,
+	SN: SnapshotService + 'static,
+	S: SyncProvider + 'static,
+	M: MinerService + 'static,
+	EM: ExternalMinerService + 'static,
+{
+	fn call(&self, request: CallRequest, num: BlockNumber, state_override: StateOverride) -> BoxFuture<Bytes, Error> {
+		let state_override = try_bf!(state_override.into_state_override());
+		let request = CallRequest::into(request);
+		let signed = try_bf!(fake_sign::sign_call(&self.client, &self.miner, request));
+
+		let result = if state_override.is_empty() {
+			match num {
+				BlockNumber::Pending => self.miner.call(&*self.client, &signed, Default::default()),
+				num => self.client.call(&signed, num.into(), Default::default()),
+			}
+		} else {
+			// the pending state lives in the miner, which knows nothing about overrides.
+			if num == BlockNumber::Pending {
+				return future::err(errors::unimplemented(Some("State overrides are not supported for the pending block.".into()))).boxed();
+			}
+			self.client.call_many(&[(signed, Default::default())], num.into(), &state_override)
+				.map(|mut results| results.pop().expect("one transaction was executed; qed"))
+		};
+
+		future::done(result
+			.map(|b| b.output.into())
+			.map_err(errors::call)
+		).boxed()
+	}
+}
@@ -20,6 +20,7 @@ use std::sync::Arc;
 use std::collections::HashSet;
 
 use jsonrpc_core::*;
+use ethcore::encoded;
 use ethcore::miner::MinerService;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::client::{BlockChainClient, BlockId};
@@ -40,6 +41,9 @@ pub trait Filterable {
 	/// Get a block hash by block id.
 	fn block_hash(&self, id: BlockId) -> Option<RpcH256>;
 
+	/// Get a block header by block id.
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header>;
+
 	/// pending transaction hashes at the given block.
 	fn pending_transactions_hashes(&self, block_number: u64) -> Vec<H256>;
 
@@ -51,6 +55,43 @@ pub trait Filterable {
 
 	/// Get a reference to the poll manager.
 	fn polls(&self) -> &Mutex<PollManager<PollFilter>>;
+
+	/// Get logs of the blocks retracted from the canonical chain, walking back from the block
+	/// with given hash. The logs are marked as removed and returned in reverse order, together
+	/// with the number of retracted blocks.
+	fn removed_logs(&self, block_hash: H256, filter: &EthcoreFilter) -> (BoxFuture<Vec<Log>, Error>, u64) {
+		let mut retracted = Vec::new();
+		let mut current = block_hash;
+		while let Some(header) = self.block_header(BlockId::Hash(current)) {
+			let canonical: Option<H256> = self.block_hash(BlockId::Number(header.number())).map(Into::into);
+			if canonical == Some(current) {
+				break;
+			}
+
+			retracted.push(current);
+			current = header.parent_hash();
+		}
+
+		let retracted_len = retracted.len() as u64;
+		let logs = retracted.into_iter()
+			.map(|hash| {
+				let mut filter = filter.clone();
+				filter.from_block = BlockId::Hash(hash);
+				filter.to_block = filter.from_block.clone();
+				self.logs(filter).map(|logs| logs.into_iter().rev().map(|mut log| {
+					log.log_type = "removed".into();
+					log.removed = true;
+					log
+				}).collect::<Vec<_>>())
+			})
+			.collect::<Vec<_>>();
+
+		let logs = future::join_all(logs)
+			.map(|logs| logs.into_iter().flat_map(|logs| logs).collect())
+			.boxed();
+
+		(logs, retracted_len)
+	}
 }
 
 /// Eth filter rpc implementation for a full node.
@@ -83,6 +124,10 @@ impl<C, M> Filterable for EthFilterClient<C, M> where C: BlockChainClient, M: Mi
 		self.client.block_hash(id).map(Into::into)
 	}
 
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		self.client.block_header(id)
+	}
+
 	fn pending_transactions_hashes(&self, best: u64) -> Vec<H256> {
 		self.miner.pending_transactions_hashes(best)
 	}
@@ -104,7 +149,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 	fn new_filter(&self, filter: Filter) -> Result<RpcU256, Error> {
 		let mut polls = self.polls().lock();
 		let block_number = self.best_block_number();
-		let id = polls.create_poll(PollFilter::Logs(block_number, Default::default(), filter));
+		let id = polls.create_poll(PollFilter::Logs(block_number, None, Default::default(), filter));
 		Ok(id.into())
 	}
 
@@ -163,7 +208,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 					// return new hashes
 					future::ok(FilterChanges::Hashes(new_hashes)).boxed()
 				},
-				PollFilter::Logs(ref mut block_number, ref mut previous_logs, ref filter) => {
+				PollFilter::Logs(ref mut block_number, ref mut last_block_hash, ref mut previous_logs, ref filter) => {
 					// retrive the current block number
 					let current_number = self.best_block_number();
 
@@ -172,7 +217,15 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 
 					// build appropriate filter
 					let mut filter: EthcoreFilter = filter.clone().into();
-					filter.from_block = BlockId::Number(*block_number);
+
+					// retrieve logs of the blocks retracted since the last poll
+					let (removed, retracted) = match *last_block_hash {
+						Some(hash) => self.removed_logs(hash, &filter),
+						None => (future::ok(Vec::new()).boxed(), 0),
+					};
+
+					// start over from the fork point if blocks were retracted
+					filter.from_block = BlockId::Number(block_number.saturating_sub(retracted));
 					filter.to_block = BlockId::Latest;
 
 					// retrieve pending logs
@@ -197,9 +250,13 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 					// we want to get logs
 					*block_number = current_number + 1;
 
+					// save the hash of the last block to detect reorgs on the next poll
+					*last_block_hash = self.block_hash(BlockId::Number(current_number)).map(Into::into);
+
 					// retrieve logs in range from_block..min(BlockId::Latest..to_block)
 					let limit = filter.limit;
-					self.logs(filter)
+					removed.join(self.logs(filter))
+						.map(|(mut logs, mined)| { logs.extend(mined); logs }) // removed logs go first
 						.map(move |mut logs| { logs.extend(pending); logs }) // append fetched pending logs
 						.map(move |logs| limit_logs(logs, limit)) // limit the logs
 						.map(FilterChanges::Logs)
@@ -212,7 +269,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 	fn filter_logs(&self, index: Index) -> BoxFuture<Vec<Log>, Error> {
 		let mut polls = self.polls().lock();
 		match polls.poll(&index.value()) {
-			Some(&PollFilter::Logs(ref _block_number, ref _previous_log, ref filter)) => {
+			Some(&PollFilter::Logs(ref _block_number, ref _last_block_hash, ref _previous_log, ref filter)) => {
 				let include_pending = filter.to_block == Some(BlockNumber::Pending);
 				let filter: EthcoreFilter = filter.clone().into();
 
@@ -19,17 +19,18 @@
 use std::sync::Arc;
 use std::collections::BTreeMap;
 
-use futures::{self, future, BoxFuture, Future};
+use futures::{future, BoxFuture, Future};
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use jsonrpc_macros::pubsub::{Sink, Subscriber};
 use jsonrpc_pubsub::SubscriptionId;
 
 use v1::helpers::{errors, limit_logs, Subscribers};
+use v1::helpers::block_import::light_sync_status;
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
-use v1::types::{pubsub, RichHeader, Log};
+use v1::types::{pubsub, RichHeader, Log, SyncStatus};
 
 use ethcore::encoded;
 use ethcore::filter::Filter as EthFilter;
@@ -42,37 +43,68 @@ use parity_reactor::Remote;
 use util::{RwLock, Mutex, H256, Bytes};
 
 type Client = Sink<pubsub::Result>;
+type SyncStatusProvider = Box<Fn() -> SyncStatus + Send + Sync>;
 
 /// Eth PubSub implementation.
 pub struct EthPubSubClient<C> {
 	handler: Arc<ChainNotificationHandler<C>>,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> EthPubSubClient<C> {
 	/// Creates new `EthPubSubClient`.
 	pub fn new(client: Arc<C>, remote: Remote) -> Self {
+		Self::new_with_sync_status(client, remote, None)
+	}
+
+	/// Creates new `EthPubSubClient` notifying `syncing` subscribers
+	/// about changes of the status returned by given function.
+	pub fn with_sync_status<F>(client: Arc<C>, remote: Remote, sync_status: F) -> Self where
+		F: Fn() -> SyncStatus + Send + Sync + 'static,
+	{
+		Self::new_with_sync_status(client, remote, Some(Box::new(sync_status)))
+	}
+
+	fn new_with_sync_status(client: Arc<C>, remote: Remote, sync_status: Option<SyncStatusProvider>) -> Self {
 		let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let sync_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		EthPubSubClient {
 			handler: Arc::new(ChainNotificationHandler {
 				client,
 				remote,
 				heads_subscribers: heads_subscribers.clone(),
 				logs_subscribers: logs_subscribers.clone(),
+				transactions_subscribers: transactions_subscribers.clone(),
+				sync_subscribers: sync_subscribers.clone(),
+				sync_status,
+				last_sync_status: Mutex::new(None),
 			}),
 			heads_subscribers,
 			logs_subscribers,
+			transactions_subscribers,
+			sync_subscribers,
 		}
 	}
 
 	/// Creates new `EthPubSubCient` with deterministic subscription ids.
 	#[cfg(test)]
 	pub fn new_test(client: Arc<C>, remote: Remote) -> Self {
-		let client = Self::new(client, remote);
+		Self::new_test_with_sync_status(client, remote, None)
+	}
+
+	/// Creates new `EthPubSubCient` with deterministic subscription ids and given sync status.
+	#[cfg(test)]
+	pub fn new_test_with_sync_status(client: Arc<C>, remote: Remote, sync_status: Option<SyncStatusProvider>) -> Self {
+		let client = Self::new_with_sync_status(client, remote, sync_status);
 		*client.heads_subscribers.write() = Subscribers::new_test();
 		*client.logs_subscribers.write() = Subscribers::new_test();
+		*client.transactions_subscribers.write() = Subscribers::new_test();
+		*client.sync_subscribers.write() = Subscribers::new_test();
 		client
 	}
 
@@ -91,13 +123,18 @@ impl EthPubSubClient<LightFetch> {
 		cache: Arc<Mutex<Cache>>,
 		remote: Remote,
 	) -> Self {
+		let sync_status = {
+			let client = client.clone();
+			let sync = sync.clone();
+			move || light_sync_status(&*client, &*sync)
+		};
 		let fetch = LightFetch {
 			client,
 			on_demand,
 			sync,
 			cache
 		};
-		EthPubSubClient::new(Arc::new(fetch), remote)
+		EthPubSubClient::with_sync_status(Arc::new(fetch), remote, sync_status)
 	}
 }
 
@@ -107,6 +144,10 @@ pub struct ChainNotificationHandler<C> {
 	remote: Remote,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	sync_status: Option<SyncStatusProvider>,
+	last_sync_status: Mutex<Option<SyncStatus>>,
 }
 
 impl<C> ChainNotificationHandler<C> {
@@ -129,26 +170,43 @@ impl<C> ChainNotificationHandler<C> {
 		}
 	}
 
-	fn notify_logs<F>(&self, enacted: &[H256], logs: F) where
+	/// Notify logs subscribers about logs of the `retracted` blocks (marked as removed and
+	/// expected in descending order) followed by the logs of `enacted` blocks (in ascending order).
+	fn notify_logs<F>(&self, enacted: &[H256], retracted: &[H256], logs: F) where
 		F: Fn(EthFilter) -> BoxFuture<Vec<Log>, Error>,
 	{
 		for &(ref subscriber, ref filter) in self.logs_subscribers.read().values() {
-			let logs = futures::future::join_all(enacted
+			let block_logs = |hash: &H256| {
+				let mut filter = filter.clone();
+				filter.from_block = BlockId::Hash(*hash);
+				filter.to_block = filter.from_block.clone();
+				logs(filter)
+			};
+			let removed = future::join_all(retracted
 				.iter()
-				.map(|hash| {
-					let mut filter = filter.clone();
-					filter.from_block = BlockId::Hash(*hash);
-					filter.to_block = filter.from_block.clone();
-					logs(filter)
-				})
+				.map(|hash| block_logs(hash).map(|logs| {
+					// undo the block's logs in reverse order
+					logs.into_iter().rev().map(|mut log| {
+						log.log_type = "removed".into();
+						log.removed = true;
+						log
+					}).collect::<Vec<_>>()
+				}))
+				.collect::<Vec<_>>()
+			);
+			let added = future::join_all(enacted
+				.iter()
+				.map(|hash| block_logs(hash))
 				.collect::<Vec<_>>()
 			);
 			let limit = filter.limit;
 			let remote = self.remote.clone();
 			let subscriber = subscriber.clone();
-			self.remote.spawn(logs
-				.map(move |logs| {
-					let logs = logs.into_iter().flat_map(|log| log).collect();
+			self.remote.spawn(removed.join(added)
+				.map(move |(removed, added)| {
+					let logs = removed.into_iter().flat_map(|log| log)
+						.chain(added.into_iter().flat_map(|log| log))
+						.collect();
 					let logs = limit_logs(logs, limit);
 					if !logs.is_empty() {
 						Self::notify(&remote, &subscriber, pubsub::Result::Logs(logs));
@@ -158,6 +216,35 @@ impl<C> ChainNotificationHandler<C> {
 			);
 		}
 	}
+
+	/// Notify all subscribers about new transaction hashes.
+	pub fn notify_new_transactions(&self, hashes: &[H256]) {
+		for subscriber in self.transactions_subscribers.read().values() {
+			for hash in hashes {
+				Self::notify(&self.remote, subscriber, pubsub::Result::TransactionHash((*hash).into()));
+			}
+		}
+	}
+
+	/// Notify `syncing` subscribers if the sync status has changed since the last notification.
+	fn notify_syncing(&self) {
+		let status = match self.sync_status {
+			Some(ref sync_status) => sync_status(),
+			None => return,
+		};
+
+		{
+			let mut last = self.last_sync_status.lock();
+			if last.as_ref() == Some(&status) {
+				return;
+			}
+			*last = Some(status.clone());
+		}
+
+		for subscriber in self.sync_subscribers.read().values() {
+			Self::notify(&self.remote, subscriber, pubsub::Result::SyncState(status.clone()));
+		}
+	}
 }
 
 /// A light client wrapper struct.
@@ -183,6 +270,7 @@ impl<C: LightClient> LightChainNotify for ChainNotificationHandler<C> {
 	fn new_headers(
 		&self,
 		enacted: &[H256],
+		retracted: &[H256],
 	) {
 		let headers = enacted
 			.iter()
@@ -191,7 +279,8 @@ impl<C: LightClient> LightChainNotify for ChainNotificationHandler<C> {
 			.collect::<Vec<_>>();
 
 		self.notify_heads(&headers);
-		self.notify_logs(&enacted, |filter| self.client.logs(filter))
+		self.notify_logs(enacted, retracted, |filter| self.client.logs(filter));
+		self.notify_syncing();
 	}
 }
 
@@ -200,14 +289,22 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 		&self,
 		_imported: Vec<H256>,
 		_invalid: Vec<H256>,
-		enacted: Vec<H256>,
-		retracted: Vec<H256>,
+		mut enacted: Vec<H256>,
+		mut retracted: Vec<H256>,
 		_sealed: Vec<H256>,
 		// Block bytes.
 		_proposed: Vec<Bytes>,
 		_duration: u64,
 	) {
 		const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
+
+		// routes are not ordered; enacted blocks go in ascending and retracted in descending order.
+		{
+			let number = |hash: &H256| self.client.block_number(BlockId::Hash(*hash));
+			enacted.sort_by(|a, b| number(a).cmp(&number(b)));
+			retracted.sort_by(|a, b| number(b).cmp(&number(a)));
+		}
+
 		let headers = enacted
 			.iter()
 			.filter_map(|hash| self.client.block_header(BlockId::Hash(*hash)))
@@ -220,18 +317,17 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 		// Headers
 		self.notify_heads(&headers);
 
-		// Enacted logs
-		self.notify_logs(&enacted, |filter| {
+		// Retracted and enacted logs
+		self.notify_logs(&enacted, &retracted, |filter| {
 			future::ok(self.client.logs(filter).into_iter().map(Into::into).collect()).boxed()
 		});
 
-		// Retracted logs
-		self.notify_logs(&retracted, |filter| {
-			future::ok(self.client.logs(filter).into_iter().map(Into::into).map(|mut log: Log| {
-				log.log_type = "removed".into();
-				log
-			}).collect()).boxed()
-		});
+		// Sync status
+		self.notify_syncing();
+	}
+
+	fn restoration_status_changed(&self) {
+		self.notify_syncing();
 	}
 }
 
@@ -252,6 +348,12 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 			(pubsub::Kind::Logs, Some(pubsub::Params::Logs(filter))) => {
 				self.logs_subscribers.write().push(subscriber, filter.into());
 			},
+			(pubsub::Kind::NewPendingTransactions, None) => {
+				self.transactions_subscribers.write().push(subscriber)
+			},
+			(pubsub::Kind::Syncing, None) => {
+				self.sync_subscribers.write().push(subscriber)
+			},
 			_ => {
 				let _ = subscriber.reject(errors::unimplemented(None));
 			},
@@ -261,7 +363,9 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 	fn unsubscribe(&self, id: SubscriptionId) -> BoxFuture<bool, Error> {
 		let res = self.heads_subscribers.write().remove(&id).is_some();
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
+		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
+		let res4 = self.sync_subscribers.write().remove(&id).is_some();
 
-		future::ok(res || res2).boxed()
+		future::ok(res || res2 || res3 || res4).boxed()
 	}
 }
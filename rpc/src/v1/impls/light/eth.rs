@@ -47,12 +47,12 @@ use futures::sync::oneshot;
 use v1::impls::eth_filter::Filterable;
 use v1::helpers::{CallRequest as CRequest, errors, limit_logs, dispatch};
 use v1::helpers::{PollFilter, PollManager};
-use v1::helpers::block_import::is_major_importing;
+use v1::helpers::block_import::light_sync_status;
 use v1::helpers::light_fetch::LightFetch;
-use v1::traits::Eth;
+use v1::traits::{Eth, EthCall};
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, StateOverride, Work,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -216,22 +216,7 @@ impl Eth for EthClient {
 	}
 
 	fn syncing(&self) -> Result<SyncStatus, Error> {
-		if self.sync.is_major_importing() {
-			let chain_info = self.client.chain_info();
-			let current_block = U256::from(chain_info.best_block_number);
-			let highest_block = self.sync.highest_block().map(U256::from)
-				.unwrap_or_else(|| current_block.clone());
-
-			Ok(SyncStatus::Info(SyncInfo {
-				starting_block: U256::from(self.sync.start_block()).into(),
-				current_block: current_block.into(),
-				highest_block: highest_block.into(),
-				warp_chunks_amount: None,
-				warp_chunks_processed: None,
-			}))
-		} else {
-			Ok(SyncStatus::None)
-		}
+		Ok(light_sync_status(&*self.client, &*self.sync))
 	}
 
 	fn author(&self, _meta: Self::Metadata) -> BoxFuture<RpcH160, Error> {
@@ -383,18 +368,9 @@ impl Eth for EthClient {
 		self.send_raw_transaction(raw)
 	}
 
-	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		self.fetcher().proved_execution(req, num).and_then(|res| {
-			match res {
-				Ok(exec) => Ok(exec.output.into()),
-				Err(e) => Err(errors::execution(e)),
-			}
-		}).boxed()
-	}
-
 	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
 		// TODO: binary chop for more accurate estimates.
-		self.fetcher().proved_execution(req, num).and_then(|res| {
+		self.fetcher().proved_execution(req, num.unwrap_or_default()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
 				Err(e) => Err(errors::execution(e)),
@@ -464,6 +440,21 @@ impl Eth for EthClient {
 	}
 }
 
+impl EthCall for EthClient {
+	fn call(&self, req: CallRequest, num: BlockNumber, state_override: StateOverride) -> BoxFuture<Bytes, Error> {
+		if state_override != StateOverride::default() {
+			return future::err(errors::light_unimplemented(None)).boxed();
+		}
+
+		self.fetcher().proved_execution(req, num).and_then(|res| {
+			match res {
+				Ok(exec) => Ok(exec.output.into()),
+				Err(e) => Err(errors::execution(e)),
+			}
+		}).boxed()
+	}
+}
+
 // This trait implementation triggers a blanked impl of `EthFilter`.
 impl Filterable for EthClient {
 	fn best_block_number(&self) -> u64 { self.client.chain_info().best_block_number }
@@ -472,6 +463,10 @@ impl Filterable for EthClient {
 		self.client.block_hash(id).map(Into::into)
 	}
 
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		self.client.block_header(id)
+	}
+
 	fn pending_transactions_hashes(&self, _block_number: u64) -> Vec<::util::H256> {
 		Vec::new()
 	}
@@ -43,8 +43,8 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader,
+	OperationsInfo, DappId, ChainStatus, SnapshotInfo,
+	AccountInfo, HwAccountInfo, Header, RichHeader, PeerScore,
 };
 
 /// Parity implementation for light client.
@@ -185,6 +185,10 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn peer_scores(&self) -> Result<Vec<PeerScore>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn net_port(&self) -> Result<u16, Error> {
 		Ok(self.settings.network_port)
 	}
@@ -343,6 +347,10 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn snapshot_status(&self) -> Result<Option<SnapshotInfo>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
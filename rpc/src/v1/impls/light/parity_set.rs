@@ -23,13 +23,13 @@ use std::sync::Arc;
 use ethsync::ManageNetwork;
 use fetch::Fetch;
 use futures::{BoxFuture, Future};
-use util::sha3;
+use util::{self, sha3};
 
 use jsonrpc_core::Error;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, BlockNumber, ReleaseInfo, Transaction, LocalDapp};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
@@ -74,6 +74,14 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn propose_signer_vote(&self, _address: H160, _authorize: bool) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn discard_signer_vote(&self, _address: H160) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_transactions_limit(&self, _limit: usize) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -96,6 +104,17 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		}
 	}
 
+	fn reset_peer_scores(&self, peer: Option<H512>) -> Result<bool, Error> {
+		let peer = peer.map(|id| {
+			let id: util::H512 = id.into();
+			id.hex()
+		});
+		match self.net.reset_peer_scores(peer) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Peer id", e)),
+		}
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool, Error> {
 		self.net.deny_unreserved_peers();
 		Ok(true)
@@ -150,4 +169,8 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn take_snapshot(&self, _number: BlockNumber) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -18,9 +18,9 @@
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::traits::Traces;
+use v1::traits::{Traces, TracesCall};
 use v1::helpers::errors;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, StateOverride, H256};
 
 /// Traces api implementation.
 // TODO: all calling APIs should be possible w. proved remote TX execution.
@@ -43,7 +43,7 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn call(&self, _request: CallRequest, _flags: Vec<String>, _block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
+	fn call_many(&self, _requests: Vec<(CallRequest, Vec<String>)>, _block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
 
@@ -54,4 +54,14 @@ impl Traces for TracesClient {
 	fn replay_transaction(&self, _transaction_hash: H256, _flags: Vec<String>) -> Result<TraceResults, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn replay_block_transactions(&self, _block_number: BlockNumber, _flags: Vec<String>) -> Result<Vec<TraceResultsWithTransactionHash>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+}
+
+impl TracesCall for TracesClient {
+	fn call(&self, _request: CallRequest, _flags: Vec<String>, _block: BlockNumber, _state_override: StateOverride) -> Result<TraceResults, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -32,6 +32,7 @@ use ethcore::ids::BlockId;
 use ethcore::miner::MinerService;
 use ethcore::client::{MiningBlockChainClient};
 use ethcore::mode::Mode;
+use ethcore::snapshot::{SnapshotService, CreationStatus};
 use ethcore::account_provider::AccountProvider;
 use updater::{Service as UpdateService};
 use crypto::DEFAULT_MAC;
@@ -47,8 +48,8 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader
+	OperationsInfo, DappId, ChainStatus, SnapshotInfo,
+	AccountInfo, HwAccountInfo, RichHeader, PeerScore,
 };
 
 /// Parity implementation.
@@ -63,6 +64,7 @@ pub struct ParityClient<C, M, S: ?Sized, U> where
 	sync: Arc<S>,
 	updater: Arc<U>,
 	net: Arc<ManageNetwork>,
+	snapshot: Arc<SnapshotService>,
 	accounts: Option<Arc<AccountProvider>>,
 	logger: Arc<RotatingLogger>,
 	settings: Arc<NetworkSettings>,
@@ -85,6 +87,7 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 		sync: &Arc<S>,
 		updater: &Arc<U>,
 		net: &Arc<ManageNetwork>,
+		snapshot: &Arc<SnapshotService>,
 		store: &Option<Arc<AccountProvider>>,
 		logger: Arc<RotatingLogger>,
 		settings: Arc<NetworkSettings>,
@@ -98,6 +101,7 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 			sync: sync.clone(),
 			updater: updater.clone(),
 			net: net.clone(),
+			snapshot: snapshot.clone(),
 			accounts: store.clone(),
 			logger: logger,
 			settings: settings,
@@ -216,6 +220,10 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn peer_scores(&self) -> Result<Vec<PeerScore>, Error> {
+		Ok(self.net.peer_scores().into_iter().map(Into::into).collect())
+	}
+
 	fn net_port(&self) -> Result<u16, Error> {
 		Ok(self.settings.network_port)
 	}
@@ -377,6 +385,18 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn snapshot_status(&self) -> Result<Option<SnapshotInfo>, Error> {
+		Ok(match self.snapshot.creation_status() {
+			CreationStatus::Inactive => None,
+			CreationStatus::Ongoing { block_number, accounts, blocks, size } => Some(SnapshotInfo {
+				block_number: block_number.into(),
+				accounts: accounts.into(),
+				blocks: blocks.into(),
+				size: size.into(),
+			}),
+		})
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
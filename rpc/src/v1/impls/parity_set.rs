@@ -21,17 +21,18 @@ use std::sync::Arc;
 use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
 use ethcore::mode::Mode;
+use ethcore::snapshot::{SnapshotService, CreationStatus};
 use ethsync::ManageNetwork;
 use fetch::{self, Fetch};
 use futures::{BoxFuture, Future};
-use util::sha3;
+use util::{self, sha3};
 use updater::{Service as UpdateService};
 
 use jsonrpc_core::Error;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, BlockNumber, ReleaseInfo, Transaction, LocalDapp};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -39,6 +40,7 @@ pub struct ParitySetClient<C, M, U, F = fetch::Client> {
 	miner: Arc<M>,
 	updater: Arc<U>,
 	net: Arc<ManageNetwork>,
+	snapshot: Arc<SnapshotService>,
 	dapps: Option<Arc<DappsService>>,
 	fetch: F,
 	eip86_transition: u64,
@@ -53,6 +55,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 		miner: &Arc<M>,
 		updater: &Arc<U>,
 		net: &Arc<ManageNetwork>,
+		snapshot: &Arc<SnapshotService>,
 		dapps: Option<Arc<DappsService>>,
 		fetch: F,
 	) -> Self {
@@ -61,6 +64,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 			miner: miner.clone(),
 			updater: updater.clone(),
 			net: net.clone(),
+			snapshot: snapshot.clone(),
 			dapps: dapps,
 			fetch: fetch,
 			eip86_transition: client.eip86_transition(),
@@ -105,6 +109,18 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn propose_signer_vote(&self, address: H160, authorize: bool) -> Result<bool, Error> {
+		self.miner.set_vote_proposal(address.into(), Some(authorize))
+			.map_err(|e| errors::unimplemented(Some(e.to_string())))?;
+		Ok(true)
+	}
+
+	fn discard_signer_vote(&self, address: H160) -> Result<bool, Error> {
+		self.miner.set_vote_proposal(address.into(), None)
+			.map_err(|e| errors::unimplemented(Some(e.to_string())))?;
+		Ok(true)
+	}
+
 	fn set_transactions_limit(&self, limit: usize) -> Result<bool, Error> {
 		self.miner.set_transactions_limit(limit);
 		Ok(true)
@@ -129,6 +145,17 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		}
 	}
 
+	fn reset_peer_scores(&self, peer: Option<H512>) -> Result<bool, Error> {
+		let peer = peer.map(|id| {
+			let id: util::H512 = id.into();
+			id.hex()
+		});
+		match self.net.reset_peer_scores(peer) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Peer id", e)),
+		}
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool, Error> {
 		self.net.deny_unreserved_peers();
 		Ok(true)
@@ -194,4 +221,18 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 
 		Ok(self.miner.remove_pending_transaction(&*self.client, &hash).map(|t| Transaction::from_pending(t, block_number, self.eip86_transition)))
 	}
+
+	fn take_snapshot(&self, number: BlockNumber) -> Result<bool, Error> {
+		let num = self.client.block_header(number.into()).map(|h| h.number()).ok_or_else(errors::unknown_block)?;
+		if num < self.client.pruning_info().earliest_state {
+			return Err(errors::state_pruned());
+		}
+
+		if let CreationStatus::Ongoing { .. } = self.snapshot.creation_status() {
+			return Ok(false);
+		}
+
+		self.snapshot.request_snapshot(num);
+		Ok(true)
+	}
 }
@@ -19,21 +19,22 @@
 use std::sync::Arc;
 
 use rlp::UntrustedRlp;
-use ethcore::client::{MiningBlockChainClient, CallAnalytics, TransactionId, TraceId};
+use ethcore::client::{MiningBlockChainClient, CallAnalytics, BlockId, TransactionId, TraceId};
 use ethcore::miner::MinerService;
 use ethcore::transaction::SignedTransaction;
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::traits::Traces;
+use v1::traits::{Traces, TracesCall};
 use v1::helpers::{errors, fake_sign};
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, StateOverride, H256};
 
 fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 	CallAnalytics {
 		transaction_tracing: flags.contains(&("trace".to_owned())),
 		vm_tracing: flags.contains(&("vmTrace".to_owned())),
 		state_diffing: flags.contains(&("stateDiff".to_owned())),
+		call_tracing: flags.contains(&("callTree".to_owned())),
 	}
 }
 
@@ -53,6 +54,27 @@ impl<C, M> TracesClient<C, M> {
 	}
 }
 
+impl<C, M> TracesClient<C, M> where C: MiningBlockChainClient {
+	/// Turn missing traces into an error if the block is known, but its history was pruned.
+	fn check_history<T>(&self, id: BlockId, value: Option<T>) -> Result<Option<T>, Error> {
+		match value {
+			None => match self.client.block_header(id) {
+				Some(ref header) if header.number() < self.client.pruning_info().earliest_history => Err(errors::history_pruned()),
+				_ => Ok(None),
+			},
+			value => Ok(value),
+		}
+	}
+
+	/// Same as `check_history`, for the block of the given transaction.
+	fn check_transaction_history<T>(&self, id: TransactionId, value: Option<T>) -> Result<Option<T>, Error> {
+		match (value, self.client.transaction_block(id)) {
+			(None, Some(block)) => self.check_history(BlockId::Hash(block), None),
+			(value, _) => Ok(value),
+		}
+	}
+}
+
 impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
 	fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>, Error> {
 		Ok(self.client.filter_traces(filter.into())
@@ -60,33 +82,40 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 	}
 
 	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>, Error> {
-		Ok(self.client.block_traces(block_number.into())
+		let id: BlockId = block_number.into();
+		self.check_history(id.clone(), self.client.block_traces(id)
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
 	fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>, Error> {
-		Ok(self.client.transaction_traces(TransactionId::Hash(transaction_hash.into()))
+		let id = TransactionId::Hash(transaction_hash.into());
+		self.check_transaction_history(id.clone(), self.client.transaction_traces(id)
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
 	fn trace(&self, transaction_hash: H256, address: Vec<Index>) -> Result<Option<LocalizedTrace>, Error> {
+		let transaction = TransactionId::Hash(transaction_hash.into());
 		let id = TraceId {
-			transaction: TransactionId::Hash(transaction_hash.into()),
+			transaction: transaction.clone(),
 			address: address.into_iter().map(|i| i.value()).collect()
 		};
 
-		Ok(self.client.trace(id)
+		self.check_transaction_history(transaction, self.client.trace(id)
 			.map(LocalizedTrace::from))
 	}
 
-	fn call(&self, request: CallRequest, flags: Vec<String>, block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
+	fn call_many(&self, requests: Vec<(CallRequest, Vec<String>)>, block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error> {
 		let block = block.unwrap_or_default();
 
-		let request = CallRequest::into(request);
-		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+		let mut transactions = Vec::with_capacity(requests.len());
+		for (request, flags) in requests {
+			let request = CallRequest::into(request);
+			let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+			transactions.push((signed, to_call_analytics(flags)));
+		}
 
-		self.client.call(&signed, block.into(), to_call_analytics(flags))
-			.map(TraceResults::from)
+		self.client.call_many(&transactions, block.into(), &Default::default())
+			.map(|results| results.into_iter().map(TraceResults::from).collect())
 			.map_err(errors::call)
 	}
 
@@ -106,4 +135,29 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
+
+	fn replay_block_transactions(&self, block_number: BlockNumber, flags: Vec<String>) -> Result<Vec<TraceResultsWithTransactionHash>, Error> {
+		self.client.replay_block_transactions(block_number.into(), to_call_analytics(flags))
+			.map(|results| results.into_iter().map(TraceResultsWithTransactionHash::from).collect())
+			.map_err(errors::call)
+	}
+}
+
+impl<C, M> TracesCall for TracesClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
+	fn call(&self, request: CallRequest, flags: Vec<String>, block: BlockNumber, state_override: StateOverride) -> Result<TraceResults, Error> {
+		let state_override = state_override.into_state_override()?;
+
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+
+		if state_override.is_empty() {
+			self.client.call(&signed, block.into(), to_call_analytics(flags))
+				.map(TraceResults::from)
+				.map_err(errors::call)
+		} else {
+			self.client.call_many(&[(signed, to_call_analytics(flags))], block.into(), &state_override)
+				.map(|mut results| results.pop().expect("one transaction was executed; qed").into())
+				.map_err(errors::call)
+		}
+	}
 }
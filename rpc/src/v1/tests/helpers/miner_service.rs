@@ -44,6 +44,8 @@ pub struct TestMinerService {
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
 	/// Password held by Engine.
 	pub password: RwLock<String>,
+	/// Votes proposed to the Engine.
+	pub vote_proposals: RwLock<BTreeMap<Address, bool>>,
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
@@ -66,6 +68,7 @@ impl Default for TestMinerService {
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
 			author: RwLock::new(Address::zero()),
 			password: RwLock::new(String::new()),
+			vote_proposals: RwLock::new(BTreeMap::new()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
 			tx_gas_limit: RwLock::new(!U256::zero()),
@@ -110,6 +113,14 @@ impl MinerService for TestMinerService {
 		Ok(())
 	}
 
+	fn set_vote_proposal(&self, address: Address, authorize: Option<bool>) -> Result<(), Error> {
+		match authorize {
+			Some(authorize) => { self.vote_proposals.write().insert(address, authorize); },
+			None => { self.vote_proposals.write().remove(&address); },
+		}
+		Ok(())
+	}
+
 	fn set_extra_data(&self, extra_data: Bytes) {
 		*self.extra_data.write() = extra_data;
 	}
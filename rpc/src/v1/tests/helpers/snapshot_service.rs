@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethcore::snapshot::{ManifestData, RestorationStatus, SnapshotService};
+use ethcore::snapshot::{ManifestData, RestorationStatus, CreationStatus, SnapshotService};
 
 use util::{Bytes, Mutex};
 use util::hash::H256;
@@ -22,6 +22,8 @@ use util::hash::H256;
 /// Mocked snapshot service (used for sync info extensions).
 pub struct TestSnapshotService {
 	status: Mutex<RestorationStatus>,
+	creation_status: Mutex<CreationStatus>,
+	requested: Mutex<Option<u64>>,
 }
 
 impl TestSnapshotService {
@@ -30,6 +32,8 @@ impl TestSnapshotService {
 	pub fn new() -> Self {
 		TestSnapshotService {
 			status: Mutex::new(RestorationStatus::Inactive),
+			creation_status: Mutex::new(CreationStatus::Inactive),
+			requested: Mutex::new(None),
 		}
 	}
 
@@ -37,13 +41,26 @@ impl TestSnapshotService {
 	pub fn set_status(&self, status: RestorationStatus) {
 		*self.status.lock() = status;
 	}
+
+	/// Set the snapshot creation status.
+	pub fn set_creation_status(&self, status: CreationStatus) {
+		*self.creation_status.lock() = status;
+	}
+
+	/// Get the number of the last block a snapshot was requested at.
+	pub fn requested_snapshot(&self) -> Option<u64> {
+		*self.requested.lock()
+	}
 }
 
 impl SnapshotService for TestSnapshotService {
 	fn manifest(&self) -> Option<ManifestData> { None }
 	fn min_supported_version(&self) -> Option<u64> { None }
 	fn chunk(&self, _hash: H256) -> Option<Bytes> { None }
+	fn can_apply_delta(&self, _base: H256) -> bool { false }
 	fn status(&self) -> RestorationStatus { self.status.lock().clone() }
+	fn creation_status(&self) -> CreationStatus { self.creation_status.lock().clone() }
+	fn request_snapshot(&self, num: u64) { *self.requested.lock() = Some(num); }
 	fn begin_restore(&self, _manifest: ManifestData) { }
 	fn abort_restore(&self) { }
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 use util::{H256, RwLock};
-use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, ProtocolTrafficInfo, TransactionStats};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -78,6 +78,13 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/62".to_owned(), "eth/63".to_owned()],
     			remote_address: "127.0.0.1:7777".to_owned(),
 				local_address: "127.0.0.1:8888".to_owned(),
+				bytes_sent: 4096,
+				bytes_received: 1024,
+				protocol_traffic: vec![ProtocolTrafficInfo {
+					protocol: "eth".to_owned(),
+					sent: 3000,
+					received: 800,
+				}],
 				eth_info: Some(EthProtocolInfo {
 					version: 62,
 					difficulty: Some(40.into()),
@@ -91,6 +98,9 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/63".to_owned(), "eth/64".to_owned()],
     			remote_address: "Handshake".to_owned(),
 				local_address: "127.0.0.1:3333".to_owned(),
+				bytes_sent: 0,
+				bytes_received: 0,
+				protocol_traffic: Vec::new(),
 				eth_info: Some(EthProtocolInfo {
 					version: 64,
 					difficulty: None,
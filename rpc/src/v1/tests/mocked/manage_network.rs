@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethsync::{ManageNetwork, NetworkConfiguration};
+use ethsync::{ManageNetwork, NetworkConfiguration, PeerScore};
 
 pub struct TestManageNetwork;
 
@@ -27,4 +27,12 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn peer_scores(&self) -> Vec<PeerScore> {
+		vec![PeerScore {
+			id: "898c9ef1a5f8bfd26e3e3c2c9a9ef8d2b26ab3aecc86c5e35e08bf7aff9d8d1c8fb5a58b04fcbbad0bb22e45b92d3b7fd1c8bbcefae5b3bcd79a4ec3d59c80c1".into(),
+			score: -120,
+			banned_until: Some(1_500_000_000),
+		}]
+	}
+	fn reset_peer_scores(&self, _peer: Option<String>) -> Result<(), String> { Ok(()) }
 }
@@ -21,13 +21,14 @@ use ethsync::ManageNetwork;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{TestBlockChainClient};
 use ethcore::miner::LocalTransactionStatus;
+use ethcore::snapshot::SnapshotService;
 use ethstore::ethkey::{Generator, Random};
 
 use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
-use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
+use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater, TestSnapshotService};
 use super::manage_network::TestManageNetwork;
 
 pub type TestParityClient = ParityClient<TestBlockChainClient, TestMinerService, TestSyncProvider, TestUpdater>;
@@ -40,6 +41,7 @@ pub struct Dependencies {
 	pub logger: Arc<RotatingLogger>,
 	pub settings: Arc<NetworkSettings>,
 	pub network: Arc<ManageNetwork>,
+	pub snapshot: Arc<TestSnapshotService>,
 	pub accounts: Arc<AccountProvider>,
 	pub dapps_address: Option<(String, u16)>,
 	pub ws_address: Option<(String, u16)>,
@@ -65,6 +67,7 @@ impl Dependencies {
 				rpc_port: 8545,
 			}),
 			network: Arc::new(TestManageNetwork),
+			snapshot: Arc::new(TestSnapshotService::new()),
 			accounts: Arc::new(AccountProvider::transient_provider()),
 			dapps_address: Some(("127.0.0.1".into(), 18080)),
 			ws_address: Some(("127.0.0.1".into(), 18546)),
@@ -73,6 +76,7 @@ impl Dependencies {
 
 	pub fn client(&self, signer: Option<Arc<SignerService>>) -> TestParityClient {
 		let opt_accounts = Some(self.accounts.clone());
+		let snapshot = self.snapshot.clone() as Arc<SnapshotService>;
 
 		ParityClient::new(
 			&self.client,
@@ -80,6 +84,7 @@ impl Dependencies {
 			&self.sync,
 			&self.updater,
 			&self.network,
+			&snapshot,
 			&opt_accounts,
 			self.logger.clone(),
 			self.settings.clone(),
@@ -304,7 +309,7 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"bytesReceived":1024,"bytesSent":4096,"localAddress":"127.0.0.1:8888","protocolTraffic":{"eth":{"received":800,"sent":3000}},"remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"bytesReceived":0,"bytesSent":0,"localAddress":"127.0.0.1:3333","protocolTraffic":{},"remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -320,6 +325,17 @@ fn rpc_parity_net_port() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_peer_scores() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_peerScores", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"bannedUntil":1500000000,"id":"898c9ef1a5f8bfd26e3e3c2c9a9ef8d2b26ab3aecc86c5e35e08bf7aff9d8d1c8fb5a58b04fcbbad0bb22e45b92d3b7fd1c8bbcefae5b3bcd79a4ec3d59c80c1","score":-120}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_rpc_settings() {
 	let deps = Dependencies::new();
@@ -483,6 +499,30 @@ fn rpc_parity_chain_status() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_snapshot_status() {
+	use ethcore::snapshot::CreationStatus;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_snapshotStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	deps.snapshot.set_creation_status(CreationStatus::Ongoing {
+		block_number: 5000,
+		accounts: 10,
+		blocks: 0,
+		size: 1024,
+	});
+
+	let response = r#"{"jsonrpc":"2.0","result":{"blockNumber":"0x1388","accounts":"0xa","blocks":"0x0","size":"0x400"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_kind() {
 	let deps = Dependencies::new();
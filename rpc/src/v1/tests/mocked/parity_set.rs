@@ -20,12 +20,13 @@ use rustc_hex::FromHex;
 use util::{U256, Address};
 
 use ethcore::miner::MinerService;
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{TestBlockChainClient, EachBlockWith};
+use ethcore::snapshot::{SnapshotService, CreationStatus};
 use ethsync::ManageNetwork;
 
 use jsonrpc_core::IoHandler;
 use v1::{ParitySet, ParitySetClient};
-use v1::tests::helpers::{TestMinerService, TestFetch, TestUpdater, TestDappsService};
+use v1::tests::helpers::{TestMinerService, TestFetch, TestUpdater, TestDappsService, TestSnapshotService};
 use super::manage_network::TestManageNetwork;
 
 fn miner_service() -> Arc<TestMinerService> {
@@ -51,9 +52,21 @@ fn parity_set_client(
 	miner: &Arc<TestMinerService>,
 	updater: &Arc<TestUpdater>,
 	net: &Arc<TestManageNetwork>,
+) -> TestParitySetClient {
+	parity_set_client_with_snapshot(client, miner, updater, net, &Arc::new(TestSnapshotService::new()))
+}
+
+fn parity_set_client_with_snapshot(
+	client: &Arc<TestBlockChainClient>,
+	miner: &Arc<TestMinerService>,
+	updater: &Arc<TestUpdater>,
+	net: &Arc<TestManageNetwork>,
+	snapshot: &Arc<TestSnapshotService>,
 ) -> TestParitySetClient {
 	let dapps_service = Arc::new(TestDappsService);
-	ParitySetClient::new(client, miner, updater, &(net.clone() as Arc<ManageNetwork>), Some(dapps_service), TestFetch::default())
+	let net = net.clone() as Arc<ManageNetwork>;
+	let snapshot = snapshot.clone() as Arc<SnapshotService>;
+	ParitySetClient::new(client, miner, updater, &net, &snapshot, Some(dapps_service), TestFetch::default())
 }
 
 #[test]
@@ -178,6 +191,27 @@ fn rpc_parity_set_engine_signer() {
 	assert_eq!(*miner.password.read(), "password".to_string());
 }
 
+#[test]
+fn rpc_parity_propose_and_discard_signer_vote() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let address = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_proposeSignerVote", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.vote_proposals.read().get(&address), Some(&true));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_discardSignerVote", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(miner.vote_proposals.read().is_empty());
+}
 
 #[test]
 fn rpc_parity_set_transactions_limit() {
@@ -195,6 +229,23 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
 
+#[test]
+fn rpc_parity_reset_peer_scores() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resetPeerScores", "params":[null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resetPeerScores", "params":["0x898c9ef1a5f8bfd26e3e3c2c9a9ef8d2b26ab3aecc86c5e35e08bf7aff9d8d1c8fb5a58b04fcbbad0bb22e45b92d3b7fd1c8bbcefae5b3bcd79a4ec3d59c80c1"], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -253,3 +304,33 @@ fn rpc_parity_set_dapps_list() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_take_snapshot() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let snapshot = Arc::new(TestSnapshotService::new());
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_snapshot(&client, &miner, &updater, &network, &snapshot).to_delegate());
+
+	client.add_blocks(20, EachBlockWith::Nothing);
+	client.set_history(Some(10));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_takeSnapshot", "params":["0xf"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(snapshot.requested_snapshot(), Some(15));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_takeSnapshot", "params":["0x5"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	snapshot.set_creation_status(CreationStatus::Ongoing { block_number: 15, accounts: 0, blocks: 0, size: 0 });
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_takeSnapshot", "params":["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(snapshot.requested_snapshot(), Some(15));
+}
@@ -17,13 +17,13 @@
 use std::sync::Arc;
 
 use ethcore::executed::{CallType, Executed, CallError};
-use ethcore::trace::trace::{Action, Res, Call};
-use ethcore::trace::LocalizedTrace;
+use ethcore::trace::trace::{Action, Res, Call, CallResult};
+use ethcore::trace::{LocalizedTrace, CallFrame};
 use ethcore::client::TestBlockChainClient;
 
 use jsonrpc_core::IoHandler;
 use v1::tests::helpers::{TestMinerService};
-use v1::{Traces, TracesClient};
+use v1::{Traces, TracesCall, TracesClient};
 
 struct Tester {
 	client: Arc<TestBlockChainClient>,
@@ -62,11 +62,13 @@ fn io() -> Tester {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		call_tree: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
 	let traces = TracesClient::new(&client, &miner);
 	let mut io = IoHandler::new();
-	io.extend_with(traces.to_delegate());
+	io.extend_with(Traces::to_delegate(TracesClient::new(&client, &miner)));
+	io.extend_with(TracesCall::to_delegate(traces));
 
 	Tester {
 		client: client,
@@ -168,6 +170,34 @@ fn rpc_trace_call() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_call_call_tree() {
+	let tester = io();
+	if let Some(Ok(ref mut executed)) = *tester.client.execution_result.write() {
+		executed.call_tree = Some(CallFrame {
+			action: Action::Call(Call {
+				from: 0xf.into(),
+				to: 0x10.into(),
+				value: 0x1.into(),
+				gas: 0x100.into(),
+				input: vec![1, 2, 3],
+				call_type: CallType::Call,
+			}),
+			result: Res::Call(CallResult {
+				gas_used: 0x50.into(),
+				output: vec![4, 5],
+			}),
+			reverted: true,
+			calls: vec![],
+		});
+	}
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["callTree"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"callTree":{"error":"execution reverted","from":"0x000000000000000000000000000000000000000f","gas":"0x100","gasUsed":"0x50","input":"0x010203","output":"0x0405","to":"0x0000000000000000000000000000000000000010","type":"CALL","value":"0x1"},"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_call_state_pruned() {
 	let tester = io();
@@ -220,3 +250,65 @@ fn rpc_trace_replay_transaction_state_pruned() {
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_trace_call_with_state_override() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["trace"], "latest", {"0x0000000000000000000000000000000000000001": {"balance": "0x10", "code": "0x6000"}}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_with_invalid_state_override() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["trace"], "latest", {"0x0000000000000000000000000000000000000001": {"state": {}, "stateDiff": {}}}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: state override","data":"\"Account 0x0000000000000000000000000000000000000001 has both state and stateDiff set\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_many() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_callMany","params":[[[{}, ["trace"]], [{}, ["stateDiff"]]], "latest"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_many_state_pruned() {
+	let tester = io();
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_callMany","params":[[[{}, ["trace"]]], "0x10"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_replay_block_transactions() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x10", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"output":"0x010203","stateDiff":null,"trace":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","vmTrace":null}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_replay_block_transactions_state_pruned() {
+	let tester = io();
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x10", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
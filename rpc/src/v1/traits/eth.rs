@@ -15,13 +15,17 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Eth rpc interface.
-use jsonrpc_core::Error;
-use jsonrpc_macros::Trailing;
+use std::sync::Arc;
 
-use futures::BoxFuture;
+use jsonrpc_core::{self, Error, Metadata, Params};
+use jsonrpc_macros::{IoDelegate, Trailing};
+
+use futures::{future, BoxFuture, Future};
+
+use v1::helpers::call_params;
 
 use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{Log, Receipt, StateOverride, SyncStatus, Transaction, Work};
 use v1::types::{H64, H160, H256, U256};
 
 build_rpc_trait! {
@@ -109,10 +113,6 @@ build_rpc_trait! {
 		#[rpc(name = "eth_submitTransaction")]
 		fn submit_transaction(&self, Bytes) -> Result<H256, Error>;
 
-		/// Call contract, returning the output data.
-		#[rpc(async, name = "eth_call")]
-		fn call(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<Bytes, Error>;
-
 		/// Estimate gas needed for execution of given contract.
 		#[rpc(async, name = "eth_estimateGas")]
 		fn estimate_gas(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<U256, Error>;
@@ -208,3 +208,27 @@ build_rpc_trait! {
 		fn uninstall_filter(&self, Index) -> Result<bool, Error>;
 	}
 }
+
+/// Eth `eth_call` rpc interface.
+///
+/// Kept apart from `Eth`, since both the block number and the state override
+/// following the call request are optional.
+pub trait EthCall: Sized + Send + Sync + 'static {
+	/// Call contract on top of optional temporarily overridden accounts, returning the output data.
+	fn call(&self, CallRequest, BlockNumber, StateOverride) -> BoxFuture<Bytes, Error>;
+
+	/// Transform this into an `IoDelegate` serving `eth_call`.
+	fn to_delegate<M: Metadata>(self) -> IoDelegate<Self, M> {
+		let mut del = IoDelegate::new(Arc::new(self));
+		del.add_method_with_meta("eth_call", |base: &Self, params: Params, _meta: M| {
+			let ((request, ), num, state_override) = match call_params::parse::<(CallRequest, )>(params, 1) {
+				Ok(params) => params,
+				Err(e) => return future::err(e).boxed(),
+			};
+			base.call(request, num, state_override)
+				.map(|output| jsonrpc_core::to_value(output).expect("Bytes are always serializable; qed"))
+				.boxed()
+		});
+		del
+	}
+}
@@ -33,7 +33,7 @@ pub mod rpc;
 pub mod secretstore;
 
 pub use self::web3::Web3;
-pub use self::eth::{Eth, EthFilter};
+pub use self::eth::{Eth, EthCall, EthFilter};
 pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
 pub use self::net::Net;
@@ -44,6 +44,6 @@ pub use self::parity_signing::ParitySigning;
 pub use self::personal::Personal;
 pub use self::pubsub::PubSub;
 pub use self::signer::Signer;
-pub use self::traces::Traces;
+pub use self::traces::{Traces, TracesCall};
 pub use self::rpc::Rpc;
 pub use self::secretstore::SecretStore;
@@ -27,8 +27,8 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader,
+	OperationsInfo, DappId, ChainStatus, SnapshotInfo,
+	AccountInfo, HwAccountInfo, RichHeader, PeerScore,
 };
 
 build_rpc_trait! {
@@ -84,6 +84,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_netPeers")]
 		fn net_peers(&self) -> Result<Peers, Error>;
 
+		/// Returns scores of known peers that have a non-neutral score or are banned
+		#[rpc(name = "parity_peerScores")]
+		fn peer_scores(&self) -> Result<Vec<PeerScore>, Error>;
+
 		/// Returns network port
 		#[rpc(name = "parity_netPort")]
 		fn net_port(&self) -> Result<u16, Error>;
@@ -191,6 +195,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
 
+		/// Get the progress of the snapshot being taken, if any.
+		#[rpc(name = "parity_snapshotStatus")]
+		fn snapshot_status(&self) -> Result<Option<SnapshotInfo>, Error>;
+
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind, Error>;
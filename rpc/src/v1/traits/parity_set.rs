@@ -19,7 +19,7 @@
 use jsonrpc_core::Error;
 use futures::BoxFuture;
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, BlockNumber, ReleaseInfo, Transaction, LocalDapp};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -48,6 +48,15 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setEngineSigner")]
 		fn set_engine_signer(&self, H160, String) -> Result<bool, Error>;
 
+		/// Proposes to authorize (`true`) or drop (`false`) a signer in the votes cast by the
+		/// blocks sealed locally. Only supported by engines which vote on their signers.
+		#[rpc(name = "parity_proposeSignerVote")]
+		fn propose_signer_vote(&self, H160, bool) -> Result<bool, Error>;
+
+		/// Discards a previously proposed vote on a signer.
+		#[rpc(name = "parity_discardSignerVote")]
+		fn discard_signer_vote(&self, H160) -> Result<bool, Error>;
+
 		/// Sets the limits for transaction queue.
 		#[rpc(name = "parity_setTransactionsLimit")]
 		fn set_transactions_limit(&self, usize) -> Result<bool, Error>;
@@ -64,6 +73,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_removeReservedPeer")]
 		fn remove_reserved_peer(&self, String) -> Result<bool, Error>;
 
+		/// Reset the score and lift the ban of a peer given by its public id, or of all peers if `null`.
+		#[rpc(name = "parity_resetPeerScores")]
+		fn reset_peer_scores(&self, Option<H512>) -> Result<bool, Error>;
+
 		/// Drop all non-reserved peers.
 		#[rpc(name = "parity_dropNonReservedPeers")]
 		fn drop_non_reserved_peers(&self) -> Result<bool, Error>;
@@ -116,5 +129,11 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>, Error>;
+
+		/// Start taking a snapshot at the given block in the background.
+		/// An interrupted snapshot of the same block is resumed.
+		/// Returns `false` if another snapshot is currently being taken.
+		#[rpc(name = "parity_takeSnapshot")]
+		fn take_snapshot(&self, BlockNumber) -> Result<bool, Error>;
 	}
 }
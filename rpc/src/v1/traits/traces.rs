@@ -16,9 +16,13 @@
 
 //! Traces specific rpc interface.
 
-use jsonrpc_core::Error;
-use jsonrpc_macros::Trailing;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use std::sync::Arc;
+
+use futures::{future, BoxFuture, Future};
+use jsonrpc_core::{self, Error, Metadata, Params};
+use jsonrpc_macros::{IoDelegate, Trailing};
+use v1::helpers::call_params;
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, StateOverride, H256};
 
 build_rpc_trait! {
 	/// Traces specific rpc interface.
@@ -39,9 +43,10 @@ build_rpc_trait! {
 		#[rpc(name = "trace_block")]
 		fn block_traces(&self, BlockNumber) -> Result<Option<Vec<LocalizedTrace>>, Error>;
 
-		/// Executes the given call and returns a number of possible traces for it.
-		#[rpc(name = "trace_call")]
-		fn call(&self, CallRequest, Vec<String>, Trailing<BlockNumber>) -> Result<TraceResults, Error>;
+		/// Executes the given calls one after another, each seeing the effects of the previous ones,
+		/// and returns a number of possible traces for each of them.
+		#[rpc(name = "trace_callMany")]
+		fn call_many(&self, Vec<(CallRequest, Vec<String>)>, Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error>;
 
 		/// Executes the given raw transaction and returns a number of possible traces for it.
 		#[rpc(name = "trace_rawTransaction")]
@@ -50,5 +55,31 @@ build_rpc_trait! {
 		/// Executes the transaction with the given hash and returns a number of possible traces for it.
 		#[rpc(name = "trace_replayTransaction")]
 		fn replay_transaction(&self, H256, Vec<String>) -> Result<TraceResults, Error>;
+
+		/// Executes all the transactions in the given block and returns a number of possible traces for each of them.
+		#[rpc(name = "trace_replayBlockTransactions")]
+		fn replay_block_transactions(&self, BlockNumber, Vec<String>) -> Result<Vec<TraceResultsWithTransactionHash>, Error>;
+	}
+}
+
+/// Traces `trace_call` rpc interface.
+///
+/// Kept apart from `Traces`, since both the block number and the state override
+/// following the call request and trace types are optional.
+pub trait TracesCall: Sized + Send + Sync + 'static {
+	/// Executes the given call on top of optional temporarily overridden accounts
+	/// and returns a number of possible traces for it.
+	fn call(&self, CallRequest, Vec<String>, BlockNumber, StateOverride) -> Result<TraceResults, Error>;
+
+	/// Transform this into an `IoDelegate` serving `trace_call`.
+	fn to_delegate<M: Metadata>(self) -> IoDelegate<Self, M> {
+		let mut del = IoDelegate::new(Arc::new(self));
+		del.add_method_with_meta("trace_call", |base: &Self, params: Params, _meta: M| {
+			future::done(call_params::parse::<(CallRequest, Vec<String>)>(params, 2)
+				.and_then(|((request, flags), block, state_override)| base.call(request, flags, block, state_override))
+				.map(|results| jsonrpc_core::to_value(results).expect("Trace results are always serializable; qed"))
+			).boxed()
+		});
+		del
 	}
 }
@@ -19,7 +19,7 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use v1::types::{RichHeader, Filter, Log};
+use v1::types::{RichHeader, Filter, Log, H256, SyncStatus};
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +28,10 @@ pub enum Result {
 	Header(RichHeader),
 	/// Logs
 	Logs(Vec<Log>),
+	/// Transaction hash
+	TransactionHash(H256),
+	/// Node syncing status
+	SyncState(SyncStatus),
 }
 
 impl Serialize for Result {
@@ -37,6 +41,8 @@ impl Serialize for Result {
 		match *self {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Logs(ref logs) => logs.serialize(serializer),
+			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::SyncState(ref sync) => sync.serialize(serializer),
 		}
 	}
 }
@@ -92,7 +98,7 @@ impl<'a> Deserialize<'a> for Params {
 mod tests {
 	use serde_json;
 	use super::{Result, Kind, Params};
-	use v1::types::{RichHeader, Header, Filter};
+	use v1::types::{RichHeader, Header, Filter, SyncStatus, SyncInfo};
 	use v1::types::filter::VariadicValue;
 
 	#[test]
@@ -165,4 +171,22 @@ mod tests {
 		let expected = r#"{"author":"0x0000000000000000000000000000000000000000","difficulty":"0x0","extraData":"0x","gasLimit":"0x0","gasUsed":"0x0","hash":"0x0000000000000000000000000000000000000000000000000000000000000000","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","sealFields":["0x","0x"],"sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000000","size":"0x45","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x0","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#;
 		assert_eq!(serde_json::to_string(&header).unwrap(), expected);
 	}
+
+	#[test]
+	fn should_serialize_transaction_hash_and_sync_state() {
+		let hash = Result::TransactionHash(5.into());
+		assert_eq!(serde_json::to_string(&hash).unwrap(), r#""0x0000000000000000000000000000000000000000000000000000000000000005""#);
+
+		let not_syncing = Result::SyncState(SyncStatus::None);
+		assert_eq!(serde_json::to_string(&not_syncing).unwrap(), "false");
+
+		let syncing = Result::SyncState(SyncStatus::Info(SyncInfo {
+			starting_block: 1.into(),
+			current_block: 2.into(),
+			highest_block: 3.into(),
+			warp_chunks_amount: None,
+			warp_chunks_processed: None,
+		}));
+		assert_eq!(serde_json::to_string(&syncing).unwrap(), r#"{"startingBlock":"0x1","currentBlock":"0x2","highestBlock":"0x3","warpChunksAmount":null,"warpChunksProcessed":null}"#);
+	}
 }
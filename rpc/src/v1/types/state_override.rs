@@ -0,0 +1,116 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use rustc_hex::ToHex;
+use ethcore::client::{StateOverride as EthStateOverride, AccountOverride as EthAccountOverride};
+use jsonrpc_core::Error;
+use v1::helpers::errors;
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Temporary changes to a single account.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountOverride {
+	/// Balance
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: Option<U256>,
+	/// Code
+	pub code: Option<Bytes>,
+	/// Full storage replacement
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage slots to change
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// Temporary changes to any number of accounts, keyed by address.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct StateOverride(BTreeMap<H160, AccountOverride>);
+
+fn into_storage(storage: BTreeMap<H256, H256>) -> BTreeMap<::util::H256, ::util::H256> {
+	storage.into_iter().map(|(k, v)| (k.into(), v.into())).collect()
+}
+
+impl StateOverride {
+	/// Converts the overrides, making sure no account replaces and patches its storage at the same time.
+	pub fn into_state_override(self) -> Result<EthStateOverride, Error> {
+		let mut raw = BTreeMap::new();
+		for (address, account) in self.0 {
+			if account.state.is_some() && account.state_diff.is_some() {
+				return Err(errors::invalid_params("state override", format!("Account 0x{} has both state and stateDiff set", address.0.to_hex())));
+			}
+
+			raw.insert(address.into(), EthAccountOverride {
+				balance: account.balance.map(Into::into),
+				nonce: account.nonce.map(Into::into),
+				code: account.code.map(Into::into),
+				state: account.state.map(into_storage),
+				state_diff: account.state_diff.map(into_storage),
+			});
+		}
+
+		Ok(EthStateOverride { raw: raw })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use v1::types::{U256, H160, H256};
+	use super::{StateOverride, AccountOverride};
+
+	#[test]
+	fn state_override_deserialize() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x10",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			}
+		}"#;
+		let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(1), H256::from(2));
+		let mut accounts = BTreeMap::new();
+		accounts.insert(H160::from(1), AccountOverride {
+			balance: Some(U256::from(0x10)),
+			nonce: None,
+			code: Some(vec![0x60, 0x00].into()),
+			state: None,
+			state_diff: Some(storage),
+		});
+		assert_eq!(deserialized, StateOverride(accounts));
+	}
+
+	#[test]
+	fn state_override_rejects_state_and_state_diff() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"state": {},
+				"stateDiff": {}
+			}
+		}"#;
+		let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+
+		assert!(deserialized.into_state_override().is_err());
+	}
+}
@@ -15,12 +15,12 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats, PeerScore as SyncPeerScore};
 use serde::{Serialize, Serializer};
 use v1::types::{U256, H512};
 
 /// Sync info
-#[derive(Default, Debug, Serialize, PartialEq)]
+#[derive(Default, Debug, Serialize, PartialEq, Eq, Clone)]
 pub struct SyncInfo {
 	/// Starting block
 	#[serde(rename="startingBlock")]
@@ -76,6 +76,24 @@ pub struct PeerNetworkInfo {
 	/// Local endpoint address
 	#[serde(rename="localAddress")]
 	pub local_address: String,
+	/// Bytes sent to the peer
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: u64,
+	/// Bytes received from the peer
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: u64,
+	/// Payload traffic per subprotocol
+	#[serde(rename="protocolTraffic")]
+	pub protocol_traffic: BTreeMap<String, PeerProtocolTraffic>,
+}
+
+/// Payload traffic of a peer subprotocol
+#[derive(Default, Debug, Serialize)]
+pub struct PeerProtocolTraffic {
+	/// Payload bytes sent
+	pub sent: u64,
+	/// Payload bytes received
+	pub received: u64,
 }
 
 /// Peer protocols information
@@ -130,7 +148,7 @@ impl From<ethsync::PipProtocolInfo> for PipProtocolInfo {
 }
 
 /// Sync status
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum SyncStatus {
 	/// Info when syncing
 	Info(SyncInfo),
@@ -168,6 +186,12 @@ impl From<SyncPeerInfo> for PeerInfo {
 			network: PeerNetworkInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
+				bytes_sent: p.bytes_sent,
+				bytes_received: p.bytes_received,
+				protocol_traffic: p.protocol_traffic
+					.into_iter()
+					.map(|t| (t.protocol, PeerProtocolTraffic { sent: t.sent, received: t.received }))
+					.collect(),
 			},
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
@@ -189,6 +213,28 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+/// Reputation of a known peer
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct PeerScore {
+	/// Public node id
+	pub id: String,
+	/// Current score
+	pub score: i32,
+	/// Unix time until which the peer is banned
+	#[serde(rename="bannedUntil")]
+	pub banned_until: Option<u64>,
+}
+
+impl From<SyncPeerScore> for PeerScore {
+	fn from(s: SyncPeerScore) -> Self {
+		PeerScore {
+			id: s.id,
+			score: s.score,
+			banned_until: s.banned_until,
+		}
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 pub struct ChainStatus {
@@ -197,11 +243,25 @@ pub struct ChainStatus {
 	pub block_gap: Option<(U256, U256)>,
 }
 
+/// Progress of the snapshot being taken.
+#[derive(Default, Debug, Serialize, PartialEq, Eq)]
+pub struct SnapshotInfo {
+	/// Number of the block the snapshot is taken at.
+	#[serde(rename="blockNumber")]
+	pub block_number: U256,
+	/// Number of accounts written so far.
+	pub accounts: U256,
+	/// Number of blocks written so far.
+	pub blocks: U256,
+	/// Size of the chunks written so far, in bytes.
+	pub size: U256,
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, SnapshotInfo, PeerScore};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -240,6 +300,19 @@ mod tests {
 		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
 	}
 
+	#[test]
+	fn test_serialize_snapshot_info() {
+		let t = SnapshotInfo {
+			block_number: 5000.into(),
+			accounts: 10.into(),
+			blocks: 0.into(),
+			size: 1024.into(),
+		};
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"blockNumber":"0x1388","accounts":"0xa","blocks":"0x0","size":"0x400"}"#);
+	}
+
 	#[test]
 	fn test_serialize_transaction_stats() {
 		let stats = TransactionStats {
@@ -252,4 +325,15 @@ mod tests {
 		let serialized = serde_json::to_string(&stats).unwrap();
 		assert_eq!(serialized, r#"{"firstSeen":100,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
 	}
+	#[test]
+	fn test_serialize_peer_score() {
+		let score = PeerScore {
+			id: "ab".into(),
+			score: -120,
+			banned_until: Some(1500000000),
+		};
+
+		let serialized = serde_json::to_string(&score).unwrap();
+		assert_eq!(serialized, r#"{"id":"ab","score":-120,"bannedUntil":1500000000}"#)
+	}
 }
@@ -544,6 +544,104 @@ impl From<FlatTrace> for Trace {
 	}
 }
 
+/// Type of a call tree frame.
+#[derive(Debug, Serialize)]
+pub enum CallFrameType {
+	/// Call
+	#[serde(rename="CALL")]
+	Call,
+	/// Call code
+	#[serde(rename="CALLCODE")]
+	CallCode,
+	/// Delegate call
+	#[serde(rename="DELEGATECALL")]
+	DelegateCall,
+	/// Static call
+	#[serde(rename="STATICCALL")]
+	StaticCall,
+	/// Create
+	#[serde(rename="CREATE")]
+	Create,
+	/// Suicide
+	#[serde(rename="SELFDESTRUCT")]
+	Suicide,
+}
+
+impl From<executed::CallType> for CallFrameType {
+	fn from(c: executed::CallType) -> Self {
+		match c {
+			executed::CallType::None | executed::CallType::Call => CallFrameType::Call,
+			executed::CallType::CallCode => CallFrameType::CallCode,
+			executed::CallType::DelegateCall => CallFrameType::DelegateCall,
+			executed::CallType::StaticCall => CallFrameType::StaticCall,
+		}
+	}
+}
+
+/// Single frame of a nested call tree.
+#[derive(Debug, Serialize)]
+pub struct CallFrame {
+	/// Frame type
+	#[serde(rename="type")]
+	frame_type: CallFrameType,
+	/// Sender
+	from: H160,
+	/// Recipient or created contract
+	#[serde(skip_serializing_if = "Option::is_none")]
+	to: Option<H160>,
+	/// Transfered value
+	value: U256,
+	/// Gas
+	gas: U256,
+	/// Gas used
+	#[serde(rename="gasUsed")]
+	gas_used: U256,
+	/// Input data or init code
+	input: Bytes,
+	/// Output data, deployed code or revert data
+	#[serde(skip_serializing_if = "Option::is_none")]
+	output: Option<Bytes>,
+	/// Failure reason
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error: Option<String>,
+	/// Subcalls
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	calls: Vec<CallFrame>,
+}
+
+impl From<et::CallFrame> for CallFrame {
+	fn from(f: et::CallFrame) -> Self {
+		let (frame_type, from, to, value, gas, input) = match f.action {
+			trace::Action::Call(call) => (call.call_type.into(), call.from, Some(call.to), call.value, call.gas, call.input),
+			trace::Action::Create(create) => (CallFrameType::Create, create.from, None, create.value, create.gas, create.init),
+			trace::Action::Suicide(suicide) => (CallFrameType::Suicide, suicide.address, Some(suicide.refund_address), suicide.balance, 0.into(), vec![]),
+		};
+
+		let (to, gas_used, output, error) = match f.result {
+			trace::Res::Call(res) => (to, res.gas_used, Some(res.output), None),
+			trace::Res::Create(res) => (Some(res.address), res.gas_used, Some(res.code), None),
+			trace::Res::FailedCall(error) | trace::Res::FailedCreate(error) => (to, gas, None, Some(error.to_string())),
+			trace::Res::None => (to, 0.into(), None, None),
+		};
+
+		CallFrame {
+			frame_type: frame_type,
+			from: from.into(),
+			to: to.map(Into::into),
+			value: value.into(),
+			gas: gas.into(),
+			gas_used: gas_used.into(),
+			input: input.into(),
+			output: output.map(Into::into),
+			error: match f.reverted {
+				true => Some("execution reverted".into()),
+				false => error,
+			},
+			calls: f.calls.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
 #[derive(Debug, Serialize)]
 /// A diff of some chunk of memory.
 pub struct TraceResults {
@@ -557,6 +655,9 @@ pub struct TraceResults {
 	/// The transaction trace.
 	#[serde(rename="stateDiff")]
 	pub state_diff: Option<StateDiff>,
+	/// The nested call tree.
+	#[serde(rename="callTree", skip_serializing_if = "Option::is_none")]
+	pub call_tree: Option<CallFrame>,
 }
 
 impl From<Executed> for TraceResults {
@@ -566,6 +667,42 @@ impl From<Executed> for TraceResults {
 			trace: t.trace.into_iter().map(Into::into).collect(),
 			vm_trace: t.vm_trace.map(Into::into),
 			state_diff: t.state_diff.map(Into::into),
+			call_tree: t.call_tree.map(Into::into),
+		}
+	}
+}
+
+/// Trace results of a replayed transaction along with its hash.
+#[derive(Debug, Serialize)]
+pub struct TraceResultsWithTransactionHash {
+	/// The output of the call/create
+	pub output: Bytes,
+	/// The transaction trace.
+	pub trace: Vec<Trace>,
+	/// The transaction trace.
+	#[serde(rename="vmTrace")]
+	pub vm_trace: Option<VMTrace>,
+	/// The transaction trace.
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<StateDiff>,
+	/// The nested call tree.
+	#[serde(rename="callTree", skip_serializing_if = "Option::is_none")]
+	pub call_tree: Option<CallFrame>,
+	/// The transaction hash.
+	#[serde(rename="transactionHash")]
+	pub transaction_hash: H256,
+}
+
+impl From<(::util::H256, Executed)> for TraceResultsWithTransactionHash {
+	fn from(t: (::util::H256, Executed)) -> Self {
+		let (transaction_hash, executed) = t;
+		TraceResultsWithTransactionHash {
+			output: executed.output.into(),
+			trace: executed.trace.into_iter().map(Into::into).collect(),
+			vm_trace: executed.vm_trace.map(Into::into),
+			state_diff: executed.state_diff.map(Into::into),
+			call_tree: executed.call_tree.map(Into::into),
+			transaction_hash: transaction_hash.into(),
 		}
 	}
 }
@@ -585,11 +722,64 @@ mod tests {
 			trace: vec![],
 			vm_trace: None,
 			state_diff: None,
+			call_tree: None,
 		};
 		let serialized = serde_json::to_string(&r).unwrap();
 		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null}"#);
 	}
 
+	#[test]
+	fn should_serialize_trace_results_with_transaction_hash() {
+		let r = TraceResultsWithTransactionHash {
+			output: vec![0x60].into(),
+			trace: vec![],
+			vm_trace: None,
+			state_diff: None,
+			call_tree: None,
+			transaction_hash: 5.into(),
+		};
+		let serialized = serde_json::to_string(&r).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005"}"#);
+	}
+
+	#[test]
+	fn test_call_frame_serialize() {
+		use ethcore::executed::CallType as EthCallType;
+		use ethcore::trace::CallFrame as EthCallFrame;
+		use ethcore::trace::trace as et_trace;
+
+		let t = EthCallFrame {
+			action: et_trace::Action::Call(et_trace::Call {
+				from: 4.into(),
+				to: 5.into(),
+				value: 6.into(),
+				gas: 7.into(),
+				input: vec![0x12, 0x34],
+				call_type: EthCallType::Call,
+			}),
+			result: et_trace::Res::Call(et_trace::CallResult {
+				gas_used: 7.into(),
+				output: vec![0x56, 0x78],
+			}),
+			reverted: true,
+			calls: vec![EthCallFrame {
+				action: et_trace::Action::Call(et_trace::Call {
+					from: 5.into(),
+					to: 6.into(),
+					value: 0.into(),
+					gas: 3.into(),
+					input: vec![],
+					call_type: EthCallType::StaticCall,
+				}),
+				result: et_trace::Res::FailedCall(TraceError::OutOfGas),
+				reverted: false,
+				calls: vec![],
+			}],
+		};
+		let serialized = serde_json::to_string(&CallFrame::from(t)).unwrap();
+		assert_eq!(serialized, r#"{"type":"CALL","from":"0x0000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000005","value":"0x6","gas":"0x7","gasUsed":"0x7","input":"0x1234","output":"0x5678","error":"execution reverted","calls":[{"type":"STATICCALL","from":"0x0000000000000000000000000000000000000005","to":"0x0000000000000000000000000000000000000006","value":"0x0","gas":"0x3","gasUsed":"0x3","input":"0x","error":"Out of gas"}]}"#);
+	}
+
 	#[test]
 	fn test_trace_call_serialize() {
 		let t = LocalizedTrace {
@@ -20,8 +20,8 @@ use std::io;
 use util::Bytes;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError,
-	AllowIP as NetworkAllowIP};
-use util::{U256, H256, H512};
+	AllowIP as NetworkAllowIP, ProtocolTraffic, NodeScore};
+use util::{U256, H256, H512, Address};
 use io::{TimerToken};
 use ethcore::ethstore::ethkey::Secret;
 use ethcore::client::{BlockChainClient, ChainNotify};
@@ -123,12 +123,62 @@ pub struct PeerInfo {
 	pub remote_address: String,
 	/// Local endpoint address
 	pub local_address: String,
+	/// Bytes sent to the peer
+	pub bytes_sent: u64,
+	/// Bytes received from the peer
+	pub bytes_received: u64,
+	/// Payload traffic per subprotocol
+	pub protocol_traffic: Vec<ProtocolTrafficInfo>,
 	/// Eth protocol info.
 	pub eth_info: Option<EthProtocolInfo>,
 	/// Light protocol info.
 	pub pip_info: Option<PipProtocolInfo>,
 }
 
+/// Subprotocol traffic of a peer.
+#[derive(Debug)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct ProtocolTrafficInfo {
+	/// Protocol name
+	pub protocol: String,
+	/// Payload bytes sent
+	pub sent: u64,
+	/// Payload bytes received
+	pub received: u64,
+}
+
+impl From<ProtocolTraffic> for ProtocolTrafficInfo {
+	fn from(traffic: ProtocolTraffic) -> Self {
+		ProtocolTrafficInfo {
+			protocol: String::from_utf8_lossy(&traffic.protocol[..]).into_owned(),
+			sent: traffic.sent,
+			received: traffic.received,
+		}
+	}
+}
+
+/// Reputation of a known peer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct PeerScore {
+	/// Public node id
+	pub id: String,
+	/// Current score
+	pub score: i32,
+	/// Unix time until which the peer is banned
+	pub banned_until: Option<u64>,
+}
+
+impl From<NodeScore> for PeerScore {
+	fn from(score: NodeScore) -> Self {
+		PeerScore {
+			id: score.id.hex(),
+			score: score.score,
+			banned_until: score.banned_until,
+		}
+	}
+}
+
 /// Ethereum protocol info.
 #[derive(Debug)]
 #[cfg_attr(feature = "ipc", derive(Binary))]
@@ -176,6 +226,11 @@ pub struct Params {
 	pub provider: Arc<::light::Provider>,
 	/// Network layer configuration.
 	pub network_config: NetworkConfiguration,
+	/// Addresses of publishers whose signed snapshots are restored.
+	/// Snapshots are not required to be signed if empty.
+	pub trusted_snapshot_publishers: Vec<Address>,
+	/// Block numbers of scheduled chain rule changes, announced in the fork identifier.
+	pub fork_blocks: Vec<BlockNumber>,
 }
 
 /// Ethereum network protocol handler
@@ -229,8 +284,13 @@ impl EthSync {
 			})
 		};
 
-		let chain_sync = ChainSync::new(params.config, &*params.chain);
-		let service = NetworkService::new(params.network_config.clone().into_basic()?)?;
+		let mut chain_sync = ChainSync::new(params.config, &*params.chain);
+		chain_sync.set_trusted_snapshot_publishers(params.trusted_snapshot_publishers.into_iter().collect());
+		let genesis_hash = params.chain.chain_info().genesis_hash;
+		chain_sync.set_fork_blocks(&genesis_hash, params.fork_blocks);
+		let mut network_config = params.network_config.clone().into_basic()?;
+		network_config.chain_identity = Some((params.config.network_id, genesis_hash));
+		let service = NetworkService::new(network_config)?;
 
 		let sync = Arc::new(EthSync {
 			network: service,
@@ -275,6 +335,9 @@ impl SyncProvider for EthSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					bytes_sent: session_info.traffic.sent,
+					bytes_received: session_info.traffic.received,
+					protocol_traffic: session_info.traffic.protocols.into_iter().map(Into::into).collect(),
 					eth_info: eth_sync.peer_info(&peer_id),
 					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(&peer_id)).map(Into::into),
 				})
@@ -370,6 +433,13 @@ impl ChainNotify for EthSync {
 			};
 
 			let chain_info = self.eth_handler.chain.chain_info();
+			// advertise the new earliest block once old history gets pruned.
+			let earliest_chain = self.eth_handler.chain.pruning_info().earliest_chain;
+			let serve_chain_since = match light_proto.capabilities().serve_chain_since {
+				Some(since) if since != earliest_chain => Some(earliest_chain),
+				_ => None,
+			};
+
 			light_proto.make_announcement(context, Announcement {
 				head_hash: chain_info.best_block_hash,
 				head_num: chain_info.best_block_number,
@@ -377,7 +447,7 @@ impl ChainNotify for EthSync {
 				reorg_depth: 0, // recalculated on a per-peer basis.
 				serve_headers: false, // these fields consist of _changes_ in capability.
 				serve_state_since: None,
-				serve_chain_since: None,
+				serve_chain_since: serve_chain_since,
 				tx_relay: false,
 			})
 		})
@@ -389,10 +459,10 @@ impl ChainNotify for EthSync {
 			Err(err) => warn!("Error starting network: {}", err),
 			_ => {},
 		}
-		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, ETH_PACKET_COUNT, &[62u8, 63u8])
+		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, ETH_PACKET_COUNT, &[62u8, 63u8, 64u8, 65u8])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8, 3u8, 4u8])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
 
 		// register the light protocol.
@@ -450,6 +520,10 @@ pub trait ManageNetwork : Send + Sync {
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Scores of known peers that have a non-neutral score or are banned
+	fn peer_scores(&self) -> Vec<PeerScore>;
+	/// Reset the score and lift the ban of a peer given by its public id, or of all peers
+	fn reset_peer_scores(&self, peer: Option<String>) -> Result<(), String>;
 }
 
 
@@ -491,6 +565,25 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn peer_scores(&self) -> Vec<PeerScore> {
+		self.network.peer_scores().into_iter().map(Into::into).collect()
+	}
+
+	fn reset_peer_scores(&self, peer: Option<String>) -> Result<(), String> {
+		reset_peer_scores(&self.network, peer)
+	}
+}
+
+fn reset_peer_scores(network: &NetworkService, peer: Option<String>) -> Result<(), String> {
+	match peer {
+		Some(peer) => {
+			let id = H512::from_str(peer.trim_left_matches("0x")).map_err(|e| format!("Invalid node id: {:?}", e))?;
+			network.reset_peer_scores(Some(&id));
+		},
+		None => network.reset_peer_scores(None),
+	}
+	Ok(())
 }
 
 /// IP fiter
@@ -553,6 +646,20 @@ pub struct NetworkConfiguration {
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub allow_ips: AllowIP,
+	/// `enrtree://` URL of a node record tree to bootstrap from.
+	pub enr_tree: Option<String>,
+	/// File with the TXT records of the node record tree.
+	pub enr_tree_file: Option<String>,
+	/// Upload limit for all peers together, in bytes per second.
+	pub upload_limit: Option<u64>,
+	/// Download limit for all peers together, in bytes per second.
+	pub download_limit: Option<u64>,
+	/// Upload limit for a single peer, in bytes per second.
+	pub peer_upload_limit: Option<u64>,
+	/// Download limit for a single peer, in bytes per second.
+	pub peer_download_limit: Option<u64>,
+	/// Number of seconds a peer is banned for after reaching the lowest score.
+	pub ban_duration: u64,
 }
 
 impl NetworkConfiguration {
@@ -589,6 +696,14 @@ impl NetworkConfiguration {
 				AllowIP::Public => NetworkAllowIP::Public,
 			},
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
+			chain_identity: None,
+			enr_tree: self.enr_tree,
+			enr_tree_file: self.enr_tree_file,
+			upload_limit: self.upload_limit.map(|l| l as usize),
+			download_limit: self.download_limit.map(|l| l as usize),
+			peer_upload_limit: self.peer_upload_limit.map(|l| l as usize),
+			peer_download_limit: self.peer_download_limit.map(|l| l as usize),
+			ban_duration: self.ban_duration,
 		})
 	}
 }
@@ -616,6 +731,13 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 				NetworkAllowIP::Public => AllowIP::Public,
 			},
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
+			enr_tree: other.enr_tree,
+			enr_tree_file: other.enr_tree_file,
+			upload_limit: other.upload_limit.map(|l| l as u64),
+			download_limit: other.download_limit.map(|l| l as u64),
+			peer_upload_limit: other.peer_upload_limit.map(|l| l as u64),
+			peer_download_limit: other.peer_download_limit.map(|l| l as u64),
+			ban_duration: other.ban_duration,
 		}
 	}
 }
@@ -628,6 +750,10 @@ pub struct ServiceConfiguration {
 	pub sync: SyncConfig,
 	/// Network configuration.
 	pub net: NetworkConfiguration,
+	/// Trusted snapshot publishers.
+	pub trusted_snapshot_publishers: Vec<Address>,
+	/// Fork block numbers.
+	pub fork_blocks: Vec<BlockNumber>,
 	/// IPC path.
 	pub io_path: String,
 }
@@ -787,6 +913,14 @@ impl ManageNetwork for LightSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn peer_scores(&self) -> Vec<PeerScore> {
+		self.network.peer_scores().into_iter().map(Into::into).collect()
+	}
+
+	fn reset_peer_scores(&self, peer: Option<String>) -> Result<(), String> {
+		reset_peer_scores(&self.network, peer)
+	}
 }
 
 impl LightSyncProvider for LightSync {
@@ -817,6 +951,9 @@ impl LightSyncProvider for LightSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					bytes_sent: session_info.traffic.sent,
+					bytes_received: session_info.traffic.received,
+					protocol_traffic: session_info.traffic.protocols.into_iter().map(Into::into).collect(),
 					eth_info: None,
 					pip_info: self.proto.peer_status(&peer_id).map(Into::into),
 				})
@@ -105,15 +105,20 @@ use rand::Rng;
 use snapshot::{Snapshot, ChunkType};
 use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use fork_filter::{ForkFilter, ForkId};
 
 known_heap_size!(0, PeerInfo);
 
 type PacketDecodeError = DecoderError;
 
+const PROTOCOL_VERSION_65: u8 = 65;
+const PROTOCOL_VERSION_64: u8 = 64;
 const PROTOCOL_VERSION_63: u8 = 63;
 const PROTOCOL_VERSION_62: u8 = 62;
 const PROTOCOL_VERSION_1: u8 = 1;
 const PROTOCOL_VERSION_2: u8 = 2;
+const PROTOCOL_VERSION_3: u8 = 3;
+const PROTOCOL_VERSION_4: u8 = 4;
 const MAX_BODIES_TO_SEND: usize = 256;
 const MAX_HEADERS_TO_SEND: usize = 512;
 const MAX_NODE_DATA_TO_SEND: usize = 1024;
@@ -130,6 +135,14 @@ const MAX_TRANSACTION_SIZE: usize = 300*1024;
 const MAX_TRANSACTION_PACKET_SIZE: usize = 8 * 1024 * 1024;
 // Maximal number of transactions in sent in single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
+// Maximal number of transaction hashes announced in single packet.
+const MAX_TRANSACTION_HASHES_TO_ANNOUNCE: usize = 4096;
+// Maximal number of pooled transactions requested from or returned to a peer in single packet.
+const MAX_POOLED_TRANSACTIONS_TO_REQUEST: usize = 256;
+// Maximal number of announced transactions waiting to be received. Further announcements are dropped.
+const MAX_PENDING_TRANSACTIONS: usize = 32768;
+// Maximal number of announced transactions waiting to be received a single peer may have announced.
+const MAX_PEER_PENDING_TRANSACTIONS: usize = 4096;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 100000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
@@ -142,6 +155,9 @@ const BLOCK_HEADERS_PACKET: u8 = 0x04;
 const GET_BLOCK_BODIES_PACKET: u8 = 0x05;
 const BLOCK_BODIES_PACKET: u8 = 0x06;
 const NEW_BLOCK_PACKET: u8 = 0x07;
+const NEW_POOLED_TRANSACTION_HASHES_PACKET: u8 = 0x08;
+const GET_POOLED_TRANSACTIONS_PACKET: u8 = 0x09;
+const POOLED_TRANSACTIONS_PACKET: u8 = 0x0a;
 
 const GET_NODE_DATA_PACKET: u8 = 0x0d;
 const NODE_DATA_PACKET: u8 = 0x0e;
@@ -168,6 +184,13 @@ const RECEIPTS_TIMEOUT_SEC: u64 = 10;
 const FORK_HEADER_TIMEOUT_SEC: u64 = 3;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: u64 = 5;
 const SNAPSHOT_DATA_TIMEOUT_SEC: u64 = 120;
+const POOLED_TRANSACTIONS_TIMEOUT_SEC: u64 = 10;
+
+// Peer score adjustments
+const REPUTATION_USEFUL_DATA: i32 = 1;
+const REPUTATION_TIMEOUT: i32 = -10;
+const REPUTATION_BAD_SNAPSHOT_CHUNK: i32 = -50;
+const REPUTATION_INVALID_DATA: i32 = -100;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -303,6 +326,8 @@ struct PeerInfo {
 	ask_time: u64,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
 	last_sent_transactions: HashSet<H256>,
+	/// Peer announces transactions by hash and serves them on request (eth/65, par/4).
+	transaction_announcements: bool,
 	/// Pending request is expired and result should be ignored
 	expired: bool,
 	/// Peer fork confirmation status
@@ -311,6 +336,10 @@ struct PeerInfo {
 	snapshot_hash: Option<H256>,
 	/// Best snapshot block number
 	snapshot_number: Option<BlockNumber>,
+	/// Earliest block the peer serves bodies and receipts for, if known
+	earliest_block: Option<BlockNumber>,
+	/// Fork identifier, sent by eth/64 and warp/3 peers
+	fork_id: Option<ForkId>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
 }
@@ -334,6 +363,30 @@ impl PeerInfo {
 	}
 }
 
+/// An announced transaction which was not received yet.
+struct TransactionRequest {
+	/// Peer the transaction is currently requested from, if any.
+	asked: Option<PeerId>,
+	/// Request timestamp.
+	ask_time: u64,
+	/// Other peers which announced the transaction, in order of announcement.
+	announcers: VecDeque<PeerId>,
+}
+
+// Forget one of the announcements of a peer, once the transaction is received or the peer isn't asked for it anymore.
+fn forget_announcement(pending_announcements: &mut HashMap<PeerId, usize>, peer_id: PeerId) {
+	let forget_peer = match pending_announcements.get_mut(&peer_id) {
+		Some(count) => {
+			*count -= 1;
+			*count == 0
+		},
+		None => false,
+	};
+	if forget_peer {
+		pending_announcements.remove(&peer_id);
+	}
+}
+
 #[cfg(not(test))]
 mod random {
 	use rand;
@@ -368,6 +421,12 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// Fork identifier of our chain, checked against the one sent by peers
+	fork_filter: ForkFilter,
+	/// Announced transactions not yet received, with the peers which announced them.
+	requested_transactions: H256FastMap<TransactionRequest>,
+	/// Number of announced transactions not yet received per peer which announced them.
+	pending_announcements: HashMap<PeerId, usize>,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
 	/// Connected peers pending Status message.
@@ -401,6 +460,9 @@ impl ChainSync {
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			fork_filter: ForkFilter::new(&chain_info.genesis_hash, Vec::new()),
+			requested_transactions: H256FastMap::default(),
+			pending_announcements: HashMap::new(),
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
@@ -411,6 +473,16 @@ impl ChainSync {
 		sync
 	}
 
+	/// Only restore snapshots whose manifest is signed by one of the given publishers.
+	pub fn set_trusted_snapshot_publishers(&mut self, publishers: HashSet<Address>) {
+		self.snapshot.set_trusted_publishers(publishers);
+	}
+
+	/// Set the block numbers of scheduled chain rule changes, used to compute the fork identifier.
+	pub fn set_fork_blocks(&mut self, genesis: &H256, forks: Vec<BlockNumber>) {
+		self.fork_filter = ForkFilter::new(genesis, forks);
+	}
+
 	/// Returns synchonization status
 	pub fn status(&self) -> SyncStatus {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
@@ -614,11 +686,19 @@ impl ChainSync {
 			asking_hash: None,
 			ask_time: 0,
 			last_sent_transactions: HashSet::new(),
+			transaction_announcements: if warp_protocol { protocol_version >= PROTOCOL_VERSION_4 } else { protocol_version >= PROTOCOL_VERSION_65 },
 			expired: false,
 			confirmation: if self.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
+			// left out of the status by older warp peers.
+			earliest_block: if warp_protocol { r.val_at(7).ok() } else { None },
+			fork_id: match warp_protocol {
+				true if protocol_version >= PROTOCOL_VERSION_3 => Some(r.val_at(8)?),
+				false if protocol_version >= PROTOCOL_VERSION_64 => Some(r.val_at(5)?),
+				_ => None,
+			},
 			block_set: None,
 		};
 
@@ -648,11 +728,19 @@ impl ChainSync {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, self.network_id, peer.network_id);
 			return Ok(());
 		}
-		if (warp_protocol && peer.protocol_version != PROTOCOL_VERSION_1 && peer.protocol_version != PROTOCOL_VERSION_2) || (!warp_protocol && peer.protocol_version != PROTOCOL_VERSION_63 && peer.protocol_version != PROTOCOL_VERSION_62) {
+		if (warp_protocol && (peer.protocol_version < PROTOCOL_VERSION_1 || peer.protocol_version > PROTOCOL_VERSION_4))
+			|| (!warp_protocol && (peer.protocol_version < PROTOCOL_VERSION_62 || peer.protocol_version > PROTOCOL_VERSION_65)) {
 			io.disable_peer(peer_id);
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Ok(());
 		}
+		if let Some(ref fork_id) = peer.fork_id {
+			if !self.fork_filter.is_compatible(chain_info.best_block_number, fork_id) {
+				io.disable_peer(peer_id);
+				trace!(target: "sync", "Peer {} fork id mismatch (ours: {:?}, theirs: {:?})", peer_id, self.fork_filter.current(chain_info.best_block_number), fork_id);
+				return Ok(());
+			}
+		}
 
 		self.peers.insert(peer_id.clone(), peer);
 		// Don't activate peer immediatelly when searching for common block.
@@ -745,12 +833,14 @@ impl ChainSync {
 				self.deactivate_peer(io, peer_id);
 			},
 			Err(DownloaderImportError::Invalid) => {
+				io.update_reputation(peer_id, REPUTATION_INVALID_DATA);
 				io.disable_peer(peer_id);
 				self.deactivate_peer(io, peer_id);
 				self.continue_sync(io);
 				return Ok(());
 			},
 			Ok(DownloadAction::Reset) => {
+				io.update_reputation(peer_id, REPUTATION_USEFUL_DATA);
 				// mark all outstanding requests as expired
 				trace!("Resetting downloads for {:?}", block_set);
 				for (_, ref mut p) in self.peers.iter_mut().filter(|&(_, ref p)| p.block_set == Some(block_set)) {
@@ -758,7 +848,9 @@ impl ChainSync {
 				}
 
 			}
-			Ok(DownloadAction::None) => {},
+			Ok(DownloadAction::None) => {
+				io.update_reputation(peer_id, REPUTATION_USEFUL_DATA);
+			},
 		}
 
 		self.collect_blocks(io, block_set);
@@ -805,6 +897,7 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.update_reputation(peer_id, REPUTATION_INVALID_DATA);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -813,7 +906,7 @@ impl ChainSync {
 				Err(DownloaderImportError::Useless) => {
 					self.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.update_reputation(peer_id, REPUTATION_USEFUL_DATA),
 			}
 
 			self.collect_blocks(io, block_set);
@@ -859,6 +952,7 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.update_reputation(peer_id, REPUTATION_INVALID_DATA);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -867,7 +961,7 @@ impl ChainSync {
 				Err(DownloaderImportError::Useless) => {
 					self.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.update_reputation(peer_id, REPUTATION_USEFUL_DATA),
 			}
 
 			self.collect_blocks(io, block_set);
@@ -929,6 +1023,7 @@ impl ChainSync {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+				io.update_reputation(peer_id, REPUTATION_INVALID_DATA);
 				io.disable_peer(peer_id);
 			}
 		};
@@ -1053,6 +1148,24 @@ impl ChainSync {
 			self.continue_sync(io);
 			return Ok(());
 		}
+
+		if !self.snapshot.is_trusted(&manifest) {
+			let hash = manifest_rlp.as_raw().sha3();
+			trace!(target: "sync", "{}: Snapshot manifest {} is not signed by a trusted publisher", peer_id, hash);
+			self.snapshot.note_bad(hash);
+			self.state = SyncState::WaitingPeers;
+			self.continue_sync(io);
+			return Ok(());
+		}
+		let missing_base = manifest.base.map_or(false, |base| !io.snapshot_service().can_apply_delta(base));
+		if missing_base {
+			let hash = manifest_rlp.as_raw().sha3();
+			trace!(target: "sync", "{}: Snapshot manifest {} is a delta snapshot against a missing base", peer_id, hash);
+			self.snapshot.note_bad(hash);
+			self.state = SyncState::WaitingPeers;
+			self.continue_sync(io);
+			return Ok(());
+		}
 		self.snapshot.reset_to(&manifest, &manifest_rlp.as_raw().sha3());
 		io.snapshot_service().begin_restore(manifest);
 		self.state = SyncState::SnapshotData;
@@ -1111,6 +1224,7 @@ impl ChainSync {
 			}
 			Err(()) => {
 				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.update_reputation(peer_id, REPUTATION_BAD_SNAPSHOT_CHUNK);
 				io.disconnect_peer(peer_id);
 				self.continue_sync(io);
 				return Ok(());
@@ -1137,6 +1251,7 @@ impl ChainSync {
 			self.clear_peer_download(peer);
 			self.peers.remove(&peer);
 			self.active_peers.remove(&peer);
+			self.forget_peer_announcements(peer);
 			self.continue_sync(io);
 		}
 	}
@@ -1191,7 +1306,7 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer {}", peer_id);
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash, peer_earliest) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
 				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
@@ -1205,7 +1320,7 @@ impl ChainSync {
 					trace!(target: "sync", "Waiting for the snapshot restoration");
 					return;
 				}
-				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
+				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned(), peer.earliest_block.unwrap_or(0))
 			} else {
 				return;
 			}
@@ -1241,7 +1356,13 @@ impl ChainSync {
 						}
 					}
 
-					if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers)) {
+					// peers which pruned their history can't help filling the gap below the snapshot.
+					let old_blocks = self.old_blocks.as_mut().and_then(|d| if peer_earliest <= d.last_imported_block_number() + 1 {
+						d.request_blocks(io, num_active_peers)
+					} else {
+						None
+					});
+					if let Some(request) = old_blocks {
 						self.request_blocks(io, peer_id, request, BlockSet::OldBlocks);
 						return;
 					}
@@ -1460,21 +1581,43 @@ impl ChainSync {
 		}
 	}
 
-	/// Called when peer sends us new transactions
-	fn on_peer_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
-		// Accept transactions only when fully synced
+	/// Checks if transactions from peers should be imported. They are accepted only when fully synced.
+	fn accepts_transactions(&self, io: &SyncIo, peer_id: PeerId) -> bool {
 		if !io.is_chain_queue_empty() || (self.state != SyncState::Idle && self.state != SyncState::NewBlocks) {
 			trace!(target: "sync", "{} Ignoring transactions while syncing", peer_id);
-			return Ok(());
+			return false;
 		}
 		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
 			trace!(target: "sync", "{} Ignoring transactions from unconfirmed/unknown peer", peer_id);
 		}
+		true
+	}
+
+	/// Called when peer sends us new transactions
+	fn on_peer_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		if !self.accepts_transactions(io, peer_id) {
+			return Ok(());
+		}
 
-		let mut item_count = r.item_count()?;
+		let item_count = r.item_count()?;
 		trace!(target: "sync", "{:02} -> Transactions ({} entries)", peer_id, item_count);
-		item_count = min(item_count, MAX_TX_TO_IMPORT);
+		self.queue_peer_transactions(io, peer_id, r, item_count)
+	}
+
+	/// Called when peer sends us transactions we have requested
+	fn on_peer_pooled_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> PooledTransactions ({} entries)", peer_id, item_count);
+		if !self.accepts_transactions(io, peer_id) {
+			return Ok(());
+		}
+		self.queue_peer_transactions(io, peer_id, r, item_count)
+	}
+
+	fn queue_peer_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp, item_count: usize) -> Result<(), PacketDecodeError> {
+		let item_count = min(item_count, MAX_TX_TO_IMPORT);
 		let mut transactions = Vec::with_capacity(item_count);
+		let mut hashes = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
 			let rlp = r.at(i)?;
 			if rlp.as_raw().len() > MAX_TRANSACTION_SIZE {
@@ -1482,19 +1625,169 @@ impl ChainSync {
 				continue;
 			}
 			let tx = rlp.as_raw().to_vec();
+			hashes.push(tx.sha3());
 			transactions.push(tx);
 		}
+		for hash in &hashes {
+			if let Some(pending) = self.requested_transactions.remove(hash) {
+				for peer_id in pending.asked.into_iter().chain(pending.announcers) {
+					forget_announcement(&mut self.pending_announcements, peer_id);
+				}
+			}
+		}
+		if let Some(peer) = self.peers.get_mut(&peer_id) {
+			if peer.transaction_announcements {
+				peer.last_sent_transactions.extend(hashes);
+			}
+		}
 		io.chain().queue_transactions(transactions, peer_id);
 		Ok(())
 	}
 
+	/// Called when peer announces hashes of new transactions
+	fn on_peer_new_pooled_transaction_hashes(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> NewPooledTransactionHashes ({} entries)", peer_id, item_count);
+		if !self.accepts_transactions(io, peer_id) {
+			return Ok(());
+		}
+
+		let tick = time::precise_time_ns();
+		let mut request = Vec::new();
+		for i in 0 .. min(item_count, MAX_TRANSACTION_HASHES_TO_ANNOUNCE) {
+			let hash: H256 = r.val_at(i)?;
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.last_sent_transactions.insert(hash);
+			}
+			if io.chain().queued_transaction(&hash).is_some() {
+				continue;
+			}
+			// announcements beyond the limits are dropped, so that a peer can't grow them unbounded.
+			if self.pending_announcements.get(&peer_id).map_or(false, |count| *count >= MAX_PEER_PENDING_TRANSACTIONS) {
+				trace!(target: "sync", "{:02}: Too many pending transaction announcements, ignoring the rest", peer_id);
+				break;
+			}
+			// remember every announcer so the transaction can be requested from another one
+			// if the first doesn't deliver it.
+			if let Some(pending) = self.requested_transactions.get_mut(&hash) {
+				if pending.asked != Some(peer_id) && !pending.announcers.contains(&peer_id) {
+					pending.announcers.push_back(peer_id);
+					*self.pending_announcements.entry(peer_id).or_insert(0) += 1;
+				}
+				continue;
+			}
+			if self.requested_transactions.len() >= MAX_PENDING_TRANSACTIONS {
+				trace!(target: "sync", "{:02}: Too many pending transactions, ignoring announcement", peer_id);
+				continue;
+			}
+			// transactions over the request limit are queued and requested on the next maintenance.
+			let pending = match request.len() < MAX_POOLED_TRANSACTIONS_TO_REQUEST {
+				true => {
+					request.push(hash);
+					TransactionRequest { asked: Some(peer_id), ask_time: tick, announcers: VecDeque::new() }
+				},
+				false => {
+					let mut announcers = VecDeque::new();
+					announcers.push_back(peer_id);
+					TransactionRequest { asked: None, ask_time: tick, announcers: announcers }
+				},
+			};
+			self.requested_transactions.insert(hash, pending);
+			*self.pending_announcements.entry(peer_id).or_insert(0) += 1;
+		}
+
+		self.request_pooled_transactions(io, peer_id, &request);
+		Ok(())
+	}
+
+	/// Request the announced transactions which were not requested yet, or not delivered in time,
+	/// from the next peer which announced them. Transactions no other peer announced are forgotten.
+	fn request_pending_transactions(&mut self, io: &mut SyncIo) {
+		let tick = time::precise_time_ns();
+		let mut requests: HashMap<PeerId, Vec<H256>> = HashMap::new();
+		let mut forgotten = Vec::new();
+		for (hash, pending) in self.requested_transactions.iter_mut() {
+			let retry = match pending.asked {
+				Some(peer_id) => !self.peers.contains_key(&peer_id)
+					|| (tick - pending.ask_time) / 1_000_000_000 > POOLED_TRANSACTIONS_TIMEOUT_SEC,
+				None => true,
+			};
+			if !retry {
+				continue;
+			}
+
+			if let Some(peer_id) = pending.asked.take() {
+				forget_announcement(&mut self.pending_announcements, peer_id);
+			}
+			while let Some(peer_id) = pending.announcers.pop_front() {
+				if !self.peers.contains_key(&peer_id) {
+					forget_announcement(&mut self.pending_announcements, peer_id);
+					continue;
+				}
+				let request = requests.entry(peer_id).or_insert_with(Vec::new);
+				if request.len() >= MAX_POOLED_TRANSACTIONS_TO_REQUEST {
+					// keep it for the next round.
+					pending.announcers.push_front(peer_id);
+					break;
+				}
+				request.push(*hash);
+				pending.asked = Some(peer_id);
+				pending.ask_time = tick;
+				break;
+			}
+
+			if pending.asked.is_none() && pending.announcers.is_empty() {
+				forgotten.push(*hash);
+			}
+		}
+
+		for hash in forgotten {
+			self.requested_transactions.remove(&hash);
+		}
+		for (peer_id, request) in requests {
+			self.request_pooled_transactions(io, peer_id, &request);
+		}
+	}
+
+	/// Remove a disconnected peer from the announcers of the transactions not yet received.
+	/// Transactions requested from it are requested from another announcer on the next maintenance.
+	fn forget_peer_announcements(&mut self, peer_id: PeerId) {
+		self.pending_announcements.remove(&peer_id);
+		for pending in self.requested_transactions.values_mut() {
+			if pending.asked == Some(peer_id) {
+				pending.asked = None;
+			}
+			if let Some(position) = pending.announcers.iter().position(|p| *p == peer_id) {
+				pending.announcers.remove(position);
+			}
+		}
+	}
+
+	fn request_pooled_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, hashes: &[H256]) {
+		if hashes.is_empty() {
+			return;
+		}
+
+		trace!(target: "sync", "{:02} <- GetPooledTransactions ({} entries)", peer_id, hashes.len());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for hash in hashes {
+			rlp.append(hash);
+		}
+		self.send_packet(io, peer_id, GET_POOLED_TRANSACTIONS_PACKET, rlp.out());
+	}
+
 	/// Send Status message
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), NetworkError> {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
-		let protocol = if warp_protocol { warp_protocol_version } else { PROTOCOL_VERSION_63 };
+		let protocol = match warp_protocol {
+			true => warp_protocol_version,
+			false => max(min(io.eth_protocol_version(peer), PROTOCOL_VERSION_65), PROTOCOL_VERSION_63),
+		};
+		let send_fork_id = if warp_protocol { protocol >= PROTOCOL_VERSION_3 } else { protocol >= PROTOCOL_VERSION_64 };
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
-		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 });
+		let base_items = if warp_protocol { 8 } else { 5 };
+		let mut packet = RlpStream::new_list(if send_fork_id { base_items + 1 } else { base_items });
 		let chain = io.chain().chain_info();
 		packet.append(&(protocol as u32));
 		packet.append(&self.network_id);
@@ -1510,6 +1803,10 @@ impl ChainSync {
 			let manifest_hash = manifest.map_or(H256::new(), |m| m.into_rlp().sha3());
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
+			packet.append(&io.chain().pruning_info().earliest_chain);
+		}
+		if send_fork_id {
+			packet.append(&self.fork_filter.current(chain.best_block_number));
 		}
 		io.respond(STATUS_PACKET, packet.out())
 	}
@@ -1658,6 +1955,32 @@ impl ChainSync {
 		Ok(Some((RECEIPTS_PACKET, rlp_result)))
 	}
 
+	/// Respond to GetPooledTransactions request
+	fn return_pooled_transactions(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+		let count = min(r.item_count().unwrap_or(0), MAX_POOLED_TRANSACTIONS_TO_REQUEST);
+		trace!(target: "sync", "{} -> GetPooledTransactions: {} entries", peer_id, count);
+		if count == 0 {
+			debug!(target: "sync", "Empty GetPooledTransactions request, ignoring.");
+			return Ok(None);
+		}
+		let mut rlp = RlpStream::new();
+		rlp.begin_unbounded_list();
+		let mut added = 0usize;
+		for i in 0..count {
+			if let Some(tx) = io.chain().queued_transaction(&r.val_at::<H256>(i)?) {
+				let mut transaction = RlpStream::new();
+				tx.transaction.rlp_append(&mut transaction);
+				if !rlp.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE) {
+					break;
+				}
+				added += 1;
+			}
+		}
+		rlp.complete_unbounded_list();
+		trace!(target: "sync", "{} -> GetPooledTransactions: return {} entries", peer_id, added);
+		Ok(Some((POOLED_TRANSACTIONS_PACKET, rlp)))
+	}
+
 	/// Respond to GetSnapshotManifest request
 	fn return_snapshot_manifest(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
 		let count = r.item_count().unwrap_or(0);
@@ -1707,7 +2030,7 @@ impl ChainSync {
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				io.respond_low_priority(packet_id, rlp_stream.out()).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -1735,6 +2058,10 @@ impl ChainSync {
 				ChainSync::return_node_data,
 				|e| format!("Error sending nodes: {:?}", e)),
 
+			GET_POOLED_TRANSACTIONS_PACKET => ChainSync::return_rlp(io, &rlp, peer,
+				ChainSync::return_pooled_transactions,
+				|e| format!("Error sending pooled transactions: {:?}", e)),
+
 			GET_SNAPSHOT_MANIFEST_PACKET => ChainSync::return_rlp(io, &rlp, peer,
 				ChainSync::return_snapshot_manifest,
 				|e| format!("Error sending snapshot manifest: {:?}", e)),
@@ -1762,6 +2089,8 @@ impl ChainSync {
 		let result = match packet_id {
 			STATUS_PACKET => self.on_peer_status(io, peer, &rlp),
 			TRANSACTIONS_PACKET => self.on_peer_transactions(io, peer, &rlp),
+			NEW_POOLED_TRANSACTION_HASHES_PACKET => self.on_peer_new_pooled_transaction_hashes(io, peer, &rlp),
+			POOLED_TRANSACTIONS_PACKET => self.on_peer_pooled_transactions(io, peer, &rlp),
 			BLOCK_HEADERS_PACKET => self.on_peer_block_headers(io, peer, &rlp),
 			BLOCK_BODIES_PACKET => self.on_peer_block_bodies(io, peer, &rlp),
 			RECEIPTS_PACKET => self.on_peer_block_receipts(io, peer, &rlp),
@@ -1796,6 +2125,7 @@ impl ChainSync {
 			};
 			if timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
+				io.update_reputation(*peer_id, REPUTATION_TIMEOUT);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
@@ -1812,6 +2142,9 @@ impl ChainSync {
 				io.disconnect_peer(*peer);
 			}
 		}
+
+		// Request transactions that were not delivered from other peers
+		self.request_pending_transactions(io);
 	}
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
@@ -1923,7 +2256,7 @@ impl ChainSync {
 	}
 
 	fn get_consensus_peers(&self) -> Vec<PeerId> {
-		self.peers.iter().filter_map(|(id, p)| if p.protocol_version == PROTOCOL_VERSION_2 { Some(*id) } else { None }).collect()
+		self.peers.iter().filter_map(|(id, p)| if p.protocol_version >= PROTOCOL_VERSION_2 && p.protocol_version <= PROTOCOL_VERSION_4 { Some(*id) } else { None }).collect()
 	}
 
 	/// propagates latest block to a set of peers
@@ -2041,6 +2374,29 @@ impl ChainSync {
 					let peer_info = self.peers.get_mut(&peer_id)
 						.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
 
+					// Announce hashes only, the peer requests transactions it does not have
+					if peer_info.transaction_announcements {
+						let to_send = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
+							.take(MAX_TRANSACTION_HASHES_TO_ANNOUNCE)
+							.cloned()
+							.collect::<Vec<_>>();
+						if to_send.is_empty() {
+							return None;
+						}
+						let mut packet = RlpStream::new_list(to_send.len());
+						let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+						for hash in &to_send {
+							packet.append(hash);
+							stats.propagated(hash, id, block_number);
+						}
+						peer_info.last_sent_transactions = all_transactions_hashes
+							.intersection(&peer_info.last_sent_transactions)
+							.chain(&to_send)
+							.cloned()
+							.collect();
+						return Some((peer_id, NEW_POOLED_TRANSACTION_HASHES_PACKET, to_send.len(), packet.out()));
+					}
+
 					// Send all transactions
 					if peer_info.last_sent_transactions.is_empty() {
 						// update stats
@@ -2049,7 +2405,7 @@ impl ChainSync {
 							stats.propagated(hash, id, block_number);
 						}
 						peer_info.last_sent_transactions = all_transactions_hashes.clone();
-						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
+						return Some((peer_id, TRANSACTIONS_PACKET, all_transactions_hashes.len(), all_transactions_rlp.clone()));
 					}
 
 					// Get hashes of all transactions to send to this peer
@@ -2098,7 +2454,7 @@ impl ChainSync {
 						.chain(&to_send)
 						.cloned()
 						.collect();
-					Some((peer_id, to_send.len(), packet.out()))
+					Some((peer_id, TRANSACTIONS_PACKET, to_send.len(), packet.out()))
 				})
 				.collect::<Vec<_>>()
 		};
@@ -2108,10 +2464,14 @@ impl ChainSync {
 		if lucky_peers.len() > 0 {
 			let mut max_sent = 0;
 			let lucky_peers_len = lucky_peers.len();
-			for (peer_id, sent, rlp) in lucky_peers {
+			for (peer_id, packet_id, sent, rlp) in lucky_peers {
 				peers.insert(peer_id);
-				self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
-				trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+				self.send_packet(io, peer_id, packet_id, rlp);
+				if packet_id == TRANSACTIONS_PACKET {
+					trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+				} else {
+					trace!(target: "sync", "{:02} <- NewPooledTransactionHashes ({} entries)", peer_id, sent);
+				}
 				max_sent = max(max_sent, sent);
 			}
 			debug!(target: "sync", "Sent up to {} transactions to {} peers.", max_sent, lucky_peers_len);
@@ -2480,9 +2840,12 @@ mod tests {
 				asking_hash: None,
 				ask_time: 0,
 				last_sent_transactions: HashSet::new(),
+				transaction_announcements: false,
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
+				earliest_block: None,
+				fork_id: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
@@ -2601,9 +2964,12 @@ mod tests {
 				asking_hash: None,
 				ask_time: 0,
 				last_sent_transactions: HashSet::new(),
+				transaction_announcements: false,
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
+				earliest_block: None,
+				fork_id: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
@@ -2812,6 +3178,180 @@ mod tests {
 		assert!(sent_transactions.iter().any(|tx| tx.hash() == tx2_hash));
 	}
 
+	#[test]
+	fn announces_transaction_hashes_to_supporting_peers() {
+		let mut client = TestBlockChainClient::new();
+		let tx_hash = client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		insert_dummy_peer(&mut sync, 2, block_hash);
+		sync.peers.get_mut(&2).unwrap().transaction_announcements = true;
+
+		assert_eq!(sync.propagate_new_transactions(&mut io), 2);
+		assert!(io.packets.iter().any(|p| p.packet_id == super::TRANSACTIONS_PACKET && p.recipient == 1));
+		let announcement = io.packets.iter()
+			.find(|p| p.packet_id == super::NEW_POOLED_TRANSACTION_HASHES_PACKET && p.recipient == 2)
+			.expect("hashes are announced to peer#2");
+		assert_eq!(UntrustedRlp::new(&announcement.data).as_list::<H256>().unwrap(), vec![tx_hash]);
+		assert_eq!(io.packets.len(), 2);
+
+		// nothing new to announce
+		assert_eq!(sync.propagate_new_transactions(&mut io), 0);
+		assert_eq!(io.packets.len(), 2);
+	}
+
+	#[test]
+	fn requests_announced_transactions_once() {
+		let mut client = TestBlockChainClient::new();
+		let known_hash = client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = dummy_sync_with_peer(block_hash, &client);
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let unknown_hash = H256::random();
+		let mut announcement = RlpStream::new_list(2);
+		announcement.append(&known_hash).append(&unknown_hash);
+		let announcement = announcement.out();
+
+		sync.on_packet(&mut io, 0, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announcement);
+		assert_eq!(io.packets.len(), 1);
+		assert_eq!(io.packets[0].packet_id, super::GET_POOLED_TRANSACTIONS_PACKET);
+		assert_eq!(io.packets[0].recipient, 0);
+		assert_eq!(UntrustedRlp::new(&io.packets[0].data).as_list::<H256>().unwrap(), vec![unknown_hash]);
+
+		// the same transaction announced by another peer is not requested again
+		sync.on_packet(&mut io, 1, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announcement);
+		assert_eq!(io.packets.len(), 1);
+	}
+
+	#[test]
+	fn requests_undelivered_transactions_from_other_announcers() {
+		let mut client = TestBlockChainClient::new();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = dummy_sync_with_peer(block_hash, &client);
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let hash = H256::random();
+		let mut announcement = RlpStream::new_list(1);
+		announcement.append(&hash);
+		let announcement = announcement.out();
+
+		sync.on_packet(&mut io, 0, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announcement);
+		sync.on_packet(&mut io, 1, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announcement);
+		assert_eq!(io.packets.len(), 1);
+		assert_eq!(io.packets[0].recipient, 0);
+
+		// not timed out yet
+		sync.maintain_peers(&mut io);
+		assert_eq!(io.packets.len(), 1);
+
+		sync.requested_transactions.get_mut(&hash).unwrap().ask_time = 0;
+		sync.maintain_peers(&mut io);
+		assert_eq!(io.packets.len(), 2);
+		assert_eq!(io.packets[1].packet_id, super::GET_POOLED_TRANSACTIONS_PACKET);
+		assert_eq!(io.packets[1].recipient, 1);
+		assert_eq!(UntrustedRlp::new(&io.packets[1].data).as_list::<H256>().unwrap(), vec![hash]);
+
+		// nobody else to ask
+		sync.requested_transactions.get_mut(&hash).unwrap().ask_time = 0;
+		sync.maintain_peers(&mut io);
+		assert_eq!(io.packets.len(), 2);
+		assert!(sync.requested_transactions.is_empty());
+	}
+
+	#[test]
+	fn queues_announced_transactions_over_request_limit() {
+		let mut client = TestBlockChainClient::new();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = dummy_sync_with_peer(block_hash, &client);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let hashes: Vec<H256> = (0..super::MAX_POOLED_TRANSACTIONS_TO_REQUEST + 1).map(|_| H256::random()).collect();
+		let mut announcement = RlpStream::new_list(hashes.len());
+		for hash in &hashes {
+			announcement.append(hash);
+		}
+		let announcement = announcement.out();
+
+		sync.on_packet(&mut io, 0, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announcement);
+		assert_eq!(io.packets.len(), 1);
+		assert_eq!(UntrustedRlp::new(&io.packets[0].data).item_count().unwrap(), super::MAX_POOLED_TRANSACTIONS_TO_REQUEST);
+
+		sync.maintain_peers(&mut io);
+		assert_eq!(io.packets.len(), 2);
+		assert_eq!(io.packets[1].recipient, 0);
+		assert_eq!(UntrustedRlp::new(&io.packets[1].data).as_list::<H256>().unwrap(), vec![hashes[super::MAX_POOLED_TRANSACTIONS_TO_REQUEST]]);
+	}
+
+	#[test]
+	fn bounds_pending_transaction_announcements() {
+		let mut client = TestBlockChainClient::new();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut sync = dummy_sync_with_peer(block_hash, &client);
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let announce = |hashes: &[H256]| {
+			let mut announcement = RlpStream::new_list(hashes.len());
+			for hash in hashes {
+				announcement.append(hash);
+			}
+			announcement.out()
+		};
+
+		let hashes: Vec<H256> = (0..super::MAX_PEER_PENDING_TRANSACTIONS).map(|_| H256::random()).collect();
+		sync.on_packet(&mut io, 0, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announce(&hashes));
+		assert_eq!(sync.requested_transactions.len(), super::MAX_PEER_PENDING_TRANSACTIONS);
+		assert_eq!(sync.pending_announcements[&0], super::MAX_PEER_PENDING_TRANSACTIONS);
+
+		// the peer announced as many as it may.
+		sync.on_packet(&mut io, 0, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announce(&[H256::random()]));
+		assert_eq!(sync.requested_transactions.len(), super::MAX_PEER_PENDING_TRANSACTIONS);
+
+		sync.on_packet(&mut io, 1, super::NEW_POOLED_TRANSACTION_HASHES_PACKET, &announce(&hashes[..1]));
+		assert_eq!(sync.pending_announcements[&1], 1);
+		assert_eq!(sync.requested_transactions[&hashes[0]].announcers.len(), 1);
+
+		sync.on_peer_aborting(&mut io, 1);
+		assert!(!sync.pending_announcements.contains_key(&1));
+		assert!(sync.requested_transactions[&hashes[0]].announcers.is_empty());
+	}
+
+	#[test]
+	fn returns_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let tx_hash = client.insert_transaction_to_queue();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&H256::random()).append(&tx_hash);
+		let request = request.out();
+
+		let (packet_id, rlp) = ChainSync::return_pooled_transactions(&io, &UntrustedRlp::new(&request), 0).unwrap().unwrap();
+		assert_eq!(packet_id, super::POOLED_TRANSACTIONS_PACKET);
+		let rlp = rlp.out();
+		let transactions: Vec<UnverifiedTransaction> = UntrustedRlp::new(&rlp).as_list().unwrap();
+		assert_eq!(transactions.len(), 1);
+		assert_eq!(transactions[0].hash(), tx_hash);
+	}
+
 	#[test]
 	fn handles_peer_new_block_malformed() {
 		let mut client = TestBlockChainClient::new();
@@ -3023,4 +3563,23 @@ mod tests {
 		assert_eq!(status.transactions_in_pending_queue, 0);
 		assert_eq!(status.transactions_in_future_queue, 0);
 	}
+
+	#[test]
+	fn punishes_peer_on_timeout() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(10), &client);
+		sync.peers.get_mut(&0).unwrap().asking = PeerAsking::BlockHeaders;
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(10));
+
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&client, &ss, &queue, None);
+		sync.maintain_peers(&mut io);
+
+		assert_eq!(io.reputation.get(&0), Some(&super::REPUTATION_TIMEOUT));
+		assert_eq!(io.reputation.get(&1), None);
+		assert!(io.to_disconnect.contains(&0));
+		assert!(!sync.peers.contains_key(&0));
+	}
 }
@@ -0,0 +1,189 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fork identifier (EIP-2124) used to reject peers on incompatible chains during the handshake.
+
+use util::H256;
+use ethcore::header::BlockNumber;
+use rlp::*;
+
+/// Chain fork identifier: checksum of the genesis hash and all passed fork blocks,
+/// together with the next scheduled fork block (zero if none is known).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// CRC32 checksum of the genesis hash and passed fork block numbers.
+	pub hash: u32,
+	/// Next scheduled fork block, or zero.
+	pub next: BlockNumber,
+}
+
+impl Encodable for ForkId {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let hash = [(self.hash >> 24) as u8, (self.hash >> 16) as u8, (self.hash >> 8) as u8, self.hash as u8];
+		s.begin_list(2)
+			.append(&&hash[..])
+			.append(&self.next);
+	}
+}
+
+impl Decodable for ForkId {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let hash: Vec<u8> = rlp.val_at(0)?;
+		if hash.len() != 4 {
+			return Err(DecoderError::Custom("fork hash must be 4 bytes"));
+		}
+		Ok(ForkId {
+			hash: (hash[0] as u32) << 24 | (hash[1] as u32) << 16 | (hash[2] as u32) << 8 | hash[3] as u32,
+			next: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Computes our own fork identifier and validates those of remote peers.
+#[derive(Debug, Clone)]
+pub struct ForkFilter {
+	/// Fork block numbers, sorted and without duplicates.
+	forks: Vec<BlockNumber>,
+	/// `checksums[i]` covers the genesis hash and the first `i` forks.
+	checksums: Vec<u32>,
+}
+
+impl ForkFilter {
+	/// Create a filter for the chain with the given genesis hash and fork blocks.
+	/// Blocks at zero are ignored since they are active from genesis.
+	pub fn new(genesis: &H256, forks: Vec<BlockNumber>) -> ForkFilter {
+		let mut forks: Vec<BlockNumber> = forks.into_iter().filter(|f| *f != 0).collect();
+		forks.sort();
+		forks.dedup();
+		let mut checksum = crc32_update(0, &genesis[..]);
+		let mut checksums = vec![checksum];
+		for fork in &forks {
+			checksum = crc32_update(checksum, &be_bytes(*fork));
+			checksums.push(checksum);
+		}
+		ForkFilter {
+			forks: forks,
+			checksums: checksums,
+		}
+	}
+
+	/// Fork identifier to announce with the given best block.
+	pub fn current(&self, head: BlockNumber) -> ForkId {
+		let passed = self.passed(head);
+		ForkId {
+			hash: self.checksums[passed],
+			next: self.forks.get(passed).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Check whether a peer announcing `remote` can be on the same chain as us, given our best block.
+	pub fn is_compatible(&self, head: BlockNumber, remote: &ForkId) -> bool {
+		let passed = self.passed(head);
+		if remote.hash == self.checksums[passed] {
+			// Same set of passed forks. The remote is incompatible only if it expects
+			// a fork we have already passed without applying.
+			return remote.next == 0 || head < remote.next;
+		}
+		if let Some(i) = self.checksums[..passed].iter().position(|c| *c == remote.hash) {
+			// Remote is behind us and must be aware of the fork that follows its state.
+			return remote.next == self.forks[i];
+		}
+		// Remote is ahead of us, on forks we know about but have not reached yet.
+		self.checksums[passed + 1..].iter().any(|c| *c == remote.hash)
+	}
+
+	/// Number of forks activated at or before `head`.
+	fn passed(&self, head: BlockNumber) -> usize {
+		self.forks.iter().take_while(|f| **f <= head).count()
+	}
+}
+
+fn be_bytes(value: u64) -> [u8; 8] {
+	let mut bytes = [0u8; 8];
+	for i in 0..8 {
+		bytes[i] = (value >> (56 - i * 8)) as u8;
+	}
+	bytes
+}
+
+/// Continue an IEEE CRC32 checksum with more data.
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for byte in data {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::crc32_update;
+	use std::str::FromStr;
+	use util::H256;
+	use rlp::{encode, decode};
+
+	fn mainnet() -> ForkFilter {
+		let genesis = H256::from_str("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3").unwrap();
+		ForkFilter::new(&genesis, vec![1150000, 1920000, 2463000, 2675000])
+	}
+
+	#[test]
+	fn crc32() {
+		assert_eq!(crc32_update(0, b"123456789"), 0xcbf43926);
+		assert_eq!(crc32_update(crc32_update(0, b"1234"), b"56789"), 0xcbf43926);
+	}
+
+	#[test]
+	fn mainnet_ids() {
+		let filter = mainnet();
+		assert_eq!(filter.current(0), ForkId { hash: 0xfc64ec04, next: 1150000 });
+		assert_eq!(filter.current(1149999), ForkId { hash: 0xfc64ec04, next: 1150000 });
+		assert_eq!(filter.current(1150000), ForkId { hash: 0x97c2c34c, next: 1920000 });
+		assert_eq!(filter.current(1920000), ForkId { hash: 0x91d1f948, next: 2463000 });
+		assert_eq!(filter.current(2463000), ForkId { hash: 0x7a64da13, next: 2675000 });
+		assert_eq!(filter.current(2675000), ForkId { hash: 0x3edd5b10, next: 0 });
+	}
+
+	#[test]
+	fn validates_remote_ids() {
+		let filter = mainnet();
+		// Same forks, remote knows of no further forks, or of one we have not reached.
+		assert!(filter.is_compatible(2675000, &ForkId { hash: 0x3edd5b10, next: 0 }));
+		assert!(filter.is_compatible(2675000, &ForkId { hash: 0x3edd5b10, next: 4370000 }));
+		// Same forks, remote expects a fork we have already passed.
+		assert!(!filter.is_compatible(4370000, &ForkId { hash: 0x3edd5b10, next: 4370000 }));
+		// Remote is behind us and knows the fork that follows.
+		assert!(filter.is_compatible(2675000, &ForkId { hash: 0x7a64da13, next: 2675000 }));
+		// Remote is behind us and is not aware of the next fork.
+		assert!(!filter.is_compatible(2675000, &ForkId { hash: 0x7a64da13, next: 0 }));
+		// Remote is ahead of us on known forks.
+		assert!(filter.is_compatible(1150000, &ForkId { hash: 0x7a64da13, next: 2675000 }));
+		// Unknown checksum.
+		assert!(!filter.is_compatible(2675000, &ForkId { hash: 0xafec6b27, next: 0 }));
+	}
+
+	#[test]
+	fn rlp_roundtrip() {
+		let id = ForkId { hash: 0x0064ec04, next: 1150000 };
+		let encoded = encode(&id);
+		assert_eq!(&encoded[..], &[0xc9, 0x84, 0x00, 0x64, 0xec, 0x04, 0x83, 0x11, 0x8c, 0x30][..]);
+		assert_eq!(decode::<ForkId>(&encoded), id);
+	}
+}
@@ -23,7 +23,7 @@
 #![cfg_attr(feature="dev", allow(if_not_else))]
 
 //! Blockchain sync module
-//! Implements ethereum protocol version 63 as specified here:
+//! Implements ethereum protocol versions 63 to 65 as specified here:
 //! https://github.com/ethereum/wiki/wiki/Ethereum-Wire-Protocol
 //!
 
@@ -58,6 +58,7 @@ mod block_sync;
 mod sync_io;
 mod snapshot;
 mod transactions_stats;
+mod fork_filter;
 
 pub mod light_sync;
 
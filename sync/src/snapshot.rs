@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 
-use util::{H256, Hashable};
+use util::{H256, Address, Hashable};
 use std::collections::HashSet;
 use ethcore::snapshot::ManifestData;
 
@@ -32,6 +32,7 @@ pub struct Snapshot {
 	completed_chunks: HashSet<H256>,
 	snapshot_hash: Option<H256>,
 	bad_hashes: HashSet<H256>,
+	trusted_publishers: HashSet<Address>,
 }
 
 impl Snapshot {
@@ -44,9 +45,23 @@ impl Snapshot {
 			completed_chunks: HashSet::new(),
 			snapshot_hash: None,
 			bad_hashes: HashSet::new(),
+			trusted_publishers: HashSet::new(),
 		}
 	}
 
+	/// Only accept manifests signed by one of the given publishers.
+	/// Any manifest is accepted if the set is empty.
+	pub fn set_trusted_publishers(&mut self, publishers: HashSet<Address>) {
+		self.trusted_publishers = publishers;
+	}
+
+	/// Check if the manifest may be restored: either no publishers are trusted
+	/// or it is signed by one of them.
+	pub fn is_trusted(&self, manifest: &ManifestData) -> bool {
+		self.trusted_publishers.is_empty() ||
+			manifest.signer().map_or(false, |signer| self.trusted_publishers.contains(&signer))
+	}
+
 	/// Clear everything.
 	pub fn clear(&mut self) {
 		self.pending_state_chunks.clear();
@@ -158,6 +173,8 @@ mod test {
 			state_root: H256::new(),
 			block_number: 42,
 			block_hash: H256::new(),
+			signature: None,
+			base: None,
 		};
 		let mhash = manifest.clone().into_rlp().sha3();
 		(manifest, mhash, state_chunks, block_chunks)
@@ -223,5 +240,23 @@ mod test {
 		snapshot.note_bad(hash);
 		assert_eq!(snapshot.is_known_bad(&hash), true);
 	}
-}
 
+	#[test]
+	fn trusted_publishers() {
+		use ethkey::{Generator, Random};
+
+		let mut snapshot = Snapshot::new();
+		let (mut manifest, _, _, _) = test_manifest();
+		assert!(snapshot.is_trusted(&manifest));
+
+		let publisher = Random.generate().unwrap();
+		snapshot.set_trusted_publishers(vec![publisher.address()].into_iter().collect());
+		assert!(!snapshot.is_trusted(&manifest));
+
+		manifest.sign(Random.generate().unwrap().secret()).unwrap();
+		assert!(!snapshot.is_trusted(&manifest));
+
+		manifest.sign(publisher.secret()).unwrap();
+		assert!(snapshot.is_trusted(&manifest));
+	}
+}
@@ -30,8 +30,14 @@ pub trait SyncIo {
 	fn disable_peer(&mut self, peer_id: PeerId);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
+	/// Adjust the score of a peer. Positive values reward useful data, negative ones punish misbehaviour.
+	fn update_reputation(&mut self, peer_id: PeerId, delta: i32);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>;
+	/// Respond to current request with a packet that is sent after everything else queued for the peer.
+	fn respond_low_priority(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError> {
+		self.respond(packet_id, data)
+	}
 	/// Send a packet to a peer.
 	fn send(&mut self, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>;
 	/// Send a packet to a peer using specified protocol.
@@ -92,10 +98,18 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.disconnect_peer(peer_id);
 	}
 
+	fn update_reputation(&mut self, peer_id: PeerId, delta: i32) {
+		self.network.update_reputation(peer_id, delta);
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>{
 		self.network.respond(packet_id, data)
 	}
 
+	fn respond_low_priority(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>{
+		self.network.respond_low_priority(packet_id, data)
+	}
+
 	fn send(&mut self, peer_id: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError>{
 		self.network.send(peer_id, packet_id, data)
 	}
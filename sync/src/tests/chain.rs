@@ -268,3 +268,41 @@ fn disconnect_on_unrelated_chain() {
 	assert_eq!(net.disconnect_events, vec![(0, 0)]);
 }
 
+
+#[test]
+fn disconnect_on_fork_id_mismatch() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(2);
+	let genesis = net.peer(0).chain.chain_info().genesis_hash;
+	net.peer(0).sync.write().set_fork_blocks(&genesis, vec![10]);
+	net.peer(1).sync.write().set_fork_blocks(&genesis, vec![20]);
+	net.peer(0).chain.add_blocks(50, EachBlockWith::Uncle);
+	net.sync();
+	assert_eq!(net.disconnect_events, vec![(0, 0), (1, 1)]);
+	assert_eq!(net.peer(1).chain.chain_info().best_block_number, 0);
+}
+
+#[test]
+fn sync_with_compatible_fork_id() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(2);
+	let genesis = net.peer(0).chain.chain_info().genesis_hash;
+	// peer 1 knows of a future fork peer 0 has not scheduled yet.
+	net.peer(0).sync.write().set_fork_blocks(&genesis, vec![10]);
+	net.peer(1).sync.write().set_fork_blocks(&genesis, vec![10, 1000]);
+	net.peer(0).chain.add_blocks(50, EachBlockWith::Uncle);
+	net.sync();
+	assert!(net.disconnect_events.is_empty());
+	assert_eq!(net.peer(1).chain.chain_info().best_block_number, 50);
+}
+
+#[test]
+fn propagates_transactions_by_announcement() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(2);
+	let hash = net.peer(0).chain.insert_transaction_to_queue();
+	let sender = net.peer(0).chain.queued_transaction(&hash).unwrap().transaction.sender();
+	net.peer(1).chain.set_balance(sender, 10_000_000_000_000_000_000u64.into());
+	net.sync();
+	assert!(net.peer(1).chain.queued_transaction(&hash).is_some());
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::*;
-use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus};
+use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus, CreationStatus};
 use ethcore::header::BlockNumber;
 use ethcore::client::{EachBlockWith};
 use super::helpers::*;
@@ -53,6 +53,8 @@ impl TestSnapshotService {
 			state_root: H256::new(),
 			block_number: block_number,
 			block_hash: block_hash,
+			signature: None,
+			base: None,
 		};
 		let mut chunks: HashMap<H256, Bytes> = state_chunks.into_iter().map(|data| (data.sha3(), data)).collect();
 		chunks.extend(block_chunks.into_iter().map(|data| (data.sha3(), data)));
@@ -79,6 +81,10 @@ impl SnapshotService for TestSnapshotService {
 		self.chunks.get(&hash).cloned()
 	}
 
+	fn can_apply_delta(&self, _base: H256) -> bool {
+		false
+	}
+
 	fn status(&self) -> RestorationStatus {
 		match *self.restoration_manifest.lock() {
 			Some(ref manifest) if self.state_restoration_chunks.lock().len() == manifest.state_hashes.len() &&
@@ -93,6 +99,12 @@ impl SnapshotService for TestSnapshotService {
 		}
 	}
 
+	fn creation_status(&self) -> CreationStatus {
+		CreationStatus::Inactive
+	}
+
+	fn request_snapshot(&self, _num: u64) { }
+
 	fn begin_restore(&self, manifest: ManifestData) {
 		*self.restoration_manifest.lock() = Some(manifest);
 		self.state_restoration_chunks.lock().clear();
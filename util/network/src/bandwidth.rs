@@ -0,0 +1,231 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Upload and download rate limiting.
+
+use std::cmp::min;
+use std::sync::Arc;
+use std::time::Instant;
+use parking_lot::Mutex;
+
+/// Token bucket allowing a number of bytes per second.
+/// At most one second worth of unused allowance is accumulated.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+	/// Bytes per second.
+	rate: usize,
+	/// Bytes that may be transferred right now.
+	allowance: usize,
+	/// Last time the allowance was refilled.
+	updated: Instant,
+}
+
+impl RateLimit {
+	/// Create a new limit of `rate` bytes per second.
+	pub fn new(rate: usize) -> RateLimit {
+		RateLimit {
+			rate: rate,
+			allowance: rate,
+			updated: Instant::now(),
+		}
+	}
+
+	fn refill(&mut self, now: Instant) {
+		if now <= self.updated {
+			return;
+		}
+		let elapsed = now.duration_since(self.updated);
+		if elapsed.as_secs() >= 1 {
+			self.allowance = self.rate;
+			self.updated = now;
+			return;
+		}
+		let refill = (self.rate as u64 * elapsed.subsec_nanos() as u64 / 1_000_000_000) as usize;
+		// Keep accumulating time until at least a byte can be added.
+		if refill > 0 {
+			self.allowance = min(self.rate, self.allowance + refill);
+			self.updated = now;
+		}
+	}
+
+	/// Number of bytes that may be transferred at `now`.
+	pub fn available(&mut self, now: Instant) -> usize {
+		self.refill(now);
+		self.allowance
+	}
+
+	/// Note that `size` bytes have been transferred.
+	pub fn consume(&mut self, size: usize) {
+		self.allowance = self.allowance.saturating_sub(size);
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+	Upload,
+	Download,
+}
+
+/// Upload and download limits. `None` means unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct Bandwidth {
+	upload: Option<RateLimit>,
+	download: Option<RateLimit>,
+}
+
+impl Bandwidth {
+	/// Create limits from upload and download rates in bytes per second.
+	pub fn new(upload: Option<usize>, download: Option<usize>) -> Bandwidth {
+		Bandwidth {
+			upload: upload.map(RateLimit::new),
+			download: download.map(RateLimit::new),
+		}
+	}
+
+	/// Check if any limit is set.
+	pub fn is_limited(&self) -> bool {
+		self.upload.is_some() || self.download.is_some()
+	}
+
+	fn limit(&mut self, direction: Direction) -> Option<&mut RateLimit> {
+		match direction {
+			Direction::Upload => self.upload.as_mut(),
+			Direction::Download => self.download.as_mut(),
+		}
+	}
+}
+
+/// Limits applied to a single connection: limits shared by all connections of the host
+/// together with the limits of this connection only.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+	global: Arc<Mutex<Bandwidth>>,
+	peer: Bandwidth,
+}
+
+impl Throttle {
+	/// Create a new instance.
+	pub fn new(global: Arc<Mutex<Bandwidth>>, peer: Bandwidth) -> Throttle {
+		Throttle {
+			global: global,
+			peer: peer,
+		}
+	}
+
+	/// Create a throttle that never limits anything.
+	pub fn unlimited() -> Throttle {
+		Throttle::new(Arc::new(Mutex::new(Bandwidth::default())), Bandwidth::default())
+	}
+
+	/// Number of bytes that may be sent now. `None` if unlimited.
+	pub fn upload_allowance(&mut self) -> Option<usize> {
+		self.allowance(Direction::Upload, Instant::now())
+	}
+
+	/// Number of bytes that may be received now. `None` if unlimited.
+	pub fn download_allowance(&mut self) -> Option<usize> {
+		self.allowance(Direction::Download, Instant::now())
+	}
+
+	/// Note that `size` bytes have been sent.
+	pub fn sent(&mut self, size: usize) {
+		self.consume(Direction::Upload, size);
+	}
+
+	/// Note that `size` bytes have been received.
+	pub fn received(&mut self, size: usize) {
+		self.consume(Direction::Download, size);
+	}
+
+	fn allowance(&mut self, direction: Direction, now: Instant) -> Option<usize> {
+		let peer = self.peer.limit(direction).map(|l| l.available(now));
+		let global = self.global.lock().limit(direction).map(|l| l.available(now));
+		match (peer, global) {
+			(Some(p), Some(g)) => Some(min(p, g)),
+			(p, g) => p.or(g),
+		}
+	}
+
+	fn consume(&mut self, direction: Direction, size: usize) {
+		if let Some(l) = self.peer.limit(direction) {
+			l.consume(size);
+		}
+		if let Some(l) = self.global.lock().limit(direction) {
+			l.consume(size);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::Direction;
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+	use parking_lot::Mutex;
+
+	#[test]
+	fn rate_limit_refills() {
+		let mut limit = RateLimit::new(1000);
+		let start = limit.updated;
+		assert_eq!(limit.available(start), 1000);
+		limit.consume(800);
+		assert_eq!(limit.available(start), 200);
+		assert_eq!(limit.available(start + Duration::from_millis(100)), 300);
+		// Allowance never exceeds one second worth of data.
+		assert_eq!(limit.available(start + Duration::from_millis(800)), 1000);
+		limit.consume(2000);
+		assert_eq!(limit.available(start + Duration::from_millis(800)), 0);
+		assert_eq!(limit.available(start + Duration::from_secs(5)), 1000);
+	}
+
+	#[test]
+	fn rate_limit_accumulates_small_intervals() {
+		let mut limit = RateLimit::new(10);
+		let start = limit.updated;
+		limit.consume(10);
+		assert_eq!(limit.available(start + Duration::from_millis(50)), 0);
+		assert_eq!(limit.available(start + Duration::from_millis(100)), 1);
+	}
+
+	#[test]
+	fn throttle_uses_lowest_limit() {
+		let global = Arc::new(Mutex::new(Bandwidth::new(Some(1000), None)));
+		let mut first = Throttle::new(global.clone(), Bandwidth::new(Some(600), Some(100)));
+		let mut second = Throttle::new(global.clone(), Bandwidth::default());
+		let now = Instant::now();
+
+		assert_eq!(first.allowance(Direction::Upload, now), Some(600));
+		assert_eq!(first.allowance(Direction::Download, now), Some(100));
+		assert_eq!(second.allowance(Direction::Download, now), None);
+
+		first.sent(500);
+		assert_eq!(first.allowance(Direction::Upload, now), Some(100));
+		// The global limit is shared by all connections.
+		assert_eq!(second.allowance(Direction::Upload, now), Some(500));
+		second.sent(500);
+		assert_eq!(first.allowance(Direction::Upload, now), Some(0));
+	}
+
+	#[test]
+	fn unlimited_throttle() {
+		let mut throttle = Throttle::unlimited();
+		throttle.sent(1 << 20);
+		throttle.received(1 << 20);
+		assert_eq!(throttle.upload_allowance(), None);
+		assert_eq!(throttle.download_allowance(), None);
+	}
+}
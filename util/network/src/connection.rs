@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::cmp::min;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
@@ -30,6 +31,7 @@ use error::*;
 use io::{IoContext, StreamToken};
 use handshake::Handshake;
 use stats::NetworkStats;
+use bandwidth::Throttle;
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
@@ -62,6 +64,14 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	interest: Ready,
 	/// Shared network statistics
 	stats: Arc<NetworkStats>,
+	/// Bytes sent over this connection
+	bytes_sent: u64,
+	/// Bytes received over this connection
+	bytes_received: u64,
+	/// Upload and download limits
+	throttle: Throttle,
+	/// Set when a read or write was cut short by the limits
+	throttled: bool,
 	/// Registered flag
 	registered: AtomicBool,
 }
@@ -82,11 +92,21 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		}
 		let sock_ref = <Socket as Read>::by_ref(&mut self.socket);
 		loop {
-			let max = self.rec_size - self.rec_buf.len();
+			let mut max = self.rec_size - self.rec_buf.len();
+			if let Some(allowance) = self.throttle.download_allowance() {
+				if allowance == 0 {
+					trace!(target:"network", "{}: Download limit reached", self.token);
+					self.throttled = true;
+					return Ok(None);
+				}
+				max = min(max, allowance);
+			}
 			match sock_ref.take(max as u64).try_read(unsafe { self.rec_buf.bytes_mut() }) {
 				Ok(Some(size)) if size != 0  => {
 					unsafe { self.rec_buf.advance_mut(size); }
 					self.stats.inc_recv(size);
+					self.bytes_received += size as u64;
+					self.throttle.received(size);
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -123,6 +143,22 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Total bytes sent over this connection.
+	pub fn bytes_sent(&self) -> u64 {
+		self.bytes_sent
+	}
+
+	/// Total bytes received over this connection.
+	pub fn bytes_received(&self) -> u64 {
+		self.bytes_received
+	}
+
+	/// Check if a read or write was deferred because of the bandwidth limits
+	/// since the last call and reset the flag.
+	pub fn take_throttled(&mut self) -> bool {
+		::std::mem::replace(&mut self.throttled, false)
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, NetworkError> where Message: Send + Clone + Sync + 'static {
 		{
@@ -137,14 +173,28 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 				return Ok(WriteStatus::Complete)
 			}
 
-			match self.socket.try_write(Buf::bytes(&buf)) {
+			let mut max = send_size - pos;
+			if let Some(allowance) = self.throttle.upload_allowance() {
+				if allowance == 0 {
+					trace!(target:"network", "{}: Upload limit reached", self.token);
+					self.throttled = true;
+					return Ok(WriteStatus::Ongoing);
+				}
+				max = min(max, allowance);
+			}
+
+			match self.socket.try_write(&Buf::bytes(&buf)[..max]) {
 				Ok(Some(size)) if (pos + size) < send_size => {
 					buf.advance(size);
 					self.stats.inc_send(size);
+					self.bytes_sent += size as u64;
+					self.throttle.sent(size);
 					Ok(WriteStatus::Ongoing)
 				},
 				Ok(Some(size)) if (pos + size) == send_size => {
 					self.stats.inc_send(size);
+					self.bytes_sent += size as u64;
+					self.throttle.sent(size);
 					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
 					Ok(WriteStatus::Complete)
 				},
@@ -170,7 +220,7 @@ pub type Connection = GenericConnection<TcpStream>;
 
 impl Connection {
 	/// Create a new connection with given id and socket.
-	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>) -> Connection {
+	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>, throttle: Throttle) -> Connection {
 		Connection {
 			token: token,
 			socket: socket,
@@ -179,6 +229,10 @@ impl Connection {
 			rec_size: 0,
 			interest: Ready::hup() | Ready::readable(),
 			stats: stats,
+			bytes_sent: 0,
+			bytes_received: 0,
+			throttle: throttle,
+			throttled: false,
 			registered: AtomicBool::new(false),
 		}
 	}
@@ -213,6 +267,10 @@ impl Connection {
 			send_queue: self.send_queue.clone(),
 			interest: Ready::hup(),
 			stats: self.stats.clone(),
+			bytes_sent: self.bytes_sent,
+			bytes_received: self.bytes_received,
+			throttle: self.throttle.clone(),
+			throttled: false,
 			registered: AtomicBool::new(false),
 		})
 	}
@@ -507,6 +565,8 @@ mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::AtomicBool;
 	use super::super::stats::*;
+	use bandwidth::{Bandwidth, Throttle};
+	use parking_lot::Mutex;
 	use std::io::{Read, Write, Error, Cursor, ErrorKind};
 	use mio::{Ready};
 	use std::collections::VecDeque;
@@ -556,6 +616,10 @@ mod tests {
 				rec_size: 0,
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bytes_sent: 0,
+				bytes_received: 0,
+				throttle: Throttle::unlimited(),
+				throttled: false,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -579,6 +643,10 @@ mod tests {
 				rec_size: 0,
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+				bytes_sent: 0,
+				bytes_received: 0,
+				throttle: Throttle::unlimited(),
+				throttled: false,
 				registered: AtomicBool::new(false),
 			}
 		}
@@ -629,6 +697,21 @@ mod tests {
 		assert_eq!(1024, connection.socket.write_buffer.len());
 	}
 
+	#[test]
+	fn connection_write_is_throttled() {
+		let mut connection = TestConnection::new();
+		connection.throttle = Throttle::new(Arc::new(Mutex::new(Bandwidth::default())), Bandwidth::new(Some(1000), None));
+		let data = Cursor::new(vec![0; 10240]);
+		connection.send_queue.push_back(data);
+
+		let status = connection.writable(&test_io());
+
+		assert!(status.is_ok());
+		assert!(WriteStatus::Ongoing == status.unwrap());
+		assert_eq!(1000, connection.socket.write_buffer.len());
+		assert_eq!(1000, connection.bytes_sent());
+	}
+
 	#[test]
 	fn connection_write_to_broken() {
 		let mut connection = TestBrokenConnection::new();
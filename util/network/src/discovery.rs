@@ -27,6 +27,7 @@ use time;
 use util::hash::*;
 use rlp::*;
 use node_table::*;
+use enr::NodeRecord;
 use error::NetworkError;
 use io::{StreamToken, IoContext};
 use ethkey::{Secret, KeyPair, sign, recover};
@@ -46,9 +47,12 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+const PACKET_ENR_REQUEST: u8 = 5;
+const PACKET_ENR_RESPONSE: u8 = 6;
 
 const PING_TIMEOUT_MS: u64 = 300;
 const MAX_NODES_PING: usize = 32; // Max nodes to add/ping at once
+const ENR_REQUEST_TIMEOUT_MS: u64 = 1000;
 
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
@@ -100,11 +104,15 @@ pub struct Discovery {
 	check_timestamps: bool,
 	adding_nodes: Vec<NodeEntry>,
 	allow_ips: AllowIP,
+	record: Option<NodeRecord>,
+	record_seqs: HashMap<NodeId, u64>,
+	enr_requests: HashMap<H256, (NodeId, u64)>,
 }
 
 pub struct TableUpdates {
 	pub added: HashMap<NodeId, NodeEntry>,
 	pub removed: HashSet<NodeId>,
+	pub records: HashMap<NodeId, NodeRecord>,
 }
 
 impl Discovery {
@@ -125,9 +133,22 @@ impl Discovery {
 			check_timestamps: true,
 			adding_nodes: Vec::new(),
 			allow_ips: allow_ips,
+			record: None,
+			record_seqs: HashMap::new(),
+			enr_requests: HashMap::new(),
 		}
 	}
 
+	/// Set our own node record. Its sequence number is advertised in Ping and Pong packets.
+	pub fn set_record(&mut self, record: NodeRecord) {
+		self.record = Some(record);
+	}
+
+	/// Note the sequence number of a node record already known for a node.
+	pub fn note_record_seq(&mut self, id: &NodeId, seq: u64) {
+		self.record_seqs.insert(id.clone(), seq);
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		if self.is_allowed(&e) {
@@ -252,16 +273,29 @@ impl Discovery {
 		self.send_packet(PACKET_PING, &node.udp_address(), &rlp.drain());
 	}
 
-	fn send_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) {
+	/// Sign and queue a packet. Returns the packet hash that replies refer to.
+	fn send_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) -> Option<H256> {
+		let timestamp = match packet_id {
+			PACKET_ENR_RESPONSE => None,
+			_ => Some(time::get_time().sec as u32 + 60),
+		};
+		let enr_seq = match packet_id {
+			PACKET_PING | PACKET_PONG => self.record.as_ref().map(|r| r.seq()),
+			_ => None,
+		};
 		let mut rlp = RlpStream::new();
 		rlp.append_raw(&[packet_id], 1);
 		let source = Rlp::new(payload);
-		rlp.begin_list(source.item_count() + 1);
+		rlp.begin_list(source.item_count() + timestamp.is_some() as usize + enr_seq.is_some() as usize);
 		for i in 0 .. source.item_count() {
 			rlp.append_raw(source.at(i).as_raw(), 1);
 		}
-		let timestamp = time::get_time().sec as u32 + 60;
-		rlp.append(&timestamp);
+		if let Some(timestamp) = timestamp {
+			rlp.append(&timestamp);
+		}
+		if let Some(seq) = enr_seq {
+			rlp.append(&seq);
+		}
 
 		let bytes = rlp.drain();
 		let hash = bytes.as_ref().sha3();
@@ -269,7 +303,7 @@ impl Discovery {
 			Ok(s) => s,
 			Err(_) => {
 				warn!("Error signing UDP packet");
-				return;
+				return None;
 			}
 		};
 		let mut packet = Bytes::with_capacity(bytes.len() + 32 + 65);
@@ -279,6 +313,7 @@ impl Discovery {
 		let signed_hash = (&packet[32..]).sha3();
 		packet[0..32].clone_from_slice(&signed_hash);
 		self.send_to(packet, address.clone());
+		Some(signed_hash)
 	}
 
 	#[cfg_attr(feature="dev", allow(map_clone))]
@@ -384,6 +419,8 @@ impl Discovery {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_ENR_REQUEST => self.on_enr_request(&rlp, &node_id, &from, &H256::from_slice(&packet[0..32])),
+			PACKET_ENR_RESPONSE => self.on_enr_response(&rlp, &node_id, &from),
 			_ => {
 				debug!("Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -411,21 +448,27 @@ impl Discovery {
 		self.check_timestamp(timestamp)?;
 		let mut added_map = HashMap::new();
 		let entry = NodeEntry { id: node.clone(), endpoint: source.clone() };
-		if !entry.endpoint.is_valid() {
+		let allowed = if !entry.endpoint.is_valid() {
 			debug!(target: "discovery", "Got bad address: {:?}", entry);
+			false
 		} else if !self.is_allowed(&entry) {
 			debug!(target: "discovery", "Address not allowed: {:?}", entry);
+			false
 		} else {
 			self.update_node(entry.clone());
 			added_map.insert(node.clone(), entry);
-		}
+			true
+		};
 		let hash = rlp.as_raw().sha3();
 		let mut response = RlpStream::new_list(2);
 		dest.to_rlp_list(&mut response);
 		response.append(&hash);
 		self.send_packet(PACKET_PONG, from, &response.drain());
+		if allowed {
+			self.check_record_seq(node, rlp.val_at(4).ok(), from);
+		}
 
-		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new() }))
+		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new(), records: HashMap::new() }))
 	}
 
 	fn on_pong(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
@@ -440,11 +483,68 @@ impl Discovery {
 			entry.endpoint.address = from.clone();
 		}
 		self.clear_ping(node);
+		if self.is_allowed(&entry) {
+			self.check_record_seq(node, rlp.val_at(3).ok(), from);
+		}
 		let mut added_map = HashMap::new();
 		added_map.insert(node.clone(), entry);
 		Ok(None)
 	}
 
+	/// Request the node record if the node advertises a sequence number newer than what we know.
+	fn check_record_seq(&mut self, node: &NodeId, seq: Option<u64>, from: &SocketAddr) {
+		let seq = match seq {
+			Some(seq) => seq,
+			None => return,
+		};
+		if self.record_seqs.get(node).map_or(false, |known| *known >= seq) {
+			return;
+		}
+		if self.enr_requests.values().any(|&(ref id, _)| id == node) {
+			return;
+		}
+		trace!(target: "discovery", "Sent ENRRequest to {:?}", &from);
+		if let Some(hash) = self.send_packet(PACKET_ENR_REQUEST, from, &RlpStream::new_list(0).out()) {
+			self.enr_requests.insert(hash, (node.clone(), time::precise_time_ns()));
+		}
+	}
+
+	fn on_enr_request(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr, hash: &H256) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got ENRRequest from {:?}", &from);
+		let timestamp: u64 = rlp.val_at(0)?;
+		self.check_timestamp(timestamp)?;
+		let record = match self.record {
+			Some(ref record) => record.encode(),
+			None => return Ok(None),
+		};
+		let mut response = RlpStream::new_list(2);
+		response.append(hash);
+		response.append_raw(&record, 1);
+		self.send_packet(PACKET_ENR_RESPONSE, from, &response.drain());
+		Ok(None)
+	}
+
+	fn on_enr_response(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got ENRResponse from {:?}", &from);
+		let request: H256 = rlp.val_at(0)?;
+		match self.enr_requests.remove(&request) {
+			Some((ref id, _)) if id == node => (),
+			_ => {
+				debug!(target: "discovery", "Unexpected ENRResponse from {:?}", &from);
+				return Ok(None);
+			}
+		}
+		let record = NodeRecord::decode(rlp.at(1)?.as_raw())?;
+		if record.id().as_ref() != Some(node) {
+			debug!(target: "discovery", "ENRResponse from {:?} is signed by another node", &from);
+			return Err(NetworkError::BadProtocol);
+		}
+		self.record_seqs.insert(node.clone(), record.seq());
+		let mut records = HashMap::new();
+		records.insert(node.clone(), record);
+		Ok(Some(TableUpdates { added: HashMap::new(), removed: HashSet::new(), records: records }))
+	}
+
 	fn on_find_node(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
 		trace!(target: "discovery", "Got FindNode from {:?}", &from);
 		let target: NodeId = rlp.val_at(0)?;
@@ -501,7 +601,7 @@ impl Discovery {
 			self.ping(&entry.endpoint);
 			self.update_node(entry);
 		}
-		Ok(Some(TableUpdates { added: added, removed: HashSet::new() }))
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new(), records: HashMap::new() }))
 	}
 
 	fn check_expired(&mut self, force: bool) -> HashSet<NodeId> {
@@ -521,6 +621,16 @@ impl Discovery {
 				} else { true }
 			});
 		}
+		for id in &removed {
+			self.record_seqs.remove(id);
+		}
+		let expired_requests: Vec<H256> = self.enr_requests.iter()
+			.filter(|&(_, &(_, sent))| now - sent >= ENR_REQUEST_TIMEOUT_MS * 1000_000)
+			.map(|(hash, _)| hash.clone())
+			.collect();
+		for hash in expired_requests {
+			self.enr_requests.remove(&hash);
+		}
 		removed
 	}
 
@@ -528,7 +638,7 @@ impl Discovery {
 		let removed = self.check_expired(false);
 		self.discover();
 		if !removed.is_empty() {
-			Some(TableUpdates { added: HashMap::new(), removed: removed })
+			Some(TableUpdates { added: HashMap::new(), removed: removed, records: HashMap::new() })
 		} else { None }
 	}
 
@@ -557,7 +667,9 @@ mod tests {
 	use super::*;
 	use std::net::{SocketAddr};
 	use node_table::{Node, NodeId, NodeEndpoint};
+	use enr::NodeRecord;
 
+	use std::collections::HashMap;
 	use std::str::FromStr;
 	use rustc_hex::FromHex;
 	use ethkey::{Random, Generator};
@@ -615,6 +727,35 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	#[test]
+	fn exchanges_node_records() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40448").unwrap(), udp_port: 40448 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, AllowIP::All);
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, AllowIP::All);
+		let mut record = NodeRecord::new(5);
+		record.set_endpoint(&ep1);
+		record.sign(&key1).unwrap();
+		discovery1.set_record(record.clone());
+
+		discovery2.add_node(NodeEntry { id: key1.public().clone(), endpoint: ep1.clone() });
+		let mut records = HashMap::new();
+		for _ in 0 .. 3 {
+			while let Some(datagramm) = discovery2.send_queue.pop_front() {
+				discovery1.on_packet(&datagramm.payload, ep2.address.clone()).unwrap();
+			}
+			while let Some(datagramm) = discovery1.send_queue.pop_front() {
+				if let Some(updates) = discovery2.on_packet(&datagramm.payload, ep1.address.clone()).unwrap() {
+					records.extend(updates.records.into_iter());
+				}
+			}
+		}
+		assert_eq!(records.get(key1.public()), Some(&record));
+		assert!(discovery2.enr_requests.is_empty());
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
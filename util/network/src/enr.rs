@@ -0,0 +1,275 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum Node Records (EIP-778).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use util::bytes::Bytes;
+use util::sha3::Hashable;
+use rlp::*;
+use rustc_serialize::base64::{ToBase64, FromBase64, URL_SAFE};
+use ethkey::{KeyPair, Signature, sign, verify_public};
+use ethkey::math::{public_to_compressed, public_from_slice};
+use error::NetworkError;
+use node_table::{NodeId, NodeEndpoint};
+
+/// Maximum size of an encoded record.
+pub const MAX_RECORD_SIZE: usize = 300;
+
+const ID_SCHEME: &'static str = "v4";
+
+/// Signed node record. Values are kept in their RLP encoded form so that
+/// keys unknown to us survive a decode/encode roundtrip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRecord {
+	seq: u64,
+	pairs: BTreeMap<String, Bytes>,
+	signature: Bytes,
+}
+
+impl NodeRecord {
+	/// Create a new empty unsigned record.
+	pub fn new(seq: u64) -> NodeRecord {
+		NodeRecord {
+			seq: seq,
+			pairs: BTreeMap::new(),
+			signature: Bytes::new(),
+		}
+	}
+
+	/// Record sequence number.
+	pub fn seq(&self) -> u64 {
+		self.seq
+	}
+
+	/// Set a key/value pair. Invalidates the signature.
+	pub fn set<E>(&mut self, key: &str, value: &E) where E: Encodable {
+		self.set_raw(key, ::rlp::encode(value).to_vec());
+	}
+
+	/// Set a key to an already RLP encoded value. Invalidates the signature.
+	pub fn set_raw(&mut self, key: &str, value: Bytes) {
+		self.pairs.insert(key.to_owned(), value);
+		self.signature.clear();
+	}
+
+	/// Get the value for a key.
+	pub fn get<T>(&self, key: &str) -> Option<T> where T: Decodable {
+		self.pairs.get(key).and_then(|v| UntrustedRlp::new(v).as_val().ok())
+	}
+
+	/// Get the raw RLP of a value.
+	pub fn get_raw(&self, key: &str) -> Option<&[u8]> {
+		self.pairs.get(key).map(|v| &v[..])
+	}
+
+	/// Set the `ip`/`tcp`/`udp` (or `ip6`/`tcp6`/`udp6`) entries.
+	pub fn set_endpoint(&mut self, endpoint: &NodeEndpoint) {
+		match endpoint.address {
+			SocketAddr::V4(a) => {
+				self.set("ip", &(&a.ip().octets()[..]));
+				self.set("tcp", &a.port());
+				self.set("udp", &endpoint.udp_port);
+			},
+			SocketAddr::V6(a) => {
+				self.set("ip6", &(&a.ip().octets()[..]));
+				self.set("tcp6", &a.port());
+				self.set("udp6", &endpoint.udp_port);
+			},
+		}
+	}
+
+	/// Node endpoint announced in the record. IPv4 is preferred if both are present.
+	pub fn endpoint(&self) -> Option<NodeEndpoint> {
+		let v4 = self.get::<Vec<u8>>("ip").and_then(|ip| {
+			if ip.len() != 4 { return None; }
+			let tcp = match self.get::<u16>("tcp") { Some(p) => p, None => return None };
+			let udp = self.get::<u16>("udp").unwrap_or(tcp);
+			let address = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]), tcp));
+			Some(NodeEndpoint { address: address, udp_port: udp })
+		});
+		if v4.is_some() {
+			return v4;
+		}
+		self.get::<Vec<u8>>("ip6").and_then(|ip| {
+			if ip.len() != 16 { return None; }
+			let tcp = match self.get::<u16>("tcp6") { Some(p) => p, None => return None };
+			let udp = self.get::<u16>("udp6").unwrap_or(tcp);
+			let mut octets = [0u8; 16];
+			octets.copy_from_slice(&ip);
+			let address = SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(octets), tcp, 0, 0));
+			Some(NodeEndpoint { address: address, udp_port: udp })
+		})
+	}
+
+	/// Node id of the signer, if the record uses the "v4" identity scheme.
+	pub fn id(&self) -> Option<NodeId> {
+		if self.get::<String>("id").as_ref().map(|s| &s[..]) != Some(ID_SCHEME) {
+			return None;
+		}
+		self.get::<Vec<u8>>("secp256k1").and_then(|key| public_from_slice(&key).ok())
+	}
+
+	/// Sign the record with the node key, setting the identity scheme entries.
+	pub fn sign(&mut self, key: &KeyPair) -> Result<(), NetworkError> {
+		let compressed = public_to_compressed(key.public())?;
+		self.set("id", &ID_SCHEME);
+		self.set("secp256k1", &compressed);
+		let signature = sign(key.secret(), &self.content().sha3())?;
+		self.signature = signature[0..64].to_vec();
+		if self.encode().len() > MAX_RECORD_SIZE {
+			self.signature.clear();
+			return Err(NetworkError::InvalidNodeRecord("record is too large".into()));
+		}
+		Ok(())
+	}
+
+	/// Check whether the record carries a signature.
+	pub fn is_signed(&self) -> bool {
+		!self.signature.is_empty()
+	}
+
+	/// RLP encoding of a signed record.
+	pub fn encode(&self) -> Bytes {
+		let mut s = RlpStream::new_list(2 + self.pairs.len() * 2);
+		s.append(&self.signature);
+		self.append_pairs(&mut s);
+		s.out()
+	}
+
+	/// Decode a record and verify its signature.
+	pub fn decode(data: &[u8]) -> Result<NodeRecord, NetworkError> {
+		if data.len() > MAX_RECORD_SIZE {
+			return Err(NetworkError::InvalidNodeRecord("record is too large".into()));
+		}
+		let rlp = UntrustedRlp::new(data);
+		if !rlp.is_list() || rlp.payload_info()?.total() != data.len() {
+			return Err(NetworkError::InvalidNodeRecord("not a list".into()));
+		}
+		let count = rlp.item_count()?;
+		if count < 2 || count % 2 != 0 {
+			return Err(NetworkError::InvalidNodeRecord("odd number of items".into()));
+		}
+		let signature: Bytes = rlp.val_at(0)?;
+		let mut record = NodeRecord::new(rlp.val_at(1)?);
+		for i in 1 .. count / 2 {
+			let key: String = rlp.val_at(i * 2)?;
+			if record.pairs.keys().next_back().map_or(false, |last| *last >= key) {
+				return Err(NetworkError::InvalidNodeRecord("keys are not sorted or not unique".into()));
+			}
+			record.pairs.insert(key, rlp.at(i * 2 + 1)?.as_raw().to_vec());
+		}
+		if signature.len() != 64 {
+			return Err(NetworkError::InvalidNodeRecord("bad signature length".into()));
+		}
+		let id = match record.id() {
+			Some(id) => id,
+			None => return Err(NetworkError::InvalidNodeRecord("unknown identity scheme".into())),
+		};
+		let mut sig = [0u8; 65];
+		sig[0..64].copy_from_slice(&signature);
+		if !verify_public(&id, &Signature::from(sig), &record.content().sha3())? {
+			return Err(NetworkError::InvalidNodeRecord("bad signature".into()));
+		}
+		record.signature = signature;
+		Ok(record)
+	}
+
+	fn content(&self) -> Bytes {
+		let mut s = RlpStream::new_list(1 + self.pairs.len() * 2);
+		self.append_pairs(&mut s);
+		s.out()
+	}
+
+	fn append_pairs(&self, s: &mut RlpStream) {
+		s.append(&self.seq);
+		for (key, value) in &self.pairs {
+			s.append(key);
+			s.append_raw(value, 1);
+		}
+	}
+}
+
+impl fmt::Display for NodeRecord {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "enr:{}", self.encode().to_base64(URL_SAFE))
+	}
+}
+
+impl FromStr for NodeRecord {
+	type Err = NetworkError;
+
+	/// Parse a record in its textual "enr:<base64>" form.
+	fn from_str(s: &str) -> Result<NodeRecord, NetworkError> {
+		if !s.starts_with("enr:") {
+			return Err(NetworkError::InvalidNodeRecord("missing enr: prefix".into()));
+		}
+		let data = s[4..].from_base64().map_err(|e| NetworkError::InvalidNodeRecord(format!("{}", e)))?;
+		NodeRecord::decode(&data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+	use std::net::SocketAddr;
+	use ethkey::{Random, Generator, Secret, KeyPair};
+	use node_table::NodeEndpoint;
+
+	#[test]
+	fn decodes_eip_example() {
+		let record = NodeRecord::from_str("enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8").unwrap();
+		let secret = Secret::from_str("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291").unwrap();
+		let key = KeyPair::from_secret(secret).unwrap();
+		assert_eq!(record.seq(), 1);
+		assert_eq!(record.id(), Some(key.public().clone()));
+		assert_eq!(record.get::<u16>("udp"), Some(30303));
+		assert_eq!(record.get::<Vec<u8>>("ip"), Some(vec![127, 0, 0, 1]));
+	}
+
+	#[test]
+	fn sign_and_decode() {
+		let key = Random.generate().unwrap();
+		let endpoint = NodeEndpoint { address: SocketAddr::from_str("10.0.0.1:30303").unwrap(), udp_port: 30304 };
+		let mut record = NodeRecord::new(7);
+		record.set_endpoint(&endpoint);
+		record.set("chain", &vec![1u64, 2u64]);
+		record.sign(&key).unwrap();
+
+		let decoded = NodeRecord::from_str(&record.to_string()).unwrap();
+		assert_eq!(decoded, record);
+		assert_eq!(decoded.id(), Some(key.public().clone()));
+		let decoded_endpoint = decoded.endpoint().unwrap();
+		assert_eq!(decoded_endpoint.address, endpoint.address);
+		assert_eq!(decoded_endpoint.udp_port, 30304);
+	}
+
+	#[test]
+	fn rejects_tampered_record() {
+		let key = Random.generate().unwrap();
+		let mut record = NodeRecord::new(1);
+		record.set("udp", &30303u16);
+		record.sign(&key).unwrap();
+		let signature = record.signature.clone();
+		record.set("udp", &30304u16);
+		record.signature = signature;
+		assert!(NodeRecord::decode(&record.encode()).is_err());
+	}
+}
@@ -0,0 +1,298 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed node record trees in the EIP-1459 (DNS node list) layout, read from a local file.
+//!
+//! The file is a JSON object mapping DNS names to TXT record contents, as produced
+//! by publishing tools before uploading to DNS. The tree is addressed by a URL of the
+//! form `enrtree://<base32 compressed public key>@<domain>`. Only the node record
+//! subtree is followed; links to other trees are ignored.
+
+use std::collections::{HashSet, BTreeMap};
+use std::fs;
+use std::io::Read;
+use std::str::FromStr;
+use util::sha3::Hashable;
+use rustc_serialize::json::Json;
+use rustc_serialize::base64::FromBase64;
+use ethkey::{Public, Signature, verify_public};
+use ethkey::math::public_from_slice;
+use enr::NodeRecord;
+use error::NetworkError;
+
+const ROOT_PREFIX: &'static str = "enrtree-root:v1";
+const BRANCH_PREFIX: &'static str = "enrtree-branch:";
+const LINK_PREFIX: &'static str = "enrtree://";
+const RECORD_PREFIX: &'static str = "enr:";
+/// Upper bound on the number of entries visited in a single tree.
+const MAX_TREE_ENTRIES: usize = 10000;
+
+const BASE32_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn invalid(msg: &str) -> NetworkError {
+	NetworkError::InvalidNodeRecord(format!("record tree: {}", msg))
+}
+
+/// Unpadded RFC 4648 base32 encoding.
+fn base32_encode(data: &[u8]) -> String {
+	let mut out = String::with_capacity((data.len() * 8 + 4) / 5);
+	let mut buffer = 0u32;
+	let mut bits = 0;
+	for byte in data {
+		buffer = (buffer << 8) | *byte as u32;
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+		}
+	}
+	if bits > 0 {
+		out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+	}
+	out
+}
+
+/// Unpadded RFC 4648 base32 decoding. Trailing padding characters are tolerated.
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+	let mut out = Vec::with_capacity(s.len() * 5 / 8);
+	let mut buffer = 0u32;
+	let mut bits = 0;
+	for c in s.trim_right_matches('=').bytes() {
+		let c = if c >= b'a' && c <= b'z' { c - b'a' + b'A' } else { c };
+		let value = match BASE32_ALPHABET.iter().position(|a| *a == c) {
+			Some(v) => v as u32,
+			None => return None,
+		};
+		buffer = (buffer << 5) | value;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			out.push((buffer >> bits) as u8);
+		}
+	}
+	Some(out)
+}
+
+/// Subdomain name of a tree entry.
+fn entry_hash(entry: &str) -> String {
+	base32_encode(&entry.as_bytes().sha3()[0..16])
+}
+
+/// Parsed `enrtree://` URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeUrl {
+	/// Public key the tree root is signed with.
+	pub public: Public,
+	/// Domain name of the tree root.
+	pub domain: String,
+}
+
+impl FromStr for TreeUrl {
+	type Err = NetworkError;
+
+	fn from_str(s: &str) -> Result<TreeUrl, NetworkError> {
+		if !s.starts_with(LINK_PREFIX) {
+			return Err(invalid("URL must start with enrtree://"));
+		}
+		let mut parts = s[LINK_PREFIX.len()..].splitn(2, '@');
+		let key = parts.next().unwrap_or("");
+		let domain = match parts.next() {
+			Some(d) if !d.is_empty() => d,
+			_ => return Err(invalid("URL has no domain")),
+		};
+		let key = base32_decode(key).ok_or_else(|| invalid("bad public key encoding"))?;
+		Ok(TreeUrl {
+			public: public_from_slice(&key)?,
+			domain: domain.to_lowercase(),
+		})
+	}
+}
+
+struct Root {
+	records: String,
+	seq: u64,
+}
+
+fn parse_root(text: &str, public: &Public) -> Result<Root, NetworkError> {
+	let sig_pos = match text.find(" sig=") {
+		Some(pos) => pos,
+		None => return Err(invalid("root has no signature")),
+	};
+	let mut records = None;
+	let mut seq = None;
+	let mut parts = text[..sig_pos].split(' ');
+	if parts.next() != Some(ROOT_PREFIX) {
+		return Err(invalid("unsupported root version"));
+	}
+	for part in parts {
+		if part.starts_with("e=") {
+			records = Some(part[2..].to_owned());
+		} else if part.starts_with("seq=") {
+			seq = part[4..].parse().ok();
+		}
+	}
+	let signature = text[sig_pos + 5..].from_base64().map_err(|_| invalid("bad root signature encoding"))?;
+	if signature.len() != 65 {
+		return Err(invalid("bad root signature length"));
+	}
+	let mut sig = [0u8; 65];
+	sig.copy_from_slice(&signature);
+	if !verify_public(public, &Signature::from(sig), &text[..sig_pos].as_bytes().sha3())? {
+		return Err(invalid("bad root signature"));
+	}
+	match (records, seq) {
+		(Some(records), Some(seq)) => Ok(Root { records: records, seq: seq }),
+		_ => Err(invalid("root is incomplete")),
+	}
+}
+
+/// Read the tree at `url` from a JSON file of TXT records and return all node records in it.
+pub fn load(path: &str, url: &str) -> Result<Vec<NodeRecord>, NetworkError> {
+	let url = TreeUrl::from_str(url)?;
+	let mut buf = String::new();
+	fs::File::open(path)?.read_to_string(&mut buf)?;
+	let json = Json::from_str(&buf).map_err(|e| invalid(&format!("{}", e)))?;
+	let entries = match json.as_object() {
+		Some(o) => o.iter()
+			.filter_map(|(name, value)| value.as_string().map(|v| (name.to_lowercase(), v.to_owned())))
+			.collect::<BTreeMap<_, _>>(),
+		None => return Err(invalid("file must contain an object")),
+	};
+	read_tree(&entries, &url)
+}
+
+fn read_tree(entries: &BTreeMap<String, String>, url: &TreeUrl) -> Result<Vec<NodeRecord>, NetworkError> {
+	let root_text = entries.get(&url.domain).ok_or_else(|| invalid("root entry not found"))?;
+	let root = parse_root(root_text, &url.public)?;
+	trace!(target: "network", "Reading node record tree {} seq {}", url.domain, root.seq);
+
+	let mut records = Vec::new();
+	let mut visited = HashSet::new();
+	let mut pending = vec![root.records];
+	while let Some(hash) = pending.pop() {
+		if !visited.insert(hash.clone()) {
+			continue;
+		}
+		if visited.len() > MAX_TREE_ENTRIES {
+			return Err(invalid("tree is too large"));
+		}
+		let name = format!("{}.{}", hash.to_lowercase(), url.domain);
+		let entry = entries.get(&name).ok_or_else(|| invalid(&format!("missing entry {}", hash)))?;
+		if entry_hash(entry) != hash.to_uppercase() {
+			return Err(invalid(&format!("entry {} does not match its hash", hash)));
+		}
+		if entry.starts_with(BRANCH_PREFIX) {
+			pending.extend(entry[BRANCH_PREFIX.len()..].split(',').filter(|h| !h.is_empty()).map(|h| h.to_owned()));
+		} else if entry.starts_with(RECORD_PREFIX) {
+			records.push(NodeRecord::from_str(entry)?);
+		} else if entry.starts_with(LINK_PREFIX) {
+			debug!(target: "network", "Ignoring link in node record tree: {}", entry);
+		} else {
+			return Err(invalid(&format!("unknown entry {}", hash)));
+		}
+	}
+	Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::{base32_encode, base32_decode, entry_hash};
+	use std::collections::BTreeMap;
+	use std::fs;
+	use std::io::Write;
+	use std::str::FromStr;
+	use util::sha3::Hashable;
+	use rustc_serialize::json::Json;
+	use rustc_serialize::base64::{ToBase64, URL_SAFE};
+	use ethkey::{Random, Generator, KeyPair, sign};
+	use ethkey::math::public_to_compressed;
+	use devtools::RandomTempPath;
+	use enr::NodeRecord;
+
+	fn signed_record(seq: u64) -> String {
+		let mut record = NodeRecord::new(seq);
+		record.set("udp", &30303u16);
+		record.sign(&Random.generate().unwrap()).unwrap();
+		record.to_string()
+	}
+
+	fn build_tree(key: &KeyPair, domain: &str, leaves: &[String]) -> BTreeMap<String, Json> {
+		let mut entries = BTreeMap::new();
+		let hashes: Vec<String> = leaves.iter().map(|l| entry_hash(l)).collect();
+		for (leaf, hash) in leaves.iter().zip(hashes.iter()) {
+			entries.insert(format!("{}.{}", hash, domain), Json::String(leaf.clone()));
+		}
+		let branch = format!("enrtree-branch:{}", hashes.join(","));
+		let branch_hash = entry_hash(&branch);
+		entries.insert(format!("{}.{}", branch_hash, domain), Json::String(branch));
+		let root = format!("enrtree-root:v1 e={} l={} seq=3", branch_hash, branch_hash);
+		let signature = sign(key.secret(), &root.as_bytes().sha3()).unwrap();
+		entries.insert(domain.to_owned(), Json::String(format!("{} sig={}", root, signature[..].to_base64(URL_SAFE))));
+		entries
+	}
+
+	fn tree_url(key: &KeyPair, domain: &str) -> String {
+		format!("enrtree://{}@{}", base32_encode(&public_to_compressed(key.public()).unwrap()), domain)
+	}
+
+	#[test]
+	fn base32() {
+		assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+		assert_eq!(base32_decode("MZXW6YTBOI======").unwrap(), b"foobar".to_vec());
+		assert!(base32_decode("MZXW1").is_none());
+	}
+
+	#[test]
+	fn parses_url() {
+		let key = Random.generate().unwrap();
+		let url = TreeUrl::from_str(&tree_url(&key, "Nodes.Example.org")).unwrap();
+		assert_eq!(&url.public, key.public());
+		assert_eq!(url.domain, "nodes.example.org");
+		assert!(TreeUrl::from_str("enrtree://nodes.example.org").is_err());
+	}
+
+	#[test]
+	fn loads_tree() {
+		let key = Random.generate().unwrap();
+		let leaves = vec![signed_record(1), signed_record(2)];
+		let entries = build_tree(&key, "nodes.example.org", &leaves);
+		let path = RandomTempPath::new();
+		fs::File::create(path.as_path()).unwrap().write_all(Json::Object(entries).to_string().as_bytes()).unwrap();
+
+		let records = load(path.as_str(), &tree_url(&key, "nodes.example.org")).unwrap();
+		assert_eq!(records.len(), 2);
+		let mut seqs: Vec<u64> = records.iter().map(|r| r.seq()).collect();
+		seqs.sort();
+		assert_eq!(seqs, vec![1, 2]);
+
+		let other = Random.generate().unwrap();
+		assert!(load(path.as_str(), &tree_url(&other, "nodes.example.org")).is_err());
+	}
+
+	#[test]
+	fn rejects_modified_entry() {
+		let key = Random.generate().unwrap();
+		let leaves = vec![signed_record(1)];
+		let mut entries = build_tree(&key, "nodes.example.org", &leaves);
+		let name = format!("{}.nodes.example.org", entry_hash(&leaves[0]));
+		entries.insert(name, Json::String(signed_record(5)));
+		let entries = entries.into_iter().map(|(k, v)| (k.to_lowercase(), v.as_string().unwrap().to_owned())).collect();
+
+		let url = TreeUrl::from_str(&tree_url(&key, "nodes.example.org")).unwrap();
+		assert!(super::read_tree(&entries, &url).is_err());
+	}
+}
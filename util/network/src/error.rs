@@ -108,6 +108,8 @@ pub enum NetworkError {
 	StdIo(::std::io::Error),
 	/// Packet size is over the protocol limit.
 	OversizedPacket,
+	/// Node record is malformed or has an invalid signature.
+	InvalidNodeRecord(String),
 }
 
 impl fmt::Display for NetworkError {
@@ -127,6 +129,7 @@ impl fmt::Display for NetworkError {
 			StdIo(ref err) => format!("{}", err),
 			Util(ref err) => format!("{}", err),
 			OversizedPacket => "Packet is too large".into(),
+			InvalidNodeRecord(ref msg) => format!("Invalid node record: {}", msg),
 		};
 
 		f.write_fmt(format_args!("Network error ({})", msg))
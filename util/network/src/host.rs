@@ -20,7 +20,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::ops::*;
-use std::cmp::min;
+use std::cmp::{min, max};
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write, ErrorKind};
 use std::fs;
@@ -38,7 +38,10 @@ use io::*;
 use {NetworkProtocolHandler, NonReservedPeerMode, AllowIP, PROTOCOL_VERSION};
 use node_table::*;
 use stats::NetworkStats;
+use bandwidth::{Bandwidth, Throttle};
 use discovery::{Discovery, TableUpdates, NodeEntry};
+use enr::NodeRecord;
+use enr_tree;
 use ip_utils::{map_external_address, select_public_address};
 use path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
@@ -57,6 +60,7 @@ const DISCOVERY: StreamToken = SYS_TIMER + 3;
 const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 5;
 const NODE_TABLE: TimerToken = SYS_TIMER + 6;
+const THROTTLE: TimerToken = SYS_TIMER + 7;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -71,6 +75,8 @@ const DISCOVERY_REFRESH_TIMEOUT: u64 = 60_000;
 const DISCOVERY_ROUND_TIMEOUT: u64 = 300;
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: u64 = 300_000;
+// for THROTTLE TimerToken
+const THROTTLE_TIMEOUT: u64 = 100;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Network service configuration
@@ -107,6 +113,22 @@ pub struct NetworkConfiguration {
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
 	pub allow_ips: AllowIP,
+	/// Network id and genesis hash advertised in the node record.
+	pub chain_identity: Option<(u64, H256)>,
+	/// `enrtree://` URL of a signed node record tree to bootstrap from.
+	pub enr_tree: Option<String>,
+	/// Path to a JSON file with the TXT records of the node record tree.
+	pub enr_tree_file: Option<String>,
+	/// Upload limit for all peers together, in bytes per second.
+	pub upload_limit: Option<usize>,
+	/// Download limit for all peers together, in bytes per second.
+	pub download_limit: Option<usize>,
+	/// Upload limit for a single peer, in bytes per second.
+	pub peer_upload_limit: Option<usize>,
+	/// Download limit for a single peer, in bytes per second.
+	pub peer_download_limit: Option<usize>,
+	/// Number of seconds a peer is banned for after reaching the lowest score. Zero disables bans.
+	pub ban_duration: u64,
 }
 
 impl Default for NetworkConfiguration {
@@ -135,6 +157,14 @@ impl NetworkConfiguration {
 			allow_ips: AllowIP::All,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			chain_identity: None,
+			enr_tree: None,
+			enr_tree_file: None,
+			upload_limit: None,
+			download_limit: None,
+			peer_upload_limit: None,
+			peer_download_limit: None,
+			ban_duration: 3600,
 		}
 	}
 
@@ -188,6 +218,8 @@ pub enum NetworkIoMessage {
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
 	DisablePeer(PeerId),
+	/// Adjust the score of a peer.
+	UpdateReputation(PeerId, i32),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
 }
@@ -268,6 +300,22 @@ impl<'s> NetworkContext<'s> {
 		self.session_id.map_or_else(|| Err(NetworkError::Expired), |id| self.send(id, packet_id, data))
 	}
 
+	/// Respond to a current network message with a packet that is only sent once everything else
+	/// queued for the peer has been written. Used for serving bulk data.
+	pub fn respond_low_priority(&self, packet_id: PacketId, data: Vec<u8>) -> Result<(), NetworkError> {
+		assert!(self.session.is_some(), "Respond called without network context");
+		let peer = match self.session_id {
+			Some(id) => id,
+			None => return Err(NetworkError::Expired),
+		};
+		if let Some(session) = self.resolve_session(peer) {
+			session.lock().send_packet_low_priority(self.io, self.protocol, packet_id as u8, &data)?;
+		} else {
+			trace!(target: "network", "Respond: Peer no longer exist")
+		}
+		Ok(())
+	}
+
 	/// Get an IoChannel.
 	pub fn io_channel(&self) -> IoChannel<NetworkIoMessage> {
 		self.io.channel()
@@ -279,6 +327,12 @@ impl<'s> NetworkContext<'s> {
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
+	/// Adjust the score of a peer. Peers reaching the lowest score are disconnected and banned.
+	pub fn update_reputation(&self, peer: PeerId, delta: i32) {
+		self.io.message(NetworkIoMessage::UpdateReputation(peer, delta))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
 	/// Disconnect peer. Reconnect can be attempted later.
 	pub fn disconnect_peer(&self, peer: PeerId) {
 		self.io.message(NetworkIoMessage::Disconnect(peer))
@@ -338,6 +392,10 @@ pub struct HostInfo {
 	pub local_endpoint: NodeEndpoint,
 	/// Public address + discovery port
 	pub public_endpoint: Option<NodeEndpoint>,
+	/// Our signed node record
+	pub record: Option<NodeRecord>,
+	/// Bandwidth limits shared by all connections
+	bandwidth: Arc<Mutex<Bandwidth>>,
 }
 
 impl HostInfo {
@@ -356,6 +414,16 @@ impl HostInfo {
 		self.nonce = self.nonce.sha3();
 		self.nonce.clone()
 	}
+
+	/// Bandwidth limits for a new connection.
+	pub fn throttle(&self) -> Throttle {
+		Throttle::new(self.bandwidth.clone(), Bandwidth::new(self.config.peer_upload_limit, self.config.peer_download_limit))
+	}
+
+	/// Check if any bandwidth limit is configured.
+	fn is_throttled(&self) -> bool {
+		self.bandwidth.lock().is_limited() || self.config.peer_upload_limit.is_some() || self.config.peer_download_limit.is_some()
+	}
 }
 
 type SharedSession = Arc<Mutex<Session>>;
@@ -413,7 +481,11 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let enr_tree = (config.enr_tree.clone(), config.enr_tree_file.clone());
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
+		let bandwidth = Bandwidth::new(config.upload_limit, config.download_limit);
+		let mut nodes = NodeTable::new(path);
+		nodes.set_ban_duration(config.ban_duration);
 
 		let mut host = Host {
 			info: RwLock::new(HostInfo {
@@ -425,11 +497,13 @@ impl Host {
 				capabilities: Vec::new(),
 				public_endpoint: None,
 				local_endpoint: local_endpoint,
+				record: None,
+				bandwidth: Arc::new(Mutex::new(bandwidth)),
 			}),
 			discovery: Mutex::new(None),
 			tcp_listener: Mutex::new(tcp_listener),
 			sessions: Arc::new(RwLock::new(Slab::new_starting_at(FIRST_SESSION, MAX_SESSIONS))),
-			nodes: RwLock::new(NodeTable::new(path)),
+			nodes: RwLock::new(nodes),
 			handlers: RwLock::new(HashMap::new()),
 			timers: RwLock::new(HashMap::new()),
 			timer_counter: RwLock::new(USER_TIMER),
@@ -448,9 +522,38 @@ impl Host {
 				debug!(target: "network", "Error parsing node id: {}: {:?}", n, e);
 			}
 		}
+
+		match enr_tree {
+			(Some(url), Some(file)) => host.load_enr_tree(&url, &file),
+			(None, None) => (),
+			_ => warn!("Both the node record tree URL and the file with its records are required"),
+		}
 		Ok(host)
 	}
 
+	fn load_enr_tree(&self, url: &str, file: &str) {
+		let records = match enr_tree::load(file, url) {
+			Ok(records) => records,
+			Err(e) => {
+				warn!("Error loading node record tree {}: {}", url, e);
+				return;
+			}
+		};
+		let chain_identity = self.info.read().config.chain_identity.clone();
+		let mut nodes = self.nodes.write();
+		let mut added = 0;
+		for record in records {
+			if !chain_matches(&chain_identity, &record) {
+				trace!(target: "network", "Skipping node record for another chain: {}", record);
+				continue;
+			}
+			if nodes.add_record(record) {
+				added += 1;
+			}
+		}
+		info!("Loaded {} nodes from node record tree", added);
+	}
+
 	pub fn add_node(&mut self, id: &str) {
 		match Node::from_str(id) {
 			Err(e) => { debug!(target: "network", "Could not add node {}: {:?}", id, e); },
@@ -516,6 +619,16 @@ impl Host {
 		Ok(())
 	}
 
+	/// Scores of known nodes that have a non-neutral score or are banned.
+	pub fn peer_scores(&self) -> Vec<NodeScore> {
+		self.nodes.read().scores()
+	}
+
+	/// Reset the score and lift the ban of a node, or of all nodes if `id` is `None`.
+	pub fn reset_peer_scores(&self, id: Option<&NodeId>) {
+		self.nodes.write().reset_scores(id);
+	}
+
 	pub fn client_version() -> String {
 		version()
 	}
@@ -605,16 +718,55 @@ impl Host {
 		if let Some(mut discovery) = discovery {
 			discovery.init_node_list(self.nodes.read().unordered_entries());
 			discovery.add_node_list(self.nodes.read().unordered_entries());
+			for (id, seq) in self.nodes.read().record_seqs() {
+				discovery.note_record_seq(&id, seq);
+			}
 			*self.discovery.lock() = Some(discovery);
 			io.register_stream(DISCOVERY)?;
 			io.register_timer(DISCOVERY_REFRESH, DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
+		self.update_record();
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
 
+	/// Sign a new node record with the current endpoint, capabilities and chain and hand it to discovery.
+	fn update_record(&self) {
+		let record = {
+			let mut info = self.info.write();
+			let endpoint = match info.public_endpoint {
+				Some(ref endpoint) => endpoint.clone(),
+				None => return,
+			};
+			let seq = max(info.record.as_ref().map_or(0, |r| r.seq() + 1), ::time::get_time().sec as u64);
+			let mut record = NodeRecord::new(seq);
+			record.set_endpoint(&endpoint);
+			let mut caps = RlpStream::new_list(info.capabilities.len());
+			for c in &info.capabilities {
+				caps.append(c);
+			}
+			record.set_raw("caps", caps.out());
+			if let Some((ref network_id, ref genesis)) = info.config.chain_identity {
+				let mut chain = RlpStream::new_list(2);
+				chain.append(network_id);
+				chain.append(genesis);
+				record.set_raw("chain", chain.out());
+			}
+			if let Err(e) = record.sign(&info.keys) {
+				warn!("Error signing node record: {}", e);
+				return;
+			}
+			debug!(target: "network", "Node record updated: {}", record);
+			info.record = Some(record.clone());
+			record
+		};
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.set_record(record);
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -794,6 +946,23 @@ impl Host {
 		}
 	}
 
+	/// Continue reading and writing on sessions that were held back by the bandwidth limits.
+	/// Sockets are edge triggered so there won't be another event for data already waiting.
+	fn resume_throttled(&self, io: &IoContext<NetworkIoMessage>) {
+		let sessions: Vec<_> = self.sessions.read().iter().cloned().collect();
+		for session in sessions {
+			let token = {
+				let mut s = session.lock();
+				if !s.take_throttled() {
+					continue;
+				}
+				s.token()
+			};
+			self.session_writable(token, io);
+			self.session_readable(token, io);
+		}
+	}
+
 	fn connection_closed(&self, token: TimerToken, io: &IoContext<NetworkIoMessage>) {
 		trace!(target: "network", "Connection closed: {}", token);
 		self.kill_connection(token, io, true);
@@ -852,6 +1021,13 @@ impl Host {
 									break;
 								}
 							}
+
+							if self.nodes.read().is_banned(&id) && !self.reserved_nodes.read().contains(&id) {
+								trace!(target: "network", "Rejecting banned peer {}", id);
+								s.disconnect(io, DisconnectReason::UselessPeer);
+								kill = true;
+								break;
+							}
 							ready_id = Some(id);
 
 							// Add it to the node table
@@ -970,7 +1146,17 @@ impl Host {
 		}
 	}
 
-	fn update_nodes(&self, _io: &IoContext<NetworkIoMessage>, node_changes: TableUpdates) {
+	fn update_nodes(&self, _io: &IoContext<NetworkIoMessage>, mut node_changes: TableUpdates) {
+		let chain_identity = self.info.read().config.chain_identity.clone();
+		let records: Vec<_> = node_changes.records.drain().collect();
+		for (id, record) in records {
+			if chain_matches(&chain_identity, &record) {
+				node_changes.records.insert(id, record);
+			} else {
+				trace!(target: "network", "Node {} is on another chain", id);
+				self.nodes.write().mark_as_useless(&id);
+			}
+		}
 		let mut to_remove: Vec<PeerId> = Vec::new();
 		{
 			let sessions = self.sessions.write();
@@ -1008,6 +1194,9 @@ impl IoHandler<NetworkIoMessage> for Host {
 	/// Initialize networking
 	fn initialize(&self, io: &IoContext<NetworkIoMessage>) {
 		io.register_timer(IDLE, MAINTENANCE_TIMEOUT).expect("Error registering Network idle timer");
+		if self.info.read().is_throttled() {
+			io.register_timer(THROTTLE, THROTTLE_TIMEOUT).expect("Error registering Network throttle timer");
+		}
 		io.message(NetworkIoMessage::InitPublicInterface).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
 		self.maintain_network(io)
 	}
@@ -1073,6 +1262,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				self.nodes.write().clear_useless();
 				self.nodes.write().save();
 			},
+			THROTTLE => self.resume_throttled(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1101,10 +1291,13 @@ impl IoHandler<NetworkIoMessage> for Host {
 				let reserved = self.reserved_nodes.read();
 				h.initialize(&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved));
 				self.handlers.write().insert(*protocol, h);
-				let mut info = self.info.write();
-				for v in versions {
-					info.capabilities.push(CapabilityInfo { protocol: *protocol, version: *v, packet_count: *packet_count });
+				{
+					let mut info = self.info.write();
+					for v in versions {
+						info.capabilities.push(CapabilityInfo { protocol: *protocol, version: *v, packet_count: *packet_count });
+					}
 				}
+				self.update_record();
 			},
 			NetworkIoMessage::AddTimer {
 				ref protocol,
@@ -1140,6 +1333,19 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::UpdateReputation(ref peer, delta) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				if let Some(session) = session {
+					let id = session.lock().id().cloned();
+					if let Some(id) = id {
+						if !self.reserved_nodes.read().contains(&id) && self.nodes.write().note_score(&id, delta) {
+							debug!(target: "network", "Banning peer {}", id);
+							session.lock().disconnect(io, DisconnectReason::UselessPeer);
+							self.kill_connection(*peer, io, false);
+						}
+					}
+				}
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
 			_ => {}	// ignore others.
@@ -1192,6 +1398,21 @@ impl IoHandler<NetworkIoMessage> for Host {
 	}
 }
 
+/// Check the chain advertised in a node record against ours. Records without chain information are accepted.
+fn chain_matches(identity: &Option<(u64, H256)>, record: &NodeRecord) -> bool {
+	let (network_id, genesis) = match *identity {
+		Some((ref network_id, ref genesis)) => (*network_id, genesis.clone()),
+		None => return true,
+	};
+	match record.get_raw("chain") {
+		Some(chain) => {
+			let rlp = UntrustedRlp::new(chain);
+			rlp.val_at::<u64>(0).ok() == Some(network_id) && rlp.val_at::<H256>(1).ok() == Some(genesis)
+		},
+		None => true,
+	}
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -89,10 +89,13 @@ mod connection;
 mod handshake;
 mod session;
 mod discovery;
+mod enr;
+mod enr_tree;
 mod service;
 mod error;
 mod node_table;
 mod stats;
+mod bandwidth;
 mod ip_utils;
 
 #[cfg(test)]
@@ -101,11 +104,12 @@ mod tests;
 pub use host::{PeerId, PacketId, ProtocolId, NetworkContext, NetworkIoMessage, NetworkConfiguration};
 pub use service::NetworkService;
 pub use error::NetworkError;
-pub use stats::NetworkStats;
+pub use stats::{NetworkStats, PeerTraffic, ProtocolTraffic};
 pub use session::SessionInfo;
+pub use enr::NodeRecord;
 
 use io::TimerToken;
-pub use node_table::{is_valid_node_url, NodeId};
+pub use node_table::{is_valid_node_url, NodeId, NodeScore};
 
 const PROTOCOL_VERSION: u32 = 4;
 
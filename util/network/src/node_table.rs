@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
+use std::cmp::{min, max, Ordering};
 use std::slice::from_raw_parts;
 use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
 use std::hash::{Hash, Hasher};
@@ -32,12 +33,22 @@ use time::Tm;
 use error::NetworkError;
 use AllowIP;
 use discovery::{TableUpdates, NodeEntry};
+use enr::NodeRecord;
 use ip_utils::*;
 pub use rustc_serialize::json::Json;
 
 /// Node public key
 pub type NodeId = H512;
 
+/// Lowest node score. A node reaching it is banned.
+const BAN_SCORE: i32 = -1000;
+/// Highest node score.
+const MAX_SCORE: i32 = 1000;
+
+fn unix_time() -> u64 {
+	::time::get_time().sec as u64
+}
+
 #[derive(Debug, Clone)]
 /// Node address info
 pub struct NodeEndpoint {
@@ -136,6 +147,11 @@ pub struct Node {
 	pub peer_type: PeerType,
 	pub failures: u32,
 	pub last_attempted: Option<Tm>,
+	pub record: Option<NodeRecord>,
+	/// Reputation built from protocol events.
+	pub score: i32,
+	/// Unix time until which the node is banned.
+	pub banned_until: Option<u64>,
 }
 
 impl Node {
@@ -146,7 +162,40 @@ impl Node {
 			peer_type: PeerType::Optional,
 			failures: 0,
 			last_attempted: None,
+			record: None,
+			score: 0,
+			banned_until: None,
+		}
+	}
+
+	/// Replace the node record if the given one is newer. Returns `true` if updated.
+	pub fn update_record(&mut self, record: NodeRecord) -> bool {
+		if self.record.as_ref().map_or(false, |r| r.seq() >= record.seq()) {
+			return false;
+		}
+		if let Some(endpoint) = record.endpoint() {
+			self.endpoint = endpoint;
 		}
+		self.record = Some(record);
+		true
+	}
+
+	/// Adjust the score. A node reaching the lowest score is banned for `ban_duration` seconds,
+	/// unless that is zero. Returns `true` if the node has been banned.
+	pub fn note_score(&mut self, delta: i32, now: u64, ban_duration: u64) -> bool {
+		self.score = max(BAN_SCORE, min(MAX_SCORE, self.score.saturating_add(delta)));
+		if self.score > BAN_SCORE || ban_duration == 0 {
+			return false;
+		}
+		self.banned_until = Some(now + ban_duration);
+		// Leave the node halfway to another ban once this one is over.
+		self.score = BAN_SCORE / 2;
+		true
+	}
+
+	/// Check if the node is banned at `now`.
+	pub fn is_banned(&self, now: u64) -> bool {
+		self.banned_until.map_or(false, |t| t > now)
 	}
 }
 
@@ -177,6 +226,9 @@ impl FromStr for Node {
 			peer_type: PeerType::Optional,
 			last_attempted: None,
 			failures: 0,
+			record: None,
+			score: 0,
+			banned_until: None,
 		})
 	}
 }
@@ -194,11 +246,24 @@ impl Hash for Node {
 	}
 }
 
+/// Reputation of a known node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeScore {
+	/// Node id
+	pub id: NodeId,
+	/// Current score
+	pub score: i32,
+	/// Unix time until which the node is banned
+	pub banned_until: Option<u64>,
+}
+
 /// Node table backed by disk file.
 pub struct NodeTable {
 	nodes: HashMap<NodeId, Node>,
 	useless_nodes: HashSet<NodeId>,
 	path: Option<String>,
+	/// Ban duration in seconds for nodes reaching the lowest score.
+	ban_duration: u64,
 }
 
 impl NodeTable {
@@ -207,21 +272,69 @@ impl NodeTable {
 			path: path.clone(),
 			nodes: NodeTable::load(path),
 			useless_nodes: HashSet::new(),
+			ban_duration: 0,
 		}
 	}
 
+	/// Set the ban duration in seconds. Zero disables bans.
+	pub fn set_ban_duration(&mut self, seconds: u64) {
+		self.ban_duration = seconds;
+	}
+
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve failure counter
-		let failures = self.nodes.get(&node.id).map_or(0, |n| n.failures);
-		node.failures = failures;
+		// preserve failure counter, reputation and node record
+		if let Some(existing) = self.nodes.get(&node.id) {
+			node.failures = existing.failures;
+			node.score = existing.score;
+			node.banned_until = existing.banned_until;
+			if node.record.is_none() {
+				node.record = existing.record.clone();
+			}
+		} else {
+			node.failures = 0;
+		}
 		self.nodes.insert(node.id.clone(), node);
 	}
 
-	/// Returns node ids sorted by number of failures
+	/// Add or update a node from a signed node record. Records without
+	/// an identity or an endpoint are ignored for new nodes.
+	pub fn add_record(&mut self, record: NodeRecord) -> bool {
+		let id = match record.id() {
+			Some(id) => id,
+			None => return false,
+		};
+		if let Some(node) = self.nodes.get_mut(&id) {
+			return node.update_record(record);
+		}
+		let mut node = match record.endpoint() {
+			Some(endpoint) => Node::new(id.clone(), endpoint),
+			None => return false,
+		};
+		node.record = Some(record);
+		self.nodes.insert(id, node);
+		true
+	}
+
+	/// Sequence numbers of all known node records.
+	pub fn record_seqs(&self) -> Vec<(NodeId, u64)> {
+		self.nodes.values().filter_map(|n| n.record.as_ref().map(|r| (n.id.clone(), r.seq()))).collect()
+	}
+
+	/// Returns ids of nodes that are not banned, best score first, then by number of failures
 	pub fn nodes(&self, filter: AllowIP) -> Vec<NodeId> {
-		let mut refs: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id) && n.endpoint.is_allowed(filter)).collect();
-		refs.sort_by(|a, b| a.failures.cmp(&b.failures));
+		self.sorted_nodes(filter, Some(unix_time()))
+	}
+
+	fn sorted_nodes(&self, filter: AllowIP, banned_at: Option<u64>) -> Vec<NodeId> {
+		let mut refs: Vec<&Node> = self.nodes.values()
+			.filter(|n| !self.useless_nodes.contains(&n.id) && n.endpoint.is_allowed(filter))
+			.filter(|n| banned_at.map_or(true, |now| !n.is_banned(now)))
+			.collect();
+		refs.sort_by(|a, b| match b.score.cmp(&a.score) {
+			Ordering::Equal => a.failures.cmp(&b.failures),
+			ordering => ordering,
+		});
 		refs.iter().map(|n| n.id.clone()).collect()
 	}
 
@@ -242,8 +355,12 @@ impl NodeTable {
 			let mut entry = self.nodes.entry(node.id.clone()).or_insert_with(|| Node::new(node.id.clone(), node.endpoint.clone()));
 			entry.endpoint = node.endpoint;
 		}
+		for (_, record) in update.records.drain() {
+			self.add_record(record);
+		}
+		// banned nodes are kept, so that the ban holds if they are discovered again.
 		for r in update.removed {
-			if !reserved.contains(&r) {
+			if !reserved.contains(&r) && !self.is_banned(&r) {
 				self.nodes.remove(&r);
 			}
 		}
@@ -256,6 +373,41 @@ impl NodeTable {
 		}
 	}
 
+	/// Adjust the score of a node. Returns `true` if the node has been banned as a result.
+	pub fn note_score(&mut self, id: &NodeId, delta: i32) -> bool {
+		let ban_duration = self.ban_duration;
+		match self.nodes.get_mut(id) {
+			Some(node) => node.note_score(delta, unix_time(), ban_duration),
+			None => false,
+		}
+	}
+
+	/// Check if a node is currently banned.
+	pub fn is_banned(&self, id: &NodeId) -> bool {
+		self.nodes.get(id).map_or(false, |n| n.is_banned(unix_time()))
+	}
+
+	/// Scores of all nodes that have a non-neutral score or are banned.
+	pub fn scores(&self) -> Vec<NodeScore> {
+		let now = unix_time();
+		self.nodes.values()
+			.filter(|n| n.score != 0 || n.is_banned(now))
+			.map(|n| NodeScore {
+				id: n.id.clone(),
+				score: n.score,
+				banned_until: if n.is_banned(now) { n.banned_until } else { None },
+			})
+			.collect()
+	}
+
+	/// Reset the score and lift the ban of a node, or of all nodes if `id` is `None`.
+	pub fn reset_scores(&mut self, id: Option<&NodeId>) {
+		for node in self.nodes.values_mut().filter(|n| id.map_or(true, |id| *id == n.id)) {
+			node.score = 0;
+			node.banned_until = None;
+		}
+	}
+
 	/// Mark as useless, no furter attempts to connect until next call to `clear_useless`.
 	pub fn mark_as_useless(&mut self, id: &NodeId) {
 		self.useless_nodes.insert(id.clone());
@@ -278,10 +430,17 @@ impl NodeTable {
 			let mut json = String::new();
 			json.push_str("{\n");
 			json.push_str("\"nodes\": [\n");
-			let node_ids = self.nodes(AllowIP::All);
+			let now = unix_time();
+			let mut node_ids = self.sorted_nodes(AllowIP::All, None);
+			// scores and bans of nodes marked as useless must survive a restart as well.
+			node_ids.extend(self.useless_nodes.iter()
+				.filter(|id| self.nodes.get(*id).map_or(false, |n| n.score != 0 || n.is_banned(now)))
+				.cloned());
 			for i in 0 .. node_ids.len() {
-				let node = self.nodes.get(&node_ids[i]).expect("self.nodes() only returns node IDs from self.nodes");
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+				let node = self.nodes.get(&node_ids[i]).expect("node_ids only holds node IDs from self.nodes");
+				let record = node.record.as_ref().map_or(String::new(), |r| format!(", \"enr\": \"{}\"", r));
+				let banned = node.banned_until.map_or(String::new(), |t| format!(", \"banned_until\": {}", t));
+				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {}, \"score\": {}{}{} }}{}\n", node, node.failures, node.score, banned, record, if i == node_ids.len() - 1 {""} else {","}))
 			}
 			json.push_str("]\n");
 			json.push_str("}");
@@ -332,6 +491,15 @@ impl NodeTable {
 							if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
 								node.failures = failures as u32;
 							}
+							if let Some(score) = n.get("score").and_then(|s| s.as_i64()) {
+								node.score = max(BAN_SCORE as i64, min(MAX_SCORE as i64, score)) as i32;
+							}
+							node.banned_until = n.get("banned_until").and_then(|b| b.as_u64());
+							if let Some(record) = n.get("enr").and_then(|r| r.as_string()).and_then(|r| NodeRecord::from_str(r).ok()) {
+								if record.id().as_ref() == Some(&node.id) {
+									node.record = Some(record);
+								}
+							}
 							nodes.insert(node.id.clone(), node);
 						}
 					}
@@ -358,6 +526,8 @@ pub fn is_valid_node_url(url: &str) -> bool {
 mod tests {
 	use super::*;
 	use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
+	use std::collections::{HashMap, HashSet};
+	use discovery::TableUpdates;
 	use util::H512;
 	use std::str::FromStr;
 	use devtools::*;
@@ -434,4 +604,153 @@ mod tests {
 			assert_eq!(r[1][..], id2[..]);
 		}
 	}
+
+	#[test]
+	fn table_score_order_and_ban() {
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		let mut table = NodeTable::new(None);
+		table.add_node(node1);
+		table.add_node(node2);
+
+		table.note_failure(&id2);
+		assert!(!table.note_score(&id2, 10));
+		assert_eq!(table.nodes(AllowIP::All), vec![id2.clone(), id1.clone()]);
+
+		// Bans are disabled by default.
+		assert!(!table.note_score(&id1, -5000));
+		assert!(!table.is_banned(&id1));
+
+		table.set_ban_duration(60);
+		assert!(table.note_score(&id1, -1));
+		assert!(table.is_banned(&id1));
+		assert_eq!(table.nodes(AllowIP::All), vec![id2.clone()]);
+		let banned = table.scores().into_iter().find(|s| s.id == id1).unwrap();
+		assert_eq!(banned.score, -500);
+		assert!(banned.banned_until.is_some());
+
+		table.reset_scores(Some(&id1));
+		assert!(!table.is_banned(&id1));
+		assert_eq!(table.scores(), vec![NodeScore { id: id2.clone(), score: 10, banned_until: None }]);
+		table.reset_scores(None);
+		assert!(table.scores().is_empty());
+	}
+
+	#[test]
+	fn node_ban_expires() {
+		let mut node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		assert!(!node.note_score(-999, 100, 60));
+		assert!(node.note_score(-1, 100, 60));
+		assert!(node.is_banned(159));
+		assert!(!node.is_banned(160));
+		assert!(!node.note_score(2000, 200, 60));
+		assert_eq!(node.score, 1000);
+	}
+
+	#[test]
+	fn table_save_load_scores() {
+		let temp_path = RandomTempPath::create_dir();
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.set_ban_duration(3600);
+			table.add_node(node1);
+			table.add_node(node2);
+			table.note_score(&id1, 42);
+			table.note_score(&id2, -1000);
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert_eq!(table.nodes(AllowIP::All), vec![id1.clone()]);
+			assert!(table.is_banned(&id2));
+			let mut scores: Vec<i32> = table.scores().into_iter().map(|s| s.score).collect();
+			scores.sort();
+			assert_eq!(scores, vec![-500, 42]);
+		}
+	}
+
+	#[test]
+	fn table_save_load_scores_of_useless_nodes() {
+		let temp_path = RandomTempPath::create_dir();
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node3 = Node::from_str("enode://c979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		let id3 = node3.id.clone();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			table.set_ban_duration(3600);
+			table.add_node(node1);
+			table.add_node(node2);
+			table.add_node(node3);
+			table.note_score(&id1, 42);
+			table.note_score(&id2, -1000);
+			table.mark_as_useless(&id1);
+			table.mark_as_useless(&id2);
+			table.mark_as_useless(&id3);
+		}
+
+		{
+			let table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert_eq!(table.nodes(AllowIP::All), vec![id1.clone()]);
+			assert!(table.is_banned(&id2));
+		}
+	}
+
+	#[test]
+	fn table_update_keeps_banned_nodes() {
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		let mut table = NodeTable::new(None);
+		table.set_ban_duration(3600);
+		table.add_node(node1);
+		table.add_node(node2);
+		table.note_score(&id2, -1000);
+
+		let mut removed = HashSet::new();
+		removed.insert(id1.clone());
+		removed.insert(id2.clone());
+		table.update(TableUpdates { added: HashMap::new(), removed: removed, records: HashMap::new() }, &HashSet::new());
+
+		assert!(table.get_mut(&id1).is_none());
+		assert!(table.is_banned(&id2));
+	}
+
+	#[test]
+	fn table_records() {
+		use ethkey::{Random, Generator};
+		use enr::NodeRecord;
+
+		let temp_path = RandomTempPath::create_dir();
+		let key = Random.generate().unwrap();
+		let endpoint = NodeEndpoint::from_str("22.99.55.44:7770").unwrap();
+		let mut record = NodeRecord::new(1);
+		record.set_endpoint(&endpoint);
+		record.sign(&key).unwrap();
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert!(table.add_record(record.clone()));
+			assert!(!table.add_record(record.clone()));
+			assert_eq!(table.nodes(AllowIP::All), vec![key.public().clone()]);
+		}
+
+		{
+			let mut table = NodeTable::new(Some(temp_path.as_path().to_str().unwrap().to_owned()));
+			assert_eq!(table.get_mut(key.public()).unwrap().record, Some(record));
+			let mut newer = NodeRecord::new(2);
+			newer.set_endpoint(&NodeEndpoint::from_str("22.99.55.45:7771").unwrap());
+			newer.sign(&key).unwrap();
+			assert!(table.add_record(newer));
+			assert_eq!(table.get_mut(key.public()).unwrap().endpoint.address, SocketAddr::from_str("22.99.55.45:7771").unwrap());
+		}
+	}
 }
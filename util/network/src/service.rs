@@ -16,6 +16,7 @@
 
 use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
 use error::NetworkError;
+use node_table::{NodeId, NodeScore};
 use host::{Host, NetworkContext, NetworkIoMessage, PeerId, ProtocolId};
 use stats::NetworkStats;
 use io::*;
@@ -163,6 +164,18 @@ impl NetworkService {
 		}
 	}
 
+	/// Scores of known peers that have a non-neutral score or are banned.
+	pub fn peer_scores(&self) -> Vec<NodeScore> {
+		self.host.read().as_ref().map(|h| h.peer_scores()).unwrap_or_else(Vec::new)
+	}
+
+	/// Reset the score and lift the ban of a peer, or of all peers if `id` is `None`.
+	pub fn reset_peer_scores(&self, id: Option<&NodeId>) {
+		if let Some(ref host) = *self.host.read() {
+			host.reset_peer_scores(id);
+		}
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
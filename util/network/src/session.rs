@@ -18,12 +18,13 @@ use std::{str, io};
 use std::net::SocketAddr;
 use std::cmp::Ordering;
 use std::sync::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use mio::*;
 use mio::deprecated::{Handler, EventLoop};
 use mio::tcp::*;
 use util::hash::*;
+use util::bytes::Bytes;
 use rlp::*;
 use connection::{EncryptedConnection, Packet, Connection};
 use handshake::Handshake;
@@ -31,7 +32,7 @@ use io::{IoContext, StreamToken};
 use error::{NetworkError, DisconnectReason};
 use host::*;
 use node_table::NodeId;
-use stats::NetworkStats;
+use stats::{NetworkStats, PeerTraffic};
 use time;
 
 // Timeout must be less than (interval - 1).
@@ -61,6 +62,8 @@ pub struct Session {
 	state: State,
 	// Protocol states -- accumulates pending packets until signaled as ready.
 	protocol_states: HashMap<ProtocolId, ProtocolState>,
+	// Packets sent only when nothing else is queued on the connection.
+	low_priority: VecDeque<Bytes>,
 }
 
 enum State {
@@ -107,6 +110,8 @@ pub struct SessionInfo {
 	pub remote_address: String,
 	/// Local endpoint address of the session
 	pub local_address: String,
+	/// Traffic counters
+	pub traffic: PeerTraffic,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -177,7 +182,7 @@ impl Session {
 		nonce: &H256, stats: Arc<NetworkStats>, host: &HostInfo) -> Result<Session, NetworkError>
 		where Message: Send + Clone + Sync + 'static {
 		let originated = id.is_some();
-		let mut handshake = Handshake::new(token, id, socket, nonce, stats).expect("Can't create handshake");
+		let mut handshake = Handshake::new(token, id, socket, nonce, stats, host.throttle()).expect("Can't create handshake");
 		let local_addr = handshake.connection.local_addr_str();
 		handshake.start(io, host, originated)?;
 		Ok(Session {
@@ -193,11 +198,13 @@ impl Session {
 				originated: originated,
 				remote_address: "Handshake".to_owned(),
 				local_address: local_addr,
+				traffic: PeerTraffic::default(),
 			},
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
 			protocol_states: HashMap::new(),
+			low_priority: VecDeque::new(),
 		})
 	}
 
@@ -222,6 +229,13 @@ impl Session {
 		}
 	}
 
+	fn connection_mut(&mut self) -> &mut Connection {
+		match self.state {
+			State::Handshake(ref mut h) => &mut h.connection,
+			State::Session(ref mut s) => &mut s.connection,
+		}
+	}
+
 	/// Get id of the remote peer
 	pub fn id(&self) -> Option<&NodeId> {
 		self.info.id.as_ref()
@@ -270,12 +284,10 @@ impl Session {
 				}
 			}
 			State::Session(ref mut c) => {
-				match c.readable(io)? {
-					data @ Some(_) => packet_data = data,
-					None => return Ok(SessionData::None)
-				}
+				packet_data = c.readable(io)?;
 			}
 		}
+		self.update_traffic();
 		if let Some(data) = packet_data {
 			return Ok(self.read_packet(io, data, host)?);
 		}
@@ -289,9 +301,17 @@ impl Session {
 	/// Writable IO handler. Sends pending packets.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>, _host: &HostInfo) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
 		match self.state {
-			State::Handshake(ref mut h) => h.writable(io),
-			State::Session(ref mut s) => s.writable(io),
+			State::Handshake(ref mut h) => h.writable(io)?,
+			State::Session(ref mut s) => s.writable(io)?,
 		}
+		self.update_traffic();
+		self.flush_low_priority(io)
+	}
+
+	/// Check if a read or write was deferred because of the bandwidth limits
+	/// since the last call and reset the flag.
+	pub fn take_throttled(&mut self) -> bool {
+		self.connection_mut().take_throttled()
 	}
 
 	/// Checks if peer supports given capability
@@ -328,6 +348,23 @@ impl Session {
 	/// Send a protocol packet to peer.
 	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, protocol: [u8; 3], packet_id: u8, data: &[u8]) -> Result<(), NetworkError>
         where Message: Send + Sync + Clone {
+		match self.prepare_packet(protocol, packet_id, data)? {
+			Some(rlp) => self.send(io, rlp),
+			None => Ok(()),
+		}
+	}
+
+	/// Send a protocol packet to peer after all other queued packets have been written.
+	pub fn send_packet_low_priority<Message>(&mut self, io: &IoContext<Message>, protocol: [u8; 3], packet_id: u8, data: &[u8]) -> Result<(), NetworkError>
+        where Message: Send + Sync + Clone {
+		if let Some(rlp) = self.prepare_packet(protocol, packet_id, data)? {
+			self.low_priority.push_back(rlp.out());
+			self.flush_low_priority(io)?;
+		}
+		Ok(())
+	}
+
+	fn prepare_packet(&mut self, protocol: [u8; 3], packet_id: u8, data: &[u8]) -> Result<Option<RlpStream>, NetworkError> {
 		if self.info.capabilities.is_empty() || !self.had_hello {
 			debug!(target: "network", "Sending to unconfirmed session {}, protocol: {}, packet: {}", self.token(), str::from_utf8(&protocol[..]).unwrap_or("??"), packet_id);
 			return Err(From::from(NetworkError::BadProtocol));
@@ -340,14 +377,36 @@ impl Session {
 			i += 1;
 			if i == self.info.capabilities.len() {
 				debug!(target: "network", "Unknown protocol: {:?}", protocol);
-				return Ok(())
+				return Ok(None)
 			}
 		}
 		let pid = self.info.capabilities[i].id_offset + packet_id;
 		let mut rlp = RlpStream::new();
 		rlp.append(&(pid as u32));
 		rlp.append_raw(data, 1);
-		self.send(io, rlp)
+		self.info.traffic.inc_protocol_send(protocol, data.len());
+		Ok(Some(rlp))
+	}
+
+	/// Pass the next low priority packet to the connection if it has nothing else to send.
+	fn flush_low_priority<Message>(&mut self, io: &IoContext<Message>) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+		if let State::Session(ref mut s) = self.state {
+			if !s.connection.is_sending() {
+				if let Some(packet) = self.low_priority.pop_front() {
+					s.send_packet(io, &packet)?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn update_traffic(&mut self) {
+		let (sent, received) = {
+			let connection = self.connection();
+			(connection.bytes_sent(), connection.bytes_received())
+		};
+		self.info.traffic.sent = sent;
+		self.info.traffic.received = received;
 	}
 
 	/// Keep this session alive. Returns false if ping timeout happened
@@ -435,6 +494,7 @@ impl Session {
 				// map to protocol
 				let protocol = self.info.capabilities[i].protocol;
 				let protocol_packet_id = packet_id - self.info.capabilities[i].id_offset;
+				self.info.traffic.inc_protocol_recv(protocol, packet.data.len() - 1);
 
 				match *self.protocol_states.entry(protocol).or_insert_with(|| ProtocolState::Pending(Vec::new())) {
 					ProtocolState::Connected => {
@@ -16,6 +16,7 @@
 
 //! Network Statistics
 use std::sync::atomic::*;
+use host::ProtocolId;
 
 /// Network statistics structure
 #[derive(Default, Debug)]
@@ -74,3 +75,48 @@ impl NetworkStats {
 		}
 	}
 }
+
+/// Payload traffic of a single subprotocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolTraffic {
+	/// Protocol id
+	pub protocol: ProtocolId,
+	/// Payload bytes sent
+	pub sent: u64,
+	/// Payload bytes received
+	pub received: u64,
+}
+
+/// Traffic counters of a single peer session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerTraffic {
+	/// Bytes sent, including framing and encryption overhead
+	pub sent: u64,
+	/// Bytes received, including framing and encryption overhead
+	pub received: u64,
+	/// Payload traffic per subprotocol
+	pub protocols: Vec<ProtocolTraffic>,
+}
+
+impl PeerTraffic {
+	/// Account a sent subprotocol packet.
+	pub fn inc_protocol_send(&mut self, protocol: ProtocolId, size: usize) {
+		self.protocol_mut(protocol).sent += size as u64;
+	}
+
+	/// Account a received subprotocol packet.
+	pub fn inc_protocol_recv(&mut self, protocol: ProtocolId, size: usize) {
+		self.protocol_mut(protocol).received += size as u64;
+	}
+
+	fn protocol_mut(&mut self, protocol: ProtocolId) -> &mut ProtocolTraffic {
+		let position = self.protocols.iter().position(|p| p.protocol == protocol);
+		match position {
+			Some(i) => &mut self.protocols[i],
+			None => {
+				self.protocols.push(ProtocolTraffic { protocol: protocol, sent: 0, received: 0 });
+				self.protocols.last_mut().expect("Element was just pushed; qed")
+			}
+		}
+	}
+}
@@ -0,0 +1,932 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only log-structured key-value store.
+//!
+//! Every flush appends a single checksummed frame with all buffered operations to the
+//! current segment file. Every key of every column is kept in an in-memory sorted index
+//! pointing at its value on disk, which is rebuilt by replaying the segments on open.
+//! Databases whose index would take more than `MAX_INDEX_SIZE` bytes of memory are
+//! refused on open.
+//! Sealed segments with mostly dead data are compacted by copying their live entries
+//! into a new file which then replaces the segment.
+
+use std::collections::Bound;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use common::*;
+use elastic_array::ElasticArray32;
+use hashdb::DBValue;
+use rlp::{UntrustedRlp, RlpType, Compressible};
+use super::{DBOp, DBTransaction, KeyState, KeyValueDB, DatabaseConfig, replace_database_dir};
+
+/// Size after which the current segment is sealed and a new one is started.
+const SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+/// Frame header: payload length followed by a payload checksum.
+const HEADER_LEN: usize = 8;
+/// Segment file extension.
+const SEGMENT_EXT: &'static str = "seg";
+/// Extension of a compacted segment copy which is yet to replace the segment.
+const COMPACTION_EXT: &'static str = "compact";
+/// Maximal memory taken by the index of a database which may be opened.
+const MAX_INDEX_SIZE: usize = 1024 * 1024 * 1024;
+/// Approximate memory taken by an index entry besides its key.
+const INDEX_ENTRY_OVERHEAD: usize = 64;
+
+const OP_INSERT: u8 = 0;
+const OP_DELETE: u8 = 1;
+
+/// Location of a value on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Location {
+	segment: u32,
+	offset: u64,
+	len: u32,
+}
+
+struct Segment {
+	file: Mutex<File>,
+	// total size of the segment file.
+	size: u64,
+	// bytes occupied by entries which are still referenced by the index.
+	live: u64,
+}
+
+struct Storage {
+	index: Vec<BTreeMap<Vec<u8>, Location>>,
+	// approximate memory taken by the index.
+	index_size: usize,
+	segments: BTreeMap<u32, Segment>,
+	current: u32,
+}
+
+// An operation decoded from a frame. Value is given as offset and length within the payload.
+struct Entry<'a> {
+	col: usize,
+	key: &'a [u8],
+	value: Option<(usize, usize)>,
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+	out.extend_from_slice(&[v as u8, (v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8]);
+}
+
+fn get_u32(data: &[u8]) -> u32 {
+	data[0] as u32 | (data[1] as u32) << 8 | (data[2] as u32) << 16 | (data[3] as u32) << 24
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+	io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+// memory an index entry for a key takes.
+fn index_entry_size(key_len: usize) -> usize {
+	key_len + INDEX_ENTRY_OVERHEAD
+}
+
+// size an operation takes within a payload.
+fn entry_len(key_len: usize, value_len: Option<usize>) -> u64 {
+	(2 + 4 + key_len + value_len.map_or(0, |len| 4 + len)) as u64
+}
+
+fn encode_op(payload: &mut Vec<u8>, col: usize, key: &[u8], value: Option<&[u8]>) {
+	payload.push(if value.is_some() { OP_INSERT } else { OP_DELETE });
+	payload.push(col as u8);
+	put_u32(payload, key.len() as u32);
+	payload.extend_from_slice(key);
+	if let Some(value) = value {
+		put_u32(payload, value.len() as u32);
+		payload.extend_from_slice(value);
+	}
+}
+
+fn decode_ops(payload: &[u8]) -> io::Result<Vec<Entry>> {
+	let mut entries = Vec::new();
+	let mut pos = 0;
+	while pos < payload.len() {
+		if payload.len() < pos + 6 {
+			return Err(invalid_data("Truncated operation"));
+		}
+		let kind = payload[pos];
+		let col = payload[pos + 1] as usize;
+		let key_len = get_u32(&payload[pos + 2..]) as usize;
+		pos += 6;
+		if payload.len() < pos + key_len {
+			return Err(invalid_data("Truncated key"));
+		}
+		let key = &payload[pos..pos + key_len];
+		pos += key_len;
+		let value = match kind {
+			OP_INSERT => {
+				if payload.len() < pos + 4 {
+					return Err(invalid_data("Truncated value length"));
+				}
+				let len = get_u32(&payload[pos..]) as usize;
+				pos += 4;
+				if payload.len() < pos + len {
+					return Err(invalid_data("Truncated value"));
+				}
+				let value = (pos, len);
+				pos += len;
+				Some(value)
+			},
+			OP_DELETE => None,
+			_ => return Err(invalid_data("Unknown operation")),
+		};
+		entries.push(Entry { col: col, key: key, value: value });
+	}
+	Ok(entries)
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+	put_u32(&mut out, payload.len() as u32);
+	out.extend_from_slice(&payload.sha3()[0..4]);
+	out.extend_from_slice(payload);
+	out
+}
+
+// Reads the frame at the current position. Returns `None` on a clean end of file
+// and an error of kind `UnexpectedEof` or `InvalidData` for a torn or corrupted frame.
+fn read_frame(file: &mut File) -> io::Result<Option<Vec<u8>>> {
+	let mut header = [0u8; HEADER_LEN];
+	let mut read = 0;
+	while read < HEADER_LEN {
+		match file.read(&mut header[read..])? {
+			0 if read == 0 => return Ok(None),
+			0 => return Err(io::Error::new(ErrorKind::UnexpectedEof, "Truncated frame header")),
+			n => read += n,
+		}
+	}
+	// a corrupted length must not make us allocate more than the file could hold.
+	let len = get_u32(&header) as u64;
+	let remaining = file.metadata()?.len().saturating_sub(file.seek(SeekFrom::Current(0))?);
+	if len > remaining {
+		return Err(io::Error::new(ErrorKind::UnexpectedEof, "Frame length exceeds the segment size"));
+	}
+	let mut payload = vec![0u8; len as usize];
+	file.read_exact(&mut payload)?;
+	if payload.sha3()[0..4] != header[4..] {
+		return Err(invalid_data("Frame checksum mismatch"));
+	}
+	Ok(Some(payload))
+}
+
+impl Storage {
+	fn segment_path(path: &str, id: u32) -> PathBuf {
+		let mut p = PathBuf::from(path);
+		p.push(format!("{:08}.{}", id, SEGMENT_EXT));
+		p
+	}
+
+	fn compaction_path(path: &str, id: u32) -> PathBuf {
+		let mut p = PathBuf::from(path);
+		p.push(format!("{:08}.{}", id, COMPACTION_EXT));
+		p
+	}
+
+	fn open_segment(path: &str, id: u32) -> io::Result<File> {
+		OpenOptions::new().read(true).append(true).create(true).open(Self::segment_path(path, id))
+	}
+
+	fn open(path: &str, columns: usize, max_index_size: usize) -> io::Result<Storage> {
+		fs::create_dir_all(path)?;
+		let mut ids = Vec::new();
+		for entry in fs::read_dir(path)? {
+			let entry_path = entry?.path();
+			if entry_path.extension().map_or(false, |ext| ext == COMPACTION_EXT) {
+				// left behind by an interrupted compaction; the segment itself is intact.
+				fs::remove_file(&entry_path)?;
+				continue;
+			}
+			if entry_path.extension().map_or(false, |ext| ext == SEGMENT_EXT) {
+				if let Some(id) = entry_path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u32>().ok()) {
+					ids.push(id);
+				}
+			}
+		}
+		ids.sort();
+		if ids.is_empty() {
+			ids.push(0);
+		}
+
+		let mut storage = Storage {
+			index: (0..columns).map(|_| BTreeMap::new()).collect(),
+			index_size: 0,
+			segments: BTreeMap::new(),
+			current: *ids.last().expect("ids is not empty; qed"),
+		};
+
+		for id in ids {
+			let mut file = Self::open_segment(path, id)?;
+			storage.segments.insert(id, Segment { file: Mutex::new(file.try_clone()?), size: 0, live: 0 });
+			file.seek(SeekFrom::Start(0))?;
+			let mut offset = 0u64;
+			loop {
+				let payload = match read_frame(&mut file) {
+					Ok(Some(payload)) => payload,
+					Ok(None) => break,
+					Err(ref e) if id == storage.current && (e.kind() == ErrorKind::UnexpectedEof || e.kind() == ErrorKind::InvalidData) => {
+						warn!("Discarding incomplete write at the end of {}: {}", Self::segment_path(path, id).display(), e);
+						file.set_len(offset)?;
+						break;
+					},
+					Err(e) => return Err(e),
+				};
+				storage.apply(id, offset + HEADER_LEN as u64, &payload)?;
+				offset += (HEADER_LEN + payload.len()) as u64;
+			}
+			storage.segments.get_mut(&id).expect("segment inserted above; qed").size = offset;
+
+			if storage.index_size > max_index_size {
+				return Err(io::Error::new(ErrorKind::Other, format!(
+					"Index needs more than {} MiB of memory, use a different database backend",
+					max_index_size / (1024 * 1024)
+				)));
+			}
+		}
+
+		Ok(storage)
+	}
+
+	// Updates the index with operations of a frame written at `payload_offset` of `segment`.
+	fn apply(&mut self, segment: u32, payload_offset: u64, payload: &[u8]) -> io::Result<()> {
+		for entry in decode_ops(payload)? {
+			if entry.col >= self.index.len() {
+				return Err(invalid_data("Operation on unknown column"));
+			}
+			let old = match entry.value {
+				Some((offset, len)) => {
+					let location = Location { segment: segment, offset: payload_offset + offset as u64, len: len as u32 };
+					if let Some(s) = self.segments.get_mut(&segment) {
+						s.live += entry_len(entry.key.len(), Some(len));
+					}
+					self.index[entry.col].insert(entry.key.to_vec(), location)
+				},
+				None => self.index[entry.col].remove(entry.key),
+			};
+			match (entry.value.is_some(), old.is_some()) {
+				(true, false) => self.index_size += index_entry_size(entry.key.len()),
+				(false, true) => self.index_size -= index_entry_size(entry.key.len()),
+				_ => {},
+			}
+			if let Some(old) = old {
+				if let Some(s) = self.segments.get_mut(&old.segment) {
+					s.live -= entry_len(entry.key.len(), Some(old.len as usize));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	// Appends a frame to the current segment and indexes it.
+	fn append(&mut self, payload: &[u8]) -> io::Result<()> {
+		let current = self.current;
+		let offset = {
+			let segment = self.segments.get_mut(&current).expect("current segment is always open; qed");
+			let offset = segment.size;
+			let mut file = segment.file.lock();
+			if let Err(e) = file.write_all(&frame(payload)) {
+				// do not leave a partial frame behind.
+				let _ = file.set_len(offset);
+				return Err(e);
+			}
+			segment.size += (HEADER_LEN + payload.len()) as u64;
+			offset
+		};
+		self.apply(current, offset + HEADER_LEN as u64, payload)
+	}
+
+	// Seals the current segment if it is full.
+	fn rotate(&mut self, path: &str) -> io::Result<()> {
+		if self.segments[&self.current].size < SEGMENT_SIZE {
+			return Ok(());
+		}
+		self.segments[&self.current].file.lock().sync_data()?;
+		let id = self.current + 1;
+		let file = Self::open_segment(path, id)?;
+		self.segments.insert(id, Segment { file: Mutex::new(file), size: 0, live: 0 });
+		self.current = id;
+		Ok(())
+	}
+
+	// Sealed segments which are mostly garbage.
+	fn compaction_candidates(&self) -> Vec<u32> {
+		self.segments.iter()
+			.filter(|&(id, s)| *id != self.current && s.live * 2 < s.size)
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
+	// Gathers the live entries of a sealed segment into a single payload.
+	fn live_entries(&self, id: u32) -> io::Result<Vec<u8>> {
+		let has_older = self.segments.keys().next().map_or(false, |first| *first < id);
+		let mut payload = Vec::new();
+		let mut file = self.segments.get(&id).ok_or_else(|| invalid_data("Missing segment"))?.file.lock();
+		file.seek(SeekFrom::Start(0))?;
+		let mut offset = 0u64;
+		while let Some(data) = read_frame(&mut file)? {
+			let payload_offset = offset + HEADER_LEN as u64;
+			for entry in decode_ops(&data)? {
+				match entry.value {
+					Some((value_offset, len)) => {
+						let location = Location { segment: id, offset: payload_offset + value_offset as u64, len: len as u32 };
+						if self.index[entry.col].get(entry.key) == Some(&location) {
+							encode_op(&mut payload, entry.col, entry.key, Some(&data[value_offset..value_offset + len]));
+						}
+					},
+					None => {
+						// older segments may still hold a value this deletion hides.
+						if has_older && !self.index[entry.col].contains_key(entry.key) {
+							encode_op(&mut payload, entry.col, entry.key, None);
+						}
+					},
+				}
+			}
+			offset += (HEADER_LEN + data.len()) as u64;
+		}
+		Ok(payload)
+	}
+
+	// Writes the compacted copy of a segment next to it.
+	fn write_compacted(path: &str, id: u32, payload: &[u8]) -> io::Result<()> {
+		let mut file = File::create(Self::compaction_path(path, id))?;
+		file.write_all(&frame(payload))?;
+		file.sync_all()
+	}
+
+	// Replaces a sealed segment with its compacted copy, or removes it if there is nothing
+	// left to copy, and points the index at the copied values.
+	// The index must not have changed since the payload was gathered.
+	fn swap_compacted(&mut self, path: &str, id: u32, payload: &[u8]) -> io::Result<()> {
+		self.segments.remove(&id);
+		if payload.is_empty() {
+			return fs::remove_file(Self::segment_path(path, id));
+		}
+
+		fs::rename(Self::compaction_path(path, id), Self::segment_path(path, id))?;
+		let mut live = 0;
+		for entry in decode_ops(payload)? {
+			if let Some((offset, len)) = entry.value {
+				let location = Location { segment: id, offset: (HEADER_LEN + offset) as u64, len: len as u32 };
+				self.index[entry.col].insert(entry.key.to_vec(), location);
+				live += entry_len(entry.key.len(), Some(len));
+			}
+		}
+		let file = Self::open_segment(path, id)?;
+		self.segments.insert(id, Segment { file: Mutex::new(file), size: (HEADER_LEN + payload.len()) as u64, live: live });
+		Ok(())
+	}
+
+	fn read(&self, location: &Location) -> io::Result<Vec<u8>> {
+		let segment = self.segments.get(&location.segment).ok_or_else(|| invalid_data("Missing segment"))?;
+		let mut file = segment.file.lock();
+		file.seek(SeekFrom::Start(location.offset))?;
+		let mut value = vec![0u8; location.len as usize];
+		file.read_exact(&mut value)?;
+		Ok(value)
+	}
+
+	fn sync(&self) -> io::Result<()> {
+		self.segments[&self.current].file.lock().sync_data()
+	}
+}
+
+/// Log-structured key-value database.
+///
+/// The index holds every key of every column in memory, along with the location
+/// of its value, so memory usage grows with the number and size of the keys stored.
+/// Opening a database whose index would exceed 1 GiB fails. Values are always read
+/// from disk. It is best suited for databases with a moderate number of keys.
+/// Cache size settings are ignored.
+pub struct LogDB {
+	storage: RwLock<Option<Storage>>,
+	config: DatabaseConfig,
+	path: String,
+	// Dirty values added with `write_buffered`. Cleaned on `flush`.
+	overlay: RwLock<Vec<HashMap<ElasticArray32<u8>, KeyState>>>,
+	// Values currently being flushed. Cleared when `flush` completes.
+	flushing: RwLock<Vec<HashMap<ElasticArray32<u8>, KeyState>>>,
+	// Prevents concurrent flushes.
+	// Value indicates if a flush is in progress.
+	flushing_lock: Mutex<bool>,
+}
+
+impl LogDB {
+	/// Open database file. Creates if it does not exist.
+	pub fn open(config: &DatabaseConfig, path: &str) -> Result<LogDB, String> {
+		let columns = config.columns.unwrap_or(0) as usize + 1;
+		let storage = Storage::open(path, columns, MAX_INDEX_SIZE).map_err(|e| format!("Error opening database at {}: {}", path, e))?;
+		Ok(LogDB {
+			storage: RwLock::new(Some(storage)),
+			config: config.clone(),
+			path: path.to_owned(),
+			overlay: RwLock::new((0..columns).map(|_| HashMap::new()).collect()),
+			flushing: RwLock::new((0..columns).map(|_| HashMap::new()).collect()),
+			flushing_lock: Mutex::new(false),
+		})
+	}
+
+	/// Helper to create new transaction for this database.
+	pub fn transaction(&self) -> DBTransaction {
+		DBTransaction::new()
+	}
+
+	fn to_overlay_column(col: Option<u32>) -> usize {
+		col.map_or(0, |c| (c + 1) as usize)
+	}
+
+	/// Commit transaction to database.
+	pub fn write_buffered(&self, tr: DBTransaction) {
+		let mut overlay = self.overlay.write();
+		for op in tr.ops {
+			match op {
+				DBOp::Insert { col, key, value } => {
+					overlay[Self::to_overlay_column(col)].insert(key, KeyState::Insert(value));
+				},
+				DBOp::InsertCompressed { col, key, value } => {
+					overlay[Self::to_overlay_column(col)].insert(key, KeyState::InsertCompressed(value));
+				},
+				DBOp::Delete { col, key } => {
+					overlay[Self::to_overlay_column(col)].insert(key, KeyState::Delete);
+				},
+			}
+		}
+	}
+
+	/// Commit buffered changes to database. Must be called under `flush_lock`
+	fn write_flushing_with_lock(&self, _lock: &mut MutexGuard<bool>) -> Result<(), String> {
+		self.append_flushing()?;
+		self.compact().map_err(|e| e.to_string())
+	}
+
+	// Appends the buffered changes to the current segment.
+	fn append_flushing(&self) -> Result<(), String> {
+		let mut storage = self.storage.write();
+		let storage = match *storage {
+			Some(ref mut storage) => storage,
+			None => return Err("Database is closed".to_owned()),
+		};
+
+		mem::swap(&mut *self.overlay.write(), &mut *self.flushing.write());
+		let mut payload = Vec::new();
+		for (c, column) in self.flushing.read().iter().enumerate() {
+			for (key, state) in column.iter() {
+				match *state {
+					KeyState::Delete => encode_op(&mut payload, c, key, None),
+					KeyState::Insert(ref value) => encode_op(&mut payload, c, key, Some(&value[..])),
+					KeyState::InsertCompressed(ref value) => {
+						let compressed = UntrustedRlp::new(value).compress(RlpType::Blocks);
+						encode_op(&mut payload, c, key, Some(&compressed[..]));
+					},
+				}
+			}
+		}
+
+		if !payload.is_empty() {
+			storage.rotate(&self.path).map_err(|e| e.to_string())?;
+			storage.append(&payload).map_err(|e| e.to_string())?;
+		}
+
+		for column in self.flushing.write().iter_mut() {
+			column.clear();
+			column.shrink_to_fit();
+		}
+		Ok(())
+	}
+
+	// Compacts sealed segments which are mostly garbage. The live entries are copied
+	// without blocking readers and the copy replaces the segment under a short write lock.
+	// Must be called under `flushing_lock`, so that the index does not change meanwhile.
+	fn compact(&self) -> io::Result<()> {
+		let candidates = match *self.storage.read() {
+			Some(ref storage) => storage.compaction_candidates(),
+			None => return Ok(()),
+		};
+
+		for id in candidates {
+			let payload = match *self.storage.read() {
+				Some(ref storage) => storage.live_entries(id)?,
+				None => return Ok(()),
+			};
+			if !payload.is_empty() {
+				Storage::write_compacted(&self.path, id, &payload)?;
+			}
+			match *self.storage.write() {
+				Some(ref mut storage) => storage.swap_compacted(&self.path, id, &payload)?,
+				None => return Ok(()),
+			}
+			trace!(target: "logdb", "Compacted segment {}", id);
+		}
+		Ok(())
+	}
+
+	/// Commit buffered changes to database.
+	pub fn flush(&self) -> Result<(), String> {
+		let mut lock = self.flushing_lock.lock();
+		if *lock {
+			// This can only happen if another flushing thread is terminated unexpectedly.
+			return Err("Database write failure. Running low on memory perhaps?".to_owned());
+		}
+		*lock = true;
+		let result = self.write_flushing_with_lock(&mut lock);
+		*lock = false;
+		result
+	}
+
+	/// Commit transaction to database.
+	pub fn write(&self, tr: DBTransaction) -> Result<(), String> {
+		self.write_buffered(tr);
+		self.flush()
+	}
+
+	/// Get value by key.
+	pub fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>, String> {
+		match *self.storage.read() {
+			Some(ref storage) => {
+				let c = Self::to_overlay_column(col);
+				match self.overlay.read()[c].get(key) {
+					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => return Ok(Some(value.clone())),
+					Some(&KeyState::Delete) => return Ok(None),
+					None => {},
+				}
+				match self.flushing.read()[c].get(key) {
+					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => return Ok(Some(value.clone())),
+					Some(&KeyState::Delete) => return Ok(None),
+					None => {},
+				}
+				match storage.index[c].get(key) {
+					Some(location) => storage.read(location).map(|v| Some(DBValue::from_vec(v))).map_err(|e| e.to_string()),
+					None => Ok(None),
+				}
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Get value by partial key. Only searches flushed values.
+	pub fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		match self.iter_from_prefix(col, prefix).next() {
+			Some((k, v)) => if k.starts_with(prefix) { Some(v) } else { None },
+			None => None,
+		}
+	}
+
+	/// Get database iterator for flushed data.
+	pub fn iter(&self, col: Option<u32>) -> LogDBIterator {
+		LogDBIterator {
+			db: self,
+			col: Self::to_overlay_column(col),
+			from: Bound::Unbounded,
+		}
+	}
+
+	/// Get database iterator for flushed data, starting at the first key not less than `prefix`.
+	pub fn iter_from_prefix(&self, col: Option<u32>, prefix: &[u8]) -> LogDBIterator {
+		LogDBIterator {
+			db: self,
+			col: Self::to_overlay_column(col),
+			from: Bound::Included(prefix.to_vec()),
+		}
+	}
+
+	/// Close the database. Must be called under `flushing_lock`, so that a compaction
+	/// does not write into the directory meanwhile.
+	fn close(&self, _lock: &mut MutexGuard<bool>) {
+		if let Some(ref storage) = *self.storage.read() {
+			if let Err(e) = storage.sync() {
+				warn!("Error syncing database at {}: {}", self.path, e);
+			}
+		}
+		*self.storage.write() = None;
+		self.overlay.write().clear();
+		self.flushing.write().clear();
+	}
+
+	/// Restore the database from a copy at given path.
+	pub fn restore(&self, new_db: &str) -> Result<(), UtilError> {
+		let mut lock = self.flushing_lock.lock();
+		self.close(&mut lock);
+		replace_database_dir(&self.path, new_db)?;
+
+		// reopen the database and steal handles into self
+		let db = Self::open(&self.config, &self.path)?;
+		*self.storage.write() = mem::replace(&mut *db.storage.write(), None);
+		*self.overlay.write() = mem::replace(&mut *db.overlay.write(), Vec::new());
+		*self.flushing.write() = mem::replace(&mut *db.flushing.write(), Vec::new());
+		Ok(())
+	}
+}
+
+/// Iterator over flushed values of a `LogDB` column, in key order.
+///
+/// The index is only locked while fetching each item, so writes flushed
+/// during iteration may or may not be observed.
+pub struct LogDBIterator<'a> {
+	db: &'a LogDB,
+	col: usize,
+	from: Bound<Vec<u8>>,
+}
+
+impl<'a> Iterator for LogDBIterator<'a> {
+	type Item = (Box<[u8]>, Box<[u8]>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let storage = self.db.storage.read();
+		let storage = match *storage {
+			Some(ref storage) => storage,
+			None => return None,
+		};
+
+		let (key, value) = {
+			let (key, location) = match storage.index[self.col].range((self.from.clone(), Bound::Unbounded)).next() {
+				Some(item) => item,
+				None => return None,
+			};
+			match storage.read(location) {
+				Ok(value) => (key.clone(), value),
+				Err(e) => {
+					warn!("Error reading database at {}: {}", self.db.path, e);
+					return None;
+				},
+			}
+		};
+
+		self.from = Bound::Excluded(key.clone());
+		Some((key.into_boxed_slice(), value.into_boxed_slice()))
+	}
+}
+
+impl KeyValueDB for LogDB {
+	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>, String> {
+		LogDB::get(self, col, key)
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		LogDB::get_by_prefix(self, col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		LogDB::write_buffered(self, transaction)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> Result<(), String> {
+		LogDB::write(self, transaction)
+	}
+
+	fn flush(&self) -> Result<(), String> {
+		LogDB::flush(self)
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(LogDB::iter(self, col))
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &'a [u8])
+		-> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>
+	{
+		Box::new(LogDB::iter_from_prefix(self, col, prefix))
+	}
+
+	fn restore(&self, new_db: &str) -> Result<(), UtilError> {
+		LogDB::restore(self, new_db)
+	}
+}
+
+impl Drop for LogDB {
+	fn drop(&mut self) {
+		// write all buffered changes if we can.
+		let _ = self.flush();
+		if let Some(ref storage) = *self.storage.read() {
+			let _ = storage.sync();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use devtools::*;
+	use super::*;
+
+	#[test]
+	fn logdb_persists_across_reopen() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let config = DatabaseConfig::with_columns(Some(2));
+		let key1 = H256::from(1);
+		let key2 = H256::from(2);
+
+		{
+			let db = LogDB::open(&config, path).unwrap();
+			let mut batch = db.transaction();
+			batch.put(None, &key1, b"cat");
+			batch.put(Some(0), &key1, b"dog");
+			batch.put(Some(1), &key2, b"horse");
+			db.write(batch).unwrap();
+
+			let mut batch = db.transaction();
+			batch.delete(Some(1), &key2);
+			batch.put(Some(1), &key1, b"elephant");
+			db.write_buffered(batch);
+		}
+
+		let db = LogDB::open(&config, path).unwrap();
+		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"cat");
+		assert_eq!(&*db.get(Some(0), &key1).unwrap().unwrap(), b"dog");
+		assert!(db.get(Some(1), &key2).unwrap().is_none());
+		assert_eq!(&*db.get(Some(1), &key1).unwrap().unwrap(), b"elephant");
+		assert!(db.get(Some(0), &key2).unwrap().is_none());
+
+		let contents: Vec<_> = db.iter(Some(1)).collect();
+		assert_eq!(contents.len(), 1);
+		assert_eq!(&*contents[0].0, &*key1);
+		assert_eq!(&*db.get_by_prefix(Some(0), &key1[0..4]).unwrap(), b"dog");
+	}
+
+	#[test]
+	fn logdb_discards_torn_write() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let config = DatabaseConfig::default();
+		let key1 = H256::from(1);
+		let key2 = H256::from(2);
+
+		{
+			let db = LogDB::open(&config, path).unwrap();
+			let mut batch = db.transaction();
+			batch.put(None, &key1, b"cat");
+			db.write(batch).unwrap();
+			let mut batch = db.transaction();
+			batch.put(None, &key2, b"dog");
+			db.write(batch).unwrap();
+		}
+
+		// cut the last frame in half.
+		let segment = Storage::segment_path(path, 0);
+		let len = fs::metadata(&segment).unwrap().len();
+		OpenOptions::new().write(true).open(&segment).unwrap().set_len(len - 10).unwrap();
+
+		{
+			let db = LogDB::open(&config, path).unwrap();
+			assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"cat");
+			assert!(db.get(None, &key2).unwrap().is_none());
+
+			let mut batch = db.transaction();
+			batch.put(None, &key2, b"horse");
+			db.write(batch).unwrap();
+		}
+
+		let db = LogDB::open(&config, path).unwrap();
+		assert_eq!(&*db.get(None, &key2).unwrap().unwrap(), b"horse");
+	}
+
+	#[test]
+	fn logdb_compacts_sealed_segments() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let key1 = H256::from(1);
+		let key2 = H256::from(2);
+
+		let mut storage = Storage::open(path, 1, MAX_INDEX_SIZE).unwrap();
+		let mut payload = Vec::new();
+		encode_op(&mut payload, 0, &key1, Some(&b"cat"[..]));
+		encode_op(&mut payload, 0, &key2, Some(&b"dog"[..]));
+		storage.append(&payload).unwrap();
+
+		// seal the first segment by hand.
+		storage.segments.get_mut(&0).unwrap().size = SEGMENT_SIZE;
+		storage.rotate(path).unwrap();
+		assert_eq!(storage.current, 1);
+
+		let mut payload = Vec::new();
+		encode_op(&mut payload, 0, &key1, None);
+		storage.append(&payload).unwrap();
+		assert_eq!(storage.compaction_candidates(), vec![0]);
+
+		let payload = storage.live_entries(0).unwrap();
+		Storage::write_compacted(path, 0, &payload).unwrap();
+		storage.swap_compacted(path, 0, &payload).unwrap();
+
+		assert!(storage.compaction_candidates().is_empty());
+		assert!(!Storage::compaction_path(path, 0).exists());
+		assert_eq!(storage.read(&storage.index[0][&*key2]).unwrap(), b"dog".to_vec());
+		drop(storage);
+
+		let storage = Storage::open(path, 1, MAX_INDEX_SIZE).unwrap();
+		assert!(storage.index[0].get(&*key1).is_none());
+		let location = storage.index[0][&*key2];
+		assert_eq!(location.segment, 0);
+		assert_eq!(storage.read(&location).unwrap(), b"dog".to_vec());
+	}
+
+	#[test]
+	fn logdb_removes_fully_compacted_segments() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let config = DatabaseConfig::default();
+		let key1 = H256::from(1);
+
+		let db = LogDB::open(&config, path).unwrap();
+		let mut batch = db.transaction();
+		batch.put(None, &key1, b"cat");
+		db.write(batch).unwrap();
+
+		// seal the first segment by hand.
+		{
+			let mut storage = db.storage.write();
+			let storage = storage.as_mut().unwrap();
+			storage.segments.get_mut(&0).unwrap().size = SEGMENT_SIZE;
+		}
+
+		let mut batch = db.transaction();
+		batch.delete(None, &key1);
+		db.write(batch).unwrap();
+
+		assert!(!Storage::segment_path(path, 0).exists());
+		assert!(db.get(None, &key1).unwrap().is_none());
+	}
+
+	#[test]
+	fn logdb_rejects_oversized_frame() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let config = DatabaseConfig::default();
+		let key1 = H256::from(1);
+
+		{
+			let db = LogDB::open(&config, path).unwrap();
+			let mut batch = db.transaction();
+			batch.put(None, &key1, b"cat");
+			db.write(batch).unwrap();
+		}
+
+		// append a header claiming a payload far larger than the file.
+		let segment = Storage::segment_path(path, 0);
+		let len = fs::metadata(&segment).unwrap().len();
+		OpenOptions::new().append(true).open(&segment).unwrap().write_all(&[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]).unwrap();
+
+		let db = LogDB::open(&config, path).unwrap();
+		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"cat");
+		assert_eq!(fs::metadata(&segment).unwrap().len(), len);
+	}
+
+	#[test]
+	fn logdb_refuses_oversized_index() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let config = DatabaseConfig::default();
+
+		{
+			let db = LogDB::open(&config, path).unwrap();
+			let mut batch = db.transaction();
+			for i in 0..10u64 {
+				batch.put(None, &H256::from(i), b"cat");
+			}
+			db.write(batch).unwrap();
+		}
+
+		let size = 10 * index_entry_size(32);
+		assert_eq!(Storage::open(path, 1, size).unwrap().index_size, size);
+		assert!(Storage::open(path, 1, size - 1).is_err());
+	}
+
+	#[test]
+	fn logdb_restore() {
+		let tempdir = RandomTempPath::create_dir();
+		let path = tempdir.new_in("db");
+		let other = tempdir.new_in("restored");
+		let config = DatabaseConfig::default();
+		let key = H256::from(1);
+
+		{
+			let db = LogDB::open(&config, &other).unwrap();
+			let mut batch = db.transaction();
+			batch.put(None, &key, b"restored");
+			db.write(batch).unwrap();
+		}
+
+		let db = LogDB::open(&config, &path).unwrap();
+		let mut batch = db.transaction();
+		batch.put(None, &key, b"original");
+		db.write(batch).unwrap();
+
+		db.restore(&other).unwrap();
+		assert_eq!(&*db.get(None, &key).unwrap().unwrap(), b"restored");
+	}
+}
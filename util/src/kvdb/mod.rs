@@ -14,11 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Key-Value store abstraction with `RocksDB` backend.
+//! Key-Value store abstraction with `RocksDB` and `LogDB` backends.
 
 use std::io::ErrorKind;
 use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use common::*;
 use elastic_array::*;
@@ -33,6 +34,10 @@ use std::process::Command;
 #[cfg(target_os = "linux")]
 use std::fs::File;
 
+mod logdb;
+
+pub use self::logdb::{LogDB, LogDBIterator};
+
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
 const DB_WRITE_BUFFER_SIZE: usize = 2048 * 1000;
@@ -368,6 +373,42 @@ impl CompactionProfile {
 	}
 }
 
+/// Database storage backend.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DatabaseBackend {
+	/// RocksDB.
+	RocksDB,
+	/// Append-only log store written in pure Rust.
+	LogDB,
+}
+
+impl Default for DatabaseBackend {
+	fn default() -> Self {
+		DatabaseBackend::RocksDB
+	}
+}
+
+impl FromStr for DatabaseBackend {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"rocksdb" => Ok(DatabaseBackend::RocksDB),
+			"logdb" => Ok(DatabaseBackend::LogDB),
+			_ => Err(format!("Invalid database backend: {}", s)),
+		}
+	}
+}
+
+impl fmt::Display for DatabaseBackend {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			DatabaseBackend::RocksDB => write!(f, "rocksdb"),
+			DatabaseBackend::LogDB => write!(f, "logdb"),
+		}
+	}
+}
+
 /// Database configuration
 #[derive(Clone)]
 pub struct DatabaseConfig {
@@ -381,6 +422,8 @@ pub struct DatabaseConfig {
 	pub columns: Option<u32>,
 	/// Should we keep WAL enabled?
 	pub wal: bool,
+	/// Storage backend.
+	pub backend: DatabaseBackend,
 }
 
 impl DatabaseConfig {
@@ -406,8 +449,53 @@ impl Default for DatabaseConfig {
 			compaction: CompactionProfile::default(),
 			columns: None,
 			wal: true,
+			backend: DatabaseBackend::default(),
+		}
+	}
+}
+
+/// Open database at given path using the backend selected in the configuration.
+pub fn open_database(config: &DatabaseConfig, path: &str) -> Result<Arc<KeyValueDB>, String> {
+	let db: Arc<KeyValueDB> = match config.backend {
+		DatabaseBackend::RocksDB => Arc::new(Database::open(config, path)?),
+		DatabaseBackend::LogDB => Arc::new(LogDB::open(config, path)?),
+	};
+	Ok(db)
+}
+
+/// Replaces database directory at `path` with the one at `new_db`, keeping
+/// a backup of the old database until the replacement succeeds.
+fn replace_database_dir(path: &str, new_db: &str) -> Result<(), UtilError> {
+	let mut backup_db = PathBuf::from(path);
+	backup_db.pop();
+	backup_db.push("backup_db");
+
+	let existed = match fs::rename(path, &backup_db) {
+		Ok(_) => true,
+		Err(e) => if let ErrorKind::NotFound = e.kind() {
+			false
+		} else {
+			return Err(e.into());
+		}
+	};
+
+	match fs::rename(new_db, path) {
+		Ok(_) => {
+			// clean up the backup.
+			if existed {
+				fs::remove_dir_all(&backup_db)?;
+			}
+		}
+		Err(e) => {
+			// restore the backup.
+			if existed {
+				fs::rename(&backup_db, path)?;
+			}
+			return Err(e.into())
 		}
 	}
+
+	Ok(())
 }
 
 /// Database iterator (for flushed data only)
@@ -770,35 +858,7 @@ impl Database {
 	/// Restore the database from a copy at given path.
 	pub fn restore(&self, new_db: &str) -> Result<(), UtilError> {
 		self.close();
-
-		let mut backup_db = PathBuf::from(&self.path);
-		backup_db.pop();
-		backup_db.push("backup_db");
-
-		let existed = match fs::rename(&self.path, &backup_db) {
-			Ok(_) => true,
-			Err(e) => if let ErrorKind::NotFound = e.kind() {
-				false
-			} else {
-				return Err(e.into());
-			}
-		};
-
-		match fs::rename(&new_db, &self.path) {
-			Ok(_) => {
-				// clean up the backup.
-				if existed {
-					fs::remove_dir_all(&backup_db)?;
-				}
-			}
-			Err(e) => {
-				// restore the backup.
-				if existed {
-					fs::rename(&backup_db, &self.path)?;
-				}
-				return Err(e.into())
-			}
-		}
+		replace_database_dir(&self.path, new_db)?;
 
 		// reopen the database and steal handles into self
 		let db = Self::open(&self.config, &self.path)?;
@@ -952,6 +1012,14 @@ mod tests {
 		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"horse");
 	}
 
+	#[test]
+	fn database_backend_from_str() {
+		assert_eq!(DatabaseBackend::RocksDB, "rocksdb".parse().unwrap());
+		assert_eq!(DatabaseBackend::LogDB, "logdb".parse().unwrap());
+		assert!("leveldb".parse::<DatabaseBackend>().is_err());
+		assert_eq!(format!("{}", DatabaseBackend::LogDB), "logdb");
+	}
+
 	#[test]
 	fn kvdb() {
 		let path = RandomTempPath::create_dir();
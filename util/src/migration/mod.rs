@@ -270,6 +270,7 @@ impl Manager {
 			compaction: config.compaction_profile,
 			columns: columns,
 			wal: true,
+			backend: Default::default(),
 		};
 
 		let db_root = database_path(old_path);
@@ -220,17 +220,19 @@ impl<'a> TrieDBIterator<'a> {
 		let node = Node::decoded(&node_data);
 		match node {
 			Node::Leaf(ref slice, _) => {
-				if slice == key {
-					self.trail.push(Crumb {
-						status: Status::At,
-						node: node.clone().into(),
-					});
+				let status = if slice == key {
+					Status::At
+				} else if slice > key {
+					// the leaf is past the key, so it has to be yielded.
+					Status::Entering
 				} else {
-					self.trail.push(Crumb {
-						status: Status::Exiting,
-						node: node.clone().into(),
-					});
-				}
+					Status::Exiting
+				};
+
+				self.trail.push(Crumb {
+					status: status,
+					node: node.clone().into(),
+				});
 
 				self.key_nibbles.extend(slice.iter());
 				Ok(())
@@ -244,9 +246,17 @@ impl<'a> TrieDBIterator<'a> {
 					self.key_nibbles.extend(slice.iter());
 					let data = self.db.get_raw_or_lookup(&*item)?;
 					self.seek_descend(data, &key.mid(slice.len()))
-				} else {
+				} else if slice > key {
 					self.descend(&node_data)?;
 					Ok(())
+				} else {
+					// the whole subtree precedes the key.
+					self.trail.push(Crumb {
+						status: Status::Exiting,
+						node: node.clone().into(),
+					});
+					self.key_nibbles.extend(slice.iter());
+					Ok(())
 				}
 			},
 			Node::Branch(ref nodes, _) => match key.is_empty() {
@@ -427,6 +437,42 @@ fn iterator_seek() {
 	assert_eq!(&d[4..], &iter.map(|x| x.unwrap().1).collect::<Vec<_>>()[..]);
 }
 
+#[test]
+fn iterator_seek_past_key() {
+	use memorydb::*;
+	use super::TrieMut;
+	use super::triedbmut::*;
+
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::new();
+	{
+		let mut t = TrieDBMut::new(&mut memdb, &mut root);
+		t.insert(b"B", b"B").unwrap();
+	}
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let mut iter = t.iter().unwrap();
+	iter.seek(b"A").unwrap();
+	assert_eq!(vec![b"B".to_vec()], iter.map(|x| x.unwrap().0).collect::<Vec<_>>());
+	let mut iter = t.iter().unwrap();
+	iter.seek(b"C").unwrap();
+	assert_eq!(0, iter.count());
+
+	{
+		let mut t = TrieDBMut::from_existing(&mut memdb, &mut root).unwrap();
+		t.insert(b"BA", b"BA").unwrap();
+		t.insert(b"BB", b"BB").unwrap();
+	}
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let mut iter = t.iter().unwrap();
+	iter.seek(b"A").unwrap();
+	assert_eq!(3, iter.count());
+	let mut iter = t.iter().unwrap();
+	iter.seek(b"C").unwrap();
+	assert_eq!(0, iter.count());
+}
+
 #[test]
 fn get_len() {
 	use memorydb::*;